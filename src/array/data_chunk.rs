@@ -219,6 +219,8 @@ pub fn datachunk_to_sqllogictest_string(chunk: &Chunk) -> String {
                     DataValue::Decimal(v) => write!(output, "{}", v),
                     DataValue::Date(v) => write!(output, "{}", v),
                     DataValue::Interval(v) => write!(output, "{}", v),
+                    DataValue::Timestamp(v) => write!(output, "{}", v),
+                    DataValue::TimestampTz(v) => write!(output, "{}", v),
                 }
                 .unwrap();
             }
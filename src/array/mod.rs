@@ -10,7 +10,10 @@ use paste::paste;
 use rust_decimal::prelude::FromStr;
 use rust_decimal::Decimal;
 
-use crate::types::{Blob, ConvertError, DataType, DataValue, Date, Interval, PhysicalDataTypeKind};
+use crate::types::{
+    Blob, ConvertError, DataType, DataValue, Date, Interval, PhysicalDataTypeKind, Timestamp,
+    TimestampTz,
+};
 
 mod data_chunk;
 mod data_chunk_builder;
@@ -159,6 +162,8 @@ pub type F64Array = PrimitiveArray<f64>;
 pub type DecimalArray = PrimitiveArray<Decimal>;
 pub type DateArray = PrimitiveArray<Date>;
 pub type IntervalArray = PrimitiveArray<Interval>;
+pub type TimestampArray = PrimitiveArray<Timestamp>;
+pub type TimestampTzArray = PrimitiveArray<TimestampTz>;
 
 /// Embeds all types of arrays in `array` module.
 #[derive(Debug, Clone, PartialEq)]
@@ -174,6 +179,8 @@ pub enum ArrayImpl {
     Decimal(Arc<DecimalArray>),
     Date(Arc<DateArray>),
     Interval(Arc<IntervalArray>),
+    Timestamp(Arc<TimestampArray>),
+    TimestampTz(Arc<TimestampTzArray>),
 }
 
 pub type BoolArrayBuilder = PrimitiveArrayBuilder<bool>;
@@ -183,6 +190,8 @@ pub type F64ArrayBuilder = PrimitiveArrayBuilder<f64>;
 pub type DecimalArrayBuilder = PrimitiveArrayBuilder<Decimal>;
 pub type DateArrayBuilder = PrimitiveArrayBuilder<Date>;
 pub type IntervalArrayBuilder = PrimitiveArrayBuilder<Interval>;
+pub type TimestampArrayBuilder = PrimitiveArrayBuilder<Timestamp>;
+pub type TimestampTzArrayBuilder = PrimitiveArrayBuilder<TimestampTz>;
 
 /// Embeds all types of array builders in `array` module.
 pub enum ArrayBuilderImpl {
@@ -197,6 +206,8 @@ pub enum ArrayBuilderImpl {
     Decimal(DecimalArrayBuilder),
     Date(DateArrayBuilder),
     Interval(IntervalArrayBuilder),
+    Timestamp(TimestampArrayBuilder),
+    TimestampTz(TimestampTzArrayBuilder),
 }
 
 /// `for_all_variants` includes all variants of our array types. If you added a new array
@@ -220,7 +231,9 @@ macro_rules! for_all_variants {
             { Bool, bool, BoolArray, BoolArrayBuilder, Bool },
             { Decimal, decimal, DecimalArray, DecimalArrayBuilder, Decimal },
             { Date, date, DateArray, DateArrayBuilder, Date },
-            { Interval, interval, IntervalArray, IntervalArrayBuilder, Interval }
+            { Interval, interval, IntervalArray, IntervalArrayBuilder, Interval },
+            { Timestamp, timestamp, TimestampArray, TimestampArrayBuilder, Timestamp },
+            { TimestampTz, timestamptz, TimestampTzArray, TimestampTzArrayBuilder, TimestampTz }
         }
     };
 }
@@ -398,6 +411,8 @@ impl ArrayBuilderImpl {
             Self::Decimal(a) if null => a.push(None),
             Self::Date(a) if null => a.push(None),
             Self::Interval(a) if null => a.push(None),
+            Self::Timestamp(a) if null => a.push(None),
+            Self::TimestampTz(a) if null => a.push(None),
             Self::Bool(a) => a.push(Some(
                 &s.parse::<bool>()
                     .map_err(|e| ConvertError::ParseBool(s.to_string(), e))?,
@@ -426,6 +441,14 @@ impl ArrayBuilderImpl {
                 &Date::from_str(s).map_err(|e| ConvertError::ParseDate(s.to_string(), e))?,
             )),
             Self::Interval(_) => return Err(ConvertError::ParseInterval(s.to_string())),
+            Self::Timestamp(a) => a
+                .push(Some(&Timestamp::from_str(s).map_err(|e| {
+                    ConvertError::ParseTimestamp(s.to_string(), e)
+                })?)),
+            Self::TimestampTz(a) => a
+                .push(Some(&TimestampTz::from_str(s).map_err(|e| {
+                    ConvertError::ParseTimestamp(s.to_string(), e)
+                })?)),
         }
         Ok(())
     }
@@ -518,6 +541,8 @@ impl From<&DataValue> for ArrayImpl {
             &DataValue::Decimal(v) => Self::new_decimal([v].into_iter().collect()),
             &DataValue::Date(v) => Self::new_date([v].into_iter().collect()),
             &DataValue::Interval(v) => Self::new_interval([v].into_iter().collect()),
+            &DataValue::Timestamp(v) => Self::new_timestamp([v].into_iter().collect()),
+            &DataValue::TimestampTz(v) => Self::new_timestamptz([v].into_iter().collect()),
             DataValue::Null => panic!("can not build array from NULL"),
         }
     }
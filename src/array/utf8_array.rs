@@ -2,23 +2,50 @@
 
 use std::marker::PhantomData;
 use std::mem;
+use std::sync::Arc;
 
 use bitvec::vec::BitVec;
 use serde::{Deserialize, Serialize};
 
 use super::iterator::NonNullArrayIter;
-use super::{Array, ArrayBuilder, ArrayEstimateExt, ArrayValidExt};
+use super::{Array, ArrayBuilder, ArrayEstimateExt, ArrayExt, ArrayValidExt};
 use crate::types::BlobRef;
 
 /// A collection of variable-length values.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BytesArray<T: ValueRef + ?Sized> {
     offset: Vec<usize>,
     valid: BitVec,
     data: Vec<u8>,
+    /// Dictionary codes of the values, if they are decoded from a single dictionary.
+    #[serde(skip)]
+    dictionary: Option<Arc<BytesDictionary<T>>>,
     _type: PhantomData<T>,
 }
 
+/// Dictionary encoding of a [`BytesArray`] without nulls: the `i`-th value of the array is
+/// `values[codes[i]]`.
+///
+/// Dictionary-encoded arrays are produced by scanning dictionary blocks, so that executors can
+/// group and compare on the codes instead of the values.
+#[derive(Debug)]
+pub struct BytesDictionary<T: ValueRef + ?Sized> {
+    values: Arc<BytesArray<T>>,
+    codes: Vec<u32>,
+}
+
+impl<T: ValueRef + ?Sized> BytesDictionary<T> {
+    /// Distinct values of the dictionary.
+    pub fn values(&self) -> &Arc<BytesArray<T>> {
+        &self.values
+    }
+
+    /// Codes of the array, which are indexes into [`values`](Self::values).
+    pub fn codes(&self) -> &[u32] {
+        &self.codes
+    }
+}
+
 /// The borrowed type of a variable-length value.
 pub trait ValueRef: ToOwned + AsRef<[u8]> + Send + Sync + 'static {
     fn from_bytes(s: &[u8]) -> &Self;
@@ -47,11 +74,42 @@ impl<T: ValueRef + ?Sized> Clone for BytesArray<T> {
             offset: self.offset.clone(),
             valid: self.valid.clone(),
             data: self.data.clone(),
+            dictionary: self.dictionary.clone(),
             _type: PhantomData,
         }
     }
 }
 
+// The dictionary is only a different representation of the same values.
+impl<T: ValueRef + ?Sized> PartialEq for BytesArray<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && self.valid == other.valid && self.data == other.data
+    }
+}
+
+impl<T: ValueRef + ?Sized> BytesArray<T> {
+    /// Get the dictionary codes of the array, if the array is dictionary-encoded.
+    pub fn dictionary(&self) -> Option<&BytesDictionary<T>> {
+        self.dictionary.as_deref()
+    }
+
+    /// Filter the elements and return a new array. Unlike [`ArrayExt::filter`], the dictionary
+    /// encoding is preserved.
+    pub fn filter(&self, visibility: impl Iterator<Item = bool>) -> Self {
+        let dictionary = match &self.dictionary {
+            Some(dictionary) => dictionary,
+            None => return ArrayExt::filter(self, visibility),
+        };
+        let mut builder = BytesArrayBuilder::with_capacity(self.len());
+        for (&code, visible) in dictionary.codes.iter().zip(visibility) {
+            if visible {
+                builder.push_dictionary_code(&dictionary.values, code);
+            }
+        }
+        builder.finish()
+    }
+}
+
 impl<T: ValueRef + ?Sized> Array for BytesArray<T> {
     type Item = T;
     type Builder = BytesArrayBuilder<T>;
@@ -92,9 +150,45 @@ pub struct BytesArrayBuilder<T: ValueRef + ?Sized> {
     offset: Vec<usize>,
     valid: BitVec,
     data: Vec<u8>,
+    dictionary: DictionaryState<T>,
     _type: PhantomData<T>,
 }
 
+/// Whether the values pushed into a [`BytesArrayBuilder`] are all from a single dictionary.
+enum DictionaryState<T: ValueRef + ?Sized> {
+    /// No value has been pushed.
+    Empty,
+    /// All values are pushed by [`push_dictionary_code`](BytesArrayBuilder::push_dictionary_code)
+    /// from the same dictionary.
+    Codes(Arc<BytesArray<T>>, Vec<u32>),
+    /// The array can't be dictionary-encoded.
+    None,
+}
+
+impl<T: ValueRef + ?Sized> BytesArrayBuilder<T> {
+    /// Append the value of `code` in the dictionary `values`. If all values of the array are
+    /// appended from the same dictionary, the array will be dictionary-encoded.
+    pub fn push_dictionary_code(&mut self, values: &Arc<BytesArray<T>>, code: u32) {
+        match &mut self.dictionary {
+            DictionaryState::Empty => {
+                self.dictionary = DictionaryState::Codes(values.clone(), vec![code]);
+            }
+            DictionaryState::Codes(dict, codes) if Arc::ptr_eq(dict, values) => codes.push(code),
+            DictionaryState::Codes(..) => self.dictionary = DictionaryState::None,
+            DictionaryState::None => {}
+        }
+        self.push_value(values.get(code as usize));
+    }
+
+    fn push_value(&mut self, value: Option<&T>) {
+        self.valid.push(value.is_some());
+        if let Some(x) = value {
+            self.data.extend_from_slice(x.as_ref());
+        }
+        self.offset.push(self.data.len());
+    }
+}
+
 impl<T: ValueRef + ?Sized> ArrayBuilder for BytesArrayBuilder<T> {
     type Array = BytesArray<T>;
 
@@ -105,6 +199,7 @@ impl<T: ValueRef + ?Sized> ArrayBuilder for BytesArrayBuilder<T> {
             offset,
             data: Vec::with_capacity(capacity),
             valid: BitVec::with_capacity(capacity),
+            dictionary: DictionaryState::Empty,
             _type: PhantomData,
         }
     }
@@ -118,14 +213,12 @@ impl<T: ValueRef + ?Sized> ArrayBuilder for BytesArrayBuilder<T> {
     }
 
     fn push(&mut self, value: Option<&T>) {
-        self.valid.push(value.is_some());
-        if let Some(x) = value {
-            self.data.extend_from_slice(x.as_ref());
-        }
-        self.offset.push(self.data.len());
+        self.dictionary = DictionaryState::None;
+        self.push_value(value);
     }
 
     fn append(&mut self, other: &BytesArray<T>) {
+        self.dictionary = DictionaryState::None;
         self.valid.extend_from_bitslice(&other.valid);
         self.data.extend_from_slice(&other.data);
         let start = *self.offset.last().unwrap();
@@ -135,10 +228,17 @@ impl<T: ValueRef + ?Sized> ArrayBuilder for BytesArrayBuilder<T> {
     }
 
     fn take(&mut self) -> BytesArray<T> {
+        let dictionary = match mem::replace(&mut self.dictionary, DictionaryState::Empty) {
+            DictionaryState::Codes(values, codes) => {
+                Some(Arc::new(BytesDictionary { values, codes }))
+            }
+            _ => None,
+        };
         BytesArray {
             valid: mem::take(&mut self.valid),
             data: mem::take(&mut self.data),
             offset: mem::replace(&mut self.offset, vec![0]),
+            dictionary,
             _type: PhantomData,
         }
     }
@@ -175,4 +275,31 @@ mod tests {
         }
         builder.finish();
     }
+
+    #[test]
+    fn test_utf8_dictionary() {
+        let values: Arc<Utf8Array> = Arc::new([Some("a"), Some("b")].into_iter().collect());
+        let mut builder = Utf8ArrayBuilder::new();
+        for code in [1, 0, 1, 1] {
+            builder.push_dictionary_code(&values, code);
+        }
+        let array = builder.finish();
+        assert_eq!(array.dictionary().unwrap().codes(), &[1, 0, 1, 1]);
+        assert_eq!(
+            array,
+            [Some("b"), Some("a"), Some("b"), Some("b")]
+                .into_iter()
+                .collect()
+        );
+
+        // filter preserves the dictionary
+        let filtered = array.filter([true, false, false, true].into_iter());
+        assert_eq!(filtered.dictionary().unwrap().codes(), &[1, 1]);
+
+        // pushing a plain value drops the dictionary
+        let mut builder = Utf8ArrayBuilder::new();
+        builder.push_dictionary_code(&values, 0);
+        builder.push(Some("a"));
+        assert!(builder.finish().dictionary().is_none());
+    }
 }
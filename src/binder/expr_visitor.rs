@@ -13,9 +13,13 @@ pub trait ExprVisitor {
             BoundExpr::UnaryOp(expr) => self.visit_unary_op(expr),
             BoundExpr::TypeCast(expr) => self.visit_type_cast(expr),
             BoundExpr::AggCall(expr) => self.visit_agg_call(expr),
+            BoundExpr::FunctionCall(expr) => self.visit_function_call(expr),
             BoundExpr::IsNull(expr) => self.visit_is_null(expr),
+            BoundExpr::Case(expr) => self.visit_case(expr),
+            BoundExpr::InList(expr) => self.visit_in_list(expr),
             BoundExpr::ExprWithAlias(expr) => self.visit_expr_with_alias(expr),
             BoundExpr::Alias(expr) => self.visit_alias(expr),
+            BoundExpr::WindowCall(expr) => self.visit_window_call(expr),
         }
     }
 
@@ -39,7 +43,13 @@ pub trait ExprVisitor {
     }
 
     fn visit_agg_call(&mut self, agg: &BoundAggCall) {
-        for arg in &agg.args {
+        for arg in agg.args.iter().chain(agg.filter.as_deref()) {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_function_call(&mut self, expr: &BoundFunctionCall) {
+        for arg in &expr.args {
             self.visit_expr(arg);
         }
     }
@@ -48,11 +58,40 @@ pub trait ExprVisitor {
         self.visit_expr(expr.expr.as_ref());
     }
 
+    fn visit_case(&mut self, expr: &BoundCase) {
+        for (cond, result) in expr.conditions.iter().zip(&expr.results) {
+            self.visit_expr(cond);
+            self.visit_expr(result);
+        }
+        if let Some(else_result) = &expr.else_result {
+            self.visit_expr(else_result.as_ref());
+        }
+    }
+
+    fn visit_in_list(&mut self, expr: &BoundInList) {
+        self.visit_expr(expr.expr.as_ref());
+        for item in &expr.list {
+            self.visit_expr(item);
+        }
+    }
+
     fn visit_expr_with_alias(&mut self, expr: &BoundExprWithAlias) {
         self.visit_expr(expr.expr.as_ref());
     }
 
     fn visit_alias(&mut self, _: &BoundAlias) {}
+
+    fn visit_window_call(&mut self, expr: &BoundWindowCall) {
+        for arg in &expr.args {
+            self.visit_expr(arg);
+        }
+        for expr in &expr.partition_by {
+            self.visit_expr(expr);
+        }
+        for order in &expr.order_by {
+            self.visit_expr(&order.expr);
+        }
+    }
 }
 
 pub trait ExprRewriter {
@@ -65,9 +104,13 @@ pub trait ExprRewriter {
             BoundExpr::UnaryOp(_) => self.rewrite_unary_op(expr),
             BoundExpr::TypeCast(_) => self.rewrite_type_cast(expr),
             BoundExpr::AggCall(_) => self.rewrite_agg_call(expr),
+            BoundExpr::FunctionCall(_) => self.rewrite_function_call(expr),
             BoundExpr::IsNull(_) => self.rewrite_is_null(expr),
+            BoundExpr::Case(_) => self.rewrite_case(expr),
+            BoundExpr::InList(_) => self.rewrite_in_list(expr),
             BoundExpr::ExprWithAlias(_) => self.rewrite_expr_with_alias(expr),
             BoundExpr::Alias(_) => self.rewrite_alias(expr),
+            BoundExpr::WindowCall(_) => self.rewrite_window_call(expr),
         }
     }
 
@@ -104,7 +147,18 @@ pub trait ExprRewriter {
     fn rewrite_agg_call(&self, agg: &mut BoundExpr) {
         match agg {
             BoundExpr::AggCall(agg) => {
-                for arg in &mut agg.args {
+                for arg in agg.args.iter_mut().chain(agg.filter.as_deref_mut()) {
+                    self.rewrite_expr(arg);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn rewrite_function_call(&self, expr: &mut BoundExpr) {
+        match expr {
+            BoundExpr::FunctionCall(expr) => {
+                for arg in &mut expr.args {
                     self.rewrite_expr(arg);
                 }
             }
@@ -119,6 +173,33 @@ pub trait ExprRewriter {
         }
     }
 
+    fn rewrite_case(&self, expr: &mut BoundExpr) {
+        match expr {
+            BoundExpr::Case(expr) => {
+                for (cond, result) in expr.conditions.iter_mut().zip(&mut expr.results) {
+                    self.rewrite_expr(cond);
+                    self.rewrite_expr(result);
+                }
+                if let Some(else_result) = &mut expr.else_result {
+                    self.rewrite_expr(else_result.as_mut());
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn rewrite_in_list(&self, expr: &mut BoundExpr) {
+        match expr {
+            BoundExpr::InList(expr) => {
+                self.rewrite_expr(expr.expr.as_mut());
+                for item in &mut expr.list {
+                    self.rewrite_expr(item);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
     fn rewrite_expr_with_alias(&self, expr: &mut BoundExpr) {
         match expr {
             BoundExpr::ExprWithAlias(expr) => self.rewrite_expr(expr.expr.as_mut()),
@@ -127,4 +208,21 @@ pub trait ExprRewriter {
     }
 
     fn rewrite_alias(&self, _: &mut BoundExpr) {}
+
+    fn rewrite_window_call(&self, expr: &mut BoundExpr) {
+        match expr {
+            BoundExpr::WindowCall(expr) => {
+                for arg in &mut expr.args {
+                    self.rewrite_expr(arg);
+                }
+                for expr in &mut expr.partition_by {
+                    self.rewrite_expr(expr);
+                }
+                for order in &mut expr.order_by {
+                    self.rewrite_expr(&mut order.expr);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
 }
@@ -8,7 +8,7 @@ use serde::Serialize;
 use super::*;
 use crate::binder::{BindError, Binder, BoundExpr};
 use crate::parser::{BinaryOperator, FunctionArg, FunctionArgExpr};
-use crate::types::{DataType, DataTypeKind};
+use crate::types::{DataType, DataTypeKind, PhysicalDataTypeKind};
 
 /// Aggregation kind
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -19,29 +19,82 @@ pub enum AggKind {
     Min,
     Sum,
     Count,
-    DPCount(f64),
-    DPSum(f64),
+    DPCount,
+    DPSum,
+    /// Differentially private count of distinct values. Each distinct value is counted at most
+    /// once no matter how many rows carry it, so the sensitivity is 1.
+    DPCountDistinct,
+    StddevSamp,
+    StddevPop,
+    VarSamp,
+    VarPop,
+    /// `corr(y, x)`, the correlation coefficient.
+    Corr,
+    /// `covar_samp(y, x)`, the sample covariance.
+    CovarSamp,
+    /// `regr_slope(y, x)`, the slope of the least-squares-fit linear equation.
+    RegrSlope,
+    /// `regr_intercept(y, x)`, the y-intercept of the least-squares-fit linear equation.
+    RegrIntercept,
+}
+
+impl AggKind {
+    /// Returns the names of constant parameters taken by the aggregation.
+    pub fn param_names(&self) -> &'static [&'static str] {
+        match self {
+            AggKind::DPCount | AggKind::DPSum => &["epsilon"],
+            AggKind::DPCountDistinct => &["epsilon", "max_contributions"],
+            _ => &[],
+        }
+    }
+
+    /// Returns true if the aggregation adds noise for differential privacy.
+    pub fn is_differentially_private(&self) -> bool {
+        matches!(
+            self,
+            AggKind::DPCount | AggKind::DPSum | AggKind::DPCountDistinct
+        )
+    }
+
+    /// Returns true if the aggregation is computed from the moments of one or two variables.
+    pub fn is_statistical(&self) -> bool {
+        use AggKind::*;
+        matches!(
+            self,
+            StddevSamp
+                | StddevPop
+                | VarSamp
+                | VarPop
+                | Corr
+                | CovarSamp
+                | RegrSlope
+                | RegrIntercept
+        )
+    }
 }
 
 impl std::fmt::Display for AggKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         use AggKind::*;
-        match self {
-            DPCount(epsilon) => write!(f, "dp_count(epsilon={})", epsilon),
-            DPSum(epsilon) => write!(f, "dp_sum(epsilon={})", epsilon),
-            _ => write!(
-                f,
-                "{}",
-                match self {
-                    Avg => "avg",
-                    RowCount | Count => "count",
-                    Max => "max",
-                    Min => "min",
-                    Sum => "sum",
-                    _ => unreachable!(),
-                }
-            ),
-        }
+        let name = match self {
+            Avg => "avg",
+            RowCount | Count => "count",
+            Max => "max",
+            Min => "min",
+            Sum => "sum",
+            DPCount => "dp_count",
+            DPSum => "dp_sum",
+            DPCountDistinct => "dp_count_distinct",
+            StddevSamp => "stddev_samp",
+            StddevPop => "stddev_pop",
+            VarSamp => "var_samp",
+            VarPop => "var_pop",
+            Corr => "corr",
+            CovarSamp => "covar_samp",
+            RegrSlope => "regr_slope",
+            RegrIntercept => "regr_intercept",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -50,17 +103,42 @@ impl std::fmt::Display for AggKind {
 pub struct BoundAggCall {
     pub kind: AggKind,
     pub args: Vec<BoundExpr>,
+    /// Constant parameters of the aggregation, e.g. the `epsilon` of DP aggregations.
+    pub params: Vec<DataValue>,
+    /// Only rows satisfying the filter are aggregated.
+    pub filter: Option<Box<BoundExpr>>,
     pub return_type: DataType,
-    // TODO: add distinct keyword
+    pub distinct: bool,
+}
+
+impl BoundAggCall {
+    /// Returns the parameters formatted as `(name=value, ...)`, or an empty string if none.
+    fn fmt_params(&self) -> String {
+        if self.params.is_empty() {
+            return String::new();
+        }
+        let params = (self.kind.param_names().iter())
+            .zip(&self.params)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .join(", ");
+        format!("({})", params)
+    }
 }
 
 impl std::fmt::Debug for BoundAggCall {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{:?}({:?}) -> {:?}",
-            self.kind, self.args, self.return_type
-        )
+            "{:?}{}({}{:?})",
+            self.kind,
+            self.fmt_params(),
+            if self.distinct { "distinct " } else { "" },
+            self.args,
+        )?;
+        if let Some(filter) = &self.filter {
+            write!(f, " filter({:?})", filter)?;
+        }
+        write!(f, " -> {:?}", self.return_type)
     }
 }
 
@@ -68,33 +146,47 @@ impl std::fmt::Display for BoundAggCall {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}({}) -> {}",
+            "{}{}({}{})",
             self.kind,
+            self.fmt_params(),
+            if self.distinct { "distinct " } else { "" },
             self.args.iter().map(|x| format!("{}", x)).join(", "),
-            self.return_type
-        )
+        )?;
+        if let Some(filter) = &self.filter {
+            write!(f, " filter({})", filter)?;
+        }
+        write!(f, " -> {}", self.return_type)
     }
 }
 
 impl Binder {
     pub fn bind_function(&mut self, func: &Function) -> Result<BoundExpr, BindError> {
-        // TODO: Support scalar function
+        if let Some(spec) = &func.over {
+            return self.bind_window_function(func, spec);
+        }
         let mut args = Vec::new();
+        let mut filter = None;
         for arg in &func.args {
             let arg = match &arg {
+                // `FILTER (WHERE cond)` is rewritten into `filter => cond` by the parser
+                FunctionArg::Named {
+                    name,
+                    arg: FunctionArgExpr::Expr(expr),
+                } if name.value.eq_ignore_ascii_case("filter") => {
+                    filter = Some(self.bind_expr(expr)?);
+                    continue;
+                }
                 FunctionArg::Named { arg, .. } => arg,
                 FunctionArg::Unnamed(arg) => arg,
             };
             match arg {
                 FunctionArgExpr::Expr(expr) => args.push(self.bind_expr(expr)?),
-                FunctionArgExpr::Wildcard => {
-                    // No argument in row count
-                    args.clear();
-                    break;
-                }
+                // No argument in row count
+                FunctionArgExpr::Wildcard => args.clear(),
                 _ => todo!("Support aggregate argument: {:?}", arg),
             }
         }
+        let mut params = vec![];
         let (kind, return_type) = match func.name.to_string().to_lowercase().as_str() {
             "avg" => (AggKind::Avg, args[0].return_type()),
             "count" => {
@@ -115,72 +207,119 @@ impl Binder {
                     )
                 }
             }
-            "dp_count" => {
-                if args.is_empty() {
-                    return Err(BindError::InvalidExpression(
-                        "Unsupported dp_count(*), please use dp_count(1, epsilon)".to_string(),
-                    ));
-                } else {
-                    if args.len() != 2 {
-                        return Err(BindError::InvalidExpression(
-                            "dp_count usage: dp_count(col, epsilon)".to_string(),
-                        ));
-                    }
-                    let epsilon = args.pop().unwrap();
-                    let epsilon: f64 = match epsilon {
-                        BoundExpr::Constant(v) => match v {
-                            DataValue::Int32(x) => x.into(),
-                            DataValue::Float64(x) => x.into(),
-                            _ => {
-                                return Err(BindError::InvalidExpression(
-                                    "dp_count usage: dp_count(col, epsilon)".to_string(),
-                                ));
-                            }
-                        },
-                        _ => {
-                            return Err(BindError::InvalidExpression(
-                                "dp_count usage: dp_count(col, epsilon)".to_string(),
-                            ));
-                        }
-                    };
-                    (
-                        AggKind::DPCount(epsilon),
-                        Some(DataType::new(DataTypeKind::Float(None), false)),
-                    )
-                }
+            "dp_count" if args.is_empty() => {
+                return Err(BindError::InvalidExpression(
+                    "Unsupported dp_count(*), please use dp_count(1, epsilon)".to_string(),
+                ));
             }
-            "dp_sum" => {
-                if args.len() != 2 {
-                    return Err(BindError::InvalidExpression(
-                        "dp_sum usage: dp_sum(col, epsilon)".to_string(),
-                    ));
-                }
-                let epsilon = args.pop().unwrap();
-                let epsilon: f64 = match epsilon {
-                    BoundExpr::Constant(v) => match v {
-                        DataValue::Int32(x) => x.into(),
-                        DataValue::Float64(x) => x.into(),
-                        _ => {
-                            return Err(BindError::InvalidExpression(
-                                "dp_sum usage: dp_count(col, epsilon)".to_string(),
-                            ));
-                        }
-                    },
+            // `dp_count_distinct(col, user, epsilon, max_contributions)` bounds the number of
+            // distinct values counted for each user
+            "dp_count_distinct" if args.len() == 4 => {
+                let max_contributions = match args.pop() {
+                    Some(BoundExpr::Constant(DataValue::Int32(x))) if x > 0 => x,
                     _ => {
                         return Err(BindError::InvalidExpression(
-                            "dp_sum usage: dp_count(col, epsilon)".to_string(),
-                        ));
+                            "dp_count_distinct usage: \
+                             dp_count_distinct(col, user, epsilon, max_contributions)"
+                                .to_string(),
+                        ))
                     }
                 };
+                let user = args.remove(1);
+                params.push(pop_epsilon(&mut args, "dp_count_distinct")?);
+                params.push(DataValue::Int32(max_contributions));
+                args.push(user);
                 (
-                    AggKind::DPSum(epsilon),
+                    AggKind::DPCountDistinct,
                     Some(DataType::new(DataTypeKind::Float(None), false)),
                 )
             }
+            name @ ("dp_count" | "dp_sum" | "dp_count_distinct") => {
+                params.push(pop_epsilon(&mut args, name)?);
+                let kind = match name {
+                    "dp_count" => AggKind::DPCount,
+                    "dp_sum" => AggKind::DPSum,
+                    _ => AggKind::DPCountDistinct,
+                };
+                (kind, Some(DataType::new(DataTypeKind::Float(None), false)))
+            }
+            name @ ("stddev" | "stddev_samp" | "stddev_pop" | "variance" | "var_samp"
+            | "var_pop") => {
+                let kind = match name {
+                    "stddev" | "stddev_samp" => AggKind::StddevSamp,
+                    "stddev_pop" => AggKind::StddevPop,
+                    "variance" | "var_samp" => AggKind::VarSamp,
+                    _ => AggKind::VarPop,
+                };
+                check_numeric_args(&args, 1, &format!("{}(x)", name))?;
+                (kind, Some(DataType::new(DataTypeKind::Double, true)))
+            }
+            name @ ("corr" | "covar_samp" | "regr_slope" | "regr_intercept") => {
+                let kind = match name {
+                    "corr" => AggKind::Corr,
+                    "covar_samp" => AggKind::CovarSamp,
+                    "regr_slope" => AggKind::RegrSlope,
+                    _ => AggKind::RegrIntercept,
+                };
+                check_numeric_args(&args, 2, &format!("{}(y, x)", name))?;
+                (kind, Some(DataType::new(DataTypeKind::Double, true)))
+            }
             "max" => (AggKind::Max, args[0].return_type()),
             "min" => (AggKind::Min, args[0].return_type()),
             "sum" => (AggKind::Sum, args[0].return_type()),
-            _ => panic!("Unsupported function: {}", func.name),
+            name if func.distinct => {
+                return Err(BindError::InvalidExpression(format!(
+                    "DISTINCT is not supported in function {}",
+                    name
+                )))
+            }
+            name if filter.is_some() => {
+                return Err(BindError::InvalidExpression(format!(
+                    "FILTER is not supported in non-aggregate function {}",
+                    name
+                )))
+            }
+            name => return self.bind_scalar_function(name, args),
+        };
+        if args
+            .iter()
+            .chain(&filter)
+            .any(|arg| arg.contains_window_call())
+        {
+            return Err(BindError::InvalidExpression(format!(
+                "window functions are not allowed in aggregation {}",
+                kind
+            )));
+        }
+        if let Some(filter) = &filter {
+            if filter.contains_agg_call() {
+                return Err(BindError::InvalidExpression(format!(
+                    "aggregate functions are not allowed in FILTER of {}",
+                    kind
+                )));
+            }
+            if filter.return_type().map(|ty| ty.kind()) != Some(DataTypeKind::Boolean) {
+                return Err(BindError::InvalidExpression(
+                    "argument of FILTER must be type boolean".to_string(),
+                ));
+            }
+        }
+        let filter = filter.map(Box::new);
+        // `count(distinct *)` is not a valid aggregation, and the DP aggregations handle
+        // duplicates by themselves.
+        let distinct = match kind {
+            AggKind::RowCount if func.distinct => {
+                return Err(BindError::InvalidExpression(
+                    "count(distinct *) is not supported".to_string(),
+                ))
+            }
+            AggKind::DPCount | AggKind::DPSum | AggKind::DPCountDistinct if func.distinct => {
+                return Err(BindError::InvalidExpression(format!(
+                    "DISTINCT is not supported in {}",
+                    kind
+                )))
+            }
+            _ => func.distinct,
         };
 
         match kind {
@@ -190,14 +329,20 @@ impl Binder {
                 left_expr: Box::new(BoundExpr::AggCall(BoundAggCall {
                     kind: AggKind::Sum,
                     args: args.clone(),
+                    params: vec![],
+                    filter: filter.clone(),
                     return_type: args[0].return_type().unwrap(),
+                    distinct,
                 })),
                 right_expr: Box::new(BoundExpr::TypeCast(BoundTypeCast {
                     ty: args[0].return_type().unwrap().kind(),
                     expr: Box::new(BoundExpr::AggCall(BoundAggCall {
                         kind: AggKind::Count,
                         args,
+                        params: vec![],
+                        filter,
                         return_type: DataType::new(DataTypeKind::Int(None), false),
+                        distinct,
                     })),
                 })),
                 return_type,
@@ -205,8 +350,45 @@ impl Binder {
             _ => Ok(BoundExpr::AggCall(BoundAggCall {
                 kind,
                 args,
+                params,
+                filter,
                 return_type: return_type.unwrap(),
+                distinct,
             })),
         }
     }
 }
+
+/// Pops the trailing `epsilon` argument of a DP aggregation `name(col, epsilon)`.
+fn pop_epsilon(args: &mut Vec<BoundExpr>, name: &str) -> Result<DataValue, BindError> {
+    let epsilon: f64 = match (args.len(), args.last()) {
+        (2, Some(BoundExpr::Constant(DataValue::Int32(x)))) if *x > 0 => (*x).into(),
+        (2, Some(BoundExpr::Constant(DataValue::Float64(x)))) if *x > 0.0 => *x,
+        _ => {
+            return Err(BindError::InvalidExpression(format!(
+                "{} usage: {}(col, epsilon)",
+                name, name
+            )))
+        }
+    };
+    args.pop();
+    Ok(DataValue::Float64(epsilon))
+}
+
+/// Checks that there are `num_args` numeric arguments, as in `usage`.
+fn check_numeric_args(args: &[BoundExpr], num_args: usize, usage: &str) -> Result<(), BindError> {
+    let is_numeric = |arg: &BoundExpr| match arg.return_type() {
+        Some(ty) => matches!(
+            ty.physical_kind(),
+            PhysicalDataTypeKind::Int32
+                | PhysicalDataTypeKind::Int64
+                | PhysicalDataTypeKind::Float64
+                | PhysicalDataTypeKind::Decimal
+        ),
+        None => false,
+    };
+    if args.len() != num_args || !args.iter().all(is_numeric) {
+        return Err(BindError::InvalidExpression(format!("usage: {}", usage)));
+    }
+    Ok(())
+}
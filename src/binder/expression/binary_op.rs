@@ -44,6 +44,28 @@ impl Binder {
         let mut left_bound_expr = self.bind_expr(left)?;
         let mut right_bound_expr = self.bind_expr(right)?;
 
+        let physical_kinds = (
+            left_bound_expr.return_type().map(|t| t.physical_kind()),
+            right_bound_expr.return_type().map(|t| t.physical_kind()),
+        );
+        // Normalize `interval + date` into `date + interval`.
+        if *op == Op::Plus
+            && matches!(
+                physical_kinds,
+                (Some(Interval), Some(Date | Timestamp | TimestampTz))
+            )
+        {
+            std::mem::swap(&mut left_bound_expr, &mut right_bound_expr);
+        }
+        // The difference of two dates is the number of days between them, and the
+        // difference of two timestamps is an interval.
+        let is_date_diff = *op == Op::Minus && physical_kinds == (Some(Date), Some(Date));
+        let is_timestamp_diff = *op == Op::Minus
+            && matches!(
+                physical_kinds,
+                (Some(Timestamp), Some(Timestamp)) | (Some(TimestampTz), Some(TimestampTz))
+            );
+
         // Implicit type cast
         let left_data_type_kind = match (
             left_bound_expr.return_type(),
@@ -60,6 +82,7 @@ impl Binder {
                         (Float64 | Decimal, Int32 | Int64)
                         | (Int64, Int32)
                         | (Date, String)
+                        | (Timestamp | TimestampTz, String | Date)
                         | (Decimal, Float64) => {
                             right_bound_expr = BoundExpr::TypeCast(BoundTypeCast {
                                 expr: Box::new(right_bound_expr),
@@ -69,6 +92,7 @@ impl Binder {
                         (Int32 | Int64, Float64 | Decimal)
                         | (Int32, Int64)
                         | (String, Date)
+                        | (String | Date, Timestamp | TimestampTz)
                         | (Float64, Decimal) => {
                             left_bound_expr = BoundExpr::TypeCast(BoundTypeCast {
                                 expr: Box::new(left_bound_expr),
@@ -76,7 +100,7 @@ impl Binder {
                             });
                             return_type_tmp = right_data_type.kind();
                         }
-                        (Date, Interval) => {}
+                        (Date | Timestamp | TimestampTz, Interval) => {}
                         (left_kind, right_kind) => todo!(
                             "Support implicit conversion of {:?} and {:?}",
                             left_kind,
@@ -96,10 +120,21 @@ impl Binder {
         };
 
         let return_type = match op {
+            Op::Minus if is_date_diff => Some(DataTypeKind::Int(None).nullable()),
+            Op::Minus if is_timestamp_diff => Some(DataTypeKind::Interval.nullable()),
             Op::Plus | Op::Minus | Op::Multiply | Op::Divide | Op::Modulo => left_data_type_kind,
-            Op::Gt | Op::GtEq | Op::Lt | Op::LtEq | Op::Eq | Op::NotEq | Op::And | Op::Or => {
-                Some(DataTypeKind::Boolean.nullable())
-            }
+            Op::Gt
+            | Op::GtEq
+            | Op::Lt
+            | Op::LtEq
+            | Op::Eq
+            | Op::NotEq
+            | Op::And
+            | Op::Or
+            | Op::Like
+            | Op::NotLike
+            | Op::ILike
+            | Op::NotILike => Some(DataTypeKind::Boolean.nullable()),
             _ => todo!("Support more binary operators"),
         };
         Ok(BoundExpr::BinaryOp(BoundBinaryOp {
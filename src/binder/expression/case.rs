@@ -0,0 +1,121 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use serde::Serialize;
+
+use super::*;
+use crate::types::{DataTypeExt, DataTypeKind};
+
+/// A bound `CASE WHEN` expression.
+///
+/// The simple form `CASE x WHEN a THEN ...` is normalized into the searched form
+/// `CASE WHEN x = a THEN ...` by the binder, so every condition is a boolean expression.
+#[derive(PartialEq, Clone, Serialize)]
+pub struct BoundCase {
+    pub conditions: Vec<BoundExpr>,
+    pub results: Vec<BoundExpr>,
+    pub else_result: Option<Box<BoundExpr>>,
+    pub return_type: Option<DataType>,
+}
+
+impl std::fmt::Debug for BoundCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Case(")?;
+        for (cond, result) in self.conditions.iter().zip(&self.results) {
+            write!(f, "when {:?} then {:?}, ", cond, result)?;
+        }
+        match &self.else_result {
+            Some(else_result) => write!(f, "else {:?})", else_result),
+            None => write!(f, "else null)"),
+        }
+    }
+}
+
+impl std::fmt::Display for BoundCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CASE")?;
+        for (cond, result) in self.conditions.iter().zip(&self.results) {
+            write!(f, " WHEN {} THEN {}", cond, result)?;
+        }
+        if let Some(else_result) = &self.else_result {
+            write!(f, " ELSE {}", else_result)?;
+        }
+        write!(f, " END")
+    }
+}
+
+impl Binder {
+    pub fn bind_case(
+        &mut self,
+        operand: &Option<Box<Expr>>,
+        conditions: &[Expr],
+        results: &[Expr],
+        else_result: &Option<Box<Expr>>,
+    ) -> Result<BoundExpr, BindError> {
+        let mut bound_conditions = Vec::with_capacity(conditions.len());
+        for cond in conditions {
+            let cond = match operand {
+                Some(operand) => self.bind_binary_op(operand, &BinaryOperator::Eq, cond)?,
+                None => self.bind_expr(cond)?,
+            };
+            match cond.return_type() {
+                Some(ty) if ty.kind() != DataTypeKind::Boolean => {
+                    return Err(BindError::InvalidExpression(format!(
+                        "CASE condition must be boolean, but got {:?}",
+                        ty
+                    )));
+                }
+                _ => bound_conditions.push(cond),
+            }
+        }
+
+        let mut bound_results = Vec::with_capacity(results.len() + 1);
+        for result in results {
+            bound_results.push(self.bind_expr(result)?);
+        }
+        if let Some(else_result) = else_result {
+            bound_results.push(self.bind_expr(else_result)?);
+        }
+        let return_type = unify_types(&mut bound_results)?;
+        let else_result = match else_result {
+            Some(_) => Some(Box::new(bound_results.pop().unwrap())),
+            None => None,
+        };
+
+        Ok(BoundExpr::Case(BoundCase {
+            conditions: bound_conditions,
+            results: bound_results,
+            else_result,
+            return_type,
+        }))
+    }
+
+    /// Bind `COALESCE(a, b, c)` as
+    /// `CASE WHEN a IS NOT NULL THEN a WHEN b IS NOT NULL THEN b ELSE c END`.
+    pub fn bind_coalesce(&mut self, mut args: Vec<BoundExpr>) -> Result<BoundExpr, BindError> {
+        if args.is_empty() {
+            return Err(BindError::InvalidExpression(
+                "coalesce requires at least one argument".to_string(),
+            ));
+        }
+        let return_type = unify_types(&mut args)?;
+        let else_result = args.pop().unwrap();
+        let conditions = args
+            .iter()
+            .map(|arg| {
+                BoundExpr::UnaryOp(BoundUnaryOp {
+                    op: UnaryOperator::Not,
+                    expr: Box::new(BoundExpr::IsNull(BoundIsNull {
+                        expr: Box::new(arg.clone()),
+                    })),
+                    return_type: Some(DataTypeKind::Boolean.not_null()),
+                })
+            })
+            .collect();
+        Ok(BoundExpr::Case(BoundCase {
+            conditions,
+            results: args,
+            else_result: Some(Box::new(else_result)),
+            return_type,
+        }))
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::sync::Arc;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::*;
+use crate::function::Function as ScalarFunction;
+use crate::types::PhysicalDataTypeKind;
+
+/// A bound scalar function call, resolved from the function registry.
+#[derive(Clone, Serialize)]
+pub struct BoundFunctionCall {
+    pub name: String,
+    pub args: Vec<BoundExpr>,
+    pub return_type: DataType,
+    #[serde(skip)]
+    pub func: Arc<dyn ScalarFunction>,
+}
+
+impl PartialEq for BoundFunctionCall {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.args == other.args && self.return_type == other.return_type
+    }
+}
+
+impl std::fmt::Debug for BoundFunctionCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({:?}) -> {:?}",
+            self.name, self.args, self.return_type
+        )
+    }
+}
+
+impl std::fmt::Display for BoundFunctionCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.name,
+            self.args.iter().map(|x| x.to_string()).join(", ")
+        )
+    }
+}
+
+impl Binder {
+    /// Bind a call to a scalar function in the function registry, or a conditional expression
+    /// which is rewritten into `CASE`.
+    pub fn bind_scalar_function(
+        &mut self,
+        name: &str,
+        mut args: Vec<BoundExpr>,
+    ) -> Result<BoundExpr, BindError> {
+        if matches!(name, "coalesce" | "ifnull") {
+            return self.bind_coalesce(args);
+        }
+        let func = self
+            .functions
+            .get(name)
+            .ok_or_else(|| BindError::FunctionNotFound(name.to_string()))?;
+
+        // Untyped `NULL` arguments take the type of the first typed argument.
+        let null_type = args
+            .iter()
+            .find_map(|arg| arg.return_type())
+            .map_or(PhysicalDataTypeKind::String, |ty| ty.physical_kind());
+        for arg in &mut args {
+            if arg.return_type().is_none() {
+                let inner = std::mem::replace(arg, BoundExpr::Constant(DataValue::Null));
+                *arg = BoundExpr::TypeCast(BoundTypeCast {
+                    expr: Box::new(inner),
+                    ty: null_type.default_logical_kind(),
+                });
+            }
+        }
+
+        let arg_types = args
+            .iter()
+            .map(|arg| arg.return_type().unwrap().physical_kind())
+            .collect_vec();
+        let physical_kind = func
+            .return_types(&arg_types)
+            .map_err(|e| BindError::InvalidExpression(format!("{}: {}", name, e)))?;
+        // Keep the logical type of the first argument if the function preserves it,
+        // e.g. `upper(varchar(10))` is still `varchar(10)`.
+        let kind = match args.first().and_then(|arg| arg.return_type()) {
+            Some(ty) if ty.physical_kind() == physical_kind => ty.kind(),
+            _ => physical_kind.default_logical_kind(),
+        };
+
+        Ok(BoundExpr::FunctionCall(BoundFunctionCall {
+            name: name.to_lowercase(),
+            args,
+            return_type: kind.nullable(),
+            func,
+        }))
+    }
+
+    /// Bind `SUBSTRING(expr FROM start FOR count)`.
+    pub fn bind_substring(
+        &mut self,
+        expr: &Expr,
+        from: &Option<Box<Expr>>,
+        count: &Option<Box<Expr>>,
+    ) -> Result<BoundExpr, BindError> {
+        let mut args = vec![self.bind_expr(expr)?];
+        match from {
+            Some(from) => args.push(self.bind_expr(from)?),
+            None => args.push(BoundExpr::Constant(DataValue::Int32(1))),
+        }
+        if let Some(count) = count {
+            args.push(self.bind_expr(count)?);
+        }
+        self.bind_scalar_function("substring", args)
+    }
+}
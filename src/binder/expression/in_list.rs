@@ -0,0 +1,63 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::*;
+
+/// A bound `expr [NOT] IN (list)` expression.
+#[derive(PartialEq, Clone, Serialize)]
+pub struct BoundInList {
+    pub expr: Box<BoundExpr>,
+    pub list: Vec<BoundExpr>,
+    pub negated: bool,
+}
+
+impl std::fmt::Debug for BoundInList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({:?}, {:?})",
+            if self.negated { "NotIn" } else { "In" },
+            self.expr,
+            self.list
+        )
+    }
+}
+
+impl std::fmt::Display for BoundInList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({} {}IN ({}))",
+            self.expr,
+            if self.negated { "NOT " } else { "" },
+            self.list.iter().map(|e| e.to_string()).join(", ")
+        )
+    }
+}
+
+impl Binder {
+    pub fn bind_in_list(
+        &mut self,
+        expr: &Expr,
+        list: &[Expr],
+        negated: bool,
+    ) -> Result<BoundExpr, BindError> {
+        let mut exprs = Vec::with_capacity(list.len() + 1);
+        exprs.push(self.bind_expr(expr)?);
+        for item in list {
+            exprs.push(self.bind_expr(item)?);
+        }
+        // Cast the operand and all list items to a common type, so that the
+        // evaluator can compare them directly.
+        unify_types(&mut exprs)?;
+        let list = exprs.split_off(1);
+        let expr = exprs.pop().unwrap();
+        Ok(BoundExpr::InList(BoundInList {
+            expr: Box::new(expr),
+            list,
+            negated,
+        }))
+    }
+}
@@ -7,25 +7,35 @@ use sqlparser::ast::BinaryOperator;
 use super::*;
 use crate::catalog::ColumnRefId;
 use crate::parser::{DateTimeField, Expr, Function, UnaryOperator, Value};
-use crate::types::{DataType, DataTypeExt, DataTypeKind, DataValue, Interval};
+use crate::types::{
+    is_timestamptz, ConvertError, DataType, DataTypeExt, DataTypeKind, DataValue, Interval,
+};
 
 mod agg_call;
 mod binary_op;
+mod case;
 mod column_ref;
 mod expr_with_alias;
+mod function_call;
+mod in_list;
 mod input_ref;
 mod isnull;
 mod type_cast;
 mod unary_op;
+mod window;
 
 pub use self::agg_call::*;
 pub use self::binary_op::*;
+pub use self::case::*;
 pub use self::column_ref::*;
 pub use self::expr_with_alias::*;
+pub use self::function_call::*;
+pub use self::in_list::*;
 pub use self::input_ref::*;
 pub use self::isnull::*;
 pub use self::type_cast::*;
 pub use self::unary_op::*;
+pub use self::window::*;
 
 /// A bound expression.
 #[derive(PartialEq, Clone, Serialize)]
@@ -38,9 +48,13 @@ pub enum BoundExpr {
     UnaryOp(BoundUnaryOp),
     TypeCast(BoundTypeCast),
     AggCall(BoundAggCall),
+    FunctionCall(BoundFunctionCall),
     IsNull(BoundIsNull),
+    Case(BoundCase),
+    InList(BoundInList),
     ExprWithAlias(BoundExprWithAlias),
     Alias(BoundAlias),
+    WindowCall(BoundWindowCall),
 }
 
 impl BoundExpr {
@@ -52,10 +66,14 @@ impl BoundExpr {
             Self::UnaryOp(expr) => expr.return_type.clone(),
             Self::TypeCast(expr) => Some(expr.ty.clone().nullable()),
             Self::AggCall(expr) => Some(expr.return_type.clone()),
+            Self::FunctionCall(expr) => Some(expr.return_type.clone()),
             Self::InputRef(expr) => Some(expr.return_type.clone()),
             Self::IsNull(_) => Some(DataTypeKind::Boolean.not_null()),
+            Self::Case(expr) => expr.return_type.clone(),
+            Self::InList(_) => Some(DataTypeKind::Boolean.nullable()),
             Self::ExprWithAlias(expr) => expr.expr.return_type(),
             Self::Alias(_) => None,
+            Self::WindowCall(expr) => Some(expr.return_type.clone()),
         }
     }
 
@@ -134,6 +152,18 @@ impl BoundExpr {
         visitor.0
     }
 
+    pub fn contains_window_call(&self) -> bool {
+        struct Visitor(bool);
+        impl ExprVisitor for Visitor {
+            fn visit_window_call(&mut self, _: &BoundWindowCall) {
+                self.0 = true;
+            }
+        }
+        let mut visitor = Visitor(false);
+        visitor.visit_expr(self);
+        visitor.0
+    }
+
     pub fn format_name(&self, child_schema: &Vec<ColumnDesc>) -> String {
         match self {
             Self::Constant(DataValue::Int64(num)) => format!("{}", num),
@@ -163,10 +193,14 @@ impl std::fmt::Debug for BoundExpr {
             Self::UnaryOp(expr) => write!(f, "{:?}", expr)?,
             Self::TypeCast(expr) => write!(f, "{:?}", expr)?,
             Self::AggCall(expr) => write!(f, "{:?} (agg)", expr)?,
+            Self::FunctionCall(expr) => write!(f, "{:?}", expr)?,
             Self::InputRef(expr) => write!(f, "InputRef #{:?}", expr)?,
             Self::IsNull(expr) => write!(f, "{:?} (isnull)", expr)?,
+            Self::Case(expr) => write!(f, "{:?}", expr)?,
+            Self::InList(expr) => write!(f, "{:?}", expr)?,
             Self::ExprWithAlias(expr) => write!(f, "{:?}", expr)?,
             Self::Alias(expr) => write!(f, "{:?}", expr)?,
+            Self::WindowCall(expr) => write!(f, "{:?} (window)", expr)?,
         }
         Ok(())
     }
@@ -181,10 +215,14 @@ impl std::fmt::Display for BoundExpr {
             Self::UnaryOp(expr) => write!(f, "{:?}", expr)?,
             Self::TypeCast(expr) => write!(f, "{}", expr)?,
             Self::AggCall(expr) => write!(f, "{:?} (agg)", expr)?,
+            Self::FunctionCall(expr) => write!(f, "{}", expr)?,
             Self::InputRef(expr) => write!(f, "InputRef #{:?}", expr)?,
             Self::IsNull(expr) => write!(f, "{:?} (isnull)", expr)?,
+            Self::Case(expr) => write!(f, "{}", expr)?,
+            Self::InList(expr) => write!(f, "{}", expr)?,
             Self::ExprWithAlias(expr) => write!(f, "{}", expr)?,
             Self::Alias(expr) => write!(f, "{:?}", expr)?,
+            Self::WindowCall(expr) => write!(f, "{} (window)", expr)?,
         }
         Ok(())
     }
@@ -194,7 +232,7 @@ impl Binder {
     /// Bind an expression.
     pub fn bind_expr(&mut self, expr: &Expr) -> Result<BoundExpr, BindError> {
         match expr {
-            Expr::Value(v) => Ok(BoundExpr::Constant(v.into())),
+            Expr::Value(v) => Ok(BoundExpr::Constant(v.try_into()?)),
             Expr::Identifier(ident) => self.bind_column_ref(std::slice::from_ref(ident)),
             Expr::CompoundIdentifier(idents) => self.bind_column_ref(idents),
             Expr::BinaryOp { left, op, right } => self.bind_binary_op(left, op, right),
@@ -218,6 +256,35 @@ impl Binder {
                 low,
                 high,
             } => self.bind_between(expr, negated, low, high),
+            Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => self.bind_case(operand, conditions, results, else_result),
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => self.bind_in_list(expr, list, *negated),
+            Expr::Extract { field, expr } => {
+                let field =
+                    BoundExpr::Constant(DataValue::String(field.to_string().to_lowercase()));
+                let expr = self.bind_expr(expr)?;
+                self.bind_scalar_function("date_part", vec![field, expr])
+            }
+            Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => self.bind_substring(expr, substring_from, substring_for),
+            Expr::Trim {
+                expr,
+                trim_where: None,
+            } => {
+                let arg = self.bind_expr(expr)?;
+                self.bind_scalar_function("trim", vec![arg])
+            }
             _ => todo!("bind expression: {:?}", expr),
         }
     }
@@ -234,6 +301,18 @@ impl Binder {
                 })?;
                 Ok(BoundExpr::Constant(DataValue::Date(date)))
             }
+            DataTypeKind::Timestamp => {
+                let timestamp = value.parse().map_err(|_| {
+                    BindError::CastError(DataValue::String(value.into()), DataTypeKind::Timestamp)
+                })?;
+                Ok(BoundExpr::Constant(DataValue::Timestamp(timestamp)))
+            }
+            t if is_timestamptz(t) => {
+                let timestamp = value.parse().map_err(|_| {
+                    BindError::CastError(DataValue::String(value.into()), t.clone())
+                })?;
+                Ok(BoundExpr::Constant(DataValue::TimestampTz(timestamp)))
+            }
             t => todo!("support typed string: {:?}", t),
         }
     }
@@ -263,9 +342,68 @@ impl Binder {
     }
 }
 
-impl From<&Value> for DataValue {
-    fn from(v: &Value) -> Self {
-        match v {
+/// Find the common type of `exprs` and cast the others to it, following the same
+/// implicit conversion rules as binary operators. Returns `None` if all expressions
+/// are `NULL` constants.
+fn unify_types(exprs: &mut [BoundExpr]) -> Result<Option<DataType>, BindError> {
+    use crate::types::PhysicalDataTypeKind as P;
+
+    fn numeric_rank(kind: &P) -> Option<u8> {
+        match kind {
+            P::Int32 => Some(0),
+            P::Int64 => Some(1),
+            P::Float64 => Some(2),
+            P::Decimal => Some(3),
+            _ => None,
+        }
+    }
+
+    let mut target: Option<DataType> = None;
+    for ty in exprs.iter().filter_map(|e| e.return_type()) {
+        target = Some(match target {
+            None => ty,
+            Some(t) if t.physical_kind() == ty.physical_kind() => t,
+            Some(t) => match (t.physical_kind(), ty.physical_kind()) {
+                (P::Date | P::Timestamp | P::TimestampTz, P::String) => t,
+                (P::String, P::Date | P::Timestamp | P::TimestampTz) => ty,
+                (P::Timestamp | P::TimestampTz, P::Date) => t,
+                (P::Date, P::Timestamp | P::TimestampTz) => ty,
+                (l, r) => match (numeric_rank(&l), numeric_rank(&r)) {
+                    (Some(a), Some(b)) if a >= b => t,
+                    (Some(_), Some(_)) => ty,
+                    _ => {
+                        return Err(BindError::BinaryOpTypeMismatch(
+                            format!("{:?}", t),
+                            format!("{:?}", ty),
+                        ))
+                    }
+                },
+            },
+        });
+    }
+
+    if let Some(target) = &target {
+        for expr in exprs.iter_mut() {
+            match expr.return_type() {
+                Some(ty) if ty.physical_kind() != target.physical_kind() => {
+                    let inner = std::mem::replace(expr, BoundExpr::Constant(DataValue::Null));
+                    *expr = BoundExpr::TypeCast(BoundTypeCast {
+                        expr: Box::new(inner),
+                        ty: target.kind(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(target.map(|t| t.kind().nullable()))
+}
+
+impl TryFrom<&Value> for DataValue {
+    type Error = ConvertError;
+
+    fn try_from(v: &Value) -> Result<Self, Self::Error> {
+        Ok(match v {
             Value::Number(n, _) => {
                 if let Ok(int) = n.parse::<i32>() {
                     Self::Int32(int)
@@ -285,20 +423,33 @@ impl From<&Value> for DataValue {
                 value,
                 leading_field,
                 ..
-            } => match leading_field {
-                Some(DateTimeField::Day) => {
-                    Self::Interval(Interval::from_days(value.parse().unwrap()))
-                }
-                Some(DateTimeField::Month) => {
-                    Self::Interval(Interval::from_months(value.parse().unwrap()))
-                }
-                Some(DateTimeField::Year) => {
-                    Self::Interval(Interval::from_years(value.parse().unwrap()))
-                }
-                _ => todo!("Support interval with leading field: {:?}", leading_field),
-            },
+            } => {
+                let err = || ConvertError::ParseInterval(value.clone());
+                let interval = match leading_field {
+                    Some(DateTimeField::Day) => {
+                        Interval::from_days(value.parse().map_err(|_| err())?)
+                    }
+                    Some(DateTimeField::Month) => {
+                        Interval::from_months(value.parse().map_err(|_| err())?)
+                    }
+                    Some(DateTimeField::Year) => {
+                        Interval::from_years(value.parse().map_err(|_| err())?)
+                    }
+                    Some(DateTimeField::Hour) => {
+                        Interval::from_hours(value.parse().map_err(|_| err())?)
+                    }
+                    Some(DateTimeField::Minute) => {
+                        Interval::from_minutes(value.parse().map_err(|_| err())?)
+                    }
+                    Some(DateTimeField::Second) => {
+                        Interval::from_seconds(value.parse().map_err(|_| err())?)
+                    }
+                    _ => todo!("Support interval with leading field: {:?}", leading_field),
+                };
+                Self::Interval(interval)
+            }
             _ => todo!("parse value: {:?}", v),
-        }
+        })
     }
 }
 
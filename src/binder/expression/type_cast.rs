@@ -3,7 +3,7 @@
 use serde::Serialize;
 
 use super::*;
-use crate::types::DataTypeKind;
+use crate::types::{is_timestamptz, timestamptz_kind, DataTypeKind};
 
 /// A bound type cast expression.
 #[derive(PartialEq, Clone, Serialize)]
@@ -19,12 +19,15 @@ impl Binder {
         mut ty: DataTypeKind,
     ) -> Result<BoundExpr, BindError> {
         let bound_expr = self.bind_expr(expr)?;
-        // workaround for 'BLOB'
+        // workaround for 'BLOB' and 'TIMESTAMPTZ'
         if let DataTypeKind::Custom(name) = &ty {
             if name.0.len() == 1 && name.0[0].value.to_lowercase() == "blob" {
                 ty = DataTypeKind::Blob(0);
             }
         }
+        if is_timestamptz(&ty) {
+            ty = timestamptz_kind();
+        }
         Ok(BoundExpr::TypeCast(BoundTypeCast {
             expr: (bound_expr.into()),
             ty,
@@ -0,0 +1,327 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt::Formatter;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use super::*;
+use crate::binder::{BindError, Binder, BoundExpr, BoundOrderBy};
+use crate::parser::{
+    Function, FunctionArg, FunctionArgExpr, WindowFrame, WindowFrameBound, WindowFrameUnits,
+    WindowSpec,
+};
+use crate::types::{DataType, DataTypeKind};
+
+/// Window function kind
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum WindowFuncKind {
+    RowNumber,
+    Rank,
+    DenseRank,
+    /// The value of the row at an offset before the current row in the partition.
+    Lag(usize),
+    /// The value of the row at an offset after the current row in the partition.
+    Lead(usize),
+    /// An aggregation over the window frame of the current row.
+    Agg(AggKind),
+}
+
+impl std::fmt::Display for WindowFuncKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use WindowFuncKind::*;
+        match self {
+            RowNumber => write!(f, "row_number"),
+            Rank => write!(f, "rank"),
+            DenseRank => write!(f, "dense_rank"),
+            Lag(offset) => write!(f, "lag(offset={})", offset),
+            Lead(offset) => write!(f, "lead(offset={})", offset),
+            Agg(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
+/// The frame of rows that a window aggregation is computed over, relative to the current row.
+///
+/// `RANGE` frames only support `UNBOUNDED` and `CURRENT ROW` bounds, where the current row
+/// includes its peers with the same values of the order keys.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct BoundWindowFrame {
+    pub units: WindowFrameUnits,
+    pub start: WindowFrameBound,
+    pub end: WindowFrameBound,
+}
+
+impl BoundWindowFrame {
+    /// The frame without an explicit frame clause: from the start of the partition to the
+    /// current row and its peers if the window is ordered, or the whole partition otherwise.
+    pub fn default_for(ordered: bool) -> Self {
+        BoundWindowFrame {
+            units: WindowFrameUnits::Range,
+            start: WindowFrameBound::Preceding(None),
+            end: match ordered {
+                true => WindowFrameBound::CurrentRow,
+                false => WindowFrameBound::Following(None),
+            },
+        }
+    }
+}
+
+/// Represents a window function call
+#[derive(PartialEq, Clone, Serialize)]
+pub struct BoundWindowCall {
+    pub kind: WindowFuncKind,
+    pub args: Vec<BoundExpr>,
+    pub partition_by: Vec<BoundExpr>,
+    pub order_by: Vec<BoundOrderBy>,
+    pub frame: BoundWindowFrame,
+    pub return_type: DataType,
+}
+
+impl std::fmt::Debug for BoundWindowCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({:?}) over (partition by {:?} order by {:?} {:?}) -> {:?}",
+            self.kind, self.args, self.partition_by, self.order_by, self.frame, self.return_type
+        )
+    }
+}
+
+impl std::fmt::Display for BoundWindowCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}) over (partition by [{}] order by {:?}) -> {}",
+            self.kind,
+            self.args.iter().map(|x| format!("{}", x)).join(", "),
+            self.partition_by
+                .iter()
+                .map(|x| format!("{}", x))
+                .join(", "),
+            self.order_by,
+            self.return_type
+        )
+    }
+}
+
+/// Rewrites aggregation calls into window aggregations over a window.
+struct WindowAggRewriter<'a> {
+    partition_by: &'a [BoundExpr],
+    order_by: &'a [BoundOrderBy],
+    frame: &'a BoundWindowFrame,
+}
+
+impl ExprRewriter for WindowAggRewriter<'_> {
+    fn rewrite_agg_call(&self, expr: &mut BoundExpr) {
+        let agg = match expr {
+            BoundExpr::AggCall(agg) => agg,
+            _ => unreachable!(),
+        };
+        *expr = BoundExpr::WindowCall(BoundWindowCall {
+            kind: WindowFuncKind::Agg(agg.kind.clone()),
+            args: std::mem::take(&mut agg.args),
+            partition_by: self.partition_by.to_vec(),
+            order_by: self.order_by.to_vec(),
+            frame: self.frame.clone(),
+            return_type: agg.return_type.clone(),
+        });
+    }
+}
+
+impl Binder {
+    /// Bind a function call with an `OVER` clause.
+    pub fn bind_window_function(
+        &mut self,
+        func: &Function,
+        spec: &WindowSpec,
+    ) -> Result<BoundExpr, BindError> {
+        let partition_by: Vec<BoundExpr> = spec
+            .partition_by
+            .iter()
+            .map(|expr| self.bind_expr(expr))
+            .try_collect()?;
+        let order_by: Vec<BoundOrderBy> = spec
+            .order_by
+            .iter()
+            .map(|expr| self.bind_order_by(expr))
+            .try_collect()?;
+        let nested = (partition_by.iter())
+            .chain(order_by.iter().map(|cmp| &cmp.expr))
+            .any(|expr| expr.contains_window_call());
+        if nested {
+            return Err(BindError::InvalidExpression(
+                "window function calls cannot be nested".to_string(),
+            ));
+        }
+        let frame = match &spec.window_frame {
+            Some(frame) => bind_window_frame(frame)?,
+            None => BoundWindowFrame::default_for(!order_by.is_empty()),
+        };
+
+        let name = func.name.to_string().to_lowercase();
+        if !matches!(
+            name.as_str(),
+            "row_number" | "rank" | "dense_rank" | "lag" | "lead"
+        ) {
+            // an aggregation over the window
+            if func.distinct {
+                return Err(BindError::InvalidExpression(format!(
+                    "DISTINCT is not supported in window function {}",
+                    name
+                )));
+            }
+            let mut expr = self.bind_function(&Function {
+                over: None,
+                ..func.clone()
+            })?;
+            if !expr.contains_agg_call() {
+                return Err(BindError::InvalidExpression(format!(
+                    "{} is not a window function",
+                    name
+                )));
+            }
+            #[derive(Default)]
+            struct Visitor {
+                dp: Option<AggKind>,
+                unsupported: bool,
+            }
+            impl ExprVisitor for Visitor {
+                fn visit_agg_call(&mut self, agg: &BoundAggCall) {
+                    if agg.kind.is_differentially_private() {
+                        self.dp.get_or_insert_with(|| agg.kind.clone());
+                    }
+                    self.unsupported |= agg.filter.is_some() || !agg.params.is_empty();
+                }
+            }
+            let mut visitor = Visitor::default();
+            visitor.visit_expr(&expr);
+            // the noise is calibrated to a single output per group, while a window aggregation
+            // outputs one value per row
+            if let Some(kind) = visitor.dp {
+                return Err(BindError::InvalidExpression(format!(
+                    "differentially private aggregation {} is not supported as a window function",
+                    kind
+                )));
+            }
+            if visitor.unsupported {
+                return Err(BindError::InvalidExpression(format!(
+                    "FILTER and parameters are not supported in window function {}",
+                    name
+                )));
+            }
+            let rewriter = WindowAggRewriter {
+                partition_by: &partition_by,
+                order_by: &order_by,
+                frame: &frame,
+            };
+            rewriter.rewrite_expr(&mut expr);
+            return Ok(expr);
+        }
+
+        let mut args = Vec::new();
+        for arg in &func.args {
+            match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => {
+                    args.push(self.bind_expr(expr)?)
+                }
+                _ => {
+                    return Err(BindError::InvalidExpression(format!(
+                        "invalid argument of window function {}",
+                        name
+                    )))
+                }
+            }
+        }
+        if args.iter().any(|arg| arg.contains_window_call()) {
+            return Err(BindError::InvalidExpression(
+                "window function calls cannot be nested".to_string(),
+            ));
+        }
+        let (kind, return_type) = match name.as_str() {
+            "lag" | "lead" => {
+                let usage = format!("{} usage: {}(expr [, offset [, default]])", name, name);
+                if args.is_empty() || args.len() > 3 {
+                    return Err(BindError::InvalidExpression(usage));
+                }
+                let offset = match args.get(1) {
+                    None => 1,
+                    Some(BoundExpr::Constant(DataValue::Int32(offset))) if *offset >= 0 => {
+                        *offset as usize
+                    }
+                    _ => return Err(BindError::InvalidExpression(usage)),
+                };
+                // keep the expression and the default value as arguments
+                if args.len() > 1 {
+                    args.remove(1);
+                }
+                let return_type =
+                    unify_types(&mut args)?.unwrap_or_else(|| DataTypeKind::Int(None).nullable());
+                let kind = match name.as_str() {
+                    "lag" => WindowFuncKind::Lag(offset),
+                    _ => WindowFuncKind::Lead(offset),
+                };
+                (kind, return_type)
+            }
+            _ => {
+                if !args.is_empty() {
+                    return Err(BindError::InvalidExpression(format!(
+                        "{} takes no arguments",
+                        name
+                    )));
+                }
+                let kind = match name.as_str() {
+                    "row_number" => WindowFuncKind::RowNumber,
+                    "rank" => WindowFuncKind::Rank,
+                    _ => WindowFuncKind::DenseRank,
+                };
+                (kind, DataTypeKind::Int(None).not_null())
+            }
+        };
+        Ok(BoundExpr::WindowCall(BoundWindowCall {
+            kind,
+            args,
+            partition_by,
+            order_by,
+            frame,
+            return_type,
+        }))
+    }
+}
+
+fn bind_window_frame(frame: &WindowFrame) -> Result<BoundWindowFrame, BindError> {
+    let end = frame
+        .end_bound
+        .clone()
+        .unwrap_or(WindowFrameBound::CurrentRow);
+    let invalid = |reason: &str| Err(BindError::InvalidExpression(reason.to_string()));
+    match (&frame.start_bound, &end) {
+        (WindowFrameBound::Following(None), _) => {
+            return invalid("frame start cannot be UNBOUNDED FOLLOWING")
+        }
+        (_, WindowFrameBound::Preceding(None)) => {
+            return invalid("frame end cannot be UNBOUNDED PRECEDING")
+        }
+        _ => {}
+    }
+    match frame.units {
+        WindowFrameUnits::Rows => {}
+        WindowFrameUnits::Range => {
+            let has_offset = [&frame.start_bound, &end].into_iter().any(|bound| {
+                matches!(
+                    bound,
+                    WindowFrameBound::Preceding(Some(_)) | WindowFrameBound::Following(Some(_))
+                )
+            });
+            if has_offset {
+                return invalid("RANGE frames with offsets are not supported");
+            }
+        }
+        WindowFrameUnits::Groups => return invalid("GROUPS frames are not supported"),
+    }
+    Ok(BoundWindowFrame {
+        units: frame.units.clone(),
+        start: frame.start_bound.clone(),
+        end,
+    })
+}
@@ -7,8 +7,9 @@ use std::vec::Vec;
 use crate::catalog::{
     ColumnDesc, RootCatalog, TableRefId, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME,
 };
+use crate::function::FunctionRegistry;
 use crate::parser::{Ident, ObjectName, Statement};
-use crate::types::{ColumnId, DataTypeKind, DataValue};
+use crate::types::{ColumnId, ConvertError, DataTypeKind, DataValue};
 
 mod expr_visitor;
 mod expression;
@@ -30,6 +31,7 @@ pub enum BoundStatement {
     Select(Box<BoundSelect>),
     Explain(Box<BoundStatement>),
     Delete(Box<BoundDelete>),
+    Update(Box<BoundUpdate>),
 }
 
 /// The error type of bind operations.
@@ -63,6 +65,12 @@ pub enum BindError {
     InvalidSQL,
     #[error("cannot cast {0:?} to {1:?}")]
     CastError(DataValue, DataTypeKind),
+    #[error("function {0} not found")]
+    FunctionNotFound(String),
+    #[error("invalid table option: {0}")]
+    InvalidTableOption(String),
+    #[error("conversion error: {0}")]
+    Convert(#[from] ConvertError),
 }
 
 /// The context of binder execution.
@@ -86,6 +94,7 @@ pub struct Binder {
     context: BinderContext,
     upper_contexts: Vec<BinderContext>,
     base_table_refs: Vec<String>,
+    functions: Arc<FunctionRegistry>,
 }
 
 impl Binder {
@@ -96,6 +105,7 @@ impl Binder {
             upper_contexts: Vec::new(),
             context: BinderContext::default(),
             base_table_refs: Vec::new(),
+            functions: Arc::new(FunctionRegistry::default()),
         }
     }
 
@@ -118,6 +128,7 @@ impl Binder {
             Statement::Drop { .. } => Ok(BoundStatement::Drop(self.bind_drop(stmt)?)),
             Statement::Insert { .. } => Ok(BoundStatement::Insert(self.bind_insert(stmt)?)),
             Statement::Delete { .. } => Ok(BoundStatement::Delete(self.bind_delete(stmt)?)),
+            Statement::Update { .. } => Ok(BoundStatement::Update(self.bind_update(stmt)?)),
             Statement::Copy { .. } => Ok(BoundStatement::Copy(self.bind_copy(stmt)?)),
             Statement::Query(query) => Ok(BoundStatement::Select(self.bind_select(&*query)?)),
             Statement::Explain { statement, .. } => {
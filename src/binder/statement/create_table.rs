@@ -3,8 +3,8 @@ use sqlparser::ast::TableConstraint;
 
 use super::*;
 use crate::catalog::{ColumnCatalog, ColumnDesc};
-use crate::parser::{ColumnDef, ColumnOption, Statement};
-use crate::types::{DataType, DatabaseId, SchemaId};
+use crate::parser::{ColumnDef, ColumnOption, SqlOption, Statement, Value};
+use crate::types::{is_timestamptz, timestamptz_kind, DataType, DatabaseId, SchemaId};
 
 /// A bound `create table` statement.
 #[derive(Debug, PartialEq, Clone)]
@@ -23,6 +23,7 @@ impl Binder {
                 name,
                 columns,
                 constraints,
+                with_options,
                 ..
             } => {
                 let name = &lower_case_name(name);
@@ -80,6 +81,10 @@ impl Binder {
                     columns[index as usize].set_nullable(false);
                 }
 
+                for option in with_options {
+                    Binder::bind_table_option(option, &mut columns)?;
+                }
+
                 Ok(BoundCreateTable {
                     database_id: db.id(),
                     schema_id: schema.id(),
@@ -92,6 +97,33 @@ impl Binder {
         }
     }
 
+    /// Apply a table option in `WITH (...)` to the columns.
+    ///
+    /// Supported options:
+    /// - `bloom_filter_columns = 'c1, c2'`: build bloom filters on the columns.
+    fn bind_table_option(
+        option: &SqlOption,
+        columns: &mut [ColumnCatalog],
+    ) -> Result<(), BindError> {
+        match option.name.value.to_lowercase().as_str() {
+            "bloom_filter_columns" => {
+                let names = match &option.value {
+                    Value::SingleQuotedString(names) => names,
+                    _ => return Err(BindError::InvalidTableOption(option.to_string())),
+                };
+                for name in names.split(',').map(|name| name.trim().to_lowercase()) {
+                    let column = columns
+                        .iter_mut()
+                        .find(|column| column.name() == name)
+                        .ok_or_else(|| BindError::InvalidColumn(name.clone()))?;
+                    column.set_bloom_filter(true);
+                }
+                Ok(())
+            }
+            _ => Err(BindError::InvalidTableOption(option.to_string())),
+        }
+    }
+
     /// get primary keys' id in declared order。
     /// we use index in columns vector as column id
     fn ordered_pks_from_columns(columns: &[ColumnDef]) -> Vec<ColumnId> {
@@ -164,10 +196,14 @@ impl From<&ColumnDef> for ColumnCatalog {
                 _ => todo!("column options"),
             }
         }
+        let data_type = match &cdef.data_type {
+            ty if is_timestamptz(ty) => timestamptz_kind(),
+            ty => ty.clone(),
+        };
         ColumnCatalog::new(
             0,
             ColumnDesc::new(
-                DataType::new(cdef.data_type.clone(), is_nullable),
+                DataType::new(data_type, is_nullable),
                 cdef.name.value.to_lowercase(),
                 is_primary_,
             ),
@@ -327,4 +363,34 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn bind_create_table_with_options() {
+        let catalog = Arc::new(RootCatalog::new());
+        let mut binder = Binder::new(catalog);
+        let sql = "
+            create table t1 (a int not null, b int) with (bloom_filter_columns = 'A, b');
+            create table t2 (a int not null) with (bloom_filter_columns = 'c');
+            create table t3 (a int not null) with (fill_factor = 10);";
+
+        let stmts = parse(sql).unwrap();
+
+        let mut a = ColumnCatalog::new(0, DataTypeKind::Int(None).not_null().to_column("a".into()));
+        a.set_bloom_filter(true);
+        let mut b = ColumnCatalog::new(1, DataTypeKind::Int(None).nullable().to_column("b".into()));
+        b.set_bloom_filter(true);
+        assert_eq!(
+            binder.bind_create_table(&stmts[0]).unwrap().columns,
+            vec![a, b]
+        );
+
+        assert_eq!(
+            binder.bind_create_table(&stmts[1]),
+            Err(BindError::InvalidColumn("c".into()))
+        );
+        assert!(matches!(
+            binder.bind_create_table(&stmts[2]),
+            Err(BindError::InvalidTableOption(_))
+        ));
+    }
 }
@@ -8,6 +8,7 @@ mod delete;
 pub(crate) mod drop;
 mod insert;
 mod select;
+mod update;
 
 pub use copy::*;
 pub use create_table::*;
@@ -15,3 +16,4 @@ pub use delete::*;
 pub use drop::*;
 pub use insert::*;
 pub use select::*;
+pub use update::*;
@@ -3,7 +3,7 @@ use serde::Serialize;
 
 use super::BoundExpr::*;
 use super::{BoundExpr, BoundTableRef, *};
-use crate::parser::{Query, SelectItem, SetExpr};
+use crate::parser::{OrderByExpr, Query, SelectItem, SetExpr};
 use crate::types::DataValue::Bool;
 
 /// A bound `select` statement.
@@ -103,12 +103,17 @@ impl Binder {
             group_by.push(self.bind_expr(group_key)?);
         }
 
+        for expr in where_clause.iter().chain(&group_by) {
+            if expr.contains_window_call() {
+                return Err(BindError::InvalidExpression(
+                    "window functions are not allowed in WHERE or GROUP BY".to_string(),
+                ));
+            }
+        }
+
         let mut orderby = vec![];
         for e in &query.order_by {
-            orderby.push(BoundOrderBy {
-                expr: self.bind_expr(&e.expr)?,
-                descending: e.asc == Some(false),
-            });
+            orderby.push(self.bind_order_by(e)?);
         }
         // Add referred columns for base table reference
         if let Some(table_ref) = &mut from_table {
@@ -126,6 +131,17 @@ impl Binder {
         }))
     }
 
+    /// Bind an `ORDER BY` item of a query or a window.
+    pub fn bind_order_by(&mut self, e: &OrderByExpr) -> Result<BoundOrderBy, BindError> {
+        let descending = e.asc == Some(false);
+        Ok(BoundOrderBy {
+            expr: self.bind_expr(&e.expr)?,
+            descending,
+            // NULL is less than any non-NULL values by default
+            nulls_first: e.nulls_first.unwrap_or(!descending),
+        })
+    }
+
     pub fn bind_column_ids(&self, table_ref: &mut BoundTableRef) {
         match table_ref {
             BoundTableRef::BaseTableRef {
@@ -155,6 +171,8 @@ impl Binder {
 pub struct BoundOrderBy {
     pub expr: BoundExpr,
     pub descending: bool,
+    /// Whether NULLs come before non-NULL values, regardless of `descending`.
+    pub nulls_first: bool,
 }
 
 impl std::fmt::Debug for BoundOrderBy {
@@ -164,6 +182,14 @@ impl std::fmt::Debug for BoundOrderBy {
             "{:?} ({})",
             self.expr,
             if self.descending { "desc" } else { "asc" }
-        )
+        )?;
+        if self.nulls_first == self.descending {
+            write!(
+                f,
+                " nulls {}",
+                if self.nulls_first { "first" } else { "last" }
+            )?;
+        }
+        Ok(())
     }
 }
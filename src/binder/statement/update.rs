@@ -0,0 +1,101 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use super::*;
+use crate::parser::{Assignment, TableFactor};
+
+/// A bound `update` statement.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BoundUpdate {
+    pub from_table: BoundTableRef,
+    /// The new value of every column in the table, in the order of the column catalog.
+    /// Columns not mentioned in `SET` keep their old value as a column reference.
+    pub values: Vec<BoundExpr>,
+    pub where_clause: Option<BoundExpr>,
+}
+
+impl Binder {
+    pub fn bind_update(&mut self, stmt: &Statement) -> Result<Box<BoundUpdate>, BindError> {
+        self.push_context();
+        let ret = self.bind_update_internal(stmt);
+        self.pop_context();
+        ret
+    }
+
+    fn bind_update_internal(&mut self, stmt: &Statement) -> Result<Box<BoundUpdate>, BindError> {
+        if let Statement::Update {
+            table,
+            assignments,
+            from,
+            selection,
+        } = stmt
+        {
+            if from.is_some() || !table.joins.is_empty() {
+                return Err(BindError::InvalidExpression(
+                    "UPDATE with multiple tables is not supported".into(),
+                ));
+            }
+            let table_name = match &table.relation {
+                TableFactor::Table { name, args, .. } if args.is_empty() => lower_case_name(name),
+                _ => return Err(BindError::InvalidSQL),
+            };
+            let (database_name, schema_name, table_name) = split_name(&table_name)?;
+            let mut from_table =
+                self.bind_table_ref_with_name(database_name, schema_name, table_name)?;
+
+            // The updated rows are appended as a whole, so all columns have to be scanned.
+            let mut values = self.bind_all_column_refs()?;
+            let mut assigned = HashSet::new();
+            for Assignment { id, value } in assignments {
+                let column = match self.bind_column_ref(id)? {
+                    BoundExpr::ColumnRef(column) => column,
+                    _ => return Err(BindError::InvalidColumn(format!("{:?}", id))),
+                };
+                let index = values
+                    .iter()
+                    .position(|v| matches!(v, BoundExpr::ColumnRef(c) if c.column_ref_id == column.column_ref_id))
+                    .unwrap();
+                if !assigned.insert(index) {
+                    return Err(BindError::DuplicatedColumn(column.desc.name().into()));
+                }
+                let mut value = self.bind_expr(value)?;
+                if value.contains_agg_call() || value.contains_window_call() {
+                    return Err(BindError::InvalidExpression(
+                        "aggregate and window functions are not allowed in UPDATE".into(),
+                    ));
+                }
+                match value.return_type() {
+                    Some(ty) if ty.physical_kind() != column.desc.datatype().physical_kind() => {
+                        value = BoundExpr::TypeCast(BoundTypeCast {
+                            expr: Box::new(value),
+                            ty: column.desc.datatype().kind(),
+                        });
+                    }
+                    Some(_) => {}
+                    None if !column.desc.is_nullable() => {
+                        return Err(BindError::NotNullableColumn(column.desc.name().into()));
+                    }
+                    None => {
+                        value = BoundExpr::TypeCast(BoundTypeCast {
+                            expr: Box::new(value),
+                            ty: column.desc.datatype().kind(),
+                        });
+                    }
+                }
+                values[index] = value;
+            }
+
+            let where_clause = selection
+                .as_ref()
+                .map(|expr| self.bind_expr(expr))
+                .transpose()?;
+            self.bind_column_ids(&mut from_table);
+            Ok(Box::new(BoundUpdate {
+                from_table,
+                values,
+                where_clause,
+            }))
+        } else {
+            panic!("unmatched statement type")
+        }
+    }
+}
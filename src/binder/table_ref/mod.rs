@@ -2,12 +2,16 @@
 
 use std::vec::Vec;
 
+use itertools::Itertools;
 use serde::Serialize;
 
 use super::BoundExpr::*;
 use super::*;
 use crate::catalog::INTERNAL_SCHEMA_NAME;
-use crate::parser::{JoinConstraint, JoinOperator, TableFactor, TableWithJoins};
+use crate::parser::{
+    Expr, FunctionArg, FunctionArgExpr, JoinConstraint, JoinOperator, TableFactor, TableWithJoins,
+    Value,
+};
 use crate::types::DataValue::Bool;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -26,6 +30,8 @@ pub enum BoundTableRef {
         column_ids: Vec<ColumnId>,
         column_descs: Vec<ColumnDesc>,
         is_internal: bool,
+        /// The epoch to read from, set by `AS OF EPOCH`.
+        as_of_epoch: Option<u64>,
     },
     JoinTableRef {
         relation: Box<BoundTableRef>,
@@ -145,6 +151,7 @@ impl Binder {
             column_ids: vec![],
             column_descs: vec![],
             is_internal: schema_name == INTERNAL_SCHEMA_NAME,
+            as_of_epoch: None,
         };
         self.base_table_refs.push(table_name.into());
         Ok(base_table_ref)
@@ -152,15 +159,52 @@ impl Binder {
 
     pub fn bind_table_ref(&mut self, table: &TableFactor) -> Result<BoundTableRef, BindError> {
         match table {
-            TableFactor::Table { name, alias, .. } => {
+            TableFactor::Table {
+                name, alias, args, ..
+            } => {
                 let name = &lower_case_name(name);
                 let (database_name, schema_name, mut table_name) = split_name(name)?;
                 if let Some(alias) = alias {
                     table_name = &alias.name.value;
                 }
-                self.bind_table_ref_with_name(database_name, schema_name, table_name)
+                let epoch = self.bind_as_of_epoch(args)?;
+                let mut table_ref =
+                    self.bind_table_ref_with_name(database_name, schema_name, table_name)?;
+                if let BoundTableRef::BaseTableRef {
+                    is_internal,
+                    as_of_epoch,
+                    ..
+                } = &mut table_ref
+                {
+                    if *is_internal && epoch.is_some() {
+                        return Err(BindError::InvalidExpression(
+                            "internal tables can not be read AS OF EPOCH".into(),
+                        ));
+                    }
+                    *as_of_epoch = epoch;
+                }
+                Ok(table_ref)
             }
             _ => panic!("bind table ref"),
         }
     }
+
+    /// Bind the table arguments `(epoch => n)`, which is rewritten from `AS OF EPOCH n` by the
+    /// parser.
+    fn bind_as_of_epoch(&mut self, args: &[FunctionArg]) -> Result<Option<u64>, BindError> {
+        match args {
+            [] => Ok(None),
+            [FunctionArg::Named {
+                name,
+                arg: FunctionArgExpr::Expr(Expr::Value(Value::Number(epoch, _))),
+            }] if name.value.eq_ignore_ascii_case("epoch") => epoch
+                .parse()
+                .map(Some)
+                .map_err(|_| BindError::InvalidExpression(format!("invalid epoch {}", epoch))),
+            _ => Err(BindError::InvalidExpression(format!(
+                "invalid table arguments ({})",
+                args.iter().map(|arg| arg.to_string()).join(", ")
+            ))),
+        }
+    }
 }
@@ -10,6 +10,9 @@ pub struct ColumnDesc {
     datatype: DataType,
     name: String,
     is_primary: bool,
+    /// Whether a bloom filter is built on the column by the storage
+    #[serde(default)]
+    has_bloom_filter: bool,
 }
 
 impl ColumnDesc {
@@ -18,6 +21,7 @@ impl ColumnDesc {
             datatype,
             name,
             is_primary,
+            has_bloom_filter: false,
         }
     }
 
@@ -29,6 +33,14 @@ impl ColumnDesc {
         self.is_primary
     }
 
+    pub fn set_bloom_filter(&mut self, has_bloom_filter: bool) {
+        self.has_bloom_filter = has_bloom_filter;
+    }
+
+    pub fn has_bloom_filter(&self) -> bool {
+        self.has_bloom_filter
+    }
+
     pub fn set_nullable(&mut self, is_nullable: bool) {
         self.datatype.nullable = is_nullable;
     }
@@ -96,6 +108,14 @@ impl ColumnCatalog {
         self.desc.is_primary()
     }
 
+    pub fn set_bloom_filter(&mut self, has_bloom_filter: bool) {
+        self.desc.set_bloom_filter(has_bloom_filter);
+    }
+
+    pub fn has_bloom_filter(&self) -> bool {
+        self.desc.has_bloom_filter()
+    }
+
     pub fn set_nullable(&mut self, is_nullable: bool) {
         self.desc.set_nullable(is_nullable);
     }
@@ -107,6 +107,27 @@ impl RootCatalog {
         schema.add_table(name, columns, is_materialized_view, ordered_pk_ids)
     }
 
+    /// Add a table with the ID in `table_ref_id`.
+    pub fn add_table_with_id(
+        &self,
+        table_ref_id: TableRefId,
+        name: String,
+        columns: Vec<ColumnCatalog>,
+        is_materialized_view: bool,
+        ordered_pk_ids: Vec<ColumnId>,
+    ) -> Result<TableId, CatalogError> {
+        let mut inner = self.inner.lock().unwrap();
+        let database = inner.databases.get_mut(&table_ref_id.database_id).unwrap();
+        let schema = database.get_schema_mut(table_ref_id.schema_id).unwrap();
+        schema.add_table_with_id(
+            table_ref_id.table_id,
+            name,
+            columns,
+            is_materialized_view,
+            ordered_pk_ids,
+        )
+    }
+
     pub fn drop_table(&self, table_ref_id: TableRefId) {
         let mut inner = self.inner.lock().unwrap();
         let database = inner.databases.get_mut(&table_ref_id.database_id).unwrap();
@@ -33,12 +33,32 @@ impl SchemaCatalog {
         columns: Vec<ColumnCatalog>,
         is_materialized_view: bool,
         ordered_pk_ids: Vec<ColumnId>,
+    ) -> Result<TableId, CatalogError> {
+        self.add_table_with_id(
+            self.next_table_id,
+            name,
+            columns,
+            is_materialized_view,
+            ordered_pk_ids,
+        )
+    }
+
+    /// Add a table with the given ID, which is used when the catalog is restored from storage.
+    pub(in crate::catalog) fn add_table_with_id(
+        &mut self,
+        table_id: TableId,
+        name: String,
+        columns: Vec<ColumnCatalog>,
+        is_materialized_view: bool,
+        ordered_pk_ids: Vec<ColumnId>,
     ) -> Result<TableId, CatalogError> {
         if self.table_idxs.contains_key(&name) {
             return Err(CatalogError::Duplicated("table", name));
         }
-        let table_id = self.next_table_id;
-        self.next_table_id += 1;
+        if self.tables.contains_key(&table_id) {
+            return Err(CatalogError::Duplicated("table id", table_id.to_string()));
+        }
+        self.next_table_id = self.next_table_id.max(table_id + 1);
         let table_catalog = Arc::new(TableCatalog::new(
             table_id,
             name.clone(),
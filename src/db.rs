@@ -7,7 +7,8 @@ use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
 use tracing::debug;
 
 use crate::array::{
-    ArrayBuilder, ArrayBuilderImpl, Chunk, DataChunk, I32ArrayBuilder, Utf8ArrayBuilder,
+    ArrayBuilder, ArrayBuilderImpl, Chunk, DataChunk, I32ArrayBuilder, I64ArrayBuilder,
+    Utf8ArrayBuilder,
 };
 use crate::binder::{BindError, Binder, BoundAggCall, BoundExpr, BoundInputRef};
 use crate::catalog::RootCatalogRef;
@@ -16,11 +17,12 @@ use crate::executor::{ExecutorBuilder, ExecutorError};
 use crate::logical_planner::{LogicalPlanError, LogicalPlaner};
 use crate::optimizer::logical_plan_rewriter::{InputRefResolver, PlanRewriter};
 use crate::optimizer::plan_nodes::{LogicalAggregate, PhysicalHashAgg, PlanRef};
+use crate::optimizer::statistics::{scanned_tables, Statistics, TableStatistics};
 use crate::optimizer::Optimizer;
-use crate::parser::{parse, ParserError};
+use crate::parser::{parse, ParserError, Statement};
 use crate::storage::{
     InMemoryStorage, SecondaryStorage, SecondaryStorageOptions, Storage, StorageColumnRef,
-    StorageImpl, Table,
+    StorageImpl, Table, Transaction,
 };
 use crate::types::DataType;
 
@@ -116,7 +118,7 @@ impl Database {
                             BlockStatisticsType::DistinctValue,
                             StorageColumnRef::Idx(col_id),
                         ),
-                    ]);
+                    ])?;
                     let mut stat_name = Utf8ArrayBuilder::with_capacity(2);
                     let mut stat_value = Utf8ArrayBuilder::with_capacity(2);
                     stat_name.push(Some("RowCount"));
@@ -151,11 +153,68 @@ impl Database {
             }
         } else if cmd == "dt" {
             self.run_dt()
+        } else if cmd == "epoch" {
+            if let StorageImpl::SecondaryStorage(ref storage) = self.storage {
+                let mut epoch = I64ArrayBuilder::new();
+                epoch.push(Some(&(storage.current_epoch() as i64)));
+                Ok(vec![Chunk::new(vec![DataChunk::from_iter([
+                    ArrayBuilderImpl::Int64(epoch),
+                ])])])
+            } else {
+                Err(Error::InternalError(
+                    "this storage engine doesn't support time travel".to_string(),
+                ))
+            }
         } else {
             Err(Error::InternalError("unsupported command".to_string()))
         }
     }
 
+    /// Collect the statistics of the tables scanned by the plan from the block statistics.
+    ///
+    /// Only the secondary storage maintains statistics. Otherwise, no statistics are returned.
+    async fn collect_statistics(&self, plan: &PlanRef) -> Result<Statistics, Error> {
+        let mut statistics = Statistics::new();
+        let storage = match &self.storage {
+            StorageImpl::SecondaryStorage(storage) => storage,
+            _ => return Ok(statistics),
+        };
+        for table_ref_id in scanned_tables(plan) {
+            let column_ids = match self.catalog.get_table(&table_ref_id) {
+                Some(table) => table.all_columns().into_keys().collect::<Vec<_>>(),
+                None => continue,
+            };
+            if column_ids.is_empty() {
+                continue;
+            }
+            let table = storage.get_table(table_ref_id)?;
+            let txn = table.read().await?;
+            // Note that column catalog ids are used as storage column ids, as in `\stat`.
+            let mut stats = vec![(
+                BlockStatisticsType::RowCount,
+                StorageColumnRef::Idx(column_ids[0]),
+            )];
+            stats.extend(column_ids.iter().map(|column_id| {
+                (
+                    BlockStatisticsType::DistinctValue,
+                    StorageColumnRef::Idx(*column_id),
+                )
+            }));
+            let values = txn.aggreagate_block_stat(&stats)?;
+            txn.abort().await?;
+
+            let value = |i: usize| values[i].as_usize().unwrap().unwrap_or(0);
+            let table_statistics = TableStatistics {
+                row_count: value(0),
+                distinct_values: (column_ids.iter().enumerate())
+                    .map(|(i, column_id)| (*column_id, value(i + 1)))
+                    .collect(),
+            };
+            statistics.insert(table_ref_id, Arc::new(table_statistics));
+        }
+        Ok(statistics)
+    }
+
     /// Run SQL queries and return the outputs.
 
     pub async fn run(&self, sql: &str) -> Result<Vec<Chunk>, Error> {
@@ -178,11 +237,31 @@ impl Database {
         let logical_planner = LogicalPlaner::default();
         let mut optimizer = Optimizer {
             enable_filter_scan: self.storage.enable_filter_scan(),
+            ..Default::default()
         };
         // TODO: parallelize
         let mut outputs: Vec<Chunk> = vec![];
         for stmt in stmts {
             debug!("{:#?}", stmt);
+            // Transaction control statements are handled by the storage directly.
+            match stmt {
+                Statement::StartTransaction { .. } => {
+                    self.storage.begin_transaction(context.session())?;
+                    outputs.push(Chunk::new(vec![]));
+                    continue;
+                }
+                Statement::Commit { .. } => {
+                    self.storage.commit_transaction(context.session()).await?;
+                    outputs.push(Chunk::new(vec![]));
+                    continue;
+                }
+                Statement::Rollback { .. } => {
+                    self.storage.rollback_transaction(context.session()).await?;
+                    outputs.push(Chunk::new(vec![]));
+                    continue;
+                }
+                _ => {}
+            }
             let stmt = binder.bind(&stmt)?;
             debug!("{:#?}", stmt);
             let logical_plan = logical_planner.plan(stmt)?;
@@ -192,6 +271,7 @@ impl Database {
             let logical_plan = input_ref_resolver.rewrite(logical_plan);
             let column_names = logical_plan.out_names();
             debug!("{:#?}", logical_plan);
+            optimizer.statistics = self.collect_statistics(&logical_plan).await?;
             let optimized_plan = optimizer.optimize(logical_plan);
             debug!("{:#?}", optimized_plan);
             let epsilon: f64 = 0.1;
@@ -215,19 +295,28 @@ impl Database {
                     let optimized_plan = Arc::new(PhysicalHashAgg::new(LogicalAggregate::new(
                         vec![
                             BoundAggCall {
-                                kind: crate::binder::AggKind::DPSum(epsilon),
+                                kind: crate::binder::AggKind::DPSum,
                                 args: vec![inpt(2)],
+                                params: vec![crate::types::DataValue::Float64(epsilon)],
+                                filter: None,
                                 return_type: agg_data_type.clone(),
+                                distinct: false,
                             },
                             BoundAggCall {
-                                kind: crate::binder::AggKind::DPSum(epsilon),
+                                kind: crate::binder::AggKind::DPSum,
                                 args: vec![inpt(3)],
+                                params: vec![crate::types::DataValue::Float64(epsilon)],
+                                filter: None,
                                 return_type: agg_data_type.clone(),
+                                distinct: false,
                             },
                             BoundAggCall {
-                                kind: crate::binder::AggKind::DPSum(epsilon),
+                                kind: crate::binder::AggKind::DPSum,
                                 args: vec![inpt(4)],
+                                params: vec![crate::types::DataValue::Float64(epsilon)],
+                                filter: None,
                                 return_type: agg_data_type.clone(),
+                                distinct: false,
                             },
                         ],
                         vec![inpt(0), inpt(1)],
@@ -269,6 +358,7 @@ impl Database {
         let logical_planner = LogicalPlaner::default();
         let mut optimizer = Optimizer {
             enable_filter_scan: self.storage.enable_filter_scan(),
+            ..Default::default()
         };
         let mut plans = vec![];
         for stmt in stmts {
@@ -2,7 +2,7 @@
 
 // use crate::array::ArrayImplValidExt;
 use super::*;
-use crate::array::ArrayImplValidExt;
+use crate::array::{Array, ArrayImplValidExt};
 
 /// State for row count aggregation
 pub struct CountAggregationState {
@@ -16,7 +16,8 @@ impl CountAggregationState {
 }
 
 impl AggregationState for CountAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
         // let temp = array.len() as i32;
         let temp = array.get_valid_bitmap().count_ones() as i32;
         self.result = match &self.result {
@@ -27,7 +28,10 @@ impl AggregationState for CountAggregationState {
         Ok(())
     }
 
-    fn update_single(&mut self, _: &DataValue) -> Result<(), ExecutorError> {
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        if args[0] == DataValue::Null {
+            return Ok(());
+        }
         self.result = match &self.result {
             DataValue::Null => DataValue::Int32(1),
             DataValue::Int32(res) => DataValue::Int32(res + 1),
@@ -39,4 +43,89 @@ impl AggregationState for CountAggregationState {
     fn output(&self) -> DataValue {
         self.result.clone()
     }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.result.clone()])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        self.result = match (&self.result, &partial[0]) {
+            (_, DataValue::Null) => return Ok(()),
+            (DataValue::Null, v) => v.clone(),
+            (DataValue::Int32(res), DataValue::Int32(v)) => DataValue::Int32(res + v),
+            _ => panic!("Mismatched type"),
+        };
+        Ok(())
+    }
+}
+
+/// Vectorized states of count and row count aggregation.
+pub struct CountGroupedState {
+    counts: Vec<i32>,
+    /// Whether to skip NULLs, i.e. count instead of row count.
+    skip_nulls: bool,
+}
+
+impl CountGroupedState {
+    pub fn new(skip_nulls: bool) -> Self {
+        Self {
+            counts: vec![],
+            skip_nulls,
+        }
+    }
+}
+
+impl GroupedAggregationState for CountGroupedState {
+    fn resize(&mut self, num_groups: usize) {
+        self.counts.resize(num_groups, 0);
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        if self.skip_nulls {
+            let valid = array.get_valid_bitmap();
+            for (i, &group) in groups.iter().enumerate() {
+                self.counts[group] += valid[i] as i32;
+            }
+        } else {
+            for &group in groups {
+                self.counts[group] += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let partial = i32::downcast(&partial[0]);
+        for (&group, count) in groups.iter().zip_eq(partial.iter()) {
+            self.counts[group] += count.unwrap_or(&0);
+        }
+        Ok(())
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        DataValue::Int32(self.counts[group])
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.output(group)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_grouped() {
+        let array = ArrayImpl::new_int32([Some(1), None, Some(3), None].into_iter().collect());
+        let groups = [0, 1, 1, 0];
+        for (skip_nulls, counts) in [(true, [1, 1]), (false, [2, 2])] {
+            let mut state = CountGroupedState::new(skip_nulls);
+            state.resize(2);
+            state.update(&groups, &[array.clone()]).unwrap();
+            assert_eq!(state.output(0), DataValue::Int32(counts[0]));
+            assert_eq!(state.output(1), DataValue::Int32(counts[1]));
+        }
+    }
 }
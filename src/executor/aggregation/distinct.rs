@@ -0,0 +1,69 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::HashSet;
+
+use super::*;
+
+/// Wraps another aggregation state and only feeds it arguments that have not been seen before.
+///
+/// Rows with a NULL argument are ignored, so the inner state sees every distinct tuple of
+/// non-null arguments exactly once.
+pub struct DistinctAggregationState {
+    seen: HashSet<Vec<DataValue>>,
+    inner: Box<dyn AggregationState>,
+}
+
+impl DistinctAggregationState {
+    pub fn new(inner: Box<dyn AggregationState>) -> Self {
+        Self {
+            seen: HashSet::new(),
+            inner,
+        }
+    }
+}
+
+/// `DataValue::Float64` can not be hashed, so floats are keyed by their bit pattern instead.
+/// Values of an argument in one state come from the same column, so the keys never collide with
+/// integers.
+pub(super) fn hash_key(value: &DataValue) -> DataValue {
+    match value {
+        // normalize `-0.0` to `0.0`
+        DataValue::Float64(v) if *v == 0.0 => DataValue::Int64(0),
+        DataValue::Float64(v) => DataValue::Int64(v.to_bits() as i64),
+        v => v.clone(),
+    }
+}
+
+impl AggregationState for DistinctAggregationState {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for i in 0..args[0].len() {
+            let values = args.iter().map(|array| array.get(i)).collect_vec();
+            self.update_single(&values)?;
+        }
+        Ok(())
+    }
+
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        if args.contains(&DataValue::Null) || !self.seen.insert(args.iter().map(hash_key).collect())
+        {
+            return Ok(());
+        }
+        self.inner.update_single(args)
+    }
+
+    fn output(&self) -> DataValue {
+        self.inner.output()
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Err(ExecutorError::NotSupported(
+            "distinct aggregation can not be split into phases".into(),
+        ))
+    }
+
+    fn merge(&mut self, _: &[DataValue]) -> Result<(), ExecutorError> {
+        Err(ExecutorError::NotSupported(
+            "distinct aggregation can not be split into phases".into(),
+        ))
+    }
+}
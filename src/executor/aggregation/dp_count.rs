@@ -6,7 +6,7 @@ use probability::distribution::{Laplace, Sample};
 use probability::source;
 
 use super::*;
-use crate::array::ArrayImplValidExt;
+use crate::array::{Array, ArrayImplValidExt};
 /// State for row count aggregation
 pub struct DPCountAggregationState {
     result: i64,
@@ -20,23 +20,87 @@ impl DPCountAggregationState {
 }
 
 impl AggregationState for DPCountAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
         // let temp = array.len() as i64;
         let temp = array.get_valid_bitmap().count_ones() as i64;
         self.result += temp;
         Ok(())
     }
 
-    fn update_single(&mut self, _: &DataValue) -> Result<(), ExecutorError> {
+    fn update_single(&mut self, _: &[DataValue]) -> Result<(), ExecutorError> {
         self.result += 1;
         Ok(())
     }
 
     fn output(&self) -> DataValue {
-        let mut source = source::default();
+        dp_count_output(self.result, self.epsilon)
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![DataValue::Int64(self.result)])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        match &partial[0] {
+            DataValue::Int64(v) => self.result += v,
+            _ => panic!("Mismatched type"),
+        }
+        Ok(())
+    }
+}
+
+/// Add Laplace noise to the exact count.
+pub(super) fn dp_count_output(count: i64, epsilon: f64) -> DataValue {
+    let mut source = source::default();
+
+    let lap = Laplace::new(0.0, 1.0 / epsilon);
+    let noise = lap.sample(&mut source);
+    DataValue::Float64(count as f64 + noise)
+}
+
+/// Vectorized states of differentially private count aggregation.
+pub struct DPCountGroupedState {
+    counts: Vec<i64>,
+    epsilon: f64,
+}
+
+impl DPCountGroupedState {
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            counts: vec![],
+            epsilon,
+        }
+    }
+}
+
+impl GroupedAggregationState for DPCountGroupedState {
+    fn resize(&mut self, num_groups: usize) {
+        self.counts.resize(num_groups, 0);
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        let valid = array.get_valid_bitmap();
+        for (i, &group) in groups.iter().enumerate() {
+            self.counts[group] += valid[i] as i64;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let partial = i64::downcast(&partial[0]);
+        for (&group, count) in groups.iter().zip_eq(partial.iter()) {
+            self.counts[group] += count.unwrap_or(&0);
+        }
+        Ok(())
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        dp_count_output(self.counts[group], self.epsilon)
+    }
 
-        let lap = Laplace::new(0.0, 1.0 / self.epsilon);
-        let noise = lap.sample(&mut source);
-        DataValue::Float64(self.result as f64 + noise)
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![DataValue::Int64(self.counts[group])])
     }
 }
@@ -0,0 +1,98 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+/// State for differentially private count of distinct values.
+///
+/// Each user contributes at most `max_contributions` distinct values, and later values of a user
+/// who reached the bound are dropped. Adding or removing a user then changes the count by at most
+/// `max_contributions`, which is the scale of the noise. Without a user argument, each value is
+/// regarded as a user, e.g. when counting distinct customers.
+pub struct DPCountDistinctAggregationState {
+    /// Distinct values that are counted.
+    values: HashSet<DataValue>,
+    /// Distinct values counted for each user.
+    contributions: HashMap<DataValue, HashSet<DataValue>>,
+    epsilon: f64,
+    max_contributions: usize,
+}
+
+impl DPCountDistinctAggregationState {
+    pub fn new(epsilon: f64, max_contributions: usize) -> Self {
+        Self {
+            values: HashSet::new(),
+            contributions: HashMap::new(),
+            epsilon,
+            max_contributions,
+        }
+    }
+}
+
+impl AggregationState for DPCountDistinctAggregationState {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for i in 0..args[0].len() {
+            let values = args.iter().map(|array| array.get(i)).collect_vec();
+            self.update_single(&values)?;
+        }
+        Ok(())
+    }
+
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        if args.contains(&DataValue::Null) {
+            return Ok(());
+        }
+        let value = hash_key(&args[0]);
+        if let Some(user) = args.get(1) {
+            let counted = self.contributions.entry(hash_key(user)).or_default();
+            if !counted.contains(&value) {
+                if counted.len() >= self.max_contributions {
+                    return Ok(());
+                }
+                counted.insert(value.clone());
+            }
+        }
+        self.values.insert(value);
+        Ok(())
+    }
+
+    fn output(&self) -> DataValue {
+        dp_count_output(
+            self.values.len() as i64,
+            self.epsilon / self.max_contributions as f64,
+        )
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Err(ExecutorError::NotSupported(
+            "dp_count_distinct can not be split into phases".into(),
+        ))
+    }
+
+    fn merge(&mut self, _: &[DataValue]) -> Result<(), ExecutorError> {
+        Err(ExecutorError::NotSupported(
+            "dp_count_distinct can not be split into phases".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contribution_bound() {
+        let mut state = DPCountDistinctAggregationState::new(1.0, 2);
+        let values = ArrayImpl::new_int32([1, 2, 3, 1, 4, 5].into_iter().map(Some).collect());
+        let users = ArrayImpl::new_int32(
+            [Some(1), Some(1), Some(1), Some(2), Some(2), None]
+                .into_iter()
+                .collect(),
+        );
+        state.update(&[values, users]).unwrap();
+        // user 1 contributes 1 and 2, user 2 contributes 1 and 4
+        assert_eq!(state.values.len(), 3);
+        assert!(state.output_partial().is_err());
+    }
+}
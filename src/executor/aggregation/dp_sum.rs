@@ -10,6 +10,7 @@ use probability::distribution::{Laplace, Sample};
 use probability::source;
 
 use super::*;
+use crate::array::Array;
 /// State for row count aggregation
 pub struct DPSumAggregationState {
     sum: f64,
@@ -28,15 +29,17 @@ impl DPSumAggregationState {
 }
 
 impl AggregationState for DPSumAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
-        for i in 0..array.len() {
-            self.update_single(&array.get(i))?;
-        }
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        for_each_f64(array, |_, v| {
+            self.max = max(FloatOrd(self.max), FloatOrd(v)).0;
+            self.sum += v;
+        });
         Ok(())
     }
 
-    fn update_single(&mut self, v: &DataValue) -> Result<(), ExecutorError> {
-        let v = match v {
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        let v = match &args[0] {
             DataValue::Int32(v) => *v as f64,
             DataValue::Int64(v) => *v as f64,
             DataValue::Float64(v) => *v,
@@ -49,11 +52,109 @@ impl AggregationState for DPSumAggregationState {
     }
 
     fn output(&self) -> DataValue {
-        let mut source = source::default();
-        // dbg!(self.max);
-        // dbg!(self.sum);
-        let lap = Laplace::new(0.0, self.max / self.epsilon);
-        let noise = lap.sample(&mut source);
-        DataValue::Float64(self.sum as f64 + noise)
+        dp_sum_output(self.sum, self.max, self.epsilon)
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![
+            DataValue::Float64(self.sum),
+            DataValue::Float64(self.max),
+        ])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        match partial {
+            [DataValue::Float64(sum), DataValue::Float64(max)] => {
+                self.sum += sum;
+                self.max = self.max.max(*max);
+            }
+            _ => panic!("Mismatched type"),
+        }
+        Ok(())
+    }
+}
+
+/// Add Laplace noise scaled by the maximum value to the exact sum.
+fn dp_sum_output(sum: f64, max: f64, epsilon: f64) -> DataValue {
+    let mut source = source::default();
+    let lap = Laplace::new(0.0, max / epsilon);
+    let noise = lap.sample(&mut source);
+    DataValue::Float64(sum + noise)
+}
+
+/// Vectorized states of differentially private sum aggregation.
+pub struct DPSumGroupedState {
+    sums: Vec<f64>,
+    maxes: Vec<f64>,
+    epsilon: f64,
+}
+
+impl DPSumGroupedState {
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            sums: vec![],
+            maxes: vec![],
+            epsilon,
+        }
+    }
+}
+
+impl GroupedAggregationState for DPSumGroupedState {
+    fn resize(&mut self, num_groups: usize) {
+        self.sums.resize(num_groups, 0.0);
+        self.maxes.resize(num_groups, 0.0);
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        for_each_f64(array, |i, v| {
+            let group = groups[i];
+            self.maxes[group] = max(FloatOrd(self.maxes[group]), FloatOrd(v)).0;
+            self.sums[group] += v;
+        });
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let (sums, maxes) = (f64::downcast(&partial[0]), f64::downcast(&partial[1]));
+        let partials = groups.iter().zip_eq(sums.iter()).zip_eq(maxes.iter());
+        for ((&group, partial_sum), partial_max) in partials {
+            self.sums[group] += partial_sum.unwrap();
+            self.maxes[group] = self.maxes[group].max(*partial_max.unwrap());
+        }
+        Ok(())
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        dp_sum_output(self.sums[group], self.maxes[group], self.epsilon)
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![
+            DataValue::Float64(self.sums[group]),
+            DataValue::Float64(self.maxes[group]),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut state1 = DPSumAggregationState::new(1.0);
+        let mut state2 = DPSumAggregationState::new(1.0);
+        state1
+            .update(&[ArrayImpl::new_int32((1..5).collect())])
+            .unwrap();
+        state2
+            .update(&[ArrayImpl::new_int32((5..7).collect())])
+            .unwrap();
+        state1.merge(&state2.output_partial().unwrap()).unwrap();
+        assert_eq!(
+            state1.output_partial().unwrap(),
+            vec![DataValue::Float64(21.0), DataValue::Float64(6.0)]
+        );
     }
 }
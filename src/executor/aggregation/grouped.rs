@@ -0,0 +1,158 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Vectorized aggregation states of all groups in hash aggregation.
+//!
+//! Instead of updating a boxed [`AggregationState`] by a [`DataValue`] for each row, hash
+//! aggregation maps each input row to the index of its group, and updates the states of all
+//! groups from the typed arrays of arguments at once.
+
+use std::ops::Range;
+
+use rust_decimal::Decimal;
+
+use super::*;
+use crate::array::{Array, PrimitiveArray};
+use crate::types::NativeType;
+
+/// `GroupedAggregationState` records the states of an aggregation for all groups.
+///
+/// The groups are numbered from 0 in the order they are added by `resize`.
+pub trait GroupedAggregationState: 'static + Send + Sync {
+    /// Add the initial states of new groups, so that there are `num_groups` groups.
+    fn resize(&mut self, num_groups: usize);
+
+    /// Update the states by the arrays of arguments, whose `i`-th row belongs to group
+    /// `groups[i]`.
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError>;
+
+    /// Merge the intermediate states output by [`output_partial`], whose `i`-th row belongs to
+    /// group `groups[i]`.
+    ///
+    /// [`output_partial`]: GroupedAggregationState::output_partial
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError>;
+
+    /// Output the result of a group.
+    fn output(&self, group: usize) -> DataValue;
+
+    /// Output the intermediate state of a group, see [`AggregationState::output_partial`].
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError>;
+}
+
+/// A primitive type of arguments and states of vectorized aggregations.
+pub trait KernelValue: NativeType {
+    /// Downcast an array to the primitive array of this type.
+    fn downcast(array: &ArrayImpl) -> &PrimitiveArray<Self>;
+
+    fn into_value(self) -> DataValue;
+}
+
+macro_rules! impl_kernel_value {
+    ($($t:ty: $Abc:ident),*) => {$(
+        impl KernelValue for $t {
+            fn downcast(array: &ArrayImpl) -> &PrimitiveArray<Self> {
+                match array {
+                    ArrayImpl::$Abc(a) => &**a,
+                    _ => panic!("Mismatched type"),
+                }
+            }
+
+            fn into_value(self) -> DataValue {
+                DataValue::$Abc(self)
+            }
+        }
+    )*}
+}
+
+impl_kernel_value!(i32: Int32, i64: Int64, f64: Float64, Decimal: Decimal);
+
+/// Returns the runs of consecutive rows in the same group, as the group and the range of rows.
+pub fn group_runs(groups: &[usize]) -> impl Iterator<Item = (usize, Range<usize>)> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let &group = groups.get(start)?;
+        let len = groups[start..].iter().take_while(|&&g| g == group).count();
+        let rows = start..start + len;
+        start += len;
+        Some((group, rows))
+    })
+}
+
+/// Call `f` with the index and the value as `f64` of each non-null element of a numeric array.
+pub fn for_each_f64(array: &ArrayImpl, mut f: impl FnMut(usize, f64)) {
+    use num_traits::ToPrimitive;
+
+    match array {
+        ArrayImpl::Int32(a) => (a.iter().enumerate())
+            .filter_map(|(i, v)| Some((i, *v? as f64)))
+            .for_each(|(i, v)| f(i, v)),
+        ArrayImpl::Int64(a) => (a.iter().enumerate())
+            .filter_map(|(i, v)| Some((i, *v? as f64)))
+            .for_each(|(i, v)| f(i, v)),
+        ArrayImpl::Float64(a) => (a.iter().enumerate())
+            .filter_map(|(i, v)| Some((i, *v?)))
+            .for_each(|(i, v)| f(i, v)),
+        ArrayImpl::Decimal(a) => (a.iter().enumerate())
+            .filter_map(|(i, v)| Some((i, v?.to_f64().unwrap())))
+            .for_each(|(i, v)| f(i, v)),
+        _ => panic!("Mismatched type"),
+    }
+}
+
+/// The [`AggregationState`]s of each group, for aggregations without a vectorized kernel.
+pub struct RowAggregationStates {
+    agg_call: BoundAggCall,
+    states: Vec<Box<dyn AggregationState>>,
+}
+
+impl RowAggregationStates {
+    pub fn new(agg_call: BoundAggCall) -> Self {
+        Self {
+            agg_call,
+            states: vec![],
+        }
+    }
+}
+
+impl GroupedAggregationState for RowAggregationStates {
+    fn resize(&mut self, num_groups: usize) {
+        while self.states.len() < num_groups {
+            self.states.push(create_agg_state(&self.agg_call));
+        }
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for (i, &group) in groups.iter().enumerate() {
+            let values = args.iter().map(|array| array.get(i)).collect_vec();
+            self.states[group].update_single(&values)?;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for (i, &group) in groups.iter().enumerate() {
+            let values = partial.iter().map(|array| array.get(i)).collect_vec();
+            self.states[group].merge(&values)?;
+        }
+        Ok(())
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        self.states[group].output()
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        self.states[group].output_partial()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_runs() {
+        let runs = group_runs(&[0, 0, 1, 0, 2, 2, 2]).collect_vec();
+        assert_eq!(runs, vec![(0, 0..2), (1, 2..3), (0, 3..4), (2, 4..7)]);
+        assert_eq!(group_runs(&[]).count(), 0);
+    }
+}
@@ -40,7 +40,8 @@ min_max_func_gen!(max_i64, i64, i64, max);
 // TODO: To support min and max on `f64`, we should implement std::cmp::Ord for `f64`
 
 impl AggregationState for MinMaxAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
         match (array, &self.input_datatype) {
             (ArrayImpl::Int32(arr), DataTypeKind::Int(_)) => {
                 let temp = arr
@@ -73,8 +74,8 @@ impl AggregationState for MinMaxAggregationState {
         Ok(())
     }
 
-    fn update_single(&mut self, value: &DataValue) -> Result<(), ExecutorError> {
-        match (value, &self.input_datatype) {
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        match (&args[0], &self.input_datatype) {
             (DataValue::Int32(val), DataTypeKind::Int(_)) => {
                 self.result = match self.result {
                     DataValue::Null => DataValue::Int32(*val),
@@ -99,4 +100,67 @@ impl AggregationState for MinMaxAggregationState {
     fn output(&self) -> DataValue {
         self.result.clone()
     }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.result.clone()])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        match &partial[0] {
+            DataValue::Null => Ok(()),
+            _ => self.update_single(partial),
+        }
+    }
+}
+
+/// Vectorized states of min or max aggregation.
+pub struct MinMaxGroupedState<T> {
+    values: Vec<Option<T>>,
+    is_min: bool,
+}
+
+impl<T: KernelValue> MinMaxGroupedState<T> {
+    pub fn new(is_min: bool) -> Self {
+        Self {
+            values: vec![],
+            is_min,
+        }
+    }
+}
+
+impl<T: KernelValue> GroupedAggregationState for MinMaxGroupedState<T> {
+    fn resize(&mut self, num_groups: usize) {
+        self.values.resize(num_groups, None);
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        let array = T::downcast(array);
+        for (&group, value) in groups.iter().zip_eq(array.iter()) {
+            if let Some(&value) = value {
+                let result = &mut self.values[group];
+                match *result {
+                    Some(r) if self.is_min && r <= value => {}
+                    Some(r) if !self.is_min && r >= value => {}
+                    _ => *result = Some(value),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        self.update(groups, partial)
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        match self.values[group] {
+            Some(value) => value.into_value(),
+            None => DataValue::Null,
+        }
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.output(group)])
+    }
 }
@@ -1,28 +1,192 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
+use smallvec::SmallVec;
+
 use super::*;
 use crate::array::ArrayImpl;
-use crate::types::DataValue;
+use crate::binder::{AggKind, BoundAggCall};
+use crate::types::{DataType, DataTypeExt, DataTypeKind, DataValue};
 
 mod count;
+mod distinct;
 mod dp_count;
+mod dp_count_distinct;
 mod dp_sum;
+mod grouped;
 mod min_max;
 mod rowcount;
+mod stats;
 mod sum;
 
 pub use count::*;
+pub use distinct::*;
 pub use dp_count::*;
+pub use dp_count_distinct::*;
 pub use dp_sum::*;
+pub use grouped::*;
 pub use min_max::*;
 pub use rowcount::*;
+pub use stats::*;
 pub use sum::*;
 
 /// `AggregationState` records the state of an aggregation
 pub trait AggregationState: 'static + Send + Sync {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError>;
+    /// Update the state by the arrays of arguments.
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError>;
 
-    fn update_single(&mut self, value: &DataValue) -> Result<(), ExecutorError>;
+    /// Update the state by the arguments of a row.
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError>;
 
     fn output(&self) -> DataValue;
+
+    /// Output the intermediate state of a partial aggregation, which has the types returned by
+    /// [`partial_types`]. Differentially private states output their exact values here.
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError>;
+
+    /// Merge the intermediate state output by [`output_partial`] of another state.
+    ///
+    /// [`output_partial`]: AggregationState::output_partial
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError>;
+}
+
+/// Returns the types of the intermediate state of an aggregation in two-phase aggregation, or
+/// `None` if the aggregation can not be split into partial and final phases.
+pub fn partial_types(agg_call: &BoundAggCall) -> Option<Vec<DataType>> {
+    if agg_call.distinct {
+        return None;
+    }
+    match agg_call.kind {
+        AggKind::RowCount | AggKind::Count => Some(vec![DataTypeKind::Int(None).nullable()]),
+        AggKind::Sum | AggKind::Min | AggKind::Max => {
+            Some(vec![agg_call.return_type.kind().nullable()])
+        }
+        AggKind::DPCount => Some(vec![DataTypeKind::BigInt(None).not_null()]),
+        AggKind::DPSum => Some(vec![
+            DataTypeKind::Double.not_null(),
+            DataTypeKind::Double.not_null(),
+        ]),
+        AggKind::StddevSamp
+        | AggKind::StddevPop
+        | AggKind::VarSamp
+        | AggKind::VarPop
+        | AggKind::Corr
+        | AggKind::CovarSamp
+        | AggKind::RegrSlope
+        | AggKind::RegrIntercept => Some(moments_partial_types()),
+        AggKind::Avg | AggKind::DPCountDistinct => None,
+    }
+}
+
+/// Returns the privacy budget of a differentially private aggregation.
+fn epsilon(agg_call: &BoundAggCall) -> f64 {
+    match agg_call.params[0] {
+        DataValue::Float64(epsilon) => epsilon,
+        _ => panic!("epsilon should be a float"),
+    }
+}
+
+/// Returns the maximum number of distinct values counted for each user in `dp_count_distinct`.
+fn max_contributions(agg_call: &BoundAggCall) -> usize {
+    match agg_call.params.get(1) {
+        Some(DataValue::Int32(bound)) => *bound as usize,
+        _ => 1,
+    }
+}
+
+/// Evaluates the arguments of an aggregation on a chunk.
+pub(super) fn eval_agg_args(
+    agg_call: &BoundAggCall,
+    chunk: &DataChunk,
+) -> Result<SmallVec<[ArrayImpl; 2]>, ExecutorError> {
+    Ok(agg_call
+        .args
+        .iter()
+        .map(|arg| arg.eval(chunk))
+        .try_collect()?)
+}
+
+/// Evaluates the filter of an aggregation on a chunk, or returns `None` if there is no filter.
+///
+/// Rows whose filter is NULL are not aggregated.
+pub(super) fn eval_agg_filter(
+    agg_call: &BoundAggCall,
+    chunk: &DataChunk,
+) -> Result<Option<Vec<bool>>, ExecutorError> {
+    let filter = match &agg_call.filter {
+        Some(filter) => filter.eval(chunk)?,
+        None => return Ok(None),
+    };
+    match filter {
+        ArrayImpl::Bool(filter) => Ok(Some(filter.iter().map(|v| v == Some(&true)).collect())),
+        _ => panic!("filter of aggregation should be boolean"),
+    }
+}
+
+pub(super) fn create_agg_states(
+    agg_calls: &[BoundAggCall],
+) -> SmallVec<[Box<dyn AggregationState>; 16]> {
+    agg_calls.iter().map(create_agg_state).collect()
+}
+
+pub(super) fn create_agg_state(agg_call: &BoundAggCall) -> Box<dyn AggregationState> {
+    let state: Box<dyn AggregationState> = match agg_call.kind {
+        AggKind::RowCount => Box::new(RowCountAggregationState::new(DataValue::Int32(0))),
+        AggKind::Count => Box::new(CountAggregationState::new(DataValue::Int32(0))),
+        AggKind::Max => Box::new(MinMaxAggregationState::new(
+            agg_call.return_type.kind(),
+            false,
+        )),
+        AggKind::Min => Box::new(MinMaxAggregationState::new(
+            agg_call.return_type.kind(),
+            true,
+        )),
+        AggKind::Sum => Box::new(SumAggregationState::new(agg_call.return_type.kind())),
+        AggKind::DPCount => Box::new(DPCountAggregationState::new(epsilon(agg_call))),
+        AggKind::DPSum => Box::new(DPSumAggregationState::new(epsilon(agg_call))),
+        AggKind::DPCountDistinct => Box::new(DPCountDistinctAggregationState::new(
+            epsilon(agg_call),
+            max_contributions(agg_call),
+        )),
+        ref kind if kind.is_statistical() => Box::new(StatsAggregationState::new(kind.clone())),
+        _ => panic!("Unsupported aggregate kind"),
+    };
+    if agg_call.distinct {
+        Box::new(DistinctAggregationState::new(state))
+    } else {
+        state
+    }
+}
+
+pub(super) fn create_grouped_agg_states(
+    agg_calls: &[BoundAggCall],
+) -> SmallVec<[Box<dyn GroupedAggregationState>; 16]> {
+    agg_calls.iter().map(create_grouped_agg_state).collect()
+}
+
+/// Create the vectorized states of an aggregation for all groups, or fall back to the states of
+/// each group if there is no kernel for the aggregation.
+fn create_grouped_agg_state(agg_call: &BoundAggCall) -> Box<dyn GroupedAggregationState> {
+    use DataTypeKind::*;
+
+    if agg_call.distinct {
+        return Box::new(RowAggregationStates::new(agg_call.clone()));
+    }
+    match (&agg_call.kind, agg_call.return_type.kind()) {
+        (AggKind::RowCount, _) => Box::new(CountGroupedState::new(false)),
+        (AggKind::Count, _) => Box::new(CountGroupedState::new(true)),
+        (AggKind::Sum, Int(_)) => Box::new(SumGroupedState::<i32>::default()),
+        (AggKind::Sum, BigInt(_)) => Box::new(SumGroupedState::<i64>::default()),
+        (AggKind::Sum, Double) => Box::new(SumGroupedState::<f64>::default()),
+        (AggKind::Sum, Decimal(_, _)) => {
+            Box::new(SumGroupedState::<rust_decimal::Decimal>::default())
+        }
+        (AggKind::Min, Int(_)) => Box::new(MinMaxGroupedState::<i32>::new(true)),
+        (AggKind::Min, BigInt(_)) => Box::new(MinMaxGroupedState::<i64>::new(true)),
+        (AggKind::Max, Int(_)) => Box::new(MinMaxGroupedState::<i32>::new(false)),
+        (AggKind::Max, BigInt(_)) => Box::new(MinMaxGroupedState::<i64>::new(false)),
+        (AggKind::DPCount, _) => Box::new(DPCountGroupedState::new(epsilon(agg_call))),
+        (AggKind::DPSum, _) => Box::new(DPSumGroupedState::new(epsilon(agg_call))),
+        (kind, _) if kind.is_statistical() => Box::new(StatsGroupedState::new(kind.clone())),
+        _ => Box::new(RowAggregationStates::new(agg_call.clone())),
+    }
 }
@@ -14,7 +14,8 @@ impl RowCountAggregationState {
 }
 
 impl AggregationState for RowCountAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
         let temp = array.len() as i32;
         // let temp = array.get_valid_bitmap().count_ones() as i32;
         self.result = match &self.result {
@@ -25,7 +26,7 @@ impl AggregationState for RowCountAggregationState {
         Ok(())
     }
 
-    fn update_single(&mut self, _: &DataValue) -> Result<(), ExecutorError> {
+    fn update_single(&mut self, _: &[DataValue]) -> Result<(), ExecutorError> {
         self.result = match &self.result {
             DataValue::Null => DataValue::Int32(1),
             DataValue::Int32(res) => DataValue::Int32(res + 1),
@@ -37,4 +38,18 @@ impl AggregationState for RowCountAggregationState {
     fn output(&self) -> DataValue {
         self.result.clone()
     }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.result.clone()])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        self.result = match (&self.result, &partial[0]) {
+            (_, DataValue::Null) => return Ok(()),
+            (DataValue::Null, v) => v.clone(),
+            (DataValue::Int32(res), DataValue::Int32(v)) => DataValue::Int32(res + v),
+            _ => panic!("Mismatched type"),
+        };
+        Ok(())
+    }
 }
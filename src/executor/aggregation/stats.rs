@@ -0,0 +1,307 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Statistical aggregations computed from the moments of one or two variables.
+//!
+//! The moments are updated by Welford's online algorithm and merged by Chan's parallel
+//! algorithm, which avoid the catastrophic cancellation of `sum(x * x) - sum(x) * sum(x) / n`.
+
+use num_traits::ToPrimitive;
+
+use super::*;
+use crate::array::Array;
+
+/// The count, means and centered second moments of `x` and `y`.
+///
+/// For aggregations of one variable, `y` is the same as `x`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Moments {
+    count: i64,
+    mean_x: f64,
+    mean_y: f64,
+    /// The sum of `(x - mean_x)^2`.
+    m2_x: f64,
+    /// The sum of `(y - mean_y)^2`.
+    m2_y: f64,
+    /// The sum of `(x - mean_x) * (y - mean_y)`.
+    c_xy: f64,
+}
+
+impl Moments {
+    fn update(&mut self, x: f64, y: f64) {
+        self.count += 1;
+        let n = self.count as f64;
+        let dx = x - self.mean_x;
+        let dy = y - self.mean_y;
+        self.mean_x += dx / n;
+        self.mean_y += dy / n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+
+    fn merge(&mut self, other: &Moments) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let (na, nb) = (self.count as f64, other.count as f64);
+        let n = na + nb;
+        let dx = other.mean_x - self.mean_x;
+        let dy = other.mean_y - self.mean_y;
+        self.mean_x += dx * nb / n;
+        self.mean_y += dy * nb / n;
+        self.m2_x += other.m2_x + dx * dx * na * nb / n;
+        self.m2_y += other.m2_y + dy * dy * na * nb / n;
+        self.c_xy += other.c_xy + dx * dy * na * nb / n;
+        self.count += other.count;
+    }
+
+    fn output(&self, kind: &AggKind) -> DataValue {
+        let n = self.count as f64;
+        let value = match kind {
+            AggKind::VarPop if self.count > 0 => self.m2_x / n,
+            AggKind::VarSamp if self.count > 1 => self.m2_x / (n - 1.0),
+            AggKind::StddevPop if self.count > 0 => (self.m2_x / n).sqrt(),
+            AggKind::StddevSamp if self.count > 1 => (self.m2_x / (n - 1.0)).sqrt(),
+            AggKind::CovarSamp if self.count > 1 => self.c_xy / (n - 1.0),
+            AggKind::Corr if self.m2_x != 0.0 && self.m2_y != 0.0 => {
+                self.c_xy / (self.m2_x * self.m2_y).sqrt()
+            }
+            AggKind::RegrSlope if self.m2_x != 0.0 => self.c_xy / self.m2_x,
+            AggKind::RegrIntercept if self.m2_x != 0.0 => {
+                self.mean_y - self.c_xy / self.m2_x * self.mean_x
+            }
+            _ => return DataValue::Null,
+        };
+        DataValue::Float64(value)
+    }
+
+    fn output_partial(&self) -> Vec<DataValue> {
+        vec![
+            DataValue::Int64(self.count),
+            DataValue::Float64(self.mean_x),
+            DataValue::Float64(self.mean_y),
+            DataValue::Float64(self.m2_x),
+            DataValue::Float64(self.m2_y),
+            DataValue::Float64(self.c_xy),
+        ]
+    }
+
+    fn from_partial(partial: &[DataValue]) -> Self {
+        let float = |i: usize| match partial[i] {
+            DataValue::Float64(v) => v,
+            _ => panic!("Mismatched type"),
+        };
+        match partial[0] {
+            DataValue::Int64(count) => Moments {
+                count,
+                mean_x: float(1),
+                mean_y: float(2),
+                m2_x: float(3),
+                m2_y: float(4),
+                c_xy: float(5),
+            },
+            _ => panic!("Mismatched type"),
+        }
+    }
+}
+
+/// Returns the types of [`Moments::output_partial`].
+pub(super) fn moments_partial_types() -> Vec<DataType> {
+    let mut types = vec![DataTypeKind::BigInt(None).not_null()];
+    types.extend(std::iter::repeat(DataTypeKind::Double.not_null()).take(5));
+    types
+}
+
+/// Call `f` with the index and the values of `x` and `y` of each row without NULLs, where the
+/// arguments are `(y, x)` for two variables, or `x` for one.
+fn for_each_xy(args: &[ArrayImpl], mut f: impl FnMut(usize, f64, f64)) {
+    match args {
+        [x] => for_each_f64(x, |i, x| f(i, x, x)),
+        [y, x] => {
+            let mut ys = vec![None; y.len()];
+            for_each_f64(y, |i, y| ys[i] = Some(y));
+            for_each_f64(x, |i, x| {
+                if let Some(y) = ys[i] {
+                    f(i, x, y);
+                }
+            });
+        }
+        _ => panic!("statistical aggregations take one or two arguments"),
+    }
+}
+
+fn to_f64(value: &DataValue) -> Option<f64> {
+    match value {
+        DataValue::Int32(v) => Some(*v as f64),
+        DataValue::Int64(v) => Some(*v as f64),
+        DataValue::Float64(v) => Some(*v),
+        DataValue::Decimal(v) => v.to_f64(),
+        DataValue::Null => None,
+        _ => panic!("Mismatched type"),
+    }
+}
+
+/// State for statistical aggregations, see [`AggKind::is_statistical`].
+pub struct StatsAggregationState {
+    kind: AggKind,
+    moments: Moments,
+}
+
+impl StatsAggregationState {
+    pub fn new(kind: AggKind) -> Self {
+        Self {
+            kind,
+            moments: Moments::default(),
+        }
+    }
+}
+
+impl AggregationState for StatsAggregationState {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for_each_xy(args, |_, x, y| self.moments.update(x, y));
+        Ok(())
+    }
+
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        let (y, x) = match args {
+            [x] => (to_f64(x), to_f64(x)),
+            [y, x] => (to_f64(y), to_f64(x)),
+            _ => panic!("statistical aggregations take one or two arguments"),
+        };
+        if let (Some(x), Some(y)) = (x, y) {
+            self.moments.update(x, y);
+        }
+        Ok(())
+    }
+
+    fn output(&self) -> DataValue {
+        self.moments.output(&self.kind)
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(self.moments.output_partial())
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        self.moments.merge(&Moments::from_partial(partial));
+        Ok(())
+    }
+}
+
+/// Vectorized states of statistical aggregations.
+pub struct StatsGroupedState {
+    kind: AggKind,
+    moments: Vec<Moments>,
+}
+
+impl StatsGroupedState {
+    pub fn new(kind: AggKind) -> Self {
+        Self {
+            kind,
+            moments: vec![],
+        }
+    }
+}
+
+impl GroupedAggregationState for StatsGroupedState {
+    fn resize(&mut self, num_groups: usize) {
+        self.moments.resize(num_groups, Moments::default());
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for_each_xy(args, |i, x, y| self.moments[groups[i]].update(x, y));
+        Ok(())
+    }
+
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        for (i, &group) in groups.iter().enumerate() {
+            let values = partial.iter().map(|array| array.get(i)).collect_vec();
+            self.moments[group].merge(&Moments::from_partial(&values));
+        }
+        Ok(())
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        self.moments[group].output(&self.kind)
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(self.moments[group].output_partial())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(kind: AggKind, args: &[ArrayImpl]) -> DataValue {
+        let mut state = StatsAggregationState::new(kind);
+        state.update(args).unwrap();
+        state.output()
+    }
+
+    fn assert_close(value: DataValue, expected: f64) {
+        match value {
+            DataValue::Float64(v) => assert!((v - expected).abs() < 1e-9, "{} != {}", v, expected),
+            v => panic!("expected {}, got {:?}", expected, v),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let x = ArrayImpl::new_int32(
+            [Some(1), Some(2), None, Some(3), Some(4)]
+                .into_iter()
+                .collect(),
+        );
+        let y = ArrayImpl::new_float64(
+            [Some(2.0), Some(4.5), Some(7.0), None, Some(8.5)]
+                .into_iter()
+                .collect(),
+        );
+        assert_close(output(AggKind::VarPop, &[x.clone()]), 1.25);
+        assert_close(output(AggKind::VarSamp, &[x.clone()]), 5.0 / 3.0);
+        assert_close(output(AggKind::StddevPop, &[x.clone()]), 1.25f64.sqrt());
+        // the rows (x, y) are (1, 2), (2, 4.5) and (4, 8.5)
+        let xy = [y, x];
+        assert_close(output(AggKind::CovarSamp, &xy), 5.0);
+        assert_close(
+            output(AggKind::Corr, &xy),
+            10.0 / (14.0f64 / 3.0 * 21.5).sqrt(),
+        );
+        assert_close(output(AggKind::RegrSlope, &xy), 15.0 / 7.0);
+        assert_close(output(AggKind::RegrIntercept, &xy), 0.0);
+        assert_eq!(
+            output(
+                AggKind::VarSamp,
+                &[ArrayImpl::new_int32([Some(1)].into_iter().collect())]
+            ),
+            DataValue::Null
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let values = [1.0, 1e9 + 2.0, 3.0, 1e9 + 4.0, 5.0, 6.0];
+        let mut state = Moments::default();
+        let mut parts = [Moments::default(), Moments::default()];
+        for (i, &v) in values.iter().enumerate() {
+            state.update(v, v);
+            parts[i % 2].update(v, v);
+        }
+        parts[0].merge(&Moments::from_partial(&parts[1].output_partial()));
+        assert_eq!(parts[0].count, state.count);
+        let (a, b) = (
+            parts[0].output(&AggKind::VarPop),
+            state.output(&AggKind::VarPop),
+        );
+        match (a, b) {
+            (DataValue::Float64(a), DataValue::Float64(b)) => assert!((a - b).abs() / b < 1e-12),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -3,7 +3,7 @@
 use rust_decimal::Decimal;
 
 use super::*;
-use crate::array::Array;
+use crate::array::{Array, ArrayImplValidExt, ArrayValidExt};
 use crate::types::DataTypeKind;
 
 /// State for sum aggregation
@@ -21,146 +21,208 @@ impl SumAggregationState {
     }
 }
 
-macro_rules! sum_func_gen {
-    ($fn_name:ident, $input:ty, $result:ty) => {
-        pub fn $fn_name(result: Option<$result>, input: Option<&$input>) -> Option<$result> {
-            match (result, input) {
-                (_, None) => result,
-                (None, Some(i)) => Some(<$result>::from(*i)),
-                (Some(r), Some(i)) => Some(r + <$result>::from(*i)),
-            }
-        }
-    };
+/// Returns the error of a sum out of the range of its type.
+fn overflow() -> ExecutorError {
+    ExecutorError::OutOfRange("sum")
 }
 
-sum_func_gen!(sum_i32, i32, i32);
-sum_func_gen!(sum_i64, i64, i64);
-sum_func_gen!(sum_f64, f64, f64);
-sum_func_gen!(sum_decimal, Decimal, Decimal);
-
-impl AggregationState for SumAggregationState {
-    fn update(&mut self, array: &ArrayImpl) -> Result<(), ExecutorError> {
-        // TODO: refactor into macros
-        match (array, &self.input_datatype) {
-            (ArrayImpl::Int32(arr), DataTypeKind::Int(_)) => {
-                let mut temp: Option<i32> = None;
-                #[cfg(feature = "simd")]
-                {
-                    use crate::array::ArrayValidExt;
-                    let bitmap = arr.get_valid_bitmap();
-                    if bitmap.any() {
-                        temp = Some(arr.batch_iter::<32>().sum());
-                    }
-                }
-                #[cfg(not(feature = "simd"))]
-                {
-                    temp = arr.iter().fold(temp, sum_i32);
-                }
-                if let Some(val) = temp {
-                    self.result = match self.result {
-                        DataValue::Null => DataValue::Int32(val),
-                        DataValue::Int32(res) => DataValue::Int32(res + val),
-                        _ => panic!("Mismatched type"),
-                    }
-                }
+impl SumAggregationState {
+    /// Add `value` to the result, which is of the same type.
+    fn add(&mut self, value: DataValue) -> Result<(), ExecutorError> {
+        self.result = match (&self.result, value) {
+            (_, DataValue::Null) => return Ok(()),
+            (DataValue::Null, value) => value,
+            (DataValue::Int32(res), DataValue::Int32(val)) => {
+                DataValue::Int32(res.checked_add(val).ok_or_else(overflow)?)
             }
-            (ArrayImpl::Int64(arr), DataTypeKind::BigInt(_)) => {
-                let mut temp: Option<i64> = None;
-                #[cfg(feature = "simd")]
-                {
-                    use crate::array::ArrayValidExt;
-                    let bitmap = arr.get_valid_bitmap();
-                    if bitmap.any() {
-                        temp = Some(arr.batch_iter::<64>().sum());
-                    }
-                }
-                #[cfg(not(feature = "simd"))]
-                {
-                    temp = arr.iter().fold(temp, sum_i64);
-                }
-                if let Some(val) = temp {
-                    self.result = match self.result {
-                        DataValue::Null => DataValue::Int64(val),
-                        DataValue::Int64(res) => DataValue::Int64(res + val),
-                        _ => panic!("Mismatched type"),
-                    }
-                }
+            (DataValue::Int64(res), DataValue::Int64(val)) => {
+                DataValue::Int64(res.checked_add(val).ok_or_else(overflow)?)
             }
+            (DataValue::Float64(res), DataValue::Float64(val)) => DataValue::Float64(res + val),
+            (DataValue::Decimal(res), DataValue::Decimal(val)) => {
+                DataValue::Decimal(res.checked_add(val).ok_or_else(overflow)?)
+            }
+            _ => panic!("Mismatched type"),
+        };
+        Ok(())
+    }
+}
+
+impl AggregationState for SumAggregationState {
+    fn update(&mut self, args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        if !array.get_valid_bitmap().any() {
+            return Ok(());
+        }
+        let sum = match (array, &self.input_datatype) {
+            (ArrayImpl::Int32(arr), DataTypeKind::Int(_)) => DataValue::Int32(
+                i32::checked_sum(arr.iter().flatten().copied()).ok_or_else(overflow)?,
+            ),
+            (ArrayImpl::Int64(arr), DataTypeKind::BigInt(_)) => DataValue::Int64(
+                i64::checked_sum(arr.iter().flatten().copied()).ok_or_else(overflow)?,
+            ),
             (ArrayImpl::Float64(arr), DataTypeKind::Double) => {
-                let mut temp: Option<f64> = None;
                 #[cfg(feature = "simd")]
-                {
-                    use crate::array::ArrayValidExt;
-                    let bitmap = arr.get_valid_bitmap();
-                    if bitmap.any() {
-                        temp = Some(arr.batch_iter::<32>().sum());
-                    }
-                }
+                let sum = arr.batch_iter::<32>().sum();
                 #[cfg(not(feature = "simd"))]
-                {
-                    temp = arr.iter().fold(temp, sum_f64);
-                }
-                if let Some(val) = temp {
-                    self.result = match self.result {
-                        DataValue::Null => DataValue::Float64(val),
-                        DataValue::Float64(res) => DataValue::Float64(res + val),
-                        _ => panic!("Mismatched type"),
-                    }
-                }
-            }
-            (ArrayImpl::Decimal(arr), DataTypeKind::Decimal(_, _)) => {
-                let mut temp: Option<Decimal> = None;
-                temp = arr.iter().fold(temp, sum_decimal);
-                if let Some(val) = temp {
-                    self.result = match self.result {
-                        DataValue::Null => DataValue::Decimal(val),
-                        DataValue::Decimal(res) => DataValue::Decimal(res + val),
-                        _ => panic!("Mismatched type"),
-                    }
-                }
+                let sum = arr.iter().flatten().sum();
+                DataValue::Float64(sum)
             }
+            (ArrayImpl::Decimal(arr), DataTypeKind::Decimal(_, _)) => DataValue::Decimal(
+                Decimal::checked_sum(arr.iter().flatten().copied()).ok_or_else(overflow)?,
+            ),
+            _ => panic!("Mismatched type"),
+        };
+        self.add(sum)
+    }
+
+    fn update_single(&mut self, args: &[DataValue]) -> Result<(), ExecutorError> {
+        match (&args[0], &self.input_datatype) {
+            (DataValue::Int32(_), DataTypeKind::Int(_))
+            | (DataValue::Int64(_), DataTypeKind::BigInt(_))
+            | (DataValue::Float64(_), DataTypeKind::Double)
+            | (DataValue::Decimal(_), DataTypeKind::Decimal(_, _)) => self.add(args[0].clone()),
             _ => panic!("Mismatched type"),
         }
-        Ok(())
     }
 
-    fn update_single(&mut self, value: &DataValue) -> Result<(), ExecutorError> {
-        match (value, &self.input_datatype) {
-            (DataValue::Int32(val), DataTypeKind::Int(_)) => {
-                self.result = match self.result {
-                    DataValue::Null => DataValue::Int32(*val),
-                    DataValue::Int32(res) => DataValue::Int32(res + val),
-                    _ => panic!("Mismatched type"),
-                }
-            }
-            (DataValue::Int64(val), DataTypeKind::BigInt(_)) => {
-                self.result = match self.result {
-                    DataValue::Null => DataValue::Int64(*val),
-                    DataValue::Int64(res) => DataValue::Int64(res + val),
-                    _ => panic!("Mismatched type"),
-                }
+    fn output(&self) -> DataValue {
+        self.result.clone()
+    }
+
+    fn output_partial(&self) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.result.clone()])
+    }
+
+    fn merge(&mut self, partial: &[DataValue]) -> Result<(), ExecutorError> {
+        match &partial[0] {
+            DataValue::Null => Ok(()),
+            _ => self.update_single(partial),
+        }
+    }
+}
+
+/// A primitive type that can be summed by [`SumAggregationState`] and [`SumGroupedState`].
+///
+/// Integers and decimals are summed with overflow checks, so that an overflow is an error in
+/// all ways of summing up.
+pub trait SumValue: KernelValue {
+    /// Add two values, or return `None` on overflow.
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// Sum the values, or return `None` on overflow.
+    fn checked_sum(values: impl Iterator<Item = Self>) -> Option<Self>;
+
+    /// Sum the values in a slice, or return `None` on overflow.
+    fn checked_sum_slice(values: &[Self]) -> Option<Self> {
+        Self::checked_sum(values.iter().copied())
+    }
+}
+
+macro_rules! impl_sum_integer {
+    ($($t:ty: $wide:ty),*) => {$(
+        impl SumValue for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
             }
-            (DataValue::Float64(val), DataTypeKind::Double) => {
-                self.result = match self.result {
-                    DataValue::Null => DataValue::Float64(*val),
-                    DataValue::Float64(res) => DataValue::Float64(res + val),
-                    _ => panic!("Mismatched type"),
-                }
+
+            /// Sum the values in a wider type, which can't overflow in the middle and is
+            /// vectorized by the compiler, and check the result at the end.
+            fn checked_sum(values: impl Iterator<Item = Self>) -> Option<Self> {
+                <$t>::try_from(values.map(<$wide>::from).sum::<$wide>()).ok()
             }
-            (DataValue::Decimal(val), DataTypeKind::Decimal(_, _)) => {
-                self.result = match self.result {
-                    DataValue::Null => DataValue::Decimal(*val),
-                    DataValue::Decimal(res) => DataValue::Decimal(res + val),
-                    _ => panic!("Mismatched type"),
+        }
+    )*}
+}
+
+impl_sum_integer!(i32: i64, i64: i128);
+
+impl SumValue for f64 {
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sum(values: impl Iterator<Item = Self>) -> Option<Self> {
+        Some(values.sum())
+    }
+
+    #[cfg(feature = "simd")]
+    fn checked_sum_slice(values: &[Self]) -> Option<Self> {
+        use std::simd::Simd;
+
+        let chunks = values.chunks_exact(32);
+        let remainder = chunks.remainder().iter().sum::<f64>();
+        let sum = chunks.fold(Simd::<f64, 32>::splat(0.0), |sum, chunk| {
+            sum + Simd::from_slice(chunk)
+        });
+        Some(sum.reduce_sum() + remainder)
+    }
+}
+
+impl SumValue for Decimal {
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Decimal::checked_add(self, other)
+    }
+
+    fn checked_sum(mut values: impl Iterator<Item = Self>) -> Option<Self> {
+        values.try_fold(Decimal::ZERO, Decimal::checked_add)
+    }
+}
+
+/// Vectorized states of sum aggregation.
+#[derive(Default)]
+pub struct SumGroupedState<T> {
+    sums: Vec<Option<T>>,
+}
+
+impl<T: SumValue> SumGroupedState<T> {
+    fn add(&mut self, group: usize, value: T) -> Result<(), ExecutorError> {
+        let sum = &mut self.sums[group];
+        *sum = Some(match *sum {
+            Some(s) => s.checked_add(value).ok_or_else(overflow)?,
+            None => value,
+        });
+        Ok(())
+    }
+}
+
+impl<T: SumValue> GroupedAggregationState for SumGroupedState<T> {
+    fn resize(&mut self, num_groups: usize) {
+        self.sums.resize(num_groups, None);
+    }
+
+    fn update(&mut self, groups: &[usize], args: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        let array = &args[0];
+        let array = T::downcast(array);
+        let values = array.non_null_iter().as_slice();
+        let valid = array.get_valid_bitmap();
+        // sum each run of rows in the same group at once
+        for (group, rows) in group_runs(groups) {
+            if valid[rows.clone()].all() {
+                let sum = T::checked_sum_slice(&values[rows]).ok_or_else(overflow)?;
+                self.add(group, sum)?;
+            } else {
+                for i in rows.filter(|&i| valid[i]) {
+                    self.add(group, values[i])?;
                 }
             }
-            _ => panic!("Mismatched type"),
         }
         Ok(())
     }
 
-    fn output(&self) -> DataValue {
-        self.result.clone()
+    fn merge(&mut self, groups: &[usize], partial: &[ArrayImpl]) -> Result<(), ExecutorError> {
+        self.update(groups, partial)
+    }
+
+    fn output(&self, group: usize) -> DataValue {
+        match self.sums[group] {
+            Some(sum) => sum.into_value(),
+            None => DataValue::Null,
+        }
+    }
+
+    fn output_partial(&self, group: usize) -> Result<Vec<DataValue>, ExecutorError> {
+        Ok(vec![self.output(group)])
     }
 }
 
@@ -172,17 +234,54 @@ mod tests {
     fn test_sum() {
         let mut state = SumAggregationState::new(DataTypeKind::Int(None));
         let array = ArrayImpl::new_int32((1..5).collect());
-        state.update(&array).unwrap();
+        state.update(&[array]).unwrap();
         assert_eq!(state.output(), DataValue::Int32(10));
 
         let mut state = SumAggregationState::new(DataTypeKind::BigInt(None));
         let array = ArrayImpl::new_int64((1..5).collect());
-        state.update(&array).unwrap();
+        state.update(&[array]).unwrap();
         assert_eq!(state.output(), DataValue::Int64(10));
 
         let mut state = SumAggregationState::new(DataTypeKind::Double);
         let array = ArrayImpl::new_float64([0.1, 0.2, 0.3, 0.4].into_iter().collect());
-        state.update(&array).unwrap();
+        state.update(&[array]).unwrap();
         assert_eq!(state.output(), DataValue::Float64(1.));
     }
+
+    #[test]
+    fn test_sum_grouped() {
+        let mut state = SumGroupedState::<i64>::default();
+        state.resize(3);
+        // rows 0..40, 40..80 and 80..100 are in 3 groups, with a NULL at row 50
+        let array = ArrayImpl::new_int64(
+            (0..100)
+                .map(|i| if i == 50 { None } else { Some(i) })
+                .collect(),
+        );
+        let groups = (0..100).map(|i| i / 40).collect::<Vec<usize>>();
+        state.update(&groups, &[array]).unwrap();
+        assert_eq!(state.output(0), DataValue::Int64((0..40).sum()));
+        assert_eq!(
+            state.output(1),
+            DataValue::Int64((40..80).sum::<i64>() - 50)
+        );
+        assert_eq!(state.output(2), DataValue::Int64((80..100).sum()));
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        // the vectorized and row-by-row sums both fail on overflow
+        let array = ArrayImpl::new_int32([i32::MAX, 1, -1].into_iter().map(Some).collect());
+        let mut state = SumAggregationState::new(DataTypeKind::Int(None));
+        assert!(state.update(&[array]).is_ok());
+        assert_eq!(state.output(), DataValue::Int32(i32::MAX));
+        assert!(state.update_single(&[DataValue::Int32(1)]).is_err());
+
+        let array = ArrayImpl::new_int64((0..40).map(|_| Some(i64::MAX)).collect());
+        let mut state = SumAggregationState::new(DataTypeKind::BigInt(None));
+        assert!(state.update(&[array.clone()]).is_err());
+        let mut state = SumGroupedState::<i64>::default();
+        state.resize(1);
+        assert!(state.update(&[0; 40], &[array]).is_err());
+    }
 }
@@ -2,17 +2,31 @@
 
 use std::future::Future;
 use std::intrinsics;
+use std::path::{Path, PathBuf};
 
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
+use crate::storage::SessionId;
 use crate::utils::sync::WaitGroup;
 
+/// The default memory budget of each executor, in bytes.
+pub const DEFAULT_MEMORY_BUDGET: usize = 1 << 30;
+
 /// Context of executors.
-#[derive(Default)]
 pub struct Context {
     token: CancellationToken,
     wg: WaitGroup,
+    memory_budget: usize,
+    spill_dir: PathBuf,
+    parallelism: usize,
+    session: SessionId,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Context {
@@ -20,8 +34,61 @@ impl Context {
         Self {
             token: Default::default(),
             wg: WaitGroup::new(),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            spill_dir: std::env::temp_dir(),
+            parallelism: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            session: 0,
         }
     }
+
+    /// Sets the memory budget of each executor buffering its input, i.e. hash aggregation, hash
+    /// join and order. Beyond the budget, they spill data to temporary files in the spill
+    /// directory. Each parallel worker of an executor has its own budget.
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Sets the directory of temporary files for spilling. Defaults to the system's temporary
+    /// directory.
+    pub fn with_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = dir.into();
+        self
+    }
+
+    /// Sets the maximum number of workers to scan, join and aggregate in parallel. Defaults to
+    /// the number of CPUs.
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Sets the session the statements run in. Statements of the same session share the
+    /// transaction started by `BEGIN`. Defaults to 0.
+    pub fn with_session(mut self, session: SessionId) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Returns the memory budget of each executor in bytes.
+    pub fn memory_budget(&self) -> usize {
+        self.memory_budget
+    }
+
+    /// Returns the directory of temporary files for spilling.
+    pub fn spill_dir(&self) -> &Path {
+        &self.spill_dir
+    }
+
+    /// Returns the maximum number of parallel workers.
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    /// Returns the session the statements run in.
+    pub fn session(&self) -> SessionId {
+        self.session
+    }
 }
 
 impl Context {
@@ -17,7 +17,9 @@ pub struct DeleteExecutor<S: Storage> {
 
 impl<S: Storage> DeleteExecutor<S> {
     async fn execute_inner(self, token: CancellationToken) -> Result<i32, ExecutorError> {
-        let table = self.storage.get_table(self.table_ref_id)?;
+        let table = self
+            .storage
+            .get_table_in_session(self.table_ref_id, self.context.session())?;
         let mut txn = table.update().await?;
         let mut cnt = 0;
         #[for_await]
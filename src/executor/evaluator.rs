@@ -5,9 +5,12 @@
 use std::borrow::Borrow;
 
 use crate::array::*;
-use crate::binder::BoundExpr;
+use crate::binder::{BoundCase, BoundExpr};
 use crate::parser::{BinaryOperator, UnaryOperator};
-use crate::types::{Blob, ConvertError, DataTypeExt, DataTypeKind, DataValue, Date};
+use crate::types::{
+    is_timestamptz, Blob, ConvertError, DataType, DataTypeExt, DataTypeKind, DataValue, Date,
+    Timestamp, TimestampTz,
+};
 
 impl BoundExpr {
     /// Evaluate the given expression as an array.
@@ -51,6 +54,30 @@ impl BoundExpr {
                         .collect(),
                 ))
             }
+            BoundExpr::FunctionCall(call) => {
+                let input: DataChunk = call
+                    .args
+                    .iter()
+                    .map(|e| e.eval(chunk))
+                    .collect::<Result<_, _>>()?;
+                Ok(call.func.execute(&input)?.array_at(0).clone())
+            }
+            BoundExpr::Case(case) => case.eval_selected(
+                chunk.cardinality(),
+                &self
+                    .return_type()
+                    .unwrap_or_else(|| DataTypeKind::Int(None).nullable()),
+                |expr, visibility| expr.eval(&chunk.filter(visibility.iter().cloned())),
+            ),
+            BoundExpr::InList(in_list) => {
+                let array = in_list.expr.eval(chunk)?;
+                let list = in_list
+                    .list
+                    .iter()
+                    .map(|e| e.eval(chunk))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(array.in_list(&list, in_list.negated))
+            }
             BoundExpr::ExprWithAlias(expr_with_alias) => expr_with_alias.expr.eval(chunk),
             _ => panic!("{:?} should not be evaluated in `eval_array`", self),
         }
@@ -101,11 +128,99 @@ impl BoundExpr {
                         .collect(),
                 ))
             }
+            BoundExpr::FunctionCall(call) => {
+                let input: DataChunk = call
+                    .args
+                    .iter()
+                    .map(|e| e.eval_array_in_storage(chunk, cardinality))
+                    .collect::<Result<_, _>>()?;
+                Ok(call.func.execute(&input)?.array_at(0).clone())
+            }
+            BoundExpr::Case(case) => case.eval_selected(
+                cardinality,
+                &self
+                    .return_type()
+                    .unwrap_or_else(|| DataTypeKind::Int(None).nullable()),
+                |expr, visibility| {
+                    let chunk: PackedVec<_> = chunk
+                        .iter()
+                        .map(|a| a.as_ref().map(|a| a.filter(visibility.iter().cloned())))
+                        .collect();
+                    let cardinality = visibility.iter().filter(|v| **v).count();
+                    expr.eval_array_in_storage(&chunk, cardinality)
+                },
+            ),
+            BoundExpr::InList(in_list) => {
+                let array = in_list.expr.eval_array_in_storage(chunk, cardinality)?;
+                let list = in_list
+                    .list
+                    .iter()
+                    .map(|e| e.eval_array_in_storage(chunk, cardinality))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(array.in_list(&list, in_list.negated))
+            }
             _ => panic!("{:?} should not be evaluated in `eval_array`", self),
         }
     }
 }
 
+impl BoundCase {
+    /// Evaluate `CASE WHEN` by branches. Each condition is only evaluated on the rows not
+    /// matched by previous conditions, and each result only on the rows selected by its
+    /// condition, so that `CASE WHEN b <> 0 THEN a / b ELSE 0 END` never divides by zero.
+    ///
+    /// `eval` evaluates an expression on the rows whose visibility is true.
+    fn eval_selected(
+        &self,
+        len: usize,
+        return_type: &DataType,
+        mut eval: impl FnMut(&BoundExpr, &[bool]) -> Result<ArrayImpl, ConvertError>,
+    ) -> Result<ArrayImpl, ConvertError> {
+        let mut values = vec![DataValue::Null; len];
+        // the rows not matched by any condition yet
+        let mut remaining = vec![true; len];
+        for (cond, result) in self.conditions.iter().zip(&self.results) {
+            if !remaining.contains(&true) {
+                break;
+            }
+            let cond = match eval(cond, &remaining)? {
+                ArrayImpl::Bool(a) => a,
+                _ => panic!("CASE conditions can only be BOOL arrays"),
+            };
+            let mut cond = cond.iter();
+            let selected = remaining
+                .iter_mut()
+                .map(|remaining| {
+                    let matched = *remaining && cond.next().unwrap() == Some(&true);
+                    *remaining &= !matched;
+                    matched
+                })
+                .collect::<Vec<_>>();
+            if selected.contains(&true) {
+                scatter(&mut values, &selected, &eval(result, &selected)?);
+            }
+        }
+        if let Some(else_result) = &self.else_result {
+            if remaining.contains(&true) {
+                scatter(&mut values, &remaining, &eval(else_result, &remaining)?);
+            }
+        }
+        let mut builder = ArrayBuilderImpl::with_capacity(len, return_type);
+        for value in &values {
+            builder.push(value);
+        }
+        Ok(builder.finish())
+    }
+}
+
+/// Write the values of `array` to the positions of `values` whose visibility is true.
+fn scatter(values: &mut [DataValue], visibility: &[bool], array: &ArrayImpl) {
+    let rows = visibility.iter().enumerate().filter(|(_, v)| **v);
+    for (i, (row, _)) in rows.enumerate() {
+        values[row] = array.get(i);
+    }
+}
+
 impl ArrayImpl {
     /// Perform unary operation.
     pub fn unary_op(&self, op: &UnaryOperator) -> ArrayImpl {
@@ -155,6 +270,8 @@ impl ArrayImpl {
 
                     (A::Decimal(a), A::Decimal(b)) => A::new_decimal(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
                     (A::Date(a), A::Interval(b)) => A::new_date(binary_op(a.as_ref(), b.as_ref(), |a, b| *a $op *b)),
+                    (A::Timestamp(a), A::Interval(b)) => A::new_timestamp(binary_op(a.as_ref(), b.as_ref(), |a, b| *a $op *b)),
+                    (A::TimestampTz(a), A::Interval(b)) => A::new_timestamptz(binary_op(a.as_ref(), b.as_ref(), |a, b| *a $op *b)),
                     _ => todo!("Support more types for {}", stringify!($op)),
                 }
             }
@@ -169,6 +286,9 @@ impl ArrayImpl {
                     (A::Float64(a), A::Float64(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
                     (A::Utf8(a), A::Utf8(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
                     (A::Date(a), A::Date(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
+                    (A::Timestamp(a), A::Timestamp(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
+                    (A::TimestampTz(a), A::TimestampTz(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
+                    (A::Interval(a), A::Interval(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
                     (A::Decimal(a), A::Decimal(b)) => A::new_bool(binary_op(a.as_ref(), b.as_ref(), |a, b| a $op b)),
                     _ => todo!("Support more types for {}", stringify!($op)),
                 }
@@ -176,7 +296,20 @@ impl ArrayImpl {
         }
         match op {
             BinaryOperator::Plus => arith!(+),
-            BinaryOperator::Minus => arith!(-),
+            BinaryOperator::Minus => match (self, right) {
+                (A::Date(a), A::Date(b)) => {
+                    A::new_int32(binary_op(a.as_ref(), b.as_ref(), |a, b| {
+                        a.get_inner() - b.get_inner()
+                    }))
+                }
+                (A::Timestamp(a), A::Timestamp(b)) => {
+                    A::new_interval(binary_op(a.as_ref(), b.as_ref(), |a, b| *a - *b))
+                }
+                (A::TimestampTz(a), A::TimestampTz(b)) => {
+                    A::new_interval(binary_op(a.as_ref(), b.as_ref(), |a, b| *a - *b))
+                }
+                _ => arith!(-),
+            },
             BinaryOperator::Multiply => arith!(*),
             BinaryOperator::Divide => arith!(/),
             BinaryOperator::Modulo => arith!(%),
@@ -198,6 +331,20 @@ impl ArrayImpl {
                 }
                 _ => panic!("And can only be applied to BOOL arrays"),
             },
+            BinaryOperator::Like
+            | BinaryOperator::NotLike
+            | BinaryOperator::ILike
+            | BinaryOperator::NotILike => match (self, right) {
+                (A::Utf8(a), A::Utf8(b)) => {
+                    let negated = matches!(op, BinaryOperator::NotLike | BinaryOperator::NotILike);
+                    let case_insensitive =
+                        matches!(op, BinaryOperator::ILike | BinaryOperator::NotILike);
+                    A::new_bool(binary_op(a.as_ref(), b.as_ref(), |s, p| {
+                        like(s, p, case_insensitive) != negated
+                    }))
+                }
+                _ => panic!("Like can only be applied to STRING arrays"),
+            },
             BinaryOperator::Or => match (self, right) {
                 (A::Bool(a), A::Bool(b)) => {
                     A::new_bool(binary_op_with_null(a.as_ref(), b.as_ref(), |a, b| {
@@ -214,6 +361,21 @@ impl ArrayImpl {
         }
     }
 
+    /// Evaluate `self [NOT] IN (list)` with three-valued logic: the result is `NULL`
+    /// if no item matches and either `self` or any item is `NULL`.
+    pub fn in_list(&self, list: &[ArrayImpl], negated: bool) -> ArrayImpl {
+        let mut result = ArrayImpl::new_bool((0..self.len()).map(|_| false).collect());
+        for item in list {
+            let eq = self.binary_op(&BinaryOperator::Eq, item);
+            result = result.binary_op(&BinaryOperator::Or, &eq);
+        }
+        if negated {
+            result.unary_op(&UnaryOperator::Not)
+        } else {
+            result
+        }
+    }
+
     /// Cast the array to another type.
     pub fn try_cast(&self, data_type: DataTypeKind) -> Result<Self, ConvertError> {
         type Type = DataTypeKind;
@@ -334,6 +496,16 @@ impl ArrayImpl {
                 Type::Date => Self::new_date(try_unary_op(a.as_ref(), |s| {
                     Date::from_str(s).map_err(|e| ConvertError::ParseDate(s.to_string(), e))
                 })?),
+                Type::Timestamp => Self::new_timestamp(try_unary_op(a.as_ref(), |s| {
+                    Timestamp::from_str(s)
+                        .map_err(|e| ConvertError::ParseTimestamp(s.to_string(), e))
+                })?),
+                ty if is_timestamptz(&ty) => {
+                    Self::new_timestamptz(try_unary_op(a.as_ref(), |s| {
+                        TimestampTz::from_str(s)
+                            .map_err(|e| ConvertError::ParseTimestamp(s.to_string(), e))
+                    })?)
+                }
                 Type::Bytea | Type::Blob(_) => Self::new_blob(try_unary_op(a.as_ref(), |s| {
                     Blob::from_str(s).map_err(|e| ConvertError::ParseBlob(s.to_string(), e))
                 })?),
@@ -375,8 +547,35 @@ impl ArrayImpl {
                 Type::String | Type::Char(_) | Type::Varchar(_) => {
                     Self::new_utf8(unary_op(a.as_ref(), |&d| d.to_string()))
                 }
+                Type::Date => Self::Date(a.clone()),
+                Type::Timestamp => {
+                    Self::new_timestamp(unary_op(a.as_ref(), |&d| Timestamp::from(d)))
+                }
+                ty if is_timestamptz(&ty) => Self::new_timestamptz(unary_op(a.as_ref(), |&d| {
+                    TimestampTz::from_utc(Timestamp::from(d))
+                })),
                 ty => return Err(ConvertError::FromDateError(ty)),
             },
+            Self::Timestamp(a) => match data_type {
+                Type::String | Type::Char(_) | Type::Varchar(_) => {
+                    Self::new_utf8(unary_op(a.as_ref(), |&t| t.to_string()))
+                }
+                Type::Date => Self::new_date(unary_op(a.as_ref(), |t| t.date())),
+                Type::Timestamp => Self::Timestamp(a.clone()),
+                ty if is_timestamptz(&ty) => {
+                    Self::new_timestamptz(unary_op(a.as_ref(), |&t| TimestampTz::from_utc(t)))
+                }
+                ty => return Err(ConvertError::FromTimestampError(ty)),
+            },
+            Self::TimestampTz(a) => match data_type {
+                Type::String | Type::Char(_) | Type::Varchar(_) => {
+                    Self::new_utf8(unary_op(a.as_ref(), |&t| t.to_string()))
+                }
+                Type::Date => Self::new_date(unary_op(a.as_ref(), |t| t.to_utc().date())),
+                Type::Timestamp => Self::new_timestamp(unary_op(a.as_ref(), |t| t.to_utc())),
+                ty if is_timestamptz(&ty) => Self::TimestampTz(a.clone()),
+                ty => return Err(ConvertError::FromTimestampError(ty)),
+            },
             Self::Interval(_) => return Err(ConvertError::FromIntervalError(data_type)),
         })
     }
@@ -505,3 +704,92 @@ where
     }
     Ok(builder.finish())
 }
+
+/// Match `s` against a SQL `LIKE` pattern, where `%` matches any sequence of characters,
+/// `_` matches any single character and `\` escapes the following character.
+fn like(s: &str, pattern: &str, case_insensitive: bool) -> bool {
+    let (s, p): (Vec<char>, Vec<char>) = if case_insensitive {
+        (
+            s.to_lowercase().chars().collect(),
+            pattern.to_lowercase().chars().collect(),
+        )
+    } else {
+        (s.chars().collect(), pattern.chars().collect())
+    };
+    let (mut si, mut pi) = (0, 0);
+    // The position after the last `%` in pattern and the position in `s` it was matched at.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while si < s.len() {
+        if pi < p.len() {
+            match p[pi] {
+                '%' => {
+                    pi += 1;
+                    backtrack = Some((pi, si));
+                    continue;
+                }
+                '_' => {
+                    pi += 1;
+                    si += 1;
+                    continue;
+                }
+                '\\' if pi + 1 < p.len() => {
+                    if p[pi + 1] == s[si] {
+                        pi += 2;
+                        si += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == s[si] {
+                        pi += 1;
+                        si += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        // Mismatch: let the last `%` consume one more character.
+        match backtrack {
+            Some((bp, bs)) => {
+                pi = bp;
+                si = bs + 1;
+                backtrack = Some((bp, bs + 1));
+            }
+            None => return false,
+        }
+    }
+    p[pi..].iter().all(|&c| c == '%')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like() {
+        assert!(like("PROMO BRUSHED", "PROMO%", false));
+        assert!(!like("STANDARD BRUSHED", "PROMO%", false));
+        assert!(like("special requests", "%special%requests%", false));
+        assert!(like("abc", "a_c", false));
+        assert!(!like("abbc", "a_c", false));
+        assert!(like("", "%", false));
+        assert!(!like("", "_", false));
+        assert!(like("100%", "100\\%", false));
+        assert!(!like("1000", "100\\%", false));
+        assert!(like("Forest Green", "forest%", true));
+        assert!(!like("Forest Green", "forest%", false));
+    }
+
+    #[test]
+    fn test_in_list_null() {
+        let array = ArrayImpl::new_int32([Some(1), Some(2), None].into_iter().collect());
+        let list = [
+            ArrayImpl::new_int32([1, 1, 1].into_iter().collect()),
+            ArrayImpl::new_int32([None, None, None].into_iter().collect()),
+        ];
+        let expected = ArrayImpl::new_bool([Some(true), None, None].into_iter().collect());
+        assert_eq!(array.in_list(&list, false), expected);
+        let expected = ArrayImpl::new_bool([Some(false), None, None].into_iter().collect());
+        assert_eq!(array.in_list(&list, true), expected);
+    }
+}
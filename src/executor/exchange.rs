@@ -0,0 +1,233 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Exchange executors redistribute data among parallel workers.
+//!
+//! Each input of an exchange is driven by its own task, so the workers producing the inputs run
+//! on different threads of the tokio runtime.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::*;
+use crate::array::ArrayImpl;
+use crate::types::DataValue;
+
+/// The number of chunks buffered for each output of an exchange.
+const EXCHANGE_BUFFER_SIZE: usize = 16;
+
+type ChunkSender = mpsc::Sender<Result<DataChunk, ExecutorError>>;
+type ChunkReceiver = mpsc::Receiver<Result<DataChunk, ExecutorError>>;
+
+/// Gathers the outputs of parallel inputs into a single stream in no particular order.
+pub struct GatherExecutor {
+    pub inputs: Vec<BoxedExecutor>,
+    pub context: Arc<Context>,
+}
+
+impl GatherExecutor {
+    #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+    pub async fn execute(self) {
+        let (tx, rx) = mpsc::channel(EXCHANGE_BUFFER_SIZE);
+        for input in self.inputs {
+            let tx = tx.clone();
+            if (self.context)
+                .spawn(|token| Self::forward(input, tx, token))
+                .is_none()
+            {
+                return Err(ExecutorError::Abort);
+            }
+        }
+        drop(tx);
+
+        #[for_await]
+        for chunk in receive_chunks(rx) {
+            yield chunk?;
+        }
+    }
+
+    /// Send the chunks of the input to `tx` until it's closed.
+    async fn forward(mut input: BoxedExecutor, tx: ChunkSender, token: CancellationToken) {
+        loop {
+            let chunk = match select_with_token(&token, input.next()).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => return,
+                Err(err) => Err(err),
+            };
+            let is_err = chunk.is_err();
+            if tx.send(chunk).await.is_err() || is_err {
+                return;
+            }
+        }
+    }
+}
+
+/// Redistributes the rows of parallel inputs into `num_partitions` outputs by the hash of
+/// `keys`, so that rows with the same keys end up in the same output.
+///
+/// Errors of the inputs are reported by the first output.
+pub struct HashExchangeExecutor {
+    pub inputs: Vec<BoxedExecutor>,
+    pub keys: Vec<BoundExpr>,
+    pub num_partitions: usize,
+    pub context: Arc<Context>,
+}
+
+impl HashExchangeExecutor {
+    pub fn execute(self) -> Vec<BoxedExecutor> {
+        let (txs, rxs): (Vec<ChunkSender>, Vec<ChunkReceiver>) = (0..self.num_partitions)
+            .map(|_| mpsc::channel(EXCHANGE_BUFFER_SIZE))
+            .unzip();
+        let keys = Arc::new(self.keys);
+        for input in self.inputs {
+            let (txs, keys) = (txs.clone(), keys.clone());
+            if (self.context)
+                .spawn(|token| Self::partition(input, keys, txs, token))
+                .is_none()
+            {
+                return (0..self.num_partitions)
+                    .map(|_| futures::stream::once(async { Err(ExecutorError::Abort) }).boxed())
+                    .collect();
+            }
+        }
+        rxs.into_iter().map(receive_chunks).collect()
+    }
+
+    /// Split the chunks of the input by the hash of keys and send them to `txs`.
+    async fn partition(
+        mut input: BoxedExecutor,
+        keys: Arc<Vec<BoundExpr>>,
+        txs: Vec<ChunkSender>,
+        token: CancellationToken,
+    ) {
+        let result: Result<(), ExecutorError> = async {
+            while let Some(chunk) = select_with_token(&token, input.next()).await? {
+                if txs.iter().all(|tx| tx.is_closed()) {
+                    return Ok(());
+                }
+                let chunks = split_chunk(&chunk?, &keys, txs.len())?;
+                for (tx, chunk) in txs.iter().zip_eq(chunks) {
+                    // outputs that are no longer consumed are skipped
+                    if chunk.cardinality() > 0 {
+                        let _ = tx.send(Ok(chunk)).await;
+                    }
+                }
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(err) = result {
+            let _ = txs[0].send(Err(err)).await;
+        }
+    }
+}
+
+/// Split the rows of a chunk into `num_partitions` chunks by the hash of keys.
+fn split_chunk(
+    chunk: &DataChunk,
+    keys: &[BoundExpr],
+    num_partitions: usize,
+) -> Result<Vec<DataChunk>, ExecutorError> {
+    let key_arrays: Vec<ArrayImpl> = keys.iter().map(|key| key.eval(chunk)).try_collect()?;
+    let partitions = (0..chunk.cardinality())
+        .map(|i| {
+            let key = key_arrays
+                .iter()
+                .map(|a| a.get(i))
+                .collect::<Vec<DataValue>>();
+            // hash differently from spilling, which partitions the rows of an exchange output
+            // again by the hash of the same keys
+            let mut hasher = DefaultHasher::new();
+            "exchange".hash(&mut hasher);
+            key.hash(&mut hasher);
+            hasher.finish() as usize % num_partitions
+        })
+        .collect_vec();
+    Ok((0..num_partitions)
+        .map(|p| chunk.filter(partitions.iter().map(|&partition| partition == p)))
+        .collect())
+}
+
+#[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+async fn receive_chunks(mut rx: ChunkReceiver) {
+    while let Some(chunk) = rx.recv().await {
+        yield chunk?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::binder::BoundInputRef;
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    fn input(range: std::ops::Range<i32>) -> BoxedExecutor {
+        let chunks = range
+            .chunks(100)
+            .into_iter()
+            .map(|values| {
+                let array = ArrayImpl::new_int32(values.collect());
+                Ok([array].into_iter().collect::<DataChunk>())
+            })
+            .collect_vec();
+        futures::stream::iter(chunks).boxed()
+    }
+
+    async fn collect_values(executor: BoxedExecutor) -> Vec<DataValue> {
+        let chunks: Vec<DataChunk> = executor.try_collect().await.unwrap();
+        let mut values = chunks
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.get(0)))
+            .collect_vec();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        values
+    }
+
+    #[tokio::test]
+    async fn test_hash_exchange() {
+        let context = Arc::new(Context::new());
+        let key = BoundExpr::InputRef(BoundInputRef {
+            index: 0,
+            return_type: DataTypeKind::Int(None).not_null(),
+        });
+        // both inputs have the keys 0..1000
+        let outputs = HashExchangeExecutor {
+            inputs: vec![input(0..1000), input(0..1000)],
+            keys: vec![key],
+            num_partitions: 4,
+            context,
+        }
+        .execute();
+        let outputs = futures::future::join_all(outputs.into_iter().map(collect_values)).await;
+
+        let mut all = vec![];
+        for values in outputs {
+            // the same keys from different inputs are in the same output
+            for pair in values.chunks(2) {
+                assert_eq!(pair[0], pair[1]);
+            }
+            all.extend(values);
+        }
+        all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let expected = (0..1000)
+            .flat_map(|v| [DataValue::Int32(v), DataValue::Int32(v)])
+            .collect_vec();
+        assert_eq!(all, expected);
+    }
+
+    #[tokio::test]
+    async fn test_gather() {
+        let context = Arc::new(Context::new());
+        let executor = GatherExecutor {
+            inputs: vec![input(0..1000), input(1000..1500), input(1500..1500)],
+            context,
+        }
+        .execute();
+        let expected = (0..1500).map(DataValue::Int32).collect_vec();
+        assert_eq!(collect_values(executor).await, expected);
+    }
+}
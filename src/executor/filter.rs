@@ -1,8 +1,9 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
 use super::*;
-use crate::array::{Array, ArrayImpl, DataChunk};
+use crate::array::{Array, ArrayBuilderImpl, ArrayImpl, DataChunk};
 use crate::binder::BoundExpr;
+use crate::types::{ConvertError, DataValue};
 
 /// The executor of a filter operation.
 pub struct FilterExecutor {
@@ -16,6 +17,10 @@ impl FilterExecutor {
         #[for_await]
         for batch in self.child {
             let batch = batch?;
+            if let Some(vis) = Self::eval_on_dictionary(&self.expr, &batch)? {
+                yield batch.filter(vis.into_iter());
+                continue;
+            }
             let vis = match self.expr.eval(&batch)? {
                 ArrayImpl::Bool(a) => a,
                 _ => panic!("filters can only accept bool array"),
@@ -23,4 +28,56 @@ impl FilterExecutor {
             yield batch.filter(vis.iter().map(|b| matches!(b, Some(true))));
         }
     }
+
+    /// If the filter only depends on a dictionary-encoded column, evaluate it on the distinct
+    /// values of the dictionary, and compare on the codes.
+    fn eval_on_dictionary(
+        expr: &BoundExpr,
+        batch: &DataChunk,
+    ) -> Result<Option<Vec<bool>>, ConvertError> {
+        let columns = expr.get_filter_column(batch.column_count());
+        if columns.count_ones() != 1 {
+            return Ok(None);
+        }
+        let column_idx = columns.first_one().unwrap();
+        let dictionary = match batch.array_at(column_idx) {
+            ArrayImpl::Utf8(a) => match a.dictionary() {
+                Some(dictionary) => dictionary,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let values = dictionary.values();
+        if values.len() >= batch.cardinality() {
+            return Ok(None);
+        }
+
+        // Other columns are not referenced, so fill them with nulls.
+        let dict_chunk: DataChunk = batch
+            .arrays()
+            .iter()
+            .enumerate()
+            .map(|(idx, array)| {
+                if idx == column_idx {
+                    return ArrayImpl::Utf8(values.clone());
+                }
+                let mut builder = ArrayBuilderImpl::from_type_of_array(array);
+                for _ in 0..values.len() {
+                    builder.push(&DataValue::Null);
+                }
+                builder.finish()
+            })
+            .collect();
+        let dict_vis = match expr.eval(&dict_chunk)? {
+            ArrayImpl::Bool(a) => a,
+            _ => panic!("filters can only accept bool array"),
+        };
+        Ok(Some(
+            dictionary
+                .codes()
+                .iter()
+                .map(|&code| matches!(dict_vis.get(code as usize), Some(true)))
+                .collect(),
+        ))
+    }
 }
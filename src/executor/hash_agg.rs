@@ -1,90 +1,276 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
 use std::collections::HashMap;
+use std::mem::size_of;
 
-use iter_chunks::IterChunks;
 use smallvec::SmallVec;
 
+use super::spill::{estimated_size, SpillPartitions, MAX_SPILL_DEPTH};
 use super::*;
-use crate::array::{ArrayBuilderImpl, ArrayImpl};
+use crate::array::{ArrayBuilderImpl, ArrayImpl, BytesDictionary};
 use crate::binder::{BoundAggCall, BoundExpr};
-use crate::executor::aggregation::AggregationState;
-use crate::types::DataValue;
+use crate::executor::aggregation::{partial_types, GroupedAggregationState};
+use crate::types::{DataType, DataValue};
 
 /// The executor of hash aggregation.
+///
+/// Each input row is mapped to the index of its group, and the states of all groups are updated
+/// from the arrays of arguments at once by vectorized kernels.
+///
+/// Once the groups exceed the memory budget, input rows of new groups are partitioned by the
+/// hash of their keys into temporary files, and each partition is aggregated separately after
+/// the groups in memory are output.
+///
+/// In parallel execution, the aggregation is split into a [`AggPhase::Partial`] phase on each
+/// part of the input and a [`AggPhase::Final`] phase on each hash partition of the groups.
 pub struct HashAggExecutor {
     pub agg_calls: Vec<BoundAggCall>,
     pub group_keys: Vec<BoundExpr>,
     pub child: BoxedExecutor,
+    pub input_types: Vec<DataType>,
+    pub phase: AggPhase,
+    pub context: Arc<Context>,
+}
+
+/// The phase of a hash aggregation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggPhase {
+    /// Aggregate the input rows into the results.
+    Single,
+    /// Aggregate the input rows into intermediate states, which are output as the group keys
+    /// followed by the [`partial_types`] of each aggregation.
+    ///
+    /// It never spills. Instead, the intermediate states are output and cleared once they exceed
+    /// the memory budget, since the final phase merges states of the same group anyway.
+    Partial,
+    /// Merge the intermediate states output by the partial phase. The group keys must be the
+    /// leading columns of the input.
+    Final,
 }
 
 pub type HashKey = SmallVec<[DataValue; 16]>;
-pub type HashValue = SmallVec<[Box<dyn AggregationState>; 16]>;
+type GroupedStates = SmallVec<[Box<dyn GroupedAggregationState>; 16]>;
+
+/// The estimated size of the state of an aggregation for a group in bytes.
+const AGG_STATE_SIZE: usize = 16;
+
+/// The aggregation states of groups within the memory budget.
+struct GroupTable {
+    /// The index of each group.
+    groups: HashMap<Arc<HashKey>, usize>,
+    /// The key of each group.
+    keys: Vec<Arc<HashKey>>,
+    /// The states of each aggregation for all groups.
+    states: GroupedStates,
+    memory_usage: usize,
+    /// The memory budget beyond which new groups are spilled.
+    budget: usize,
+    context: Arc<Context>,
+    depth: usize,
+    /// The input rows of groups not in the table.
+    spilled: Option<SpillPartitions>,
+}
+
+impl GroupTable {
+    fn new(agg_calls: &[BoundAggCall], context: Arc<Context>, depth: usize, budget: usize) -> Self {
+        GroupTable {
+            groups: HashMap::new(),
+            keys: vec![],
+            states: create_grouped_agg_states(agg_calls),
+            memory_usage: 0,
+            budget,
+            context,
+            depth,
+            spilled: None,
+        }
+    }
+
+    /// Get the index of the group, or `None` if it's new and exceeds the memory budget.
+    fn get_or_insert(&mut self, group_key: &HashKey) -> Option<usize> {
+        if let Some(&group) = self.groups.get(group_key) {
+            return Some(group);
+        }
+        let size = size_of::<HashKey>()
+            + 2 * size_of::<usize>()
+            + estimated_size(group_key)
+            + self.states.len() * AGG_STATE_SIZE;
+        if self.memory_usage + size > self.budget {
+            return None;
+        }
+        self.memory_usage += size;
+        let group = self.keys.len();
+        let group_key = Arc::new(group_key.clone());
+        self.keys.push(group_key.clone());
+        self.groups.insert(group_key, group);
+        for state in self.states.iter_mut() {
+            state.resize(group + 1);
+        }
+        Some(group)
+    }
+
+    /// Spill an input row of the group.
+    fn spill(
+        &mut self,
+        group_key: &HashKey,
+        row: impl IntoIterator<Item = DataValue>,
+    ) -> Result<(), ExecutorError> {
+        if self.spilled.is_none() {
+            let partitions = SpillPartitions::create(self.context.spill_dir(), self.depth)?;
+            self.spilled = Some(partitions);
+        }
+        self.spilled.as_mut().unwrap().write_row(group_key, row)?;
+        Ok(())
+    }
+
+    /// Take the keys and states of all groups out of the table.
+    fn take(&mut self, agg_calls: &[BoundAggCall]) -> (Vec<Arc<HashKey>>, GroupedStates) {
+        self.groups.clear();
+        self.memory_usage = 0;
+        let states = std::mem::replace(&mut self.states, create_grouped_agg_states(agg_calls));
+        (std::mem::take(&mut self.keys), states)
+    }
+}
 
 impl HashAggExecutor {
     fn execute_inner(
-        state_entries: &mut HashMap<Arc<HashKey>, HashValue>,
+        table: &mut GroupTable,
         chunk: DataChunk,
         agg_calls: &[BoundAggCall],
         group_keys: &[BoundExpr],
+        partial_widths: Option<&[usize]>,
     ) -> Result<(), ExecutorError> {
-        // Eval group keys and arguments
+        // Eval group keys
         let group_cols: SmallVec<[ArrayImpl; 16]> =
             group_keys.iter().map(|e| e.eval(&chunk)).try_collect()?;
-        let arrays: SmallVec<[ArrayImpl; 16]> = agg_calls
+        let group_key =
+            |row_idx| -> HashKey { group_cols.iter().map(|col| col.get(row_idx)).collect() };
+
+        // Map each row to its group, or `None` if the group exceeds the memory budget.
+        //
+        // If all group keys are dictionary-encoded, group rows on the codes, so that each
+        // group key is only built and looked up once per chunk.
+        let dictionaries: Option<SmallVec<[&BytesDictionary<str>; 16]>> = group_cols
             .iter()
-            .map(|agg| agg.args[0].eval(&chunk))
-            .try_collect()?;
-
-        // Update states
-        let num_rows = chunk.cardinality();
-        for row_idx in 0..num_rows {
-            let mut group_key = HashKey::new();
-            for col in group_cols.iter() {
-                group_key.push(col.get(row_idx));
+            .map(|col| match col {
+                ArrayImpl::Utf8(a) => a.dictionary(),
+                _ => None,
+            })
+            .collect();
+        let row_groups: Vec<Option<usize>> = match dictionaries.filter(|d| !d.is_empty()) {
+            Some(dictionaries) => {
+                let mut code_groups: HashMap<SmallVec<[u32; 16]>, Option<usize>> = HashMap::new();
+                (0..chunk.cardinality())
+                    .map(|row_idx| {
+                        let codes = dictionaries.iter().map(|d| d.codes()[row_idx]).collect();
+                        *code_groups
+                            .entry(codes)
+                            .or_insert_with(|| table.get_or_insert(&group_key(row_idx)))
+                    })
+                    .collect()
             }
-            let group_key = Arc::new(group_key);
+            None => (0..chunk.cardinality())
+                .map(|row_idx| table.get_or_insert(&group_key(row_idx)))
+                .collect(),
+        };
 
-            if !state_entries.contains_key(&group_key) {
-                state_entries.insert(group_key.clone(), create_agg_states(agg_calls));
-            }
-            // since we just checked existence, the key must exist so we `unwrap` directly
-            let states = state_entries.get_mut(&group_key).unwrap();
-            for (array, state) in arrays.iter().zip_eq(states.iter_mut()) {
-                // TODO: support aggregations with multiple arguments
-                state.update_single(&array.get(row_idx))?;
+        let groups = row_groups.iter().flatten().copied().collect_vec();
+        let spilled = groups.len() < row_groups.len();
+        if spilled {
+            for (row_idx, _) in row_groups.iter().enumerate().filter(|(_, g)| g.is_none()) {
+                table.spill(&group_key(row_idx), chunk.row(row_idx).values())?;
             }
         }
+        let visibility = |filter: Option<Vec<bool>>| -> Option<Vec<bool>> {
+            match filter {
+                Some(filter) => Some(
+                    (row_groups.iter().zip_eq(filter))
+                        .map(|(group, visible)| group.is_some() && visible)
+                        .collect(),
+                ),
+                None if spilled => Some(row_groups.iter().map(Option::is_some).collect()),
+                None => None,
+            }
+        };
 
+        // Update states from the arguments, or merge the intermediate states in the final phase
+        match partial_widths {
+            Some(widths) => {
+                let mut arrays = chunk.arrays()[group_keys.len()..].to_vec();
+                if let Some(visibility) = visibility(None) {
+                    for array in arrays.iter_mut() {
+                        *array = array.filter(visibility.iter().copied());
+                    }
+                }
+                let mut offset = 0;
+                for (&width, state) in widths.iter().zip_eq(table.states.iter_mut()) {
+                    state.merge(&groups, &arrays[offset..offset + width])?;
+                    offset += width;
+                }
+            }
+            None => {
+                for (agg, state) in agg_calls.iter().zip_eq(table.states.iter_mut()) {
+                    let mut args = eval_agg_args(agg, &chunk)?;
+                    let visibility = match visibility(eval_agg_filter(agg, &chunk)?) {
+                        Some(visibility) => visibility,
+                        None => {
+                            state.update(&groups, &args)?;
+                            continue;
+                        }
+                    };
+                    for array in args.iter_mut() {
+                        *array = array.filter(visibility.iter().copied());
+                    }
+                    let groups = (row_groups.iter().zip_eq(&visibility))
+                        .filter(|&(_, &visible)| visible)
+                        .map(|(group, _)| group.unwrap())
+                        .collect_vec();
+                    state.update(&groups, &args)?;
+                }
+            }
+        }
         Ok(())
     }
 
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     async fn finish_agg(
-        state_entries: HashMap<Arc<HashKey>, HashValue>,
+        keys: Vec<Arc<HashKey>>,
+        states: GroupedStates,
         agg_calls: Vec<BoundAggCall>,
         group_keys: Vec<BoundExpr>,
+        phase: AggPhase,
     ) {
-        // We use `iter_chunks::IterChunks` instead of `IterTools::Chunks` here, since
-        // the latter doesn't implement Send.
-        let mut batches = IterChunks::chunks(state_entries.iter(), PROCESSING_WINDOW_SIZE);
-        while let Some(batch) = batches.next() {
+        for batch_start in (0..keys.len()).step_by(PROCESSING_WINDOW_SIZE) {
+            let batch = batch_start..keys.len().min(batch_start + PROCESSING_WINDOW_SIZE);
             let mut key_builders = group_keys
                 .iter()
                 .map(|e| ArrayBuilderImpl::new(&e.return_type().unwrap()))
                 .collect::<Vec<ArrayBuilderImpl>>();
-            let mut res_builders = agg_calls
-                .iter()
-                .map(|agg| ArrayBuilderImpl::new(&agg.return_type))
-                .collect::<Vec<ArrayBuilderImpl>>();
-            for (key, val) in batch {
+            let mut res_builders = match phase {
+                AggPhase::Partial => agg_calls
+                    .iter()
+                    .flat_map(|agg| partial_types(agg).unwrap())
+                    .map(|ty| ArrayBuilderImpl::new(&ty))
+                    .collect::<Vec<ArrayBuilderImpl>>(),
+                _ => agg_calls
+                    .iter()
+                    .map(|agg| ArrayBuilderImpl::new(&agg.return_type))
+                    .collect::<Vec<ArrayBuilderImpl>>(),
+            };
+            for group in batch {
                 // Push group key
-                for (k, builder) in key.iter().zip_eq(key_builders.iter_mut()) {
+                for (k, builder) in keys[group].iter().zip_eq(key_builders.iter_mut()) {
                     builder.push(k);
                 }
                 // Push aggregate result
-                for (state, builder) in val.iter().zip_eq(res_builders.iter_mut()) {
-                    builder.push(&state.output());
+                let mut values = vec![];
+                for state in &states {
+                    match phase {
+                        AggPhase::Partial => values.extend(state.output_partial(group)?),
+                        _ => values.push(state.output(group)),
+                    }
+                }
+                for (value, builder) in values.iter().zip_eq(res_builders.iter_mut()) {
+                    builder.push(value);
                 }
             }
             key_builders.append(&mut res_builders);
@@ -94,18 +280,213 @@ impl HashAggExecutor {
 
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     pub async fn execute(self) {
-        let mut state_entries = HashMap::new();
+        let budget = self.context.memory_budget();
+        let partial_widths = match self.phase {
+            AggPhase::Final => Some(
+                (self.agg_calls.iter())
+                    .map(|agg| partial_types(agg).unwrap().len())
+                    .collect_vec(),
+            ),
+            _ => None,
+        };
+        // inputs to aggregate, with their depth of spilling
+        let mut inputs = vec![(self.child, 0)];
 
-        #[for_await]
-        for chunk in self.child {
-            let chunk = chunk?;
-            Self::execute_inner(&mut state_entries, chunk, &self.agg_calls, &self.group_keys)?;
-        }
+        while let Some((child, depth)) = inputs.pop() {
+            let table_budget = match self.phase {
+                AggPhase::Partial => usize::MAX,
+                _ => budget,
+            };
+            let mut table =
+                GroupTable::new(&self.agg_calls, self.context.clone(), depth, table_budget);
+
+            #[for_await]
+            for chunk in child {
+                let chunk = chunk?;
+                Self::execute_inner(
+                    &mut table,
+                    chunk,
+                    &self.agg_calls,
+                    &self.group_keys,
+                    partial_widths.as_deref(),
+                )?;
+                if self.phase == AggPhase::Partial && table.memory_usage > budget {
+                    let (keys, states) = table.take(&self.agg_calls);
+                    #[for_await]
+                    for chunk in Self::finish_agg(
+                        keys,
+                        states,
+                        self.agg_calls.clone(),
+                        self.group_keys.clone(),
+                        self.phase,
+                    ) {
+                        yield chunk?;
+                    }
+                }
+            }
 
-        #[for_await]
-        for chunk in Self::finish_agg(state_entries, self.agg_calls, self.group_keys) {
-            let chunk = chunk?;
-            yield chunk
+            if let Some(partitions) = table.spilled.take() {
+                if depth == MAX_SPILL_DEPTH {
+                    return Err(ExecutorError::MemoryBudgetExceeded { budget });
+                }
+                for file in partitions.into_files() {
+                    inputs.push((file.into_executor(self.input_types.clone()), depth + 1));
+                }
+            }
+
+            #[for_await]
+            for chunk in Self::finish_agg(
+                table.keys,
+                table.states,
+                self.agg_calls.clone(),
+                self.group_keys.clone(),
+                self.phase,
+            ) {
+                yield chunk?;
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::binder::{AggKind, BoundInputRef};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    async fn count_by_key(memory_budget: usize) -> Result<Vec<Vec<DataValue>>, ExecutorError> {
+        let dir = tempfile::tempdir().unwrap();
+        let context = Context::new()
+            .with_memory_budget(memory_budget)
+            .with_spill_dir(dir.path());
+        let ty = DataTypeKind::Int(None).not_null();
+        let input_ref = BoundExpr::InputRef(BoundInputRef {
+            index: 0,
+            return_type: ty.clone(),
+        });
+        // 10 chunks of keys 0..100
+        let chunks = (0..10)
+            .map(|_| {
+                let array = ArrayImpl::new_int32((0..100).collect());
+                Ok([array].into_iter().collect::<DataChunk>())
+            })
+            .collect_vec();
+        let executor = HashAggExecutor {
+            agg_calls: vec![BoundAggCall {
+                kind: AggKind::Count,
+                args: vec![input_ref.clone()],
+                params: vec![],
+                filter: None,
+                return_type: ty.clone(),
+                distinct: false,
+            }],
+            group_keys: vec![input_ref],
+            child: futures::stream::iter(chunks).boxed(),
+            input_types: vec![ty],
+            phase: AggPhase::Single,
+            context: Arc::new(context),
+        };
+        let chunks: Vec<DataChunk> = executor.execute().try_collect().await?;
+        let mut rows = chunks
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.values().collect_vec()))
+            .collect_vec();
+        rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Ok(rows)
+    }
+
+    #[tokio::test]
+    async fn test_spill() {
+        let expected = (0..100)
+            .map(|key| vec![DataValue::Int32(key), DataValue::Int32(10)])
+            .collect_vec();
+        assert_eq!(count_by_key(usize::MAX).await.unwrap(), expected);
+        // about 10 groups fit in memory
+        let memory_budget = 10 * (size_of::<HashKey>() + 100);
+        assert_eq!(count_by_key(memory_budget).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_memory_budget_exceeded() {
+        assert!(matches!(
+            count_by_key(0).await,
+            Err(ExecutorError::MemoryBudgetExceeded { budget: 0 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_two_phase() {
+        // memory budget of about 10 groups, so that the partial phase outputs states early
+        let context =
+            Arc::new(Context::new().with_memory_budget(10 * (size_of::<HashKey>() + 100)));
+        let ty = DataTypeKind::Int(None).nullable();
+        let input_ref = |index| {
+            BoundExpr::InputRef(BoundInputRef {
+                index,
+                return_type: ty.clone(),
+            })
+        };
+        let agg_calls = vec![
+            BoundAggCall {
+                kind: AggKind::Count,
+                args: vec![input_ref(0)],
+                params: vec![],
+                filter: None,
+                return_type: ty.clone(),
+                distinct: false,
+            },
+            BoundAggCall {
+                kind: AggKind::Sum,
+                args: vec![input_ref(0)],
+                params: vec![],
+                filter: None,
+                return_type: ty.clone(),
+                distinct: false,
+            },
+        ];
+        // each partial aggregation counts and sums 5 chunks of keys 0..100
+        let partial = |context: &Arc<Context>| {
+            let chunks = (0..5)
+                .map(|_| {
+                    let array = ArrayImpl::new_int32((0..100).collect());
+                    Ok([array].into_iter().collect::<DataChunk>())
+                })
+                .collect_vec();
+            HashAggExecutor {
+                agg_calls: agg_calls.clone(),
+                group_keys: vec![input_ref(0)],
+                child: futures::stream::iter(chunks).boxed(),
+                input_types: vec![ty.clone()],
+                phase: AggPhase::Partial,
+                context: context.clone(),
+            }
+            .execute()
+        };
+        let executor = HashAggExecutor {
+            agg_calls: agg_calls.clone(),
+            group_keys: vec![input_ref(0)],
+            child: futures::stream::select(partial(&context), partial(&context)).boxed(),
+            input_types: vec![ty.clone(); 3],
+            phase: AggPhase::Final,
+            context,
+        };
+        let chunks: Vec<DataChunk> = executor.execute().try_collect().await.unwrap();
+        let mut rows = chunks
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.values().collect_vec()))
+            .collect_vec();
+        rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let expected = (0..100)
+            .map(|key| {
+                vec![
+                    DataValue::Int32(key),
+                    DataValue::Int32(10),
+                    DataValue::Int32(key * 10),
+                ]
+            })
+            .collect_vec();
+        assert_eq!(rows, expected);
+    }
+}
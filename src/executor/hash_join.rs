@@ -1,111 +1,316 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::vec::Vec;
 
 use futures::TryStreamExt;
+use itertools::Itertools;
 
+use super::spill::{SpillPartitions, MAX_SPILL_DEPTH};
 use super::*;
-use crate::array::{DataChunk, DataChunkBuilder, RowRef};
+use crate::array::{Array, ArrayImpl, DataChunk, DataChunkBuilder};
 use crate::binder::{BoundExpr, BoundJoinOperator};
 use crate::types::{DataType, DataValue};
 
-/// The executor for hash join
+/// The executor for hash join.
+///
+/// The hash table is built from the left child on the composite key of `left_column_indexes`,
+/// and probed by the right child on `right_column_indexes`. Rows with NULL in their keys never
+/// match. The `condition` is evaluated on each joined row that matches on the keys.
+///
+/// If the left child exceeds the memory budget, both children are partitioned by the hash of
+/// their keys into temporary files, and each pair of partitions is joined separately.
 pub struct HashJoinExecutor {
     pub left_child: BoxedExecutor,
     pub right_child: BoxedExecutor,
     pub join_op: BoundJoinOperator,
-    // TODO: filter by condition
+    /// The join condition except the equality of keys.
     pub condition: BoundExpr,
     pub left_column_indexes: Vec<usize>,
     pub right_column_indexes: Vec<usize>,
     pub left_types: Vec<DataType>,
     pub right_types: Vec<DataType>,
+    pub context: Arc<Context>,
 }
 
 impl HashJoinExecutor {
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     pub async fn execute(self) {
-        // collect all chunks from children
-        let (left_chunks, right_chunks) = async {
-            tokio::try_join!(
-                self.left_child.try_collect::<Vec<DataChunk>>(),
-                self.right_child.try_collect::<Vec<DataChunk>>(),
-            )
-        }
-        .await?;
-
-        // helper functions
-        let left_rows = || left_chunks.iter().flat_map(|chunk| chunk.rows());
-        let right_rows = || right_chunks.iter().flat_map(|chunk| chunk.rows());
-
-        // build
-        let mut hash_map: HashMap<Vec<DataValue>, Vec<RowRef<'_>>> = HashMap::new();
-        for left_row in left_rows() {
-            let hash_value = left_row.get_by_indexes(&self.left_column_indexes);
-            hash_map
-                .entry(hash_value)
-                .or_insert_with(Vec::new)
-                .push(left_row);
-        }
+        let budget = self.context.memory_budget();
+        // pairs of inputs to join, with their depth of spilling
+        let mut inputs = vec![(self.left_child, self.right_child, 0)];
 
-        let data_types = self.left_types.iter().chain(self.right_types.iter());
-        let mut builder = DataChunkBuilder::new(data_types, PROCESSING_WINDOW_SIZE);
+        while let Some((mut left_child, right_child, depth)) = inputs.pop() {
+            // collect the left child within the memory budget
+            let mut left_chunks = vec![];
+            let mut memory_usage = 0;
+            let mut exceeded = false;
+            while let Some(chunk) = left_child.try_next().await? {
+                memory_usage += chunk.estimated_size();
+                left_chunks.push(chunk);
+                if memory_usage > budget {
+                    exceeded = true;
+                    break;
+                }
+            }
 
-        // probe
-        for right_row in right_rows() {
-            let hash_value = right_row.get_by_indexes(&self.right_column_indexes);
-            for left_row in hash_map.get(&hash_value).unwrap_or(&vec![]) {
-                let values = left_row.values().chain(right_row.values());
-                if let Some(chunk) = builder.push_row(values) {
-                    yield chunk;
+            if exceeded {
+                if depth == MAX_SPILL_DEPTH {
+                    return Err(ExecutorError::MemoryBudgetExceeded { budget });
+                }
+                let dir = self.context.spill_dir();
+                let mut left_partitions = SpillPartitions::create(dir, depth)?;
+                for chunk in left_chunks {
+                    left_partitions.write_chunk(&chunk, &self.left_column_indexes)?;
+                }
+                #[for_await]
+                for chunk in left_child {
+                    left_partitions.write_chunk(&chunk?, &self.left_column_indexes)?;
+                }
+                let mut right_partitions = SpillPartitions::create(dir, depth)?;
+                #[for_await]
+                for chunk in right_child {
+                    right_partitions.write_chunk(&chunk?, &self.right_column_indexes)?;
+                }
+                let partitions = (left_partitions.into_all_files().into_iter())
+                    .zip_eq(right_partitions.into_all_files());
+                for (left, right) in partitions {
+                    let is_empty = match self.join_op {
+                        BoundJoinOperator::Inner => left.num_rows() == 0 || right.num_rows() == 0,
+                        BoundJoinOperator::LeftOuter => left.num_rows() == 0,
+                        BoundJoinOperator::RightOuter => right.num_rows() == 0,
+                        BoundJoinOperator::FullOuter => {
+                            left.num_rows() == 0 && right.num_rows() == 0
+                        }
+                    };
+                    if !is_empty {
+                        inputs.push((
+                            left.into_executor(self.left_types.clone()),
+                            right.into_executor(self.right_types.clone()),
+                            depth + 1,
+                        ));
+                    }
                 }
+                continue;
             }
-        }
 
-        // append rows for left outer join
-        if matches!(
-            self.join_op,
-            BoundJoinOperator::LeftOuter | BoundJoinOperator::FullOuter
-        ) {
-            let right_keys = right_rows()
-                .map(|row| row.get_by_indexes(&self.right_column_indexes))
-                .collect::<HashSet<Vec<DataValue>>>();
-            for left_row in left_rows() {
+            let left_rows = left_chunks
+                .iter()
+                .flat_map(|chunk| chunk.rows())
+                .collect_vec();
+
+            // build
+            let mut hash_map: HashMap<Vec<DataValue>, Vec<usize>> = HashMap::new();
+            for (i, left_row) in left_rows.iter().enumerate() {
                 let hash_value = left_row.get_by_indexes(&self.left_column_indexes);
-                if right_keys.contains(&hash_value) {
+                if hash_value.contains(&DataValue::Null) {
                     continue;
                 }
-                // append row: (left, NULL)
-                let values =
-                    (left_row.values()).chain(self.right_types.iter().map(|_| DataValue::Null));
-                if let Some(chunk) = builder.push_row(values) {
-                    yield chunk;
-                }
+                hash_map.entry(hash_value).or_insert_with(Vec::new).push(i);
             }
-        }
 
-        // append rows for right outer join
-        if matches!(
-            self.join_op,
-            BoundJoinOperator::RightOuter | BoundJoinOperator::FullOuter
-        ) {
-            for right_row in right_rows() {
-                let hash_value = right_row.get_by_indexes(&self.right_column_indexes);
-                if hash_map.contains_key(&hash_value) {
-                    continue;
+            let data_types = self.left_types.iter().chain(self.right_types.iter());
+            let mut builder = DataChunkBuilder::new(data_types.clone(), PROCESSING_WINDOW_SIZE);
+            let mut outer_builder = DataChunkBuilder::new(data_types, PROCESSING_WINDOW_SIZE);
+            let mut left_matched = vec![false; left_rows.len()];
+            // the indexes of left and right rows in the builder
+            let mut pairs = Vec::with_capacity(PROCESSING_WINDOW_SIZE);
+
+            // probe
+            #[for_await]
+            for right_chunk in right_child {
+                let right_chunk = right_chunk?;
+                let mut right_matched = vec![false; right_chunk.cardinality()];
+                for (j, right_row) in right_chunk.rows().enumerate() {
+                    let hash_value = right_row.get_by_indexes(&self.right_column_indexes);
+                    for &i in hash_map.get(&hash_value).unwrap_or(&vec![]) {
+                        pairs.push((i, j));
+                        let values = left_rows[i].values().chain(right_row.values());
+                        if let Some(chunk) = builder.push_row(values) {
+                            if let Some(chunk) = Self::filter(
+                                &self.condition,
+                                chunk,
+                                &mut pairs,
+                                &mut left_matched,
+                                &mut right_matched,
+                            )? {
+                                yield chunk;
+                            }
+                        }
+                    }
+                }
+                if let Some(chunk) = builder.take() {
+                    if let Some(chunk) = Self::filter(
+                        &self.condition,
+                        chunk,
+                        &mut pairs,
+                        &mut left_matched,
+                        &mut right_matched,
+                    )? {
+                        yield chunk;
+                    }
+                }
+
+                // append rows for right outer join
+                if matches!(
+                    self.join_op,
+                    BoundJoinOperator::RightOuter | BoundJoinOperator::FullOuter
+                ) {
+                    for (right_row, _) in
+                        (right_chunk.rows().zip(&right_matched)).filter(|(_, m)| !**m)
+                    {
+                        // append row: (NULL, right)
+                        let values = (self.left_types.iter().map(|_| DataValue::Null))
+                            .chain(right_row.values());
+                        if let Some(chunk) = outer_builder.push_row(values) {
+                            yield chunk;
+                        }
+                    }
                 }
-                // append row: (NULL, right)
-                let values =
-                    (self.left_types.iter().map(|_| DataValue::Null)).chain(right_row.values());
-                if let Some(chunk) = builder.push_row(values) {
-                    yield chunk;
+            }
+
+            // append rows for left outer join
+            if matches!(
+                self.join_op,
+                BoundJoinOperator::LeftOuter | BoundJoinOperator::FullOuter
+            ) {
+                for (left_row, _) in (left_rows.iter().zip(&left_matched)).filter(|(_, m)| !**m) {
+                    // append row: (left, NULL)
+                    let values =
+                        (left_row.values()).chain(self.right_types.iter().map(|_| DataValue::Null));
+                    if let Some(chunk) = outer_builder.push_row(values) {
+                        yield chunk;
+                    }
                 }
             }
+
+            if let Some(chunk) = { outer_builder }.take() {
+                yield chunk;
+            }
+        }
+    }
+
+    /// Evaluate the condition on the joined rows, and mark the left and right rows of the
+    /// satisfied ones as matched. Returns `None` if no rows satisfy the condition.
+    fn filter(
+        condition: &BoundExpr,
+        chunk: DataChunk,
+        pairs: &mut Vec<(usize, usize)>,
+        left_matched: &mut [bool],
+        right_matched: &mut [bool],
+    ) -> Result<Option<DataChunk>, ExecutorError> {
+        let visibility = match condition {
+            BoundExpr::Constant(DataValue::Bool(true)) => vec![true; chunk.cardinality()],
+            _ => match condition.eval(&chunk)? {
+                ArrayImpl::Bool(a) => a.iter().map(|b| matches!(b, Some(true))).collect_vec(),
+                _ => {
+                    return Err(ExecutorError::NotSupported(
+                        "non-boolean join condition".into(),
+                    ))
+                }
+            },
+        };
+        for (&(i, j), _) in pairs.iter().zip(&visibility).filter(|(_, v)| **v) {
+            left_matched[i] = true;
+            right_matched[j] = true;
         }
+        pairs.clear();
+        let chunk = chunk.filter(visibility.into_iter());
+        Ok((chunk.cardinality() > 0).then(|| chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{ArrayImpl, I32Array};
+    use crate::types::{DataTypeExt, DataTypeKind};
 
-        if let Some(chunk) = { builder }.take() {
-            yield chunk;
+    async fn join(join_op: BoundJoinOperator, memory_budget: usize) -> Vec<Vec<DataValue>> {
+        let dir = tempfile::tempdir().unwrap();
+        let context = Context::new()
+            .with_memory_budget(memory_budget)
+            .with_spill_dir(dir.path());
+        // left: (k, v) for k in 0..2000 with v = k; right: (k) for k in 1000..3000
+        let chunks = |range: std::ops::Range<i32>, columns: usize| {
+            let data = range
+                .chunks(100)
+                .into_iter()
+                .map(|keys| {
+                    let keys = keys.collect::<I32Array>();
+                    (0..columns)
+                        .map(|_| ArrayImpl::new_int32(keys.clone()))
+                        .collect::<DataChunk>()
+                })
+                .collect_vec();
+            futures::stream::iter(data.into_iter().map(Ok)).boxed()
+        };
+        let ty = DataTypeKind::Int(None).nullable();
+        let executor = HashJoinExecutor {
+            left_child: chunks(0..2000, 2),
+            right_child: chunks(1000..3000, 1),
+            join_op,
+            condition: BoundExpr::Constant(DataValue::Bool(true)),
+            left_column_indexes: vec![0],
+            right_column_indexes: vec![0],
+            left_types: vec![ty.clone(), ty.clone()],
+            right_types: vec![ty],
+            context: Arc::new(context),
+        };
+        let chunks: Vec<DataChunk> = executor.execute().try_collect().await.unwrap();
+        let mut rows = chunks
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.values().collect_vec()))
+            .collect_vec();
+        rows.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        rows
+    }
+
+    #[tokio::test]
+    async fn test_spill() {
+        for join_op in [
+            BoundJoinOperator::Inner,
+            BoundJoinOperator::LeftOuter,
+            BoundJoinOperator::RightOuter,
+            BoundJoinOperator::FullOuter,
+        ] {
+            let expected = join(join_op, usize::MAX).await;
+            let num_rows = match join_op {
+                BoundJoinOperator::Inner => 1000,
+                BoundJoinOperator::LeftOuter | BoundJoinOperator::RightOuter => 2000,
+                BoundJoinOperator::FullOuter => 3000,
+            };
+            assert_eq!(expected.len(), num_rows);
+            assert_eq!(join(join_op, 1000).await, expected);
         }
     }
+
+    #[tokio::test]
+    async fn test_memory_budget_exceeded() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = Context::new()
+            .with_memory_budget(0)
+            .with_spill_dir(dir.path());
+        // all rows have the same key
+        let chunk: DataChunk = [ArrayImpl::new_int32([1; 100].into_iter().collect())]
+            .into_iter()
+            .collect();
+        let ty = DataTypeKind::Int(None).nullable();
+        let executor = HashJoinExecutor {
+            left_child: futures::stream::iter([Ok(chunk.clone())]).boxed(),
+            right_child: futures::stream::iter([Ok(chunk)]).boxed(),
+            join_op: BoundJoinOperator::Inner,
+            condition: BoundExpr::Constant(DataValue::Bool(true)),
+            left_column_indexes: vec![0],
+            right_column_indexes: vec![0],
+            left_types: vec![ty.clone()],
+            right_types: vec![ty],
+            context: Arc::new(context),
+        };
+        let result: Result<Vec<DataChunk>, _> = executor.execute().try_collect().await;
+        assert!(matches!(
+            result,
+            Err(ExecutorError::MemoryBudgetExceeded { budget: 0 })
+        ));
+    }
 }
@@ -19,7 +19,9 @@ pub struct InsertExecutor<S: Storage> {
 
 impl<S: Storage> InsertExecutor<S> {
     async fn execute_inner(self, token: CancellationToken) -> Result<i32, ExecutorError> {
-        let table = self.storage.get_table(self.table_ref_id)?;
+        let table = self
+            .storage
+            .get_table_in_session(self.table_ref_id, self.context.session())?;
         let columns = table.columns()?;
 
         // Describe each column of the output chunks.
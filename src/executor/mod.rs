@@ -29,6 +29,7 @@ use self::create::*;
 use self::delete::*;
 use self::drop::*;
 use self::dummy_scan::*;
+use self::exchange::*;
 use self::explain::*;
 use self::filter::*;
 use self::hash_agg::*;
@@ -40,19 +41,19 @@ use self::nested_loop_join::*;
 use self::order::*;
 use self::projection::*;
 use self::simple_agg::*;
-#[allow(unused_imports)]
 use self::sort_agg::*;
-#[allow(unused_imports)]
 use self::sort_merge_join::*;
 use self::table_scan::*;
 use self::top_n::TopNExecutor;
+use self::update::*;
 use self::values::*;
+use self::window::*;
 use crate::array::DataChunk;
-use crate::binder::BoundExpr;
+use crate::binder::{BoundExpr, BoundInputRef};
 use crate::optimizer::plan_nodes::*;
 use crate::optimizer::PlanVisitor;
 use crate::storage::{StorageImpl, TracedStorageError};
-use crate::types::{ConvertError, DataValue};
+use crate::types::ConvertError;
 
 mod aggregation;
 pub mod context;
@@ -63,6 +64,7 @@ mod delete;
 mod drop;
 mod dummy_scan;
 pub mod evaluator;
+mod exchange;
 mod explain;
 mod filter;
 mod hash_agg;
@@ -75,10 +77,14 @@ mod order;
 mod projection;
 mod simple_agg;
 mod sort_agg;
+mod sort_key;
 mod sort_merge_join;
+mod spill;
 mod table_scan;
 mod top_n;
+mod update;
 mod values;
+mod window;
 
 /// The error type of execution.
 #[derive(thiserror::Error, Debug)]
@@ -114,11 +120,20 @@ pub enum ExecutorError {
     ExceedLengthLimit { length: u64, width: u64 },
     #[error("abort")]
     Abort,
+    #[error("not supported: {0}")]
+    NotSupported(String),
+    #[error("numeric value out of range in {0}")]
+    OutOfRange(&'static str),
+    #[error("exceed memory budget of {budget} bytes even after spilling to disk")]
+    MemoryBudgetExceeded { budget: usize },
 }
 
 /// The maximum chunk length produced by executor at a time.
 const PROCESSING_WINDOW_SIZE: usize = 1024;
 
+/// The minimum estimated number of input rows of each parallel worker.
+const MIN_ROWS_PER_WORKER: usize = 1 << 16;
+
 /// A type-erased executor object.
 ///
 /// Logically an executor is a stream of data chunks.
@@ -144,6 +159,239 @@ impl ExecutorBuilder {
         self.visit(plan).unwrap()
     }
 
+    /// Returns the number of parallel workers to process the estimated number of rows.
+    fn num_workers(&self, rows: usize) -> usize {
+        (rows / MIN_ROWS_PER_WORKER).clamp(1, self.context.parallelism())
+    }
+
+    /// Build the plan into `num_partitions` executors that output disjoint parts of its result
+    /// in parallel. Plans that can not be partitioned are built into a single executor.
+    fn build_partitioned(&mut self, plan: PlanRef, num_partitions: usize) -> Vec<BoxedExecutor> {
+        if let Ok(plan) = plan.as_physical_table_scan() {
+            self.build_table_scan(plan, num_partitions)
+        } else if let Ok(plan) = plan.as_physical_filter() {
+            let children = self.build_partitioned(plan.child(), num_partitions);
+            (children.into_iter())
+                .map(|child| self.build_filter(plan, child))
+                .collect()
+        } else if let Ok(plan) = plan.as_physical_projection() {
+            let children = self.build_partitioned(plan.child(), num_partitions);
+            (children.into_iter())
+                .map(|child| self.build_projection(plan, child))
+                .collect()
+        } else if let Ok(plan) = plan.as_physical_hash_join() {
+            self.build_partitioned_hash_join(plan, num_partitions)
+        } else {
+            vec![self.visit(plan).unwrap()]
+        }
+    }
+
+    fn build_table_scan(
+        &self,
+        plan: &PhysicalTableScan,
+        num_partitions: usize,
+    ) -> Vec<BoxedExecutor> {
+        let executors = match &self.storage {
+            StorageImpl::InMemoryStorage(storage) => TableScanExecutor {
+                context: self.context.clone(),
+                plan: plan.clone(),
+                expr: None,
+                storage: storage.clone(),
+            }
+            .execute_partitioned(num_partitions),
+            StorageImpl::SecondaryStorage(storage) => TableScanExecutor {
+                context: self.context.clone(),
+                plan: plan.clone(),
+                expr: plan.logical().expr().cloned(),
+                storage: storage.clone(),
+            }
+            .execute_partitioned(num_partitions),
+        };
+        (executors.into_iter())
+            .map(|executor| {
+                ExecutorBuilder::trace_execute(
+                    executor.cancellable(self.context.token().child_token()),
+                    "TableScanExecutor",
+                )
+            })
+            .collect()
+    }
+
+    fn build_filter(&self, plan: &PhysicalFilter, child: BoxedExecutor) -> BoxedExecutor {
+        ExecutorBuilder::trace_execute(
+            FilterExecutor {
+                expr: plan.logical().expr().clone(),
+                child,
+            }
+            .execute(),
+            "FilterExecutor",
+        )
+    }
+
+    fn build_projection(&self, plan: &PhysicalProjection, child: BoxedExecutor) -> BoxedExecutor {
+        ExecutorBuilder::trace_execute(
+            ProjectionExecutor {
+                project_expressions: plan.logical().project_expressions().to_vec(),
+                child,
+            }
+            .execute(),
+            "ProjectionExecutor",
+        )
+    }
+
+    fn build_hash_join(
+        &self,
+        plan: &PhysicalHashJoin,
+        left_child: BoxedExecutor,
+        right_child: BoxedExecutor,
+    ) -> BoxedExecutor {
+        let left_col_num = plan.left().out_types().len();
+        let (left_column_indexes, right_column_indexes) = plan
+            .logical()
+            .predicate()
+            .eq_keys()
+            .iter()
+            .map(|(left, right)| (left.index, right.index - left_col_num))
+            .unzip();
+        ExecutorBuilder::trace_execute(
+            HashJoinExecutor {
+                left_child,
+                right_child,
+                join_op: plan.logical().join_op(),
+                condition: plan.logical().predicate().non_eq_cond(),
+                left_column_indexes,
+                right_column_indexes,
+                left_types: plan.left().out_types(),
+                right_types: plan.right().out_types(),
+                context: self.context.clone(),
+            }
+            .execute(),
+            "HashJoinExecutor",
+        )
+    }
+
+    /// Build `num_partitions` hash joins, each joining a hash partition of both children by the
+    /// join keys.
+    fn build_partitioned_hash_join(
+        &mut self,
+        plan: &PhysicalHashJoin,
+        num_partitions: usize,
+    ) -> Vec<BoxedExecutor> {
+        let left_col_num = plan.left().out_types().len();
+        let eq_keys = plan.logical().predicate().eq_keys();
+        let left_keys = (eq_keys.iter())
+            .map(|(left, _)| BoundExpr::InputRef(left.clone()))
+            .collect();
+        let right_keys = (eq_keys.iter())
+            .map(|(_, right)| {
+                BoundExpr::InputRef(BoundInputRef {
+                    index: right.index - left_col_num,
+                    return_type: right.return_type.clone(),
+                })
+            })
+            .collect();
+        let left_children = HashExchangeExecutor {
+            inputs: self.build_partitioned(plan.left(), num_partitions),
+            keys: left_keys,
+            num_partitions,
+            context: self.context.clone(),
+        }
+        .execute();
+        let right_children = HashExchangeExecutor {
+            inputs: self.build_partitioned(plan.right(), num_partitions),
+            keys: right_keys,
+            num_partitions,
+            context: self.context.clone(),
+        }
+        .execute();
+        (left_children.into_iter().zip_eq(right_children))
+            .map(|(left_child, right_child)| self.build_hash_join(plan, left_child, right_child))
+            .collect()
+    }
+
+    /// Build a hash aggregation running in `num_partitions` workers.
+    ///
+    /// Each part of the input is aggregated into intermediate states, which are merged in the
+    /// final phase on each hash partition of the groups. Aggregations that can not be split into
+    /// phases are done on the hash partitions of the input instead.
+    fn build_parallel_hash_agg(
+        &mut self,
+        plan: &PhysicalHashAgg,
+        num_partitions: usize,
+    ) -> BoxedExecutor {
+        let agg_calls = plan.logical().agg_calls();
+        let group_keys = plan.logical().group_keys();
+        let input_types = plan.child().out_types();
+        let inputs = self.build_partitioned(plan.child(), num_partitions);
+        let hash_agg = |child, group_keys: &[BoundExpr], input_types, phase| {
+            HashAggExecutor {
+                agg_calls: agg_calls.to_vec(),
+                group_keys: group_keys.to_vec(),
+                child,
+                input_types,
+                phase,
+                context: self.context.clone(),
+            }
+            .execute()
+        };
+
+        let partial_types: Option<Vec<_>> = agg_calls.iter().map(partial_types).collect();
+        let outputs = match partial_types {
+            Some(partial_types) => {
+                let partials = (inputs.into_iter())
+                    .map(|child| {
+                        hash_agg(child, group_keys, input_types.clone(), AggPhase::Partial)
+                    })
+                    .collect();
+                // the group keys are the leading columns of intermediate states
+                let final_keys = (group_keys.iter().enumerate())
+                    .map(|(index, key)| {
+                        BoundExpr::InputRef(BoundInputRef {
+                            index,
+                            return_type: key.return_type().unwrap(),
+                        })
+                    })
+                    .collect_vec();
+                let final_input_types = (final_keys.iter())
+                    .map(|key| key.return_type().unwrap())
+                    .chain(partial_types.into_iter().flatten())
+                    .collect_vec();
+                let exchange = HashExchangeExecutor {
+                    inputs: partials,
+                    keys: final_keys.clone(),
+                    num_partitions,
+                    context: self.context.clone(),
+                };
+                (exchange.execute().into_iter())
+                    .map(|child| {
+                        hash_agg(
+                            child,
+                            &final_keys,
+                            final_input_types.clone(),
+                            AggPhase::Final,
+                        )
+                    })
+                    .collect()
+            }
+            None => {
+                let exchange = HashExchangeExecutor {
+                    inputs,
+                    keys: group_keys.to_vec(),
+                    num_partitions,
+                    context: self.context.clone(),
+                };
+                (exchange.execute().into_iter())
+                    .map(|child| hash_agg(child, group_keys, input_types.clone(), AggPhase::Single))
+                    .collect()
+            }
+        };
+        GatherExecutor {
+            inputs: outputs,
+            context: self.context.clone(),
+        }
+        .execute()
+    }
+
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     pub async fn trace_execute(mut executor_stream: BoxedExecutor, identifier: &'static str) {
         while let Some(item) = executor_stream
@@ -327,48 +575,28 @@ impl PlanVisitor<BoxedExecutor> for ExecutorBuilder {
     }
 
     fn visit_physical_table_scan(&mut self, plan: &PhysicalTableScan) -> Option<BoxedExecutor> {
-        Some(ExecutorBuilder::trace_execute(
-            match &self.storage {
-                StorageImpl::InMemoryStorage(storage) => TableScanExecutor {
-                    context: self.context.clone(),
-                    plan: plan.clone(),
-                    expr: None,
-                    storage: storage.clone(),
-                }
-                .execute()
-                .cancellable(self.context.token().child_token()),
-                StorageImpl::SecondaryStorage(storage) => TableScanExecutor {
-                    context: self.context.clone(),
-                    plan: plan.clone(),
-                    expr: plan.logical().expr().cloned(),
-                    storage: storage.clone(),
-                }
-                .execute()
-                .cancellable(self.context.token().child_token()),
-            },
-            "TableScanExecutor",
-        ))
+        self.build_table_scan(plan, 1).pop()
     }
 
     fn visit_physical_projection(&mut self, plan: &PhysicalProjection) -> Option<BoxedExecutor> {
-        Some(ExecutorBuilder::trace_execute(
-            ProjectionExecutor {
-                project_expressions: plan.logical().project_expressions().to_vec(),
-                child: self.visit(plan.child()).unwrap(),
-            }
-            .execute(),
-            "ProjectionExecutor",
-        ))
+        let child = self.visit(plan.child()).unwrap();
+        Some(self.build_projection(plan, child))
     }
 
     fn visit_physical_filter(&mut self, plan: &PhysicalFilter) -> Option<BoxedExecutor> {
+        let child = self.visit(plan.child()).unwrap();
+        Some(self.build_filter(plan, child))
+    }
+
+    fn visit_physical_window(&mut self, plan: &PhysicalWindow) -> Option<BoxedExecutor> {
         Some(ExecutorBuilder::trace_execute(
-            FilterExecutor {
-                expr: plan.logical().expr().clone(),
+            WindowExecutor {
                 child: self.visit(plan.child()).unwrap(),
+                window_calls: plan.logical().window_calls().to_vec(),
+                output_types: plan.logical().out_types(),
             }
             .execute(),
-            "FilterExecutor",
+            "WindowExecutor",
         ))
     }
 
@@ -378,6 +606,7 @@ impl PlanVisitor<BoxedExecutor> for ExecutorBuilder {
                 comparators: plan.logical().comparators().to_vec(),
                 child: self.visit(plan.child()).unwrap(),
                 output_types: plan.logical().out_types(),
+                context: self.context.clone(),
             }
             .execute(),
             "OrderExecutor",
@@ -418,11 +647,21 @@ impl PlanVisitor<BoxedExecutor> for ExecutorBuilder {
     }
 
     fn visit_physical_hash_agg(&mut self, plan: &PhysicalHashAgg) -> Option<BoxedExecutor> {
+        let num_workers = self.num_workers(plan.child().estimated_cardinality());
+        if num_workers > 1 {
+            return Some(ExecutorBuilder::trace_execute(
+                self.build_parallel_hash_agg(plan, num_workers),
+                "HashAggExecutor",
+            ));
+        }
         Some(ExecutorBuilder::trace_execute(
             HashAggExecutor {
                 agg_calls: plan.logical().agg_calls().to_vec(),
                 group_keys: plan.logical().group_keys().to_vec(),
                 child: self.visit(plan.child()).unwrap(),
+                input_types: plan.child().out_types(),
+                phase: AggPhase::Single,
+                context: self.context.clone(),
             }
             .execute(),
             "HashAggExecutor",
@@ -430,30 +669,56 @@ impl PlanVisitor<BoxedExecutor> for ExecutorBuilder {
     }
 
     fn visit_physical_hash_join(&mut self, plan: &PhysicalHashJoin) -> Option<BoxedExecutor> {
+        let num_workers = self.num_workers(
+            plan.left().estimated_cardinality() + plan.right().estimated_cardinality(),
+        );
+        if num_workers > 1 {
+            return Some(ExecutorBuilder::trace_execute(
+                GatherExecutor {
+                    inputs: self.build_partitioned_hash_join(plan, num_workers),
+                    context: self.context.clone(),
+                }
+                .execute(),
+                "GatherExecutor",
+            ));
+        }
+        let left_child = self.visit(plan.left()).unwrap();
+        let right_child = self.visit(plan.right()).unwrap();
+        Some(self.build_hash_join(plan, left_child, right_child))
+    }
+
+    fn visit_physical_sort_merge_join(
+        &mut self,
+        plan: &PhysicalSortMergeJoin,
+    ) -> Option<BoxedExecutor> {
         let left_child = self.visit(plan.left()).unwrap();
         let right_child = self.visit(plan.right()).unwrap();
 
         let left_col_num = plan.left().out_types().len();
-        let (left_column_indexes, right_column_indexes) = plan
-            .logical()
-            .predicate()
-            .eq_keys()
-            .iter()
-            .map(|(left, right)| (left.index, right.index - left_col_num))
-            .unzip();
+        let (left, right) = &plan.logical().predicate().eq_keys()[0];
         Some(ExecutorBuilder::trace_execute(
-            HashJoinExecutor {
+            SortMergeJoinExecutor {
                 left_child,
                 right_child,
-                join_op: plan.logical().join_op(),
-                condition: BoundExpr::Constant(DataValue::Bool(true)),
-                left_column_indexes,
-                right_column_indexes,
+                left_column_index: left.index,
+                right_column_index: right.index - left_col_num,
                 left_types: plan.left().out_types(),
                 right_types: plan.right().out_types(),
             }
             .execute(),
-            "HashJoinExecutor",
+            "SortMergeJoinExecutor",
+        ))
+    }
+
+    fn visit_physical_sort_agg(&mut self, plan: &PhysicalSortAgg) -> Option<BoxedExecutor> {
+        Some(ExecutorBuilder::trace_execute(
+            SortAggExecutor {
+                agg_calls: plan.logical().agg_calls().to_vec(),
+                group_keys: plan.logical().group_keys().to_vec(),
+                child: self.visit(plan.child()).unwrap(),
+            }
+            .execute(),
+            "SortAggExecutor",
         ))
     }
 
@@ -493,6 +758,30 @@ impl PlanVisitor<BoxedExecutor> for ExecutorBuilder {
         ))
     }
 
+    fn visit_physical_update(&mut self, plan: &PhysicalUpdate) -> Option<BoxedExecutor> {
+        let child = self.visit(plan.child()).unwrap();
+        Some(ExecutorBuilder::trace_execute(
+            match &self.storage {
+                StorageImpl::InMemoryStorage(_) => futures::stream::once(async {
+                    Err::<DataChunk, _>(
+                        TracedStorageError::unsupported("UPDATE on in-memory storage").into(),
+                    )
+                })
+                .boxed(),
+                StorageImpl::SecondaryStorage(storage) => UpdateExecutor {
+                    context: self.context.clone(),
+                    child,
+                    table_ref_id: plan.logical().table_ref_id(),
+                    value_exprs: plan.logical().value_exprs().to_vec(),
+                    storage: storage.clone(),
+                }
+                .execute()
+                .cancellable(self.context.token().child_token()),
+            },
+            "UpdateExecutor",
+        ))
+    }
+
     fn visit_physical_values(&mut self, plan: &PhysicalValues) -> Option<BoxedExecutor> {
         Some(ExecutorBuilder::trace_execute(
             ValuesExecutor {
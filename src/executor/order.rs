@@ -1,64 +1,197 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
-use std::cmp::Ordering;
+use std::path::Path;
 
+use super::sort_key::encode_sort_keys;
+use super::spill::{SpillFile, SpillReader};
 use super::*;
 use crate::array::{DataChunk, DataChunkBuilder, RowRef};
-use crate::binder::{BoundExpr, BoundOrderBy};
-use crate::types::DataType;
+use crate::binder::BoundOrderBy;
+use crate::types::{DataType, DataValue, Row};
+
+/// The maximum number of sorted runs to merge at once.
+const MAX_MERGE_WIDTH: usize = 64;
 
 /// The executor of an order operation.
+///
+/// Rows are sorted by their normalized sort keys, see [`encode_sort_keys`].
+///
+/// Once the buffered rows exceed the memory budget, they are sorted and spilled to a temporary
+/// file as a sorted run. The runs are merged at the end.
 pub struct OrderExecutor {
     pub child: BoxedExecutor,
     pub comparators: Vec<BoundOrderBy>,
     pub output_types: Vec<DataType>,
+    pub context: Arc<Context>,
 }
 
 impl OrderExecutor {
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     pub async fn execute(self) {
-        // collect all chunks
+        let budget = self.context.memory_budget();
+        let spill_dir = self.context.spill_dir();
+
+        // collect all chunks with the sort keys of their rows, spilling sorted runs when
+        // exceeding the memory budget
         let mut chunks = vec![];
+        let mut memory_usage = 0;
+        let mut runs = vec![];
         #[for_await]
         for batch in self.child {
-            chunks.push(batch?);
+            let batch = batch?;
+            let keys = encode_sort_keys(&batch, &self.comparators)?;
+            memory_usage += batch.estimated_size() + keys.iter().map(Vec::len).sum::<usize>();
+            chunks.push((batch, keys));
+            if memory_usage > budget {
+                runs.push(Self::spill_run(&chunks, spill_dir)?);
+                chunks.clear();
+                memory_usage = 0;
+            }
         }
-        // sort the indexes
-        let mut indexes = gen_index_array(&chunks);
-        let comparators = self.comparators;
-        indexes.sort_unstable_by(|row1, row2| cmp(row1, row2, &comparators));
-        // build chunk by the new order
+
         let mut builder = DataChunkBuilder::new(self.output_types.iter(), PROCESSING_WINDOW_SIZE);
-        for row in indexes {
-            if let Some(chunk) = builder.push_row(row.values()) {
-                yield chunk;
+        if runs.is_empty() {
+            // build chunk by the new order
+            for (_, row) in sort(&chunks) {
+                if let Some(chunk) = builder.push_row(row.values()) {
+                    yield chunk;
+                }
+            }
+        } else {
+            if !chunks.is_empty() {
+                runs.push(Self::spill_run(&chunks, spill_dir)?);
+                chunks.clear();
+            }
+            // merge runs until they can be merged at once
+            while runs.len() > MAX_MERGE_WIDTH {
+                let mut merger = MergeRuns::new(runs.drain(..MAX_MERGE_WIDTH))?;
+                let mut file = SpillFile::create(spill_dir)?;
+                while let Some(row) = merger.next_row()? {
+                    file.write_row(row)?;
+                }
+                runs.push(file);
+            }
+            let mut merger = MergeRuns::new(runs.into_iter())?;
+            while let Some(mut row) = merger.next_row()? {
+                // remove the sort key
+                row.pop();
+                if let Some(chunk) = builder.push_row(row) {
+                    yield chunk;
+                }
             }
         }
         if let Some(chunk) = { builder }.take() {
             yield chunk;
         }
     }
+
+    /// Sort the rows of chunks and write them to a temporary file, with their sort keys in the
+    /// last column.
+    fn spill_run(
+        chunks: &[(DataChunk, Vec<Vec<u8>>)],
+        spill_dir: &Path,
+    ) -> Result<SpillFile, ExecutorError> {
+        let mut file = SpillFile::create(spill_dir)?;
+        for (key, row) in sort(chunks) {
+            file.write_row(row.values().chain([DataValue::Blob(key.into())]))?;
+        }
+        Ok(file)
+    }
 }
 
-/// Compare two rows by the comparators.
-pub(super) fn cmp(row1: &RowRef, row2: &RowRef, comparators: &[BoundOrderBy]) -> Ordering {
-    for cmp in comparators {
-        let column_index = match &cmp.expr {
-            BoundExpr::InputRef(input_ref) => input_ref.index,
-            _ => todo!("only support order by columns now"),
-        };
-        let v1 = row1.get(column_index);
-        let v2 = row2.get(column_index);
-        match v1.partial_cmp(&v2).unwrap() {
-            Ordering::Equal => continue,
-            o if cmp.descending => return o.reverse(),
-            o => return o,
+/// Merges sorted runs into a sorted sequence of rows.
+struct MergeRuns {
+    readers: Vec<SpillReader>,
+    /// The next row of each run.
+    heads: Vec<Option<Row>>,
+}
+
+impl MergeRuns {
+    fn new(runs: impl Iterator<Item = SpillFile>) -> Result<Self, ExecutorError> {
+        let mut readers: Vec<SpillReader> = runs.map(|run| run.into_reader()).try_collect()?;
+        let heads = readers.iter_mut().map(|r| r.next_row()).try_collect()?;
+        Ok(MergeRuns { readers, heads })
+    }
+
+    /// Returns the smallest row among the heads of runs.
+    fn next_row(&mut self) -> Result<Option<Row>, ExecutorError> {
+        let heads = &self.heads;
+        let min = (0..heads.len())
+            .filter(|&i| heads[i].is_some())
+            .min_by_key(|&i| spilled_key(heads[i].as_ref().unwrap()));
+        match min {
+            Some(i) => {
+                let next = self.readers[i].next_row()?;
+                Ok(std::mem::replace(&mut self.heads[i], next))
+            }
+            None => Ok(None),
         }
     }
-    Ordering::Equal
 }
 
-/// Generate an array of indexes for each element of the chunks.
-fn gen_index_array(chunks: &[DataChunk]) -> Vec<RowRef<'_>> {
-    chunks.iter().flat_map(|chunk| chunk.rows()).collect()
+/// Returns the sort key in the last column of a spilled row.
+fn spilled_key(row: &Row) -> &[u8] {
+    match row.last() {
+        Some(DataValue::Blob(key)) => key,
+        _ => panic!("spilled row without sort key"),
+    }
+}
+
+/// Sort the rows of chunks by their sort keys.
+fn sort(chunks: &[(DataChunk, Vec<Vec<u8>>)]) -> Vec<(&[u8], RowRef<'_>)> {
+    let mut rows = chunks
+        .iter()
+        .flat_map(|(chunk, keys)| keys.iter().map(Vec::as_slice).zip(chunk.rows()))
+        .collect_vec();
+    rows.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::array::ArrayImpl;
+    use crate::binder::{BoundExpr, BoundInputRef};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    #[tokio::test]
+    async fn test_spill() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = Context::new()
+            .with_memory_budget(0)
+            .with_spill_dir(dir.path());
+        // 200 chunks of 10 rows in descending order, each spilled as a sorted run
+        let chunks = (0..2000)
+            .rev()
+            .chunks(10)
+            .into_iter()
+            .map(|values| {
+                let array = ArrayImpl::new_int32(values.collect());
+                Ok::<_, ExecutorError>([array].into_iter().collect::<DataChunk>())
+            })
+            .collect_vec();
+        let ty = DataTypeKind::Int(None).not_null();
+        let executor = OrderExecutor {
+            child: futures::stream::iter(chunks).boxed(),
+            comparators: vec![BoundOrderBy {
+                expr: BoundExpr::InputRef(BoundInputRef {
+                    index: 0,
+                    return_type: ty.clone(),
+                }),
+                descending: false,
+                nulls_first: true,
+            }],
+            output_types: vec![ty],
+            context: Arc::new(context),
+        };
+        let chunks: Vec<DataChunk> = executor.execute().try_collect().await.unwrap();
+        let values = chunks
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.get(0)))
+            .collect_vec();
+        assert_eq!(values, (0..2000).map(DataValue::Int32).collect_vec());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
 }
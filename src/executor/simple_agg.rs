@@ -4,9 +4,9 @@ use itertools::Itertools;
 use smallvec::SmallVec;
 
 use super::*;
-use crate::array::{ArrayBuilderImpl, ArrayImpl};
-use crate::binder::{AggKind, BoundAggCall};
-use crate::types::{DataTypeExt, DataTypeKind, DataValue};
+use crate::array::ArrayBuilderImpl;
+use crate::binder::BoundAggCall;
+use crate::types::{DataTypeExt, DataTypeKind};
 
 /// The executor of simple aggregation.
 pub struct SimpleAggExecutor {
@@ -20,14 +20,14 @@ impl SimpleAggExecutor {
         chunk: DataChunk,
         agg_calls: &[BoundAggCall],
     ) -> Result<(), ExecutorError> {
-        // TODO: support aggregations with multiple arguments
-        let exprs: SmallVec<[ArrayImpl; 16]> = agg_calls
-            .iter()
-            .map(|agg| agg.args[0].eval(&chunk))
-            .try_collect()?;
-
-        for (state, expr) in states.iter_mut().zip_eq(exprs) {
-            state.update(&expr)?;
+        for (state, agg) in states.iter_mut().zip_eq(agg_calls) {
+            let mut args = eval_agg_args(agg, &chunk)?;
+            if let Some(visibility) = eval_agg_filter(agg, &chunk)? {
+                for array in args.iter_mut() {
+                    *array = array.filter(visibility.iter().copied());
+                }
+            }
+            state.update(&args)?;
         }
 
         Ok(())
@@ -64,28 +64,3 @@ impl SimpleAggExecutor {
         yield chunk;
     }
 }
-
-pub(super) fn create_agg_states(
-    agg_calls: &[BoundAggCall],
-) -> SmallVec<[Box<dyn AggregationState>; 16]> {
-    agg_calls.iter().map(create_agg_state).collect()
-}
-
-fn create_agg_state(agg_call: &BoundAggCall) -> Box<dyn AggregationState> {
-    match agg_call.kind {
-        AggKind::RowCount => Box::new(RowCountAggregationState::new(DataValue::Int32(0))),
-        AggKind::Count => Box::new(CountAggregationState::new(DataValue::Int32(0))),
-        AggKind::Max => Box::new(MinMaxAggregationState::new(
-            agg_call.return_type.kind(),
-            false,
-        )),
-        AggKind::Min => Box::new(MinMaxAggregationState::new(
-            agg_call.return_type.kind(),
-            true,
-        )),
-        AggKind::Sum => Box::new(SumAggregationState::new(agg_call.return_type.kind())),
-        AggKind::DPCount(epsilon) => Box::new(DPCountAggregationState::new(epsilon)),
-        AggKind::DPSum(epsilon) => Box::new(DPSumAggregationState::new(epsilon)),
-        _ => panic!("Unsupported aggregate kind"),
-    }
-}
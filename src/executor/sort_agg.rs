@@ -1,12 +1,16 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
-#![allow(dead_code)]
+
 use smallvec::SmallVec;
 
 use super::*;
 use crate::array::{ArrayBuilderImpl, ArrayImpl};
 use crate::binder::BoundAggCall;
-use crate::types::{DataTypeExt, DataTypeKind};
+use crate::types::DataValue;
 
+/// The executor of sort aggregation.
+///
+/// The rows of each group must be consecutive in the input. The output has the same layout as
+/// [`HashAggExecutor`]: group keys followed by aggregation results.
 pub struct SortAggExecutor {
     pub agg_calls: Vec<BoundAggCall>,
     pub group_keys: Vec<BoundExpr>,
@@ -18,15 +22,17 @@ impl SortAggExecutor {
     pub async fn execute(self) {
         let mut last_key = None::<HashKey>;
         let mut states = create_agg_states(&self.agg_calls);
+        let mut builders = Self::create_builders(&self.group_keys, &self.agg_calls);
+        let mut num_groups = 0;
 
         #[for_await]
         for chunk in self.child {
             // Eval group keys and arguments
             let chunk = chunk?;
-            let exprs: SmallVec<[ArrayImpl; 16]> = self
-                .agg_calls
-                .iter()
-                .map(|agg| agg.args[0].eval(&chunk))
+            let args: SmallVec<[_; 16]> = (self.agg_calls.iter())
+                .map(|agg| -> Result<_, ExecutorError> {
+                    Ok((eval_agg_args(agg, &chunk)?, eval_agg_filter(agg, &chunk)?))
+                })
                 .try_collect()?;
             let group_cols: SmallVec<[ArrayImpl; 16]> = self
                 .group_keys
@@ -42,46 +48,65 @@ impl SortAggExecutor {
                     group_key.push(col.get(row_idx));
                 }
                 // Check group key & last key
-                match last_key {
-                    Some(last_key) => {
-                        if last_key != group_key {
-                            yield Self::finish_agg(&states);
-                            states = create_agg_states(&self.agg_calls);
+                if let Some(last_key) = &last_key {
+                    if *last_key != group_key {
+                        Self::finish_group(&mut builders, last_key, &states);
+                        states = create_agg_states(&self.agg_calls);
+                        num_groups += 1;
+                        if num_groups == PROCESSING_WINDOW_SIZE {
+                            let builders = std::mem::replace(
+                                &mut builders,
+                                Self::create_builders(&self.group_keys, &self.agg_calls),
+                            );
+                            yield builders.into_iter().collect::<DataChunk>();
+                            num_groups = 0;
                         }
                     }
-                    None => (),
                 }
-                for (state, expr) in states.iter_mut().zip_eq(&exprs) {
-                    state.update_single(&expr.get(row_idx))?;
+                for (state, (args, filter)) in states.iter_mut().zip_eq(&args) {
+                    if filter.as_ref().map_or(true, |filter| filter[row_idx]) {
+                        let values: SmallVec<[DataValue; 2]> =
+                            args.iter().map(|array| array.get(row_idx)).collect();
+                        state.update_single(&values)?;
+                    }
                 }
                 last_key = Some(group_key);
             }
         }
-        if last_key.is_some() {
-            yield Self::finish_agg(&states);
+        if let Some(last_key) = &last_key {
+            Self::finish_group(&mut builders, last_key, &states);
+            yield builders.into_iter().collect::<DataChunk>();
         }
     }
 
-    fn finish_agg(states: &SmallVec<[Box<dyn AggregationState>; 16]>) -> DataChunk {
-        return states
-            .iter()
-            .map(|s| {
-                let result = &s.output();
-                match &result.data_type() {
-                    Some(r) => {
-                        let mut builder = ArrayBuilderImpl::with_capacity(1, r);
-                        builder.push(result);
-                        builder.finish()
-                    }
-                    None => ArrayBuilderImpl::new(&DataTypeKind::Int(None).nullable()).finish(),
-                }
-            })
-            .collect::<DataChunk>();
+    fn create_builders(
+        group_keys: &[BoundExpr],
+        agg_calls: &[BoundAggCall],
+    ) -> Vec<ArrayBuilderImpl> {
+        let key_types = group_keys.iter().map(|e| e.return_type().unwrap());
+        let agg_types = agg_calls.iter().map(|agg| agg.return_type.clone());
+        key_types
+            .chain(agg_types)
+            .map(|ty| ArrayBuilderImpl::new(&ty))
+            .collect()
+    }
+
+    /// Push the group key and aggregation results of a group.
+    fn finish_group(
+        builders: &mut [ArrayBuilderImpl],
+        key: &HashKey,
+        states: &SmallVec<[Box<dyn AggregationState>; 16]>,
+    ) {
+        let values = key.iter().cloned().chain(states.iter().map(|s| s.output()));
+        for (value, builder) in values.zip_eq(builders.iter_mut()) {
+            builder.push(&value);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::TryStreamExt;
 
     use super::*;
     use crate::array::ArrayImpl;
@@ -104,10 +129,10 @@ mod tests {
                 vec![1.2, 1.2, 2.3, 2.4, 2.5],
             ],
             vec![
-                vec![1.3, 2.2],
-                vec![0.3, 1.3],
-                vec![0.4, 1.4],
-                vec![0.5, 1.5],
+                vec![1.1, 1.2, 1.3, 2.2],
+                vec![1.3, 2.3, 0.3, 1.3],
+                vec![1.4, 2.4, 0.4, 1.4],
+                vec![1.5, 2.5, 0.5, 1.5],
             ],
         )
         .await;
@@ -120,11 +145,11 @@ mod tests {
                 vec![1.1, 1.2, 2.3, 2.4, 2.5],
             ],
             vec![
-                vec![0.1, 1.1],
-                vec![0.2, 1.1],
-                vec![0.3, 1.3],
-                vec![0.4, 1.4],
-                vec![0.5, 1.5],
+                vec![1.1, 1.1, 0.1, 1.1],
+                vec![1.1, 1.2, 0.2, 1.1],
+                vec![1.3, 2.3, 0.3, 1.3],
+                vec![1.4, 2.4, 0.4, 1.4],
+                vec![1.5, 2.5, 0.5, 1.5],
             ],
         )
         .await
@@ -136,7 +161,7 @@ mod tests {
             vec![0, 1],
             vec![0],
             vec![vec![1.0, 1.0], vec![1.0, 2.0]],
-            vec![vec![2.0, 3.0]],
+            vec![vec![1.0, 2.0, 3.0]],
         )
         .await;
         test_group_agg(
@@ -148,10 +173,10 @@ mod tests {
                 vec![2.1, 2.2, 2.3, 2.4, 2.5],
             ],
             vec![
-                vec![1.3, 2.2],
-                vec![0.3, 1.3],
-                vec![0.4, 1.4],
-                vec![0.5, 1.5],
+                vec![1.1, 1.3, 2.2],
+                vec![1.3, 0.3, 1.3],
+                vec![1.4, 0.4, 1.4],
+                vec![1.5, 0.5, 1.5],
             ],
         )
         .await;
@@ -164,21 +189,22 @@ mod tests {
                 vec![2.1, 2.2, 2.3, 2.4, 2.5],
             ],
             vec![
-                vec![0.1, 1.1],
-                vec![0.2, 1.2],
-                vec![0.3, 1.3],
-                vec![0.4, 1.4],
-                vec![0.5, 1.5],
+                vec![1.1, 0.1, 1.1],
+                vec![1.2, 0.2, 1.2],
+                vec![1.3, 0.3, 1.3],
+                vec![1.4, 0.4, 1.4],
+                vec![1.5, 0.5, 1.5],
             ],
         )
         .await
     }
 
+    /// `expected_rows` are the group keys followed by the aggregation results of each group.
     async fn test_group_agg(
         agg_call_index: Vec<usize>,
         group_key_index: Vec<usize>,
         cols: Vec<Vec<f64>>,
-        expected_cols: Vec<Vec<f64>>,
+        expected_rows: Vec<Vec<f64>>,
     ) {
         let mut agg_calls = vec![];
         for index in agg_call_index {
@@ -204,20 +230,17 @@ mod tests {
             group_keys,
             child,
         };
-        let mut executor = executor.execute();
-
-        let mut expected_cols_size = 0;
-        while let Some(chunk) = executor.next().await {
-            let chunk = chunk.unwrap();
-            let expected_col = expected_cols.get(expected_cols_size).unwrap();
-            let mut expected_array = vec![];
-            for data in expected_col {
-                expected_array.push(ArrayImpl::new_float64(vec![*data].into_iter().collect()));
-            }
-            assert_eq!(chunk.arrays(), expected_array);
-            expected_cols_size += 1;
+        let chunks: Vec<DataChunk> = executor.execute().try_collect().await.unwrap();
+
+        if expected_rows.is_empty() {
+            assert!(chunks.is_empty());
+            return;
         }
-        assert_eq!(expected_cols_size, expected_cols.len());
+        assert_eq!(chunks.len(), 1);
+        let expected_arrays = (0..expected_rows[0].len())
+            .map(|i| ArrayImpl::new_float64(expected_rows.iter().map(|row| row[i]).collect()))
+            .collect_vec();
+        assert_eq!(chunks[0].arrays(), expected_arrays);
     }
 
     fn create_sum_agg_call(value: usize) -> BoundAggCall {
@@ -230,22 +253,17 @@ mod tests {
                     false,
                 ),
             })],
+            params: vec![],
+            filter: None,
             return_type: DataType::new(DataTypeKind::Double, false),
+            distinct: false,
         }
     }
 
     fn create_input_ref(value: usize) -> BoundExpr {
         BoundExpr::InputRef(BoundInputRef {
             index: value,
-            return_type: DataType::new(DataTypeKind::Int(Option::None), false),
+            return_type: DataType::new(DataTypeKind::Double, false),
         })
     }
-
-    fn create_expected_col(cols: Vec<f64>) -> Vec<ArrayImpl> {
-        let mut expected = Vec::new();
-        for col in cols {
-            expected.push(ArrayImpl::new_float64([col].into_iter().collect()));
-        }
-        expected
-    }
 }
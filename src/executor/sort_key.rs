@@ -0,0 +1,206 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Normalized sort keys.
+//!
+//! The sort keys of each row are encoded into a byte string, so that comparing the byte strings
+//! gives the order of rows. Sorting by the byte strings avoids dispatching on the types, the
+//! directions and the NULL ordering of sort keys in every comparison.
+
+use rust_decimal::Decimal;
+
+use super::*;
+use crate::array::{Array, ArrayImpl, DataChunk};
+use crate::binder::BoundOrderBy;
+
+/// Returns the normalized sort keys of the rows of a chunk.
+pub fn encode_sort_keys(
+    chunk: &DataChunk,
+    comparators: &[BoundOrderBy],
+) -> Result<Vec<Vec<u8>>, ExecutorError> {
+    let mut keys = vec![vec![]; chunk.cardinality()];
+    for cmp in comparators {
+        let array = cmp.expr.eval(chunk)?;
+        encode_array(&mut keys, &array, cmp);
+    }
+    Ok(keys)
+}
+
+/// Append the encoded values of an array to the keys of the rows.
+fn encode_array(keys: &mut [Vec<u8>], array: &ArrayImpl, cmp: &BoundOrderBy) {
+    match array {
+        ArrayImpl::Bool(a) => encode_column(keys, &**a, cmp, |key, v| key.push(*v as u8)),
+        ArrayImpl::Int32(a) => encode_column(keys, &**a, cmp, |key, v| encode_i32(key, *v)),
+        ArrayImpl::Int64(a) => encode_column(keys, &**a, cmp, |key, v| encode_i64(key, *v)),
+        ArrayImpl::Float64(a) => encode_column(keys, &**a, cmp, |key, v| encode_f64(key, *v)),
+        ArrayImpl::Utf8(a) => {
+            encode_column(keys, &**a, cmp, |key, v| encode_bytes(key, v.as_bytes()))
+        }
+        ArrayImpl::Blob(a) => encode_column(keys, &**a, cmp, |key, v| encode_bytes(key, v)),
+        ArrayImpl::Decimal(a) => encode_column(keys, &**a, cmp, |key, v| encode_decimal(key, *v)),
+        ArrayImpl::Date(a) => {
+            encode_column(keys, &**a, cmp, |key, v| encode_i32(key, v.get_inner()))
+        }
+        ArrayImpl::Interval(a) => encode_column(keys, &**a, cmp, |key, v| {
+            encode_i32(key, v.num_months());
+            encode_i32(key, v.days());
+            encode_i64(key, v.micros());
+        }),
+        ArrayImpl::Timestamp(a) => {
+            encode_column(keys, &**a, cmp, |key, v| encode_i64(key, v.get_inner()))
+        }
+        ArrayImpl::TimestampTz(a) => {
+            encode_column(keys, &**a, cmp, |key, v| encode_i64(key, v.get_inner()))
+        }
+    }
+}
+
+/// Append the encoded values of an array to the keys, with a leading byte for NULL ordering.
+///
+/// `encode` must write a prefix-free encoding that preserves the ascending order of values, so
+/// that inverting its bytes reverses the order.
+fn encode_column<A: Array>(
+    keys: &mut [Vec<u8>],
+    array: &A,
+    cmp: &BoundOrderBy,
+    encode: impl Fn(&mut Vec<u8>, &A::Item),
+) {
+    let null = if cmp.nulls_first { 0 } else { 2 };
+    for (key, value) in keys.iter_mut().zip(array.iter()) {
+        let value = match value {
+            Some(value) => value,
+            None => {
+                key.push(null);
+                continue;
+            }
+        };
+        key.push(1);
+        let start = key.len();
+        encode(key, value);
+        if cmp.descending {
+            key[start..].iter_mut().for_each(|b| *b = !*b);
+        }
+    }
+}
+
+fn encode_i32(key: &mut Vec<u8>, v: i32) {
+    key.extend_from_slice(&((v as u32) ^ (1 << 31)).to_be_bytes());
+}
+
+fn encode_i64(key: &mut Vec<u8>, v: i64) {
+    key.extend_from_slice(&((v as u64) ^ (1 << 63)).to_be_bytes());
+}
+
+/// Floats are in a total order, where -0 equals 0, and NaN equals NaN and is greater than any
+/// other values.
+fn encode_f64(key: &mut Vec<u8>, v: f64) {
+    let v = if v == 0.0 {
+        0.0
+    } else if v.is_nan() {
+        f64::NAN
+    } else {
+        v
+    };
+    let bits = v.to_bits();
+    let bits = if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    };
+    key.extend_from_slice(&bits.to_be_bytes());
+}
+
+/// Bytes are escaped with `0x00 -> 0x00 0xFF` and terminated by `0x00 0x00`.
+fn encode_bytes(key: &mut Vec<u8>, bytes: &[u8]) {
+    for &b in bytes {
+        key.push(b);
+        if b == 0 {
+            key.push(0xFF);
+        }
+    }
+    key.extend_from_slice(&[0, 0]);
+}
+
+/// A non-zero decimal is written as `0.{digits} * 10^exponent` without trailing zeros in the
+/// digits, after a byte for its sign. The exponent and the digits of negative values are inverted.
+fn encode_decimal(key: &mut Vec<u8>, v: Decimal) {
+    if v.is_zero() {
+        key.push(1);
+        return;
+    }
+    let v = v.normalize();
+    key.push(if v.is_sign_negative() { 0 } else { 2 });
+    let start = key.len();
+    let digits = v.mantissa().unsigned_abs().to_string();
+    let exponent = digits.len() as i32 - v.scale() as i32;
+    key.push((exponent + 128) as u8);
+    key.extend_from_slice(digits.as_bytes());
+    key.push(0);
+    if v.is_sign_negative() {
+        key[start..].iter_mut().for_each(|b| *b = !*b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{DecimalArray, F64Array, Utf8Array};
+    use crate::binder::BoundInputRef;
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    /// Returns the indexes of the rows of a single column, sorted by their normalized keys.
+    fn sorted(array: &ArrayImpl, kind: DataTypeKind, desc: bool, nulls_first: bool) -> Vec<usize> {
+        let comparators = [BoundOrderBy {
+            expr: BoundExpr::InputRef(BoundInputRef {
+                index: 0,
+                return_type: kind.nullable(),
+            }),
+            descending: desc,
+            nulls_first,
+        }];
+        let chunk: DataChunk = [array.clone()].into_iter().collect();
+        let keys = encode_sort_keys(&chunk, &comparators).unwrap();
+        let mut indexes = (0..keys.len()).collect_vec();
+        indexes.sort_by(|&i, &j| keys[i].cmp(&keys[j]).then(i.cmp(&j)));
+        indexes
+    }
+
+    #[test]
+    fn test_f64() {
+        let values = [
+            Some(1.5),
+            Some(f64::NAN),
+            None,
+            Some(-0.0),
+            Some(f64::NEG_INFINITY),
+            Some(0.0),
+        ];
+        let array = ArrayImpl::new_float64(values.into_iter().collect::<F64Array>());
+        let sorted = |desc, nulls_first| sorted(&array, DataTypeKind::Double, desc, nulls_first);
+        // -0 equals 0
+        assert_eq!(sorted(false, true), vec![2, 4, 3, 5, 0, 1]);
+        assert_eq!(sorted(false, false), vec![4, 3, 5, 0, 1, 2]);
+        assert_eq!(sorted(true, true), vec![2, 1, 0, 3, 5, 4]);
+        assert_eq!(sorted(true, false), vec![1, 0, 3, 5, 4, 2]);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let values = ["ab", "a", "a\0", "", "b"];
+        let array = ArrayImpl::new_utf8(values.into_iter().map(Some).collect::<Utf8Array>());
+        let sorted = |desc| sorted(&array, DataTypeKind::String, desc, true);
+        assert_eq!(sorted(false), vec![3, 1, 2, 0, 4]);
+        assert_eq!(sorted(true), vec![4, 0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_decimal() {
+        let values = [
+            "-12.5", "0.00", "1.20", "-0.3", "1.2", "100", "0.05", "-100",
+        ];
+        let values = values.into_iter().map(|v| Some(v.parse().unwrap()));
+        let array = ArrayImpl::new_decimal(values.collect::<DecimalArray>());
+        let sorted = |desc| sorted(&array, DataTypeKind::Decimal(None, None), desc, true);
+        assert_eq!(sorted(false), vec![7, 0, 3, 1, 6, 2, 4, 5]);
+        assert_eq!(sorted(true), vec![5, 2, 4, 6, 1, 3, 0, 7]);
+    }
+}
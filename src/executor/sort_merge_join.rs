@@ -1,12 +1,16 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
-#![allow(dead_code)]
+
 use std::cmp::Ordering;
 use std::vec::Vec;
 
 use super::*;
 use crate::array::{DataChunk, DataChunkBuilder};
-use crate::types::{DataType, Row};
-/// The executor for sort merge join
+use crate::types::{DataType, DataValue, Row};
+
+/// The executor for sort merge join.
+///
+/// Both children must be sorted in ascending order by the join key. Rows with NULL keys never
+/// match.
 pub struct SortMergeJoinExecutor {
     pub left_child: BoxedExecutor,
     pub right_child: BoxedExecutor,
@@ -100,7 +104,7 @@ async fn row_stream(stream: BoxedExecutor) {
     }
 }
 
-// chunk same join key rows together
+// chunk same join key rows together, skipping rows with NULL key
 #[try_stream(boxed, ok = Vec<Row>, error = ExecutorError)]
 async fn same_key_chunks(
     row_stream: BoxStream<'static, Result<Row, ExecutorError>>,
@@ -111,6 +115,9 @@ async fn same_key_chunks(
     #[for_await]
     for row in row_stream {
         let row = row?;
+        if row[column_index] == DataValue::Null {
+            continue;
+        }
         if current_row.is_none() {
             current_row = Some(row);
             chunk.push(current_row.clone().unwrap());
@@ -125,7 +132,9 @@ async fn same_key_chunks(
             chunk.push(current_row.clone().unwrap());
         }
     }
-    yield chunk;
+    if !chunk.is_empty() {
+        yield chunk;
+    }
 }
 
 // for example:
@@ -142,7 +151,7 @@ async fn same_key_chunks(
 // 2 c 2 f
 // 2 d 2 e
 // 2 d 2 f
-fn full_join(left_chunk: &Vec<Row>, right_chunk: &Vec<Row>) -> Vec<Row> {
+fn full_join(left_chunk: &[Row], right_chunk: &[Row]) -> Vec<Row> {
     let mut join_chunk = Vec::new();
     for left_row in left_chunk {
         for right_row in right_chunk {
@@ -221,4 +230,10 @@ mod tests {
     async fn test_no_intersection() {
         sort_merge_test(vec![1, 2, 3], vec![4, 5, 6], vec![]).await;
     }
+
+    #[tokio::test]
+    async fn test_empty_input() {
+        sort_merge_test(vec![], vec![1, 2], vec![]).await;
+        sort_merge_test(vec![1, 2], vec![], vec![]).await;
+    }
 }
@@ -0,0 +1,375 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Spilling rows of memory-intensive executors to temporary files.
+//!
+//! Hash aggregation and hash join partition their input by the hash of the keys into
+//! [`SpillPartitions`] once they exceed the memory budget of the [`Context`], and then process
+//! each partition separately. Order writes sorted runs to [`SpillFile`]s and merges them.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rust_decimal::Decimal;
+
+use super::*;
+use crate::array::DataChunkBuilder;
+use crate::types::{
+    Blob, BlobRef, DataType, DataValue, Date, Interval, Row, Timestamp, TimestampTz,
+};
+
+/// The number of partitions to split the input into at each level of spilling.
+pub const NUM_SPILL_PARTITIONS: usize = 16;
+
+/// The maximum times of recursive partitioning.
+///
+/// If a partition still exceeds the memory budget after that, most of its rows share the same
+/// key and can't be split any further.
+pub const MAX_SPILL_DEPTH: usize = 4;
+
+/// Estimate the in-memory size of the values.
+pub fn estimated_size(values: &[DataValue]) -> usize {
+    values
+        .iter()
+        .map(|value| {
+            size_of::<DataValue>()
+                + match value {
+                    DataValue::String(s) => s.len(),
+                    DataValue::Blob(b) => <BlobRef as AsRef<[u8]>>::as_ref(b).len(),
+                    _ => 0,
+                }
+        })
+        .sum()
+}
+
+/// A temporary file of rows, which is removed on drop.
+pub struct SpillFile {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    num_rows: usize,
+}
+
+impl SpillFile {
+    /// Create an empty file in `dir`.
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("risinglight-spill-{}-{}", std::process::id(), id));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillFile {
+            path,
+            writer: Some(BufWriter::new(file)),
+            num_rows: 0,
+        })
+    }
+
+    /// Append a row to the file.
+    pub fn write_row(&mut self, row: impl IntoIterator<Item = DataValue>) -> io::Result<()> {
+        let writer = self.writer.as_mut().expect("file is closed for writing");
+        let row = row.into_iter().collect::<Row>();
+        writer.write_all(&(row.len() as u32).to_le_bytes())?;
+        for value in &row {
+            write_value(writer, value)?;
+        }
+        self.num_rows += 1;
+        Ok(())
+    }
+
+    /// Returns the number of rows in the file.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Flush the written rows and read them from the beginning.
+    pub fn into_reader(mut self) -> io::Result<SpillReader> {
+        if let Some(writer) = self.writer.take() {
+            writer.into_inner().map_err(|e| e.into_error())?;
+        }
+        Ok(SpillReader {
+            reader: BufReader::new(File::open(&self.path)?),
+            remaining: self.num_rows,
+            _file: self,
+        })
+    }
+
+    /// Read the rows back as a stream of chunks.
+    #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+    pub async fn into_executor(self, data_types: Vec<DataType>) {
+        let mut reader = self.into_reader()?;
+        let mut builder = DataChunkBuilder::new(data_types.iter(), PROCESSING_WINDOW_SIZE);
+        while let Some(row) = reader.next_row()? {
+            if let Some(chunk) = builder.push_row(row) {
+                yield chunk;
+            }
+        }
+        if let Some(chunk) = { builder }.take() {
+            yield chunk;
+        }
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        self.writer.take();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("failed to remove spill file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// A reader of rows in a [`SpillFile`].
+pub struct SpillReader {
+    reader: BufReader<File>,
+    remaining: usize,
+    // keep the file until the reader is dropped
+    _file: SpillFile,
+}
+
+impl SpillReader {
+    /// Read the next row, or `None` at the end of file.
+    pub fn next_row(&mut self) -> io::Result<Option<Row>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let len = u32::from_le_bytes(read_bytes(&mut self.reader)?) as usize;
+        let row = (0..len)
+            .map(|_| read_value(&mut self.reader))
+            .try_collect()?;
+        Ok(Some(row))
+    }
+}
+
+/// Rows partitioned by the hash of their keys into [`SpillFile`]s.
+pub struct SpillPartitions {
+    files: Vec<SpillFile>,
+    depth: usize,
+}
+
+impl SpillPartitions {
+    /// Create partitions at the `depth`-th level of spilling.
+    ///
+    /// Each level hashes the keys differently, so that the rows of a partition are spread over
+    /// all partitions of the next level.
+    pub fn create(dir: &Path, depth: usize) -> io::Result<Self> {
+        let files = (0..NUM_SPILL_PARTITIONS)
+            .map(|_| SpillFile::create(dir))
+            .try_collect()?;
+        Ok(SpillPartitions { files, depth })
+    }
+
+    /// Append a row to the partition of `key`.
+    pub fn write_row(
+        &mut self,
+        key: &[DataValue],
+        row: impl IntoIterator<Item = DataValue>,
+    ) -> io::Result<()> {
+        let mut hasher = DefaultHasher::new();
+        self.depth.hash(&mut hasher);
+        key.hash(&mut hasher);
+        let partition = hasher.finish() as usize % self.files.len();
+        self.files[partition].write_row(row)
+    }
+
+    /// Append all rows of the chunk, with keys at `key_indexes`.
+    pub fn write_chunk(&mut self, chunk: &DataChunk, key_indexes: &[usize]) -> io::Result<()> {
+        for row in chunk.rows() {
+            self.write_row(&row.get_by_indexes(key_indexes), row.values())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the non-empty partitions.
+    pub fn into_files(self) -> Vec<SpillFile> {
+        self.files
+            .into_iter()
+            .filter(|file| file.num_rows() > 0)
+            .collect()
+    }
+
+    /// Returns all partitions, including the empty ones.
+    pub fn into_all_files(self) -> Vec<SpillFile> {
+        self.files
+    }
+}
+
+fn write_value(writer: &mut impl Write, value: &DataValue) -> io::Result<()> {
+    match value {
+        DataValue::Null => writer.write_all(&[0]),
+        DataValue::Bool(v) => writer.write_all(&[1, *v as u8]),
+        DataValue::Int32(v) => {
+            writer.write_all(&[2])?;
+            writer.write_all(&v.to_le_bytes())
+        }
+        DataValue::Int64(v) => {
+            writer.write_all(&[3])?;
+            writer.write_all(&v.to_le_bytes())
+        }
+        DataValue::Float64(v) => {
+            writer.write_all(&[4])?;
+            writer.write_all(&v.to_le_bytes())
+        }
+        DataValue::String(v) => {
+            writer.write_all(&[5])?;
+            writer.write_all(&(v.len() as u32).to_le_bytes())?;
+            writer.write_all(v.as_bytes())
+        }
+        DataValue::Blob(v) => {
+            let bytes = <BlobRef as AsRef<[u8]>>::as_ref(v);
+            writer.write_all(&[6])?;
+            writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+            writer.write_all(bytes)
+        }
+        DataValue::Decimal(v) => {
+            writer.write_all(&[7])?;
+            writer.write_all(&v.serialize())
+        }
+        DataValue::Date(v) => {
+            writer.write_all(&[8])?;
+            writer.write_all(&v.get_inner().to_le_bytes())
+        }
+        DataValue::Interval(v) => {
+            writer.write_all(&[9])?;
+            writer.write_all(&v.num_months().to_le_bytes())?;
+            writer.write_all(&v.days().to_le_bytes())?;
+            writer.write_all(&v.micros().to_le_bytes())
+        }
+        DataValue::Timestamp(v) => {
+            writer.write_all(&[10])?;
+            writer.write_all(&v.get_inner().to_le_bytes())
+        }
+        DataValue::TimestampTz(v) => {
+            writer.write_all(&[11])?;
+            writer.write_all(&v.get_inner().to_le_bytes())
+        }
+    }
+}
+
+fn read_value(reader: &mut impl Read) -> io::Result<DataValue> {
+    let [tag] = read_bytes(reader)?;
+    Ok(match tag {
+        0 => DataValue::Null,
+        1 => DataValue::Bool(read_bytes::<1>(reader)?[0] != 0),
+        2 => DataValue::Int32(i32::from_le_bytes(read_bytes(reader)?)),
+        3 => DataValue::Int64(i64::from_le_bytes(read_bytes(reader)?)),
+        4 => DataValue::Float64(f64::from_le_bytes(read_bytes(reader)?)),
+        5 => {
+            let bytes = read_vec(reader)?;
+            let s = String::from_utf8(bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            DataValue::String(s)
+        }
+        6 => DataValue::Blob(Blob::from(read_vec(reader)?)),
+        7 => DataValue::Decimal(Decimal::deserialize(read_bytes(reader)?)),
+        8 => DataValue::Date(Date::new(i32::from_le_bytes(read_bytes(reader)?))),
+        9 => {
+            let months = i32::from_le_bytes(read_bytes(reader)?);
+            let days = i32::from_le_bytes(read_bytes(reader)?);
+            let micros = i64::from_le_bytes(read_bytes(reader)?);
+            DataValue::Interval(Interval::from_mdm(months, days, micros))
+        }
+        10 => DataValue::Timestamp(Timestamp::new(i64::from_le_bytes(read_bytes(reader)?))),
+        11 => DataValue::TimestampTz(TimestampTz::new(i64::from_le_bytes(read_bytes(reader)?))),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid value tag in spill file: {}", tag),
+            ))
+        }
+    })
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_vec(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = u32::from_le_bytes(read_bytes(reader)?) as usize;
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = vec![
+            vec![
+                DataValue::Null,
+                DataValue::Bool(true),
+                DataValue::Int32(-1),
+                DataValue::Int64(1 << 40),
+                DataValue::Float64(1.5),
+                DataValue::String("risinglight".into()),
+            ],
+            vec![
+                DataValue::Blob(Blob::from(&b"\x00\xff"[..])),
+                DataValue::Decimal(Decimal::new(12345, 2)),
+                DataValue::Date(Date::new(19000)),
+                DataValue::Interval(Interval::from_mdm(13, 2, 3)),
+                DataValue::Timestamp(Timestamp::new(1)),
+                DataValue::TimestampTz(TimestampTz::new(2)),
+            ],
+        ];
+        let mut file = SpillFile::create(dir.path()).unwrap();
+        let path = file.path.clone();
+        for row in &rows {
+            file.write_row(row.iter().cloned()).unwrap();
+        }
+        assert_eq!(file.num_rows(), 2);
+
+        let mut reader = file.into_reader().unwrap();
+        assert_eq!(reader.next_row().unwrap().as_ref(), Some(&rows[0]));
+        assert_eq!(reader.next_row().unwrap().as_ref(), Some(&rows[1]));
+        assert_eq!(reader.next_row().unwrap(), None);
+        drop(reader);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_spill_partitions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut partitions = SpillPartitions::create(dir.path(), 0).unwrap();
+        for i in 0..100 {
+            let key = [DataValue::Int32(i % 10)];
+            partitions
+                .write_row(&key, [DataValue::Int32(i % 10), DataValue::Int32(i)])
+                .unwrap();
+        }
+        let files = partitions.into_files();
+        assert!(files.len() > 1);
+
+        // rows of the same key are in the same partition
+        let mut total = 0;
+        let mut seen_keys = vec![];
+        for file in files {
+            let mut reader = file.into_reader().unwrap();
+            let mut keys = vec![];
+            while let Some(row) = reader.next_row().unwrap() {
+                keys.push(row[0].clone());
+                total += 1;
+            }
+            keys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            keys.dedup();
+            for key in keys {
+                assert!(!seen_keys.contains(&key));
+                seen_keys.push(key);
+            }
+        }
+        assert_eq!(total, 100);
+        assert_eq!(seen_keys.len(), 10);
+    }
+}
@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
@@ -10,7 +11,7 @@ use super::*;
 use crate::array::{ArrayBuilder, ArrayBuilderImpl, DataChunk, I64ArrayBuilder};
 use crate::binder::BoundExpr;
 use crate::optimizer::plan_nodes::PhysicalTableScan;
-use crate::storage::{Storage, StorageColumnRef, Table, Transaction, TxnIterator};
+use crate::storage::{ScanPartition, Storage, StorageColumnRef, Table, Transaction, TxnIterator};
 
 /// The executor of table scan operation.
 pub struct TableScanExecutor<S: Storage> {
@@ -59,14 +60,15 @@ impl<S: Storage> TableScanExecutor<S> {
 
     pub async fn execute_inner(
         self,
-        tx: mpsc::Sender<DataChunk>,
+        txs: Vec<mpsc::Sender<DataChunk>>,
         token: CancellationToken,
     ) -> Result<(), ExecutorError> {
-        let table = self.storage.get_table(self.plan.logical().table_ref_id())?;
+        let table = self
+            .storage
+            .get_table_in_session(self.plan.logical().table_ref_id(), self.context.session())?;
 
         // TODO: remove this when we have schema
         let empty_chunk = self.build_empty_chunk(&table)?;
-        let mut have_chunk = false;
 
         let mut col_idx = self
             .plan
@@ -84,86 +86,115 @@ impl<S: Storage> TableScanExecutor<S> {
         if token.is_cancelled() {
             return Err(ExecutorError::Abort);
         }
-        let txn = table.read().await?;
-
-        let mut it = match unified_select_with_token(
-            &token,
-            txn.scan(
-                &[],
-                &[],
-                &col_idx,
-                self.plan.logical().is_sorted(),
-                false,
-                self.expr,
-            ),
-        )
-        .await
-        {
-            Ok(it) => it,
-            Err(err) => {
-                txn.abort().await?;
-                return Err(err);
-            }
+        let txn = match self.plan.logical().as_of_epoch() {
+            Some(epoch) => table.read_as_of(epoch).await?,
+            None => table.read().await?,
         };
 
-        loop {
-            let chunk = match select_with_token(&token, it.next_batch(None)).await {
-                Ok(chunk) => chunk,
+        // scan each partition of the table in a separate task
+        let count = txs.len();
+        let mut handles = vec![];
+        for (index, tx) in txs.into_iter().enumerate() {
+            let it = match unified_select_with_token(
+                &token,
+                txn.scan(
+                    self.plan.logical().begin_keys(),
+                    self.plan.logical().end_keys(),
+                    &col_idx,
+                    self.plan.logical().is_sorted(),
+                    false,
+                    self.expr.clone(),
+                    ScanPartition { index, count },
+                ),
+            )
+            .await
+            {
+                Ok(it) => it,
                 Err(err) => {
                     txn.abort().await?;
                     return Err(err);
                 }
             };
-            match chunk {
-                Ok(x) => {
-                    if let Some(x) = x {
-                        if tx.send(x).await.is_err() {
-                            txn.abort().await?;
-                            return Err(ExecutorError::Abort);
-                        }
-                        have_chunk = true;
-                    } else {
-                        break;
-                    }
-                }
-                Err(err) => {
+            let empty_chunk = empty_chunk.clone();
+            match (self.context).spawn(|token| Self::scan_partition(it, tx, empty_chunk, token)) {
+                Some(handle) => handles.push(handle),
+                None => {
                     txn.abort().await?;
-                    return Err(err.into());
+                    return Err(ExecutorError::Abort);
                 }
             }
         }
 
+        // report the first error of partitions
+        let mut result = Ok(());
+        for handle in handles {
+            let ret = handle.await.expect("failed to join scan thread");
+            if result.is_ok() {
+                result = ret;
+            }
+        }
         txn.abort().await?;
+        result
+    }
 
+    /// Send the chunks of a partition to `tx`, or an empty chunk if there is none.
+    async fn scan_partition(
+        mut it: <S::TransactionType as Transaction>::TxnIteratorType,
+        tx: mpsc::Sender<DataChunk>,
+        empty_chunk: DataChunk,
+        token: CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let mut have_chunk = false;
+        while let Some(chunk) = select_with_token(&token, it.next_batch(None)).await?? {
+            if tx.send(chunk).await.is_err() {
+                return Err(ExecutorError::Abort);
+            }
+            have_chunk = true;
+        }
         if !have_chunk && tx.send(empty_chunk).await.is_err() {
             return Err(ExecutorError::Abort);
         }
-
         Ok(())
     }
 
-    #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
-    pub async fn execute(self) {
-        // Buffer at most 128 chunks in memory
-        let (tx, mut rx) = tokio::sync::mpsc::channel(128);
+    /// Scan the table in `num_partitions` disjoint parts in parallel, which are split across
+    /// RowSets in the secondary storage. All parts read the same snapshot of the table.
+    pub fn execute_partitioned(self, num_partitions: usize) -> Vec<BoxedExecutor> {
+        // Buffer at most 128 chunks in memory for each partition
+        let (txs, rxs): (Vec<_>, Vec<_>) = (0..num_partitions).map(|_| mpsc::channel(128)).unzip();
 
         let context = self.context.clone();
-        match context.spawn(|token| async {
-            let result = self.execute_inner(tx, token).await;
+        let handle = match context.spawn(|token| async {
+            let result = self.execute_inner(txs, token).await;
             if let Err(ExecutorError::Abort) = result {
                 warn!("Abort!")
             }
             result
         }) {
-            Some(handler) => {
-                while let Some(item) = rx.recv().await {
-                    yield item;
-                }
-                handler.await.expect("failed to join scan thread")?;
-            }
+            Some(handle) => handle,
             None => {
-                return Err(ExecutorError::Abort);
+                return (0..num_partitions)
+                    .map(|_| futures::stream::once(async { Err(ExecutorError::Abort) }).boxed())
+                    .collect();
             }
-        }
+        };
+        // the first partition reports the errors of the scan
+        let mut handle = Some(handle);
+        rxs.into_iter()
+            .map(|rx| receive_chunks(rx, handle.take()))
+            .collect()
+    }
+}
+
+#[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+async fn receive_chunks(
+    mut rx: mpsc::Receiver<DataChunk>,
+    handle: Option<JoinHandle<Result<(), ExecutorError>>>,
+) {
+    while let Some(item) = rx.recv().await {
+        yield item;
+    }
+    if let Some(handle) = handle {
+        handle.await.expect("failed to join scan thread")?;
     }
 }
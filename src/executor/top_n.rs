@@ -4,12 +4,15 @@ use std::cmp::Ordering;
 
 use binary_heap_plus::BinaryHeap;
 
+use super::sort_key::encode_sort_keys;
 use super::*;
 use crate::array::{DataChunk, DataChunkBuilder};
 use crate::binder::BoundOrderBy;
 use crate::types::DataType;
 
 /// The executor of a Top N operation.
+///
+/// Rows are compared by their normalized sort keys, see [`encode_sort_keys`].
 pub struct TopNExecutor {
     pub child: BoxedExecutor,
     pub offset: usize,
@@ -21,32 +24,32 @@ pub struct TopNExecutor {
 impl TopNExecutor {
     #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
     pub async fn execute(self) {
-        let heap_size = self.offset + self.limit;
-        let mut heap = BinaryHeap::with_capacity_by(heap_size, |row1, row2| {
-            cmp(row1, row2, &self.comparators)
-        });
-
-        // collect all chunks
+        // collect all chunks with the sort keys of their rows
         let mut chunks = vec![];
         #[for_await]
         for batch in self.child {
-            chunks.push(batch?);
+            let batch = batch?;
+            let keys = encode_sort_keys(&batch, &self.comparators)?;
+            chunks.push((batch, keys));
         }
-        chunks.iter().for_each(|chunk| {
-            chunk.rows().for_each(|row| {
+
+        let heap_size = self.offset + self.limit;
+        let mut heap = BinaryHeap::with_capacity_by(heap_size, cmp_keys);
+        for (chunk, keys) in &chunks {
+            for (key, row) in keys.iter().map(Vec::as_slice).zip(chunk.rows()) {
                 if heap.len() < heap_size {
-                    heap.push(row);
+                    heap.push((key, row));
                 } else {
                     let mut top = heap.peek_mut().unwrap();
-                    if cmp(&row, &top, &self.comparators) == Ordering::Less {
-                        *top = row;
+                    if key < top.0 {
+                        *top = (key, row);
                     }
                 }
-            })
-        });
+            }
+        }
 
         let mut builder = DataChunkBuilder::new(self.output_types.iter(), PROCESSING_WINDOW_SIZE);
-        for row in heap
+        for (_, row) in heap
             .into_sorted_vec()
             .into_iter()
             .skip(self.offset)
@@ -62,6 +65,11 @@ impl TopNExecutor {
     }
 }
 
+/// Compare rows by their sort keys.
+fn cmp_keys<T>(row1: &(&[u8], T), row2: &(&[u8], T)) -> Ordering {
+    row1.0.cmp(row2.0)
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
@@ -148,6 +156,7 @@ mod tests {
                 child: futures::stream::iter(inputs.into_iter().map(Ok)).boxed(),
                 comparators,
                 output_types: input_types,
+                context: Default::default(),
             }
             .execute(),
             offset,
@@ -165,6 +174,7 @@ mod tests {
                     return_type: catalog[*idx].datatype(),
                 }),
                 descending: *desc,
+                nulls_first: !*desc,
             })
             .collect()
     }
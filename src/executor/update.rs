@@ -0,0 +1,81 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::sync::Arc;
+
+use super::*;
+use crate::array::DataChunk;
+use crate::catalog::TableRefId;
+use crate::storage::{RowHandler, Storage, Table, Transaction};
+
+/// The executor of `update` statement.
+///
+/// Each updated row is deleted and the new row is appended in the same transaction.
+pub struct UpdateExecutor<S: Storage> {
+    pub context: Arc<Context>,
+    pub table_ref_id: TableRefId,
+    pub value_exprs: Vec<BoundExpr>,
+    pub storage: Arc<S>,
+    pub child: BoxedExecutor,
+}
+
+impl<S: Storage> UpdateExecutor<S> {
+    async fn execute_inner(self, token: CancellationToken) -> Result<i32, ExecutorError> {
+        let table = self
+            .storage
+            .get_table_in_session(self.table_ref_id, self.context.session())?;
+        let mut txn = table.update().await?;
+        let mut cnt = 0;
+        #[for_await]
+        for chunk in self.child {
+            let chunk = chunk?;
+            if chunk.cardinality() == 0 {
+                continue;
+            }
+            let new_chunk: Result<DataChunk, _> = self
+                .value_exprs
+                .iter()
+                .map(|expr| expr.eval(&chunk))
+                .collect();
+            let new_chunk = match new_chunk {
+                Ok(new_chunk) => new_chunk,
+                Err(err) => {
+                    txn.abort().await?;
+                    return Err(err.into());
+                }
+            };
+            let row_handlers = chunk.array_at(chunk.column_count() - 1);
+            for row_handler_idx in 0..row_handlers.len() {
+                let row_handler = <S::TransactionType as Transaction>::RowHandlerType::from_column(
+                    row_handlers,
+                    row_handler_idx,
+                );
+                if let Err(err) = unified_select_with_token(&token, txn.delete(&row_handler)).await
+                {
+                    txn.abort().await?;
+                    return Err(err);
+                }
+            }
+            cnt += new_chunk.cardinality();
+            if let Err(err) = unified_select_with_token(&token, txn.append(new_chunk)).await {
+                txn.abort().await?;
+                return Err(err);
+            }
+        }
+        txn.commit().await?;
+
+        Ok(cnt as i32)
+    }
+
+    #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+    pub async fn execute(self) {
+        let context = self.context.clone();
+        match context.spawn(|token| async move { self.execute_inner(token).await }) {
+            Some(handler) => {
+                let cnt = handler.await.expect("failed to join update thread")?;
+                let chunk = DataChunk::single(cnt as i32);
+                yield chunk;
+            }
+            None => return Err(ExecutorError::Abort),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use super::sort_key::encode_sort_keys;
+use super::*;
+use crate::array::{ArrayBuilderImpl, DataChunk};
+use crate::binder::{
+    AggKind, BoundAggCall, BoundOrderBy, BoundWindowCall, BoundWindowFrame, WindowFuncKind,
+};
+use crate::parser::{WindowFrameBound, WindowFrameUnits};
+use crate::types::{DataType, DataValue};
+
+/// The executor of window functions.
+///
+/// All input rows are buffered. For each window call, the rows are sorted by the normalized keys
+/// of the partition and order expressions, and the results are computed partition by partition.
+/// The output has the input columns followed by the results, in the order of input rows.
+pub struct WindowExecutor {
+    pub child: BoxedExecutor,
+    pub window_calls: Vec<BoundWindowCall>,
+    pub output_types: Vec<DataType>,
+}
+
+impl WindowExecutor {
+    #[try_stream(boxed, ok = DataChunk, error = ExecutorError)]
+    pub async fn execute(self) {
+        let mut chunks = vec![];
+        #[for_await]
+        for chunk in self.child {
+            chunks.push(chunk?);
+        }
+
+        let results: Vec<Vec<DataValue>> = (self.window_calls.iter())
+            .map(|call| compute_window_call(call, &chunks))
+            .try_collect()?;
+
+        let num_inputs = self.output_types.len() - self.window_calls.len();
+        let mut offset = 0;
+        for chunk in chunks {
+            let range = offset..offset + chunk.cardinality();
+            offset = range.end;
+            let outputs =
+                (results.iter().zip(&self.output_types[num_inputs..])).map(|(values, ty)| {
+                    let mut builder = ArrayBuilderImpl::with_capacity(range.len(), ty);
+                    for value in &values[range.clone()] {
+                        builder.push(value);
+                    }
+                    builder.finish()
+                });
+            yield chunk.arrays().iter().cloned().chain(outputs).collect();
+        }
+    }
+}
+
+/// Returns the results of a window call over all rows.
+fn compute_window_call(
+    call: &BoundWindowCall,
+    chunks: &[DataChunk],
+) -> Result<Vec<DataValue>, ExecutorError> {
+    let partition_by = (call.partition_by.iter())
+        .map(|expr| BoundOrderBy {
+            expr: expr.clone(),
+            descending: false,
+            nulls_first: true,
+        })
+        .collect_vec();
+    let mut partition_keys = vec![];
+    let mut order_keys = vec![];
+    let mut args = vec![vec![]; call.args.len()];
+    for chunk in chunks {
+        partition_keys.extend(encode_sort_keys(chunk, &partition_by)?);
+        order_keys.extend(encode_sort_keys(chunk, &call.order_by)?);
+        for (values, arg) in args.iter_mut().zip(&call.args) {
+            let array = arg.eval(chunk)?;
+            values.extend((0..array.len()).map(|i| array.get(i)));
+        }
+    }
+
+    // a stable sort keeps the input order of peers
+    let mut rows = (0..partition_keys.len()).collect_vec();
+    rows.sort_by(|&a, &b| {
+        (&partition_keys[a], &order_keys[a]).cmp(&(&partition_keys[b], &order_keys[b]))
+    });
+
+    let mut results = vec![DataValue::Null; rows.len()];
+    let mut start = 0;
+    while start < rows.len() {
+        let key = &partition_keys[rows[start]];
+        let end = start
+            + rows[start..]
+                .iter()
+                .position(|&row| partition_keys[row] != *key)
+                .unwrap_or(rows.len() - start);
+        let partition = Partition {
+            rows: &rows[start..end],
+            order_keys: &order_keys,
+            args: &args,
+        };
+        partition.compute(call, &mut results)?;
+        start = end;
+    }
+    Ok(results)
+}
+
+/// The sorted rows of a partition.
+struct Partition<'a> {
+    /// The indexes of rows in the input.
+    rows: &'a [usize],
+    order_keys: &'a [Vec<u8>],
+    /// The values of arguments of all rows in the input.
+    args: &'a [Vec<DataValue>],
+}
+
+impl Partition<'_> {
+    fn compute(
+        &self,
+        call: &BoundWindowCall,
+        results: &mut [DataValue],
+    ) -> Result<(), ExecutorError> {
+        let peers = self.peers();
+        if let WindowFuncKind::Agg(kind) = &call.kind {
+            return self.compute_agg(call, kind, &peers, results);
+        }
+        let mut dense_rank = 0;
+        for (i, &row) in self.rows.iter().enumerate() {
+            if peers[i].0 == i {
+                dense_rank += 1;
+            }
+            results[row] = match &call.kind {
+                WindowFuncKind::RowNumber => DataValue::Int32(i as i32 + 1),
+                WindowFuncKind::Rank => DataValue::Int32(peers[i].0 as i32 + 1),
+                WindowFuncKind::DenseRank => DataValue::Int32(dense_rank),
+                WindowFuncKind::Lag(offset) => match i.checked_sub(*offset) {
+                    Some(j) => self.args[0][self.rows[j]].clone(),
+                    None => self.default_value(row),
+                },
+                WindowFuncKind::Lead(offset) => match self.rows.get(i + offset) {
+                    Some(&j) => self.args[0][j].clone(),
+                    None => self.default_value(row),
+                },
+                WindowFuncKind::Agg(_) => unreachable!(),
+            };
+        }
+        Ok(())
+    }
+
+    /// Returns the default value of `lag` and `lead`.
+    fn default_value(&self, row: usize) -> DataValue {
+        match self.args.get(1) {
+            Some(default) => default[row].clone(),
+            None => DataValue::Null,
+        }
+    }
+
+    /// Returns the range of peers of each row, which have the same order keys.
+    fn peers(&self) -> Vec<(usize, usize)> {
+        let mut peers = vec![(0, 0); self.rows.len()];
+        let mut start = 0;
+        for i in 1..=self.rows.len() {
+            if i == self.rows.len()
+                || self.order_keys[self.rows[i]] != self.order_keys[self.rows[start]]
+            {
+                peers[start..i].fill((start, i));
+                start = i;
+            }
+        }
+        peers
+    }
+
+    /// Computes an aggregation over the frame of each row.
+    ///
+    /// If the frame starts at the beginning of the partition, the frame only grows and a single
+    /// state is updated incrementally. Otherwise, each frame is aggregated from scratch.
+    fn compute_agg(
+        &self,
+        call: &BoundWindowCall,
+        kind: &AggKind,
+        peers: &[(usize, usize)],
+        results: &mut [DataValue],
+    ) -> Result<(), ExecutorError> {
+        let agg_call = BoundAggCall {
+            kind: kind.clone(),
+            args: call.args.clone(),
+            params: vec![],
+            filter: None,
+            return_type: call.return_type.clone(),
+            distinct: false,
+        };
+        let mut state = create_agg_state(&agg_call);
+        let mut end = 0;
+        for (i, &row) in self.rows.iter().enumerate() {
+            let (frame_start, frame_end) = frame_range(&call.frame, i, peers[i], self.rows.len());
+            if frame_start != 0 {
+                state = create_agg_state(&agg_call);
+                end = frame_start;
+            }
+            for &j in &self.rows[end.min(frame_end)..frame_end] {
+                self.update(state.as_mut(), kind, j)?;
+            }
+            end = end.max(frame_end);
+            results[row] = state.output();
+        }
+        Ok(())
+    }
+
+    /// Updates the state with the arguments of a row, skipping NULLs except for `count(*)`.
+    fn update(
+        &self,
+        state: &mut dyn AggregationState,
+        kind: &AggKind,
+        row: usize,
+    ) -> Result<(), ExecutorError> {
+        let values = self.args.iter().map(|arg| arg[row].clone()).collect_vec();
+        if *kind == AggKind::RowCount || !values.contains(&DataValue::Null) {
+            state.update_single(&values)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the range of rows in the frame of the `i`-th row of a partition with `len` rows,
+/// where `peers` is the range of its peers.
+fn frame_range(
+    frame: &BoundWindowFrame,
+    i: usize,
+    peers: (usize, usize),
+    len: usize,
+) -> (usize, usize) {
+    let rows = frame.units == WindowFrameUnits::Rows;
+    let start = match frame.start {
+        WindowFrameBound::Preceding(None) => 0,
+        WindowFrameBound::Preceding(Some(n)) => i.saturating_sub(n as usize),
+        WindowFrameBound::CurrentRow if rows => i,
+        WindowFrameBound::CurrentRow => peers.0,
+        WindowFrameBound::Following(Some(n)) => (i + n as usize).min(len),
+        WindowFrameBound::Following(None) => unreachable!("rejected by binder"),
+    };
+    let end = match frame.end {
+        WindowFrameBound::Following(None) => len,
+        WindowFrameBound::Following(Some(n)) => (i + n as usize + 1).min(len),
+        WindowFrameBound::CurrentRow if rows => i + 1,
+        WindowFrameBound::CurrentRow => peers.1,
+        WindowFrameBound::Preceding(Some(n)) => (i + 1).saturating_sub(n as usize),
+        WindowFrameBound::Preceding(None) => unreachable!("rejected by binder"),
+    };
+    (start, end.max(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::array::{ArrayImpl, I32Array};
+    use crate::binder::{BoundExpr, BoundInputRef};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    fn input_ref(index: usize) -> BoundExpr {
+        BoundExpr::InputRef(BoundInputRef {
+            index,
+            return_type: DataTypeKind::Int(None).nullable(),
+        })
+    }
+
+    fn window_call(kind: WindowFuncKind, frame: BoundWindowFrame) -> BoundWindowCall {
+        BoundWindowCall {
+            kind,
+            args: vec![input_ref(1)],
+            partition_by: vec![input_ref(0)],
+            order_by: vec![BoundOrderBy {
+                expr: input_ref(1),
+                descending: false,
+                nulls_first: true,
+            }],
+            frame,
+            return_type: DataTypeKind::Int(None).nullable(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window() {
+        let chunk = |k: &[i32], v: &[Option<i32>]| -> DataChunk {
+            [
+                ArrayImpl::new_int32(k.iter().map(|k| Some(*k)).collect::<I32Array>()),
+                ArrayImpl::new_int32(v.iter().cloned().collect::<I32Array>()),
+            ]
+            .into_iter()
+            .collect()
+        };
+        let inputs = vec![
+            chunk(&[1, 2, 1], &[Some(3), Some(5), Some(1)]),
+            chunk(&[1, 2, 1], &[Some(3), None, Some(7)]),
+        ];
+        let rows = BoundWindowFrame {
+            units: WindowFrameUnits::Rows,
+            start: WindowFrameBound::Preceding(Some(1)),
+            end: WindowFrameBound::Following(Some(1)),
+        };
+        let window_calls = vec![
+            window_call(WindowFuncKind::Rank, BoundWindowFrame::default_for(true)),
+            window_call(WindowFuncKind::Lag(1), BoundWindowFrame::default_for(true)),
+            window_call(
+                WindowFuncKind::Agg(AggKind::Sum),
+                BoundWindowFrame::default_for(true),
+            ),
+            window_call(WindowFuncKind::Agg(AggKind::Sum), rows),
+        ];
+        let executor = WindowExecutor {
+            child: futures::stream::iter(inputs.into_iter().map(Ok)).boxed(),
+            window_calls,
+            output_types: vec![DataTypeKind::Int(None).nullable(); 6],
+        };
+        let outputs = executor.execute().try_collect::<Vec<_>>().await.unwrap();
+        let rows = outputs
+            .iter()
+            .flat_map(|chunk| chunk.rows().map(|row| row.values().collect_vec()))
+            .collect_vec();
+        let expected = [
+            [1, 3, 2, 1, 7, 7],
+            [2, 5, 2, -1, 5, 5],
+            [1, 1, 1, -1, 1, 4],
+            [1, 3, 2, 3, 7, 13],
+            [2, -1, 1, -1, -1, 5],
+            [1, 7, 4, 3, 14, 10],
+        ];
+        let expected = (expected.iter())
+            .map(|row| {
+                row.iter()
+                    .map(|&v| match v {
+                        -1 => DataValue::Null,
+                        v => DataValue::Int32(v),
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(rows, expected);
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use super::*;
+use crate::function::FunctionError::{InvalidDataTypes, InvalidParameters};
+use crate::types::{Date, DateField};
+
+/// Check the arguments are `(field, value)` where `value` is an `A`, and get them.
+fn field_and_value<'a, A: Array>(
+    input: &'a DataChunk,
+) -> Result<(&'a Utf8Array, &'a A), FunctionError>
+where
+    &'a A: TryFrom<&'a ArrayImpl>,
+{
+    match (input.array_at(0), <&A>::try_from(input.array_at(1))) {
+        (ArrayImpl::Utf8(field), Ok(value)) => Ok((field, value)),
+        _ => Err(InvalidParameters(
+            "arguments should be a field name and a date or timestamp".to_string(),
+        )),
+    }
+}
+
+/// Apply `f` on each pair of field and value. The field is usually a constant, so the
+/// parsed value is reused between rows.
+fn map_field_and_value<'a, A: Array, T>(
+    input: &'a DataChunk,
+    mut f: impl FnMut(DateField, &A::Item) -> Result<T, FunctionError>,
+) -> Result<Vec<Option<T>>, FunctionError>
+where
+    &'a A: TryFrom<&'a ArrayImpl>,
+{
+    let (fields, values) = field_and_value::<A>(input)?;
+    let mut last: Option<(&str, DateField)> = None;
+    let mut results = Vec::with_capacity(values.len());
+    for (field, value) in fields.iter().zip(values.iter()) {
+        let (field, value) = match (field, value) {
+            (Some(field), Some(value)) => (field, value),
+            _ => {
+                results.push(None);
+                continue;
+            }
+        };
+        let field = match last {
+            Some((name, parsed)) if name == field => parsed,
+            _ => {
+                let parsed = field
+                    .parse::<DateField>()
+                    .map_err(|e| InvalidParameters(e.to_string()))?;
+                last = Some((field, parsed));
+                parsed
+            }
+        };
+        results.push(Some(f(field, value)?));
+    }
+    Ok(results)
+}
+
+fn unsupported_field(field: DateField) -> FunctionError {
+    InvalidParameters(format!("date_trunc does not support {:?}", field))
+}
+
+/// `date_part(field, source)`: extract a field from the date or timestamp.
+/// `EXTRACT(field FROM source)` is bound to this function.
+pub struct DatePartFunction;
+
+impl Function for DatePartFunction {
+    fn name(&self) -> &str {
+        "date_part"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        use PhysicalDataTypeKind as P;
+        check_arg_count(self.name(), input_types.len(), 2, 2)?;
+        match input_types {
+            [P::String, P::Date | P::Timestamp | P::TimestampTz] => Ok(P::Int32),
+            _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+        }
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 2, 2)?;
+        let values = match input.array_at(1) {
+            ArrayImpl::Date(_) => {
+                map_field_and_value::<DateArray, _>(input, |field, v| Ok(v.extract(field)))?
+            }
+            ArrayImpl::Timestamp(_) => {
+                map_field_and_value::<TimestampArray, _>(input, |field, v| Ok(v.extract(field)))?
+            }
+            _ => {
+                map_field_and_value::<TimestampTzArray, _>(input, |field, v| Ok(v.extract(field)))?
+            }
+        };
+        Ok(single_column(I32Array::from_iter(values).into()))
+    }
+}
+
+/// `date_trunc(field, source)`: truncate the date or timestamp to the start of the year,
+/// quarter, month, week, day, hour, minute or second.
+pub struct DateTruncFunction;
+
+impl Function for DateTruncFunction {
+    fn name(&self) -> &str {
+        "date_trunc"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        use PhysicalDataTypeKind as P;
+        check_arg_count(self.name(), input_types.len(), 2, 2)?;
+        match input_types {
+            [P::String, source @ (P::Date | P::Timestamp | P::TimestampTz)] => Ok(source.clone()),
+            _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+        }
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 2, 2)?;
+        let array: ArrayImpl = match input.array_at(1) {
+            ArrayImpl::Date(_) => {
+                DateArray::from_iter(map_field_and_value::<DateArray, _>(input, |field, v| {
+                    v.truncate(field).ok_or_else(|| unsupported_field(field))
+                })?)
+                .into()
+            }
+            ArrayImpl::Timestamp(_) => {
+                TimestampArray::from_iter(map_field_and_value::<TimestampArray, _>(
+                    input,
+                    |field, v| v.truncate(field).ok_or_else(|| unsupported_field(field)),
+                )?)
+                .into()
+            }
+            _ => TimestampTzArray::from_iter(map_field_and_value::<TimestampTzArray, _>(
+                input,
+                |field, v| v.truncate(field).ok_or_else(|| unsupported_field(field)),
+            )?)
+            .into(),
+        };
+        Ok(single_column(array))
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use super::*;
+use crate::function::FunctionError::{InvalidDataTypes, InvalidParameters};
+
+/// Convert a numeric argument to a float array.
+fn float_arg(input: &DataChunk, idx: usize) -> Result<F64Array, FunctionError> {
+    Ok(match input.array_at(idx) {
+        ArrayImpl::Int32(a) => a.iter().map(|v| v.map(|v| *v as f64)).collect(),
+        ArrayImpl::Int64(a) => a.iter().map(|v| v.map(|v| *v as f64)).collect(),
+        ArrayImpl::Float64(a) => (**a).clone(),
+        ArrayImpl::Decimal(a) => a.iter().map(|v| v.and_then(|v| v.to_f64())).collect(),
+        _ => {
+            return Err(InvalidParameters(format!(
+                "argument {} should be a number",
+                idx
+            )))
+        }
+    })
+}
+
+fn is_numeric(kind: &PhysicalDataTypeKind) -> bool {
+    use PhysicalDataTypeKind::*;
+    matches!(kind, Int32 | Int64 | Float64 | Decimal)
+}
+
+/// Define a function that maps a number to a value of the same type.
+///
+/// Integers are returned unchanged.
+macro_rules! rounding_function {
+    ($Func:ident, $name:literal, $float:expr, $decimal:expr) => {
+        pub struct $Func;
+
+        impl Function for $Func {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn return_types(
+                &self,
+                input_types: &[PhysicalDataTypeKind],
+            ) -> Result<PhysicalDataTypeKind, FunctionError> {
+                check_arg_count(self.name(), input_types.len(), 1, 1)?;
+                match &input_types[0] {
+                    kind if is_numeric(kind) => Ok(kind.clone()),
+                    _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+                }
+            }
+
+            fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+                check_arg_count(self.name(), input.column_count(), 1, 1)?;
+                let float: fn(f64) -> f64 = $float;
+                let decimal: fn(Decimal) -> Decimal = $decimal;
+                let result = match input.array_at(0) {
+                    a @ (ArrayImpl::Int32(_) | ArrayImpl::Int64(_)) => a.clone(),
+                    ArrayImpl::Float64(a) => {
+                        F64Array::from_iter(a.iter().map(|v| v.map(|v| float(*v)))).into()
+                    }
+                    ArrayImpl::Decimal(a) => {
+                        DecimalArray::from_iter(a.iter().map(|v| v.map(|v| decimal(*v)))).into()
+                    }
+                    _ => return Err(InvalidDataTypes("Data type is not supported".to_string())),
+                };
+                Ok(single_column(result))
+            }
+        }
+    };
+}
+
+rounding_function!(FloorFunction, "floor", f64::floor, |d| d.floor());
+rounding_function!(CeilFunction, "ceil", f64::ceil, |d| d.ceil());
+
+/// `round(x [, digits])`: round to the nearest integer, or to `digits` decimal places.
+pub struct RoundFunction;
+
+impl Function for RoundFunction {
+    fn name(&self) -> &str {
+        "round"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        use PhysicalDataTypeKind::*;
+        check_arg_count(self.name(), input_types.len(), 1, 2)?;
+        match input_types {
+            [kind] | [kind, Int32 | Int64] if is_numeric(kind) => Ok(kind.clone()),
+            _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+        }
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 1, 2)?;
+        let digits: Vec<Option<i32>> = match input.column_count() {
+            2 => match input.array_at(1) {
+                ArrayImpl::Int32(a) => a.iter().map(|v| v.cloned()).collect(),
+                ArrayImpl::Int64(a) => a.iter().map(|v| v.map(|v| *v as i32)).collect(),
+                _ => return Err(InvalidParameters("digits should be an integer".to_string())),
+            },
+            _ => vec![Some(0); input.cardinality()],
+        };
+        let result = match input.array_at(0) {
+            a @ (ArrayImpl::Int32(_) | ArrayImpl::Int64(_)) => a.clone(),
+            ArrayImpl::Float64(a) => F64Array::from_iter(a.iter().zip(&digits).map(|(v, d)| {
+                let (v, d) = (v?, (*d)?);
+                let scale = 10f64.powi(d);
+                Some((v * scale).round() / scale)
+            }))
+            .into(),
+            ArrayImpl::Decimal(a) => {
+                DecimalArray::from_iter(a.iter().zip(&digits).map(|(v, d)| {
+                    let (v, d) = (v?, (*d)?);
+                    Some(match d {
+                        d if d >= 0 => v.round_dp(d as u32),
+                        d => {
+                            let scale = Decimal::from(10i64.pow((-d).min(18) as u32));
+                            (v / scale).round() * scale
+                        }
+                    })
+                }))
+                .into()
+            }
+            _ => return Err(InvalidDataTypes("Data type is not supported".to_string())),
+        };
+        Ok(single_column(result))
+    }
+}
+
+/// Define a function that maps numbers to a float.
+///
+/// The function returns an error message if the result is not defined for the input.
+macro_rules! float_function {
+    ($Func:ident, $name:literal, $argc:literal, $f:expr) => {
+        pub struct $Func;
+
+        impl Function for $Func {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn return_types(
+                &self,
+                input_types: &[PhysicalDataTypeKind],
+            ) -> Result<PhysicalDataTypeKind, FunctionError> {
+                check_arg_count(self.name(), input_types.len(), $argc, $argc)?;
+                match input_types.iter().all(is_numeric) {
+                    true => Ok(PhysicalDataTypeKind::Float64),
+                    false => Err(InvalidDataTypes("Data type is not supported".to_string())),
+                }
+            }
+
+            fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+                check_arg_count(self.name(), input.column_count(), $argc, $argc)?;
+                let args = (0..$argc)
+                    .map(|i| float_arg(input, i))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let f: fn(&[f64]) -> Result<f64, &'static str> = $f;
+                let mut builder = F64ArrayBuilder::with_capacity(input.cardinality());
+                let mut values = Vec::with_capacity($argc);
+                for row in 0..input.cardinality() {
+                    values.clear();
+                    values.extend(args.iter().filter_map(|a| a.get(row).cloned()));
+                    if values.len() < $argc {
+                        builder.push(None);
+                        continue;
+                    }
+                    let v = f(&values).map_err(|e| InvalidParameters(e.to_string()))?;
+                    builder.push(Some(&v));
+                }
+                Ok(single_column(builder.finish().into()))
+            }
+        }
+    };
+}
+
+float_function!(PowerFunction, "power", 2, |v| Ok(v[0].powf(v[1])));
+float_function!(SqrtFunction, "sqrt", 1, |v| match v[0] {
+    x if x < 0.0 => Err("cannot take square root of a negative number"),
+    x => Ok(x.sqrt()),
+});
+float_function!(LnFunction, "ln", 1, |v| match v[0] {
+    x if x == 0.0 => Err("cannot take logarithm of zero"),
+    x if x < 0.0 => Err("cannot take logarithm of a negative number"),
+    x => Ok(x.ln()),
+});
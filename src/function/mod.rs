@@ -2,9 +2,19 @@ use crate::array::*;
 use crate::types::PhysicalDataTypeKind;
 
 pub mod abs;
+pub mod date;
+pub mod math;
+pub mod null;
+pub mod registry;
+pub mod string;
 
 pub use self::abs::*;
-#[derive(thiserror::Error, Debug, PartialEq)]
+pub use self::date::*;
+pub use self::math::*;
+pub use self::null::*;
+pub use self::registry::*;
+pub use self::string::*;
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
 pub enum FunctionError {
     #[error("invalid parameters {0}")]
     InvalidParameters(String),
@@ -12,7 +22,7 @@ pub enum FunctionError {
     InvalidDataTypes(String),
 }
 // Definition of function.
-pub trait Function {
+pub trait Function: Send + Sync {
     // Each function should have an unique name.
     fn name(&self) -> &str;
     // A function could support mutiple kinds of data types.
@@ -25,3 +35,25 @@ pub trait Function {
     // The execution logic of function.
     fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError>;
 }
+
+/// Check that the number of arguments is within `min..=max`.
+fn check_arg_count(name: &str, len: usize, min: usize, max: usize) -> Result<(), FunctionError> {
+    if len < min || len > max {
+        return Err(FunctionError::InvalidParameters(format!(
+            "{} expects {} arguments, but got {}",
+            name,
+            if min == max {
+                min.to_string()
+            } else {
+                format!("{} to {}", min, max)
+            },
+            len
+        )));
+    }
+    Ok(())
+}
+
+/// Wrap the result array of a function into a chunk.
+fn single_column(array: ArrayImpl) -> DataChunk {
+    [array].into_iter().collect()
+}
@@ -0,0 +1,41 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use super::*;
+use crate::function::FunctionError::InvalidDataTypes;
+use crate::types::DataValue;
+
+/// `nullif(a, b)`: returns NULL if `a` equals `b`, otherwise returns `a`.
+pub struct NullIfFunction;
+
+impl Function for NullIfFunction {
+    fn name(&self) -> &str {
+        "nullif"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        check_arg_count(self.name(), input_types.len(), 2, 2)?;
+        if input_types[0] != input_types[1] {
+            return Err(InvalidDataTypes(format!(
+                "nullif between {:?} and {:?} is not supported",
+                input_types[0], input_types[1]
+            )));
+        }
+        Ok(input_types[0].clone())
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 2, 2)?;
+        let (a, b) = (input.array_at(0), input.array_at(1));
+        let mut builder = ArrayBuilderImpl::from_type_of_array(a);
+        for row in 0..input.cardinality() {
+            match (a.get(row), b.get(row)) {
+                (v, w) if v != DataValue::Null && v == w => builder.push(&DataValue::Null),
+                (v, _) => builder.push(&v),
+            }
+        }
+        Ok(single_column(builder.finish()))
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::*;
+
+/// A collection of scalar functions, keyed by their names.
+///
+/// The binder resolves function calls by looking up this registry. The default registry
+/// contains all built-in functions.
+pub struct FunctionRegistry {
+    functions: HashMap<String, Arc<dyn Function>>,
+}
+
+impl FunctionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        FunctionRegistry {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Register a function. A function registered earlier with the same name is replaced.
+    pub fn register(&mut self, func: Arc<dyn Function>) {
+        self.functions.insert(func.name().to_lowercase(), func);
+    }
+
+    /// Get a function by its name.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Function>> {
+        self.functions.get(&name.to_lowercase()).cloned()
+    }
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        let builtins: [Arc<dyn Function>; 16] = [
+            Arc::new(AbsFunction {}),
+            // string
+            Arc::new(SubstringFunction),
+            Arc::new(UpperFunction),
+            Arc::new(LowerFunction),
+            Arc::new(LengthFunction),
+            Arc::new(ConcatFunction),
+            Arc::new(TrimFunction),
+            // math
+            Arc::new(RoundFunction),
+            Arc::new(FloorFunction),
+            Arc::new(CeilFunction),
+            Arc::new(PowerFunction),
+            Arc::new(SqrtFunction),
+            Arc::new(LnFunction),
+            // date
+            Arc::new(DatePartFunction),
+            Arc::new(DateTruncFunction),
+            // null handling
+            Arc::new(NullIfFunction),
+        ];
+        for func in builtins {
+            registry.register(func);
+        }
+        registry
+    }
+}
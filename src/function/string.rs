@@ -0,0 +1,210 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt::Write;
+
+use super::*;
+use crate::function::FunctionError::{InvalidDataTypes, InvalidParameters};
+use crate::types::DataValue;
+
+/// Get the argument at `idx` as a string array.
+fn utf8_arg(input: &DataChunk, idx: usize) -> Result<&Utf8Array, FunctionError> {
+    match input.array_at(idx) {
+        ArrayImpl::Utf8(a) => Ok(a),
+        _ => Err(InvalidParameters(format!(
+            "argument {} should be a string",
+            idx
+        ))),
+    }
+}
+
+/// Get the argument at `idx` as an integer array.
+fn int_arg(input: &DataChunk, idx: usize) -> Result<I64Array, FunctionError> {
+    match input.array_at(idx) {
+        ArrayImpl::Int32(a) => Ok(a.iter().map(|v| v.map(|v| *v as i64)).collect()),
+        ArrayImpl::Int64(a) => Ok((**a).clone()),
+        _ => Err(InvalidParameters(format!(
+            "argument {} should be an integer",
+            idx
+        ))),
+    }
+}
+
+/// Define a function that maps a string to a string.
+macro_rules! string_to_string_function {
+    ($Func:ident, $name:literal, $f:expr) => {
+        pub struct $Func;
+
+        impl Function for $Func {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn return_types(
+                &self,
+                input_types: &[PhysicalDataTypeKind],
+            ) -> Result<PhysicalDataTypeKind, FunctionError> {
+                check_arg_count(self.name(), input_types.len(), 1, 1)?;
+                match &input_types[0] {
+                    PhysicalDataTypeKind::String => Ok(PhysicalDataTypeKind::String),
+                    _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+                }
+            }
+
+            fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+                check_arg_count(self.name(), input.column_count(), 1, 1)?;
+                let array = utf8_arg(input, 0)?;
+                let f: fn(&str) -> String = $f;
+                let result: Utf8Array = array.iter().map(|s| s.map(f)).collect();
+                Ok(single_column(result.into()))
+            }
+        }
+    };
+}
+
+string_to_string_function!(UpperFunction, "upper", |s| s.to_uppercase());
+string_to_string_function!(LowerFunction, "lower", |s| s.to_lowercase());
+string_to_string_function!(TrimFunction, "trim", |s| s.trim().to_string());
+
+/// `length(string)`: the number of characters in the string.
+pub struct LengthFunction;
+
+impl Function for LengthFunction {
+    fn name(&self) -> &str {
+        "length"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        check_arg_count(self.name(), input_types.len(), 1, 1)?;
+        match &input_types[0] {
+            PhysicalDataTypeKind::String => Ok(PhysicalDataTypeKind::Int32),
+            _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+        }
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 1, 1)?;
+        let array = utf8_arg(input, 0)?;
+        let result: I32Array = array
+            .iter()
+            .map(|s| s.map(|s| s.chars().count() as i32))
+            .collect();
+        Ok(single_column(result.into()))
+    }
+}
+
+/// `substring(string, start [, count])`: extract the substring starting at the 1-based
+/// character position `start`, with at most `count` characters.
+pub struct SubstringFunction;
+
+impl Function for SubstringFunction {
+    fn name(&self) -> &str {
+        "substring"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        use PhysicalDataTypeKind::*;
+        check_arg_count(self.name(), input_types.len(), 2, 3)?;
+        match input_types {
+            [String, Int32 | Int64] | [String, Int32 | Int64, Int32 | Int64] => Ok(String),
+            _ => Err(InvalidDataTypes("Data type is not supported".to_string())),
+        }
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 2, 3)?;
+        let array = utf8_arg(input, 0)?;
+        let start = int_arg(input, 1)?;
+        let count = match input.column_count() {
+            3 => Some(int_arg(input, 2)?),
+            _ => None,
+        };
+        let mut builder = Utf8ArrayBuilder::with_capacity(array.len());
+        for i in 0..array.len() {
+            let count = match &count {
+                Some(count) => match count.get(i) {
+                    Some(&c) if c < 0 => {
+                        return Err(InvalidParameters(
+                            "negative substring length not allowed".to_string(),
+                        ))
+                    }
+                    Some(&c) => Some(c),
+                    None => {
+                        builder.push(None);
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            match (array.get(i), start.get(i)) {
+                (Some(s), Some(&start)) => builder.push(Some(&substring(s, start, count))),
+                _ => builder.push(None),
+            }
+        }
+        Ok(single_column(builder.finish().into()))
+    }
+}
+
+fn substring(s: &str, start: i64, count: Option<i64>) -> String {
+    // Take the characters in positions `[start, start + count)`, where positions start from 1.
+    let skip = start.max(1);
+    let take = match count {
+        Some(count) => (start.saturating_add(count) - skip).max(0) as usize,
+        None => usize::MAX,
+    };
+    s.chars().skip(skip as usize - 1).take(take).collect()
+}
+
+/// `concat(value, ...)`: concatenate the text representations of all non-null arguments.
+pub struct ConcatFunction;
+
+impl Function for ConcatFunction {
+    fn name(&self) -> &str {
+        "concat"
+    }
+
+    fn return_types(
+        &self,
+        input_types: &[PhysicalDataTypeKind],
+    ) -> Result<PhysicalDataTypeKind, FunctionError> {
+        check_arg_count(self.name(), input_types.len(), 1, usize::MAX)?;
+        Ok(PhysicalDataTypeKind::String)
+    }
+
+    fn execute(&self, input: &DataChunk) -> Result<DataChunk, FunctionError> {
+        check_arg_count(self.name(), input.column_count(), 1, usize::MAX)?;
+        let mut builder = Utf8ArrayBuilder::with_capacity(input.cardinality());
+        let mut s = String::new();
+        for i in 0..input.cardinality() {
+            s.clear();
+            for array in input.arrays() {
+                match array.get(i) {
+                    DataValue::Null => {}
+                    v => write!(s, "{}", v).unwrap(),
+                }
+            }
+            builder.push(Some(&s));
+        }
+        Ok(single_column(builder.finish().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substring() {
+        assert_eq!(substring("Thomas", 2, Some(3)), "hom");
+        assert_eq!(substring("Thomas", 3, None), "omas");
+        assert_eq!(substring("Thomas", 0, Some(3)), "Th");
+        assert_eq!(substring("Thomas", -2, Some(2)), "");
+        assert_eq!(substring("Thomas", 5, Some(10)), "as");
+        assert_eq!(substring("Thomas", 10, None), "");
+    }
+}
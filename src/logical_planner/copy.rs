@@ -30,6 +30,7 @@ impl LogicalPlaner {
                     false,
                     false,
                     None,
+                    None,
                 )),
             )))
         } else {
@@ -13,6 +13,7 @@ mod drop;
 mod explain;
 mod insert;
 mod select;
+mod update;
 
 pub use copy::*;
 pub use create::*;
@@ -20,6 +21,7 @@ pub use delete::*;
 pub use drop::*;
 pub use explain::*;
 pub use insert::*;
+pub use update::*;
 
 /// The error type of logical planner.
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -47,6 +49,7 @@ impl LogicalPlaner {
             Select(stmt) => self.plan_select(stmt),
             Explain(stmt) => self.plan_explain(*stmt),
             Delete(stmt) => self.plan_delete(*stmt),
+            Update(stmt) => self.plan_update(*stmt),
         }
     }
 }
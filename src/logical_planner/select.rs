@@ -7,16 +7,20 @@
 //! - [`LogicalTableScan`] (from *) or dummy plan (no from)
 //! - [`LogicalFilter`] (where *)
 //! - [`LogicalProjection`] (select *)
+//! - [`LogicalWindow`] (window functions in select *)
 //! - [`LogicalOrder`] (order by *)
+use std::cell::Cell;
+
 use itertools::Itertools;
 
 use super::*;
 use crate::binder::{
-    BoundAggCall, BoundExpr, BoundInputRef, BoundOrderBy, BoundSelect, BoundTableRef, ExprVisitor,
+    BoundAggCall, BoundExpr, BoundInputRef, BoundOrderBy, BoundSelect, BoundTableRef,
+    BoundWindowCall, ExprRewriter, ExprVisitor,
 };
 use crate::optimizer::plan_nodes::{
     Internal, LogicalAggregate, LogicalFilter, LogicalJoin, LogicalLimit, LogicalOrder,
-    LogicalProjection, LogicalTableScan, LogicalValues,
+    LogicalProjection, LogicalTableScan, LogicalValues, LogicalWindow,
 };
 
 impl LogicalPlaner {
@@ -42,7 +46,9 @@ impl LogicalPlaner {
                 if let BoundTableRef::BaseTableRef { column_ids, .. } = &**relation {
                     if join_tables.is_empty() && column_ids.is_empty() {
                         stmt.select_list.iter().for_each(|expr| {
-                            if expr.contains_row_count() && !expr.contains_column_ref() {
+                            if (expr.contains_row_count() || expr.contains_window_call())
+                                && !expr.contains_column_ref()
+                            {
                                 with_row_handler = true;
                             }
                         });
@@ -65,6 +71,15 @@ impl LogicalPlaner {
             plan = Arc::new(LogicalFilter::new(expr, plan));
         }
 
+        // the sort keys not in the select list are appended to it
+        let num_columns = stmt.select_list.len();
+        let mut sort_key_extractor = SortKeyExtractor::new(&mut stmt.select_list);
+        let comparators = stmt
+            .orderby
+            .into_iter()
+            .map(|expr| sort_key_extractor.visit_expr(expr))
+            .collect_vec();
+
         let mut agg_extractor = AggExtractor::new();
         for expr in &mut stmt.select_list {
             agg_extractor.visit_select_expr(expr);
@@ -73,7 +88,7 @@ impl LogicalPlaner {
             agg_extractor.visit_group_by_expr(expr, &stmt.select_list);
         }
         if !stmt.group_by.is_empty() {
-            agg_extractor.validate_illegal_column(&stmt.select_list, &stmt.orderby)?;
+            agg_extractor.validate_illegal_column(&stmt.select_list, &comparators)?;
         }
         if !agg_extractor.agg_calls.is_empty() || !agg_extractor.group_by_exprs.is_empty() {
             plan = Arc::new(LogicalAggregate::new(
@@ -83,16 +98,28 @@ impl LogicalPlaner {
             ));
         }
 
-        let mut alias_extractor = AliasExtractor::new(&stmt.select_list);
-        let comparators = stmt
-            .orderby
-            .into_iter()
-            .map(|expr| alias_extractor.visit_expr(expr))
-            .collect_vec();
+        if stmt
+            .select_list
+            .iter()
+            .any(|expr| expr.contains_window_call())
+        {
+            let mut window_extractor = WindowExtractor::default();
+            for expr in &mut stmt.select_list {
+                window_extractor.visit_select_expr(expr);
+            }
+            window_extractor.resolve_window_calls(&mut stmt.select_list, &plan);
+            plan = Arc::new(LogicalProjection::new(window_extractor.inputs, plan));
+            plan = Arc::new(LogicalWindow::new(window_extractor.window_calls, plan));
+        }
 
         // TODO: support the following clauses
         assert!(!stmt.select_distinct, "TODO: plan distinct");
 
+        let has_hidden_columns = stmt.select_list.len() > num_columns;
+        let output_types = stmt.select_list[..num_columns]
+            .iter()
+            .map(|expr| expr.return_type().unwrap())
+            .collect_vec();
         if !stmt.select_list.is_empty() {
             plan = Arc::new(LogicalProjection::new(stmt.select_list, plan));
         }
@@ -116,6 +143,15 @@ impl LogicalPlaner {
             };
             plan = Arc::new(LogicalLimit::new(offset, limit, plan));
         }
+        // project out the sort keys appended to the select list
+        if has_hidden_columns {
+            let exprs = (output_types.into_iter().enumerate())
+                .map(|(index, return_type)| {
+                    BoundExpr::InputRef(BoundInputRef { index, return_type })
+                })
+                .collect();
+            plan = Arc::new(LogicalProjection::new(exprs, plan));
+        }
         Ok(plan)
     }
 
@@ -132,6 +168,7 @@ impl LogicalPlaner {
                 column_ids,
                 column_descs,
                 is_internal,
+                as_of_epoch,
             } => {
                 if *is_internal {
                     Ok(Arc::new(Internal::new(
@@ -148,6 +185,7 @@ impl LogicalPlaner {
                         with_row_handler,
                         is_sorted,
                         None,
+                        *as_of_epoch,
                     )))
                 }
             }
@@ -207,6 +245,33 @@ impl AggExtractor {
                 self.validate_illegal_column_inner(&e.expr)?;
             }
             IsNull(isnull) => self.validate_illegal_column_inner(&isnull.expr)?,
+            FunctionCall(call) => {
+                for arg in &call.args {
+                    self.validate_illegal_column_inner(arg)?;
+                }
+            }
+            Case(case) => {
+                for expr in case.conditions.iter().chain(&case.results) {
+                    self.validate_illegal_column_inner(expr)?;
+                }
+                if let Some(else_result) = &case.else_result {
+                    self.validate_illegal_column_inner(else_result)?;
+                }
+            }
+            InList(in_list) => {
+                self.validate_illegal_column_inner(&in_list.expr)?;
+                for item in &in_list.list {
+                    self.validate_illegal_column_inner(item)?;
+                }
+            }
+            WindowCall(call) => {
+                for expr in call.args.iter().chain(&call.partition_by) {
+                    self.validate_illegal_column_inner(expr)?;
+                }
+                for cmp in &call.order_by {
+                    self.validate_illegal_column_inner(&cmp.expr)?;
+                }
+            }
             AggCall(_) | Constant(_) | InputRef(_) | Alias(_) => {}
             ColumnRef(_) => {
                 return Err(LogicalPlanError::IllegalGroupBySQL(format!(r#"{}"#, expr)));
@@ -263,31 +328,143 @@ impl AggExtractor {
     }
 }
 
-/// And expression visitor that extracts aliases in order-by expressions and replaces them with
-/// `InputRef`.
+/// Extracts window function calls from the select list.
 ///
 /// For example,
-/// In SQL: `select a, b as c from t order by c;`
-/// The expression `c` in the order-by clause will be rewritten to `InputRef(1)`, because the
-/// underlying projection plan will output `(a, b)`, where `b` is alias to `c`.
+/// In SQL: `select v1, sum(v2) over (partition by v1) + 1 from t;`
+/// The inputs of window functions and the other select expressions are projected below the
+/// [`LogicalWindow`] as `(v1, v2, v1)`. The window call is rewritten to
+/// `sum(InputRef(1)) over (partition by InputRef(2))`, and the select list is rewritten to
+/// `(InputRef(0), InputRef(3) + 1)` over the output of the window.
+///
+/// The inputs are not deduplicated, so that aggregations in them are in the same order as the
+/// ones extracted by [`AggExtractor`].
 #[derive(Default)]
-struct AliasExtractor<'a> {
-    select_list: &'a [BoundExpr],
+struct WindowExtractor {
+    /// The expressions projected below the window.
+    inputs: Vec<BoundExpr>,
+    window_calls: Vec<BoundWindowCall>,
 }
 
-impl<'a> AliasExtractor<'a> {
-    fn new(select_list: &'a [BoundExpr]) -> Self {
-        AliasExtractor { select_list }
+impl WindowExtractor {
+    fn push_input(&mut self, expr: &mut BoundExpr) {
+        let input_ref = BoundExpr::InputRef(BoundInputRef {
+            index: self.inputs.len(),
+            return_type: expr.return_type().unwrap(),
+        });
+        self.inputs.push(std::mem::replace(expr, input_ref));
+    }
+
+    /// Rewrites the expressions without window calls into `InputRef`s to the inputs, and
+    /// collects the window calls.
+    fn visit_select_expr(&mut self, expr: &mut BoundExpr) {
+        use BoundExpr::*;
+        if !expr.contains_window_call() {
+            match expr {
+                Constant(_) => {}
+                // keep the alias in the select list
+                ExprWithAlias(e) => self.visit_select_expr(&mut e.expr),
+                _ => self.push_input(expr),
+            }
+            return;
+        }
+        match expr {
+            WindowCall(call) => {
+                for expr in call.args.iter_mut().chain(&mut call.partition_by) {
+                    self.push_input(expr);
+                }
+                for cmp in &mut call.order_by {
+                    self.push_input(&mut cmp.expr);
+                }
+                self.window_calls.push(call.clone());
+            }
+            BinaryOp(bin_op) => {
+                self.visit_select_expr(&mut bin_op.left_expr);
+                self.visit_select_expr(&mut bin_op.right_expr);
+            }
+            UnaryOp(unary_op) => self.visit_select_expr(&mut unary_op.expr),
+            TypeCast(type_cast) => self.visit_select_expr(&mut type_cast.expr),
+            ExprWithAlias(e) => self.visit_select_expr(&mut e.expr),
+            IsNull(isnull) => self.visit_select_expr(&mut isnull.expr),
+            FunctionCall(call) => {
+                for arg in &mut call.args {
+                    self.visit_select_expr(arg);
+                }
+            }
+            Case(case) => {
+                for (cond, result) in case.conditions.iter_mut().zip(&mut case.results) {
+                    self.visit_select_expr(cond);
+                    self.visit_select_expr(result);
+                }
+                if let Some(else_result) = &mut case.else_result {
+                    self.visit_select_expr(else_result);
+                }
+            }
+            InList(in_list) => {
+                self.visit_select_expr(&mut in_list.expr);
+                for item in &mut in_list.list {
+                    self.visit_select_expr(item);
+                }
+            }
+            // the binder rejects window calls in aggregations
+            AggCall(_) | Constant(_) | ColumnRef(_) | InputRef(_) | Alias(_) => unreachable!(),
+        }
+    }
+
+    /// Rewrites the window calls in the select list into `InputRef`s to their results, which
+    /// follow the inputs in the output of the window.
+    fn resolve_window_calls(&mut self, select_list: &mut [BoundExpr], child: &PlanRef) {
+        struct Rewriter(Cell<usize>);
+        impl ExprRewriter for Rewriter {
+            fn rewrite_window_call(&self, expr: &mut BoundExpr) {
+                *expr = BoundExpr::InputRef(BoundInputRef {
+                    index: self.0.get(),
+                    return_type: expr.return_type().unwrap(),
+                });
+                self.0.set(self.0.get() + 1);
+            }
+        }
+        if self.inputs.is_empty() {
+            // like `count(*)`, keep the first column of the child to retain the number of rows,
+            // which is the row handler if no column is scanned
+            let return_type = (child.out_types().into_iter().next())
+                .expect("the child of a window outputs at least one column");
+            self.inputs.push(BoundExpr::InputRef(BoundInputRef {
+                index: 0,
+                return_type,
+            }));
+        }
+        let rewriter = Rewriter(Cell::new(self.inputs.len()));
+        for expr in select_list {
+            rewriter.rewrite_expr(expr);
+        }
+    }
+}
+
+/// Resolves order-by expressions to `InputRef`s into the select list.
+///
+/// For example,
+/// In SQL: `select a, b as c from t order by c, a + 1;`
+/// The expression `c` in the order-by clause will be rewritten to `InputRef(1)`, because the
+/// underlying projection plan will output `(a, b)`, where `b` is alias to `c`. The expression
+/// `a + 1` is not in the select list, so it is appended to the select list as `InputRef(2)`, and
+/// projected out after sorting.
+struct SortKeyExtractor<'a> {
+    select_list: &'a mut Vec<BoundExpr>,
+}
+
+impl<'a> SortKeyExtractor<'a> {
+    fn new(select_list: &'a mut Vec<BoundExpr>) -> Self {
+        SortKeyExtractor { select_list }
     }
 
     fn visit_expr(&mut self, expr: BoundOrderBy) -> BoundOrderBy {
-        use BoundExpr::{Alias, ColumnRef, ExprWithAlias, InputRef};
-        match expr.expr {
+        use BoundExpr::{Alias, ExprWithAlias, InputRef};
+        let index = match &expr.expr {
             Alias(alias) => {
                 // Binder has pushed the alias expression to `select_list`, so we can unwrap
                 // directly
-                let index = self
-                    .select_list
+                self.select_list
                     .iter()
                     .position(|inner_expr| {
                         if let ExprWithAlias(e) = inner_expr {
@@ -296,19 +473,25 @@ impl<'a> AliasExtractor<'a> {
                             false
                         }
                     })
-                    .unwrap();
-                let select_item = &self.select_list[index];
-                let input_ref = InputRef(BoundInputRef {
-                    index,
-                    return_type: select_item.return_type().unwrap(),
-                });
-                BoundOrderBy {
-                    expr: input_ref,
-                    descending: expr.descending,
-                }
+                    .unwrap()
             }
-            ColumnRef(_) => expr,
-            _ => panic!("order-by expression should be column ref or expr alias"),
+            e => match self.select_list.iter().position(|inner_expr| {
+                inner_expr == e || matches!(inner_expr, ExprWithAlias(a) if *a.expr == *e)
+            }) {
+                Some(index) => index,
+                None => {
+                    self.select_list.push(expr.expr.clone());
+                    self.select_list.len() - 1
+                }
+            },
+        };
+        let select_item = &self.select_list[index];
+        BoundOrderBy {
+            expr: InputRef(BoundInputRef {
+                index,
+                return_type: select_item.return_type().unwrap(),
+            }),
+            ..expr
         }
     }
 }
@@ -374,7 +557,10 @@ mod tests {
         let count_wildcard = BoundExpr::AggCall(BoundAggCall {
             kind: AggKind::Count,
             args: vec![],
+            params: vec![],
+            filter: None,
             return_type: DataTypeKind::Int(None).not_null(),
+            distinct: false,
         });
         let v2_puls_2_plus_count = BoundExpr::BinaryOp(BoundBinaryOp {
             op: BinaryOperator::Plus,
@@ -396,6 +582,7 @@ mod tests {
         let order_by_v1 = BoundOrderBy {
             expr: v1,
             descending: false,
+            nulls_first: true,
         };
         assert!(
             validate_illegal_column(&mut [v2_plus_count_wildcard], &mut [v2], &[order_by_v1])
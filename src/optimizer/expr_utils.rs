@@ -57,7 +57,12 @@ pub fn input_col_refs_inner(expr: &BoundExpr, input_set: &mut BitSet) {
             input_set.insert(input_ref.index);
         }
         AggCall(agg) => {
-            for arg in &agg.args {
+            for arg in agg.args.iter().chain(agg.filter.as_deref()) {
+                input_col_refs_inner(arg, input_set);
+            }
+        }
+        FunctionCall(call) => {
+            for arg in &call.args {
                 input_col_refs_inner(arg, input_set);
             }
         }
@@ -68,7 +73,29 @@ pub fn input_col_refs_inner(expr: &BoundExpr, input_set: &mut BitSet) {
         UnaryOp(unary_op) => input_col_refs_inner(unary_op.expr.as_ref(), input_set),
         TypeCast(cast) => input_col_refs_inner(cast.expr.as_ref(), input_set),
         IsNull(isnull) => input_col_refs_inner(isnull.expr.as_ref(), input_set),
+        Case(case) => {
+            for expr in case.conditions.iter().chain(&case.results) {
+                input_col_refs_inner(expr, input_set);
+            }
+            if let Some(else_result) = &case.else_result {
+                input_col_refs_inner(else_result.as_ref(), input_set);
+            }
+        }
+        InList(in_list) => {
+            input_col_refs_inner(in_list.expr.as_ref(), input_set);
+            for item in &in_list.list {
+                input_col_refs_inner(item, input_set);
+            }
+        }
         ExprWithAlias(inner) => input_col_refs_inner(inner.expr.as_ref(), input_set),
+        WindowCall(call) => {
+            for expr in call.args.iter().chain(&call.partition_by) {
+                input_col_refs_inner(expr, input_set);
+            }
+            for cmp in &call.order_by {
+                input_col_refs_inner(&cmp.expr, input_set);
+            }
+        }
         Constant(_) => {}
         Alias(_) => {}
     };
@@ -82,8 +109,13 @@ pub fn shift_input_col_refs(expr: &mut BoundExpr, delta: i32) {
             input_ref.index = (input_ref.index as i32 + delta) as usize;
         }
         AggCall(agg) => {
-            for arg in &mut agg.args {
-                shift_input_col_refs(&mut *arg, delta);
+            for arg in agg.args.iter_mut().chain(agg.filter.as_deref_mut()) {
+                shift_input_col_refs(arg, delta);
+            }
+        }
+        FunctionCall(call) => {
+            for arg in &mut call.args {
+                shift_input_col_refs(arg, delta);
             }
         }
         BinaryOp(binary_op) => {
@@ -93,7 +125,29 @@ pub fn shift_input_col_refs(expr: &mut BoundExpr, delta: i32) {
         UnaryOp(unary_op) => shift_input_col_refs(&mut *unary_op.expr, delta),
         TypeCast(cast) => shift_input_col_refs(&mut *cast.expr, delta),
         IsNull(isnull) => shift_input_col_refs(&mut *isnull.expr, delta),
+        Case(case) => {
+            for expr in case.conditions.iter_mut().chain(&mut case.results) {
+                shift_input_col_refs(expr, delta);
+            }
+            if let Some(else_result) = &mut case.else_result {
+                shift_input_col_refs(&mut *else_result, delta);
+            }
+        }
+        InList(in_list) => {
+            shift_input_col_refs(&mut *in_list.expr, delta);
+            for item in &mut in_list.list {
+                shift_input_col_refs(item, delta);
+            }
+        }
         ExprWithAlias(inner) => shift_input_col_refs(&mut *inner.expr, delta),
+        WindowCall(call) => {
+            for expr in call.args.iter_mut().chain(&mut call.partition_by) {
+                shift_input_col_refs(expr, delta);
+            }
+            for cmp in &mut call.order_by {
+                shift_input_col_refs(&mut cmp.expr, delta);
+            }
+        }
         Constant(_) => {}
         Alias(_) => {}
     };
@@ -3,8 +3,9 @@
 use BoundExpr::*;
 
 use super::*;
-use crate::array::ArrayImpl;
+use crate::array::{ArrayImpl, DataChunk};
 use crate::binder::BoundExpr;
+use crate::types::DataValue;
 
 /// Constant folding rule aims to evalute the constant expression before query execution.
 ///
@@ -49,7 +50,11 @@ impl ExprRewriter for ConstantFoldingRule {
         match expr {
             TypeCast(cast) => {
                 self.rewrite_expr(&mut *cast.expr);
+                // `NULL` is kept as a cast to preserve its type.
                 if let Constant(v) = &*cast.expr {
+                    if *v == DataValue::Null {
+                        return;
+                    }
                     if let Ok(array) = ArrayImpl::from(v).try_cast(cast.ty.clone()) {
                         let res = array.get(0);
                         *expr = Constant(res);
@@ -61,6 +66,110 @@ impl ExprRewriter for ConstantFoldingRule {
             _ => unreachable!(),
         }
     }
+
+    fn rewrite_function_call(&self, expr: &mut BoundExpr) {
+        match expr {
+            FunctionCall(call) => {
+                for arg in &mut call.args {
+                    self.rewrite_expr(arg);
+                }
+                let all_constant = call
+                    .args
+                    .iter()
+                    .all(|e| matches!(e, Constant(v) if *v != DataValue::Null));
+                if !all_constant || call.args.is_empty() {
+                    return;
+                }
+                let input: DataChunk = call
+                    .args
+                    .iter()
+                    .map(|e| match e {
+                        Constant(v) => ArrayImpl::from(v),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                // Errors are left to be reported at execution time.
+                if let Ok(output) = call.func.execute(&input) {
+                    let res = output.array_at(0).get(0);
+                    if res != DataValue::Null {
+                        *expr = Constant(res);
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn rewrite_case(&self, expr: &mut BoundExpr) {
+        match expr {
+            Case(case) => {
+                for (cond, result) in case.conditions.iter_mut().zip(&mut case.results) {
+                    self.rewrite_expr(cond);
+                    self.rewrite_expr(result);
+                }
+                if let Some(else_result) = &mut case.else_result {
+                    self.rewrite_expr(else_result);
+                }
+                // Remove branches that can never be taken.
+                let mut branches = vec![];
+                for (cond, result) in case.conditions.drain(..).zip(case.results.drain(..)) {
+                    match cond {
+                        Constant(DataValue::Bool(false) | DataValue::Null) => {}
+                        _ => branches.push((cond, result)),
+                    }
+                }
+                (case.conditions, case.results) = branches.into_iter().unzip();
+                // The whole expression is determined if the first remaining branch is always
+                // taken, or if no branch remains.
+                let folded = match case.conditions.first() {
+                    Some(Constant(DataValue::Bool(true))) => Some(case.results[0].clone()),
+                    None => Some(match &case.else_result {
+                        Some(else_result) => (**else_result).clone(),
+                        None => Constant(DataValue::Null),
+                    }),
+                    _ => None,
+                };
+                if let Some(folded) = folded {
+                    // Keep the `CASE` if folding would lose its return type, e.g. a `NULL` result.
+                    if folded.return_type().map(|t| t.kind())
+                        == case.return_type.as_ref().map(|t| t.kind())
+                    {
+                        *expr = folded;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn rewrite_in_list(&self, expr: &mut BoundExpr) {
+        match expr {
+            InList(in_list) => {
+                self.rewrite_expr(&mut *in_list.expr);
+                for item in &mut in_list.list {
+                    self.rewrite_expr(item);
+                }
+                let all_constant = std::iter::once(&*in_list.expr)
+                    .chain(&in_list.list)
+                    .all(|e| matches!(e, Constant(v) if *v != DataValue::Null));
+                if all_constant {
+                    if let Constant(v) = &*in_list.expr {
+                        let list = in_list
+                            .list
+                            .iter()
+                            .map(|e| match e {
+                                Constant(v) => ArrayImpl::from(v),
+                                _ => unreachable!(),
+                            })
+                            .collect::<Vec<_>>();
+                        let res = ArrayImpl::from(v).in_list(&list, in_list.negated).get(0);
+                        *expr = Constant(res);
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl PlanRewriter for ConstantFoldingRule {
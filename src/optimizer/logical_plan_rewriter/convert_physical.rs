@@ -2,8 +2,9 @@
 
 use super::super::plan_nodes::*;
 use super::*;
-use crate::binder::BoundJoinOperator;
+use crate::binder::{BoundExpr, BoundInputRef, BoundJoinOperator};
 use crate::optimizer::expr_utils::merge_conjunctions;
+use crate::types::{DataValue, PhysicalDataTypeKind};
 /// Convert all logical plan nodes to physical.
 pub struct PhysicalConverter;
 
@@ -23,6 +24,12 @@ impl PlanRewriter for PhysicalConverter {
         Arc::new(PhysicalOrder::new(logical))
     }
 
+    fn rewrite_logical_window(&mut self, logical: &LogicalWindow) -> PlanRef {
+        let child = self.rewrite(logical.child());
+        let logical = logical.clone_with_child(child);
+        Arc::new(PhysicalWindow::new(logical))
+    }
+
     fn rewrite_logical_limit(&mut self, logical: &LogicalLimit) -> PlanRef {
         let child = self.rewrite(logical.child());
         let logical = logical.clone_with_child(child);
@@ -39,42 +46,69 @@ impl PlanRewriter for PhysicalConverter {
         let left = self.rewrite(logical_join.left());
         let right = self.rewrite(logical_join.right());
         let predicate = logical_join.predicate();
-        // FIXME: Currently just Inner join use HashJoin
-        if !predicate.eq_keys().is_empty() && logical_join.join_op() == BoundJoinOperator::Inner {
-            // TODO: Currently hash join just use one column pair as hash index
-            // TODO: Currently HashJoinExecutor ignores the condition, so for correctness we pull
-            // the conditions as a filter operator. And this transformation is only correct for
-            // inner join
-
-            let join = Arc::new(PhysicalHashJoin::new(LogicalJoin::create(
+        if predicate.eq_keys().is_empty() {
+            return Arc::new(PhysicalNestedLoopJoin::new(
+                logical_join.clone_with_left_right(left, right),
+            ));
+        }
+        if let Some(key) = merge_join_key(logical_join) {
+            let mut eq_conds = predicate.eq_conds();
+            let join = Arc::new(PhysicalSortMergeJoin::new(LogicalJoin::create(
                 left,
                 right,
                 BoundJoinOperator::Inner,
-                merge_conjunctions(logical_join.predicate().eq_conds().into_iter()),
+                eq_conds.remove(key),
             )));
-
-            let need_pull_filter = !predicate.left_conds().is_empty()
-                || !predicate.right_conds().is_empty()
-                || !predicate.other_conds().is_empty();
-            if need_pull_filter {
-                return Arc::new(PhysicalFilter::new(LogicalFilter::new(
-                    merge_conjunctions(
-                        predicate
-                            .left_conds()
-                            .iter()
-                            .cloned()
-                            .chain(predicate.right_conds().iter().cloned())
-                            .chain(predicate.other_conds().iter().cloned()),
-                    ),
-                    join,
-                )));
-            } else {
+            // SortMergeJoinExecutor only compares the join key, so for correctness we pull the
+            // other conditions as a filter operator.
+            let condition = merge_conjunctions(
+                eq_conds
+                    .into_iter()
+                    .chain(std::iter::once(predicate.non_eq_cond())),
+            );
+            if condition == BoundExpr::Constant(DataValue::Bool(true)) {
                 return join;
-            };
+            }
+            return Arc::new(PhysicalFilter::new(LogicalFilter::new(condition, join)));
+        }
+        // The hash table is built from the left child, so the smaller side goes left.
+        if logical_join.left().estimated_cardinality()
+            <= logical_join.right().estimated_cardinality()
+        {
+            return Arc::new(PhysicalHashJoin::new(
+                logical_join.clone_with_left_right(left, right),
+            ));
         }
-        Arc::new(PhysicalNestedLoopJoin::new(
-            logical_join.clone_with_left_right(left, right),
-        ))
+        let left_cols_num = left.out_types().len();
+        let right_cols_num = right.out_types().len();
+        let join_op = match logical_join.join_op() {
+            BoundJoinOperator::LeftOuter => BoundJoinOperator::RightOuter,
+            BoundJoinOperator::RightOuter => BoundJoinOperator::LeftOuter,
+            op => op,
+        };
+        let predicate = logical_join.predicate().clone_with_rewrite_expr(
+            right_cols_num,
+            &SwapJoinSides {
+                left_cols_num,
+                right_cols_num,
+            },
+        );
+        let join = Arc::new(PhysicalHashJoin::new(LogicalJoin::new(
+            right, left, join_op, predicate,
+        )));
+        // restore the column order of the join
+        let types = join.out_types();
+        let exprs = (0..left_cols_num)
+            .map(|i| i + right_cols_num)
+            .chain(0..right_cols_num)
+            .map(|index| {
+                BoundExpr::InputRef(BoundInputRef {
+                    index,
+                    return_type: types[index].clone(),
+                })
+            })
+            .collect();
+        Arc::new(PhysicalProjection::new(LogicalProjection::new(exprs, join)))
     }
 
     fn rewrite_logical_insert(&mut self, logical: &LogicalInsert) -> PlanRef {
@@ -109,6 +143,12 @@ impl PlanRewriter for PhysicalConverter {
         Arc::new(PhysicalDelete::new(logical))
     }
 
+    fn rewrite_logical_update(&mut self, logical: &LogicalUpdate) -> PlanRef {
+        let child = self.rewrite(logical.child());
+        let logical = logical.clone_with_child(child);
+        Arc::new(PhysicalUpdate::new(logical))
+    }
+
     fn rewrite_logical_create_table(&mut self, logical: &LogicalCreateTable) -> PlanRef {
         Arc::new(PhysicalCreateTable::new(logical.clone()))
     }
@@ -130,9 +170,117 @@ impl PlanRewriter for PhysicalConverter {
                 self.rewrite(logical.child()),
             ))
         } else {
+            let is_sorted = is_sorted_by_group_keys(logical);
             let child = self.rewrite(logical.child());
             let logical = logical.clone_with_child(child);
-            Arc::new(PhysicalHashAgg::new(logical))
+            if is_sorted {
+                Arc::new(PhysicalSortAgg::new(logical))
+            } else {
+                Arc::new(PhysicalHashAgg::new(logical))
+            }
+        }
+    }
+}
+
+/// The columns that the output of a logical plan is known to be sorted by in ascending order,
+/// from the most significant one.
+fn sort_keys(plan: &PlanRef) -> Vec<usize> {
+    if let Ok(scan) = plan.as_logical_table_scan() {
+        // sorted scans return rows in the order of the primary key
+        if !scan.is_sorted() {
+            return vec![];
+        }
+        return (scan.column_descs().iter())
+            .position(|desc| desc.is_primary())
+            .into_iter()
+            .collect();
+    }
+    if let Ok(order) = plan.as_logical_order() {
+        return (order.comparators().iter())
+            .map_while(|comparator| match &comparator.expr {
+                BoundExpr::InputRef(input_ref)
+                    if !comparator.descending && comparator.nulls_first =>
+                {
+                    Some(input_ref.index)
+                }
+                _ => None,
+            })
+            .collect();
+    }
+    if let Ok(projection) = plan.as_logical_projection() {
+        let exprs = projection.project_expressions();
+        return (sort_keys(&projection.child()).into_iter())
+            .map_while(|key| {
+                exprs.iter().position(
+                    |expr| matches!(expr, BoundExpr::InputRef(input_ref) if input_ref.index == key),
+                )
+            })
+            .collect();
+    }
+    if let Ok(filter) = plan.as_logical_filter() {
+        return sort_keys(&filter.child());
+    }
+    vec![]
+}
+
+/// Find an eq key of an inner join that both children are sorted by. Returns its index in the
+/// eq keys.
+///
+/// Float keys are excluded since the merge join compares them with `partial_cmp`, which has no
+/// order for NaN.
+fn merge_join_key(join: &LogicalJoin) -> Option<usize> {
+    if join.join_op() != BoundJoinOperator::Inner {
+        return None;
+    }
+    let left_cols_num = join.left().out_types().len();
+    let left_key = *sort_keys(&join.left()).first()?;
+    let right_key = *sort_keys(&join.right()).first()?;
+    join.predicate().eq_keys().iter().position(|(left, right)| {
+        left.index == left_key
+            && right.index - left_cols_num == right_key
+            && left.return_type.physical_kind() == right.return_type.physical_kind()
+            && left.return_type.physical_kind() != PhysicalDataTypeKind::Float64
+    })
+}
+
+/// Whether the rows of each group are consecutive in the input of an aggregation, i.e. the input
+/// is sorted by the group keys.
+fn is_sorted_by_group_keys(agg: &LogicalAggregate) -> bool {
+    let group_keys: Option<Vec<usize>> = (agg.group_keys().iter())
+        .map(|key| match key {
+            BoundExpr::InputRef(input_ref) => Some(input_ref.index),
+            _ => None,
+        })
+        .collect();
+    let mut group_keys = match group_keys {
+        Some(keys) => keys,
+        None => return false,
+    };
+    group_keys.sort_unstable();
+    group_keys.dedup();
+    let sort_keys = sort_keys(&agg.child());
+    if group_keys.len() > sort_keys.len() {
+        return false;
+    }
+    let mut prefix = sort_keys[..group_keys.len()].to_vec();
+    prefix.sort_unstable();
+    prefix.dedup();
+    prefix == group_keys
+}
+
+/// Maps the columns of a join to the columns of the join with its children swapped.
+struct SwapJoinSides {
+    left_cols_num: usize,
+    right_cols_num: usize,
+}
+
+impl ExprRewriter for SwapJoinSides {
+    fn rewrite_input_ref(&self, expr: &mut BoundExpr) {
+        if let BoundExpr::InputRef(input_ref) = expr {
+            input_ref.index = match input_ref.index < self.left_cols_num {
+                true => input_ref.index + self.right_cols_num,
+                false => input_ref.index - self.left_cols_num,
+            };
         }
     }
 }
@@ -124,9 +124,21 @@ impl PlanRewriter for InputRefResolver {
         let child = self.rewrite(plan.child());
         Arc::new(plan.clone_with_rewrite_expr(child, self))
     }
+    fn rewrite_logical_window(&mut self, plan: &LogicalWindow) -> PlanRef {
+        let child = self.rewrite(plan.child());
+        let ret = Arc::new(plan.clone_with_rewrite_expr(child, self));
+        // the results of window functions can only be referred by `InputRef`
+        self.bindings
+            .extend(plan.window_calls().iter().map(|_| None));
+        ret
+    }
     fn rewrite_logical_values(&mut self, plan: &LogicalValues) -> PlanRef {
         Arc::new(plan.clone_with_rewrite_expr(self))
     }
+    fn rewrite_logical_update(&mut self, plan: &LogicalUpdate) -> PlanRef {
+        let child = self.rewrite(plan.child());
+        Arc::new(plan.clone_with_rewrite_expr(child, self))
+    }
 }
 
 /// Resolves select expression into `InputRef` using group by expressions
@@ -178,6 +190,33 @@ impl AggInputRefResolver {
                 self.resolve_select_expr(&mut expr_with_alias.expr, group_keys)
             }
             IsNull(isnull) => self.resolve_select_expr(&mut isnull.expr, group_keys),
+            FunctionCall(call) => {
+                for arg in &mut call.args {
+                    self.resolve_select_expr(arg, group_keys);
+                }
+            }
+            Case(case) => {
+                for expr in case.conditions.iter_mut().chain(&mut case.results) {
+                    self.resolve_select_expr(expr, group_keys);
+                }
+                if let Some(else_result) = &mut case.else_result {
+                    self.resolve_select_expr(else_result, group_keys);
+                }
+            }
+            InList(in_list) => {
+                self.resolve_select_expr(&mut in_list.expr, group_keys);
+                for item in &mut in_list.list {
+                    self.resolve_select_expr(item, group_keys);
+                }
+            }
+            WindowCall(call) => {
+                for expr in call.args.iter_mut().chain(&mut call.partition_by) {
+                    self.resolve_select_expr(expr, group_keys);
+                }
+                for cmp in &mut call.order_by {
+                    self.resolve_select_expr(&mut cmp.expr, group_keys);
+                }
+            }
             Constant(_) | ColumnRef(_) | InputRef(_) | Alias(_) => {}
         }
     }
@@ -205,7 +244,10 @@ mod tests {
                 is_primary_key: false,
                 desc: DataTypeKind::Int(None).not_null().to_column("v1".into()),
             })],
+            params: vec![],
+            filter: None,
             return_type: DataTypeKind::Int(None).not_null(),
+            distinct: false,
         });
         let v2_plus_1_expr = BoundExpr::BinaryOp(BoundBinaryOp {
             op: BinaryOperator::Plus,
@@ -256,7 +298,10 @@ mod tests {
                 is_primary_key: false,
                 desc: DataTypeKind::Int(None).not_null().to_column("v1".into()),
             })],
+            params: vec![],
+            filter: None,
             return_type: DataTypeKind::Int(None).not_null(),
+            distinct: false,
         });
         let v2_expr = BoundExpr::ColumnRef(BoundColumnRef {
             column_ref_id: ColumnRefId::new(0, 0, 0, 1),
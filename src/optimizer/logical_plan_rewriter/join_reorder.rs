@@ -0,0 +1,429 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::HashMap;
+
+use super::*;
+use crate::binder::{BoundExpr, BoundInputRef, BoundJoinOperator};
+use crate::optimizer::expr_utils::{
+    conjunctions, input_col_refs, merge_conjunctions, shift_input_col_refs,
+};
+use crate::optimizer::statistics::selectivity;
+use crate::types::DataValue;
+
+/// The maximum number of relations to reorder by dynamic programming. Larger join trees are
+/// reordered greedily.
+const MAX_DP_RELATIONS: usize = 10;
+
+/// The maximum number of relations to reorder, limited by the width of [`RelationSet`].
+const MAX_RELATIONS: usize = 64;
+
+/// Reorders trees of inner joins by the estimated cost.
+///
+/// An inner join tree is flattened into its relations and join conditions. The cost of a join
+/// tree is the sum of the estimated cardinalities of all joins in it. The tree of the minimum
+/// cost is found by dynamic programming over the subsets of relations, or greedily by joining
+/// the pair of the smallest result first if there are too many relations. Cross products are
+/// only considered when no condition connects the relations.
+///
+/// The tree is only changed if the new one is estimated to be cheaper, and a projection restores
+/// the original column order on top of it.
+pub struct JoinReorder;
+
+impl PlanRewriter for JoinReorder {
+    fn rewrite_logical_join(&mut self, join: &LogicalJoin) -> PlanRef {
+        if join.join_op() != BoundJoinOperator::Inner
+            || num_relations(&join.clone_as_plan_ref()) > MAX_RELATIONS
+        {
+            let left = self.rewrite(join.left());
+            let right = self.rewrite(join.right());
+            return Arc::new(join.clone_with_left_right(left, right));
+        }
+        let mut graph = JoinGraph::default();
+        let mut original_splits = HashMap::new();
+        graph.flatten(self, join.clone_as_plan_ref(), &mut original_splits);
+        graph.push_down_filters();
+
+        let full = graph.all_relations();
+        let splits = match graph.relations.len() {
+            n if n <= 2 => original_splits,
+            n => {
+                let splits = match n {
+                    n if n <= MAX_DP_RELATIONS => graph.reorder_dp(),
+                    _ => graph.reorder_greedy(),
+                };
+                let cost = graph.cost(full, &splits);
+                let original_cost = graph.cost(full, &original_splits);
+                match cost < original_cost {
+                    true => splits,
+                    false => original_splits,
+                }
+            }
+        };
+
+        let (plan, columns) = graph.build(full, &splits);
+        if columns.iter().enumerate().all(|(i, column)| i == *column) {
+            return plan;
+        }
+        // restore the original column order
+        let mut positions = vec![0; columns.len()];
+        for (position, column) in columns.iter().enumerate() {
+            positions[*column] = position;
+        }
+        let types = plan.out_types();
+        let exprs = positions
+            .into_iter()
+            .map(|index| {
+                BoundExpr::InputRef(BoundInputRef {
+                    index,
+                    return_type: types[index].clone(),
+                })
+            })
+            .collect();
+        Arc::new(LogicalProjection::new(exprs, plan))
+    }
+}
+
+/// Number of relations in the inner join tree of `plan`.
+fn num_relations(plan: &PlanRef) -> usize {
+    match plan.as_logical_join() {
+        Ok(join) if join.join_op() == BoundJoinOperator::Inner => {
+            num_relations(&join.left()) + num_relations(&join.right())
+        }
+        _ => 1,
+    }
+}
+
+/// A set of relations in a [`JoinGraph`], as a bitmap of their indexes.
+type RelationSet = u64;
+
+/// A flattened tree of inner joins.
+#[derive(Default)]
+struct JoinGraph {
+    /// The inputs of the joins, in the original order.
+    relations: Vec<PlanRef>,
+    /// Index of the first column of each relation in the output of the join tree.
+    offsets: Vec<usize>,
+    /// Conditions on the output of the join tree, with the relations referenced by them.
+    conditions: Vec<(BoundExpr, RelationSet)>,
+    /// Estimated selectivity of each condition.
+    selectivities: Vec<f64>,
+}
+
+impl JoinGraph {
+    /// Flatten the inner join tree of `plan` into the graph, and record the splits of the
+    /// original tree. Returns the relations of `plan`.
+    fn flatten(
+        &mut self,
+        rewriter: &mut JoinReorder,
+        plan: PlanRef,
+        splits: &mut HashMap<RelationSet, (RelationSet, RelationSet)>,
+    ) -> RelationSet {
+        let offset = self.num_columns();
+        match plan.as_logical_join() {
+            Ok(join) if join.join_op() == BoundJoinOperator::Inner => {
+                let left = self.flatten(rewriter, join.left(), splits);
+                let right = self.flatten(rewriter, join.right(), splits);
+                for mut condition in conjunctions(join.predicate().to_on_clause()) {
+                    if condition == BoundExpr::Constant(DataValue::Bool(true)) {
+                        continue;
+                    }
+                    shift_input_col_refs(&mut condition, offset as i32);
+                    self.conditions.push((condition, 0));
+                }
+                splits.insert(left | right, (left, right));
+                left | right
+            }
+            _ => {
+                self.relations.push(rewriter.rewrite(plan));
+                self.offsets.push(offset);
+                1 << (self.relations.len() - 1)
+            }
+        }
+    }
+
+    /// The set of all relations.
+    fn all_relations(&self) -> RelationSet {
+        RelationSet::MAX >> (RelationSet::BITS as usize - self.relations.len())
+    }
+
+    /// Number of output columns of the join tree.
+    fn num_columns(&self) -> usize {
+        match (self.offsets.last(), self.relations.last()) {
+            (Some(offset), Some(plan)) => offset + plan.out_types().len(),
+            _ => 0,
+        }
+    }
+
+    /// Resolve the relations of conditions, and push the conditions on a single relation down
+    /// to it. Then estimate the selectivity of the remaining conditions.
+    fn push_down_filters(&mut self) {
+        for (condition, relations) in &mut self.conditions {
+            *relations = input_col_refs(condition)
+                .iter()
+                .map(|column| self.offsets.partition_point(|offset| *offset <= column) - 1)
+                .fold(0, |relations, relation| relations | 1 << relation);
+        }
+        let (filters, conditions) = std::mem::take(&mut self.conditions)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, relations)| relations.count_ones() == 1);
+        self.conditions = conditions;
+        for (mut filter, relations) in filters {
+            let relation = relations.trailing_zeros() as usize;
+            shift_input_col_refs(&mut filter, -(self.offsets[relation] as i32));
+            self.relations[relation] =
+                Arc::new(LogicalFilter::new(filter, self.relations[relation].clone()));
+        }
+
+        let distinct_values = |column: usize| {
+            let relation = self.offsets.partition_point(|offset| *offset <= column) - 1;
+            self.relations[relation].estimated_distinct_values(column - self.offsets[relation])
+        };
+        self.selectivities = self
+            .conditions
+            .iter()
+            .map(|(condition, _)| selectivity(condition, &distinct_values))
+            .collect();
+    }
+
+    /// Estimated cardinality of joining the relations.
+    fn cardinality(&self, set: RelationSet) -> f64 {
+        let relations = (0..self.relations.len())
+            .filter(|relation| set & 1 << relation != 0)
+            .map(|relation| self.relations[relation].estimated_cardinality() as f64)
+            .product::<f64>();
+        let selectivity = self
+            .conditions
+            .iter()
+            .zip(&self.selectivities)
+            .filter(|((_, relations), _)| *relations != 0 && relations & !set == 0)
+            .map(|(_, selectivity)| *selectivity)
+            .product::<f64>();
+        relations * selectivity
+    }
+
+    /// Whether a condition can join the two sets of relations.
+    fn is_connected(&self, left: RelationSet, right: RelationSet) -> bool {
+        self.conditions.iter().any(|(_, relations)| {
+            relations & left != 0 && relations & right != 0 && relations & !(left | right) == 0
+        })
+    }
+
+    /// Estimated cost of the join tree of `set`.
+    fn cost(
+        &self,
+        set: RelationSet,
+        splits: &HashMap<RelationSet, (RelationSet, RelationSet)>,
+    ) -> f64 {
+        match splits.get(&set) {
+            Some((left, right)) => {
+                self.cardinality(set) + self.cost(*left, splits) + self.cost(*right, splits)
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Find the join tree of the minimum cost by dynamic programming.
+    fn reorder_dp(&self) -> HashMap<RelationSet, (RelationSet, RelationSet)> {
+        let full = self.all_relations();
+        let mut costs = HashMap::new();
+        let mut splits = HashMap::new();
+        for relation in 0..self.relations.len() {
+            costs.insert(1 << relation, 0.0);
+        }
+        // subsets of a set are always less than it
+        for set in 1..=full {
+            if set.count_ones() < 2 {
+                continue;
+            }
+            let cardinality = self.cardinality(set);
+            // the side of the first relation is on the left, to keep the original order
+            let first = set & set.wrapping_neg();
+            let mut best: Option<(f64, RelationSet, RelationSet)> = None;
+            for allow_cross_product in [false, true] {
+                // enumerate the proper subsets of `set` containing the first relation
+                let mut left = (set - 1) & set;
+                while left != 0 {
+                    let right = set ^ left;
+                    if left & first != 0 && (allow_cross_product || self.is_connected(left, right))
+                    {
+                        let cost = cardinality + costs[&left] + costs[&right];
+                        if best.map_or(true, |(best, _, _)| cost < best) {
+                            best = Some((cost, left, right));
+                        }
+                    }
+                    left = (left - 1) & set;
+                }
+                if best.is_some() {
+                    break;
+                }
+            }
+            let (cost, left, right) = best.unwrap();
+            costs.insert(set, cost);
+            splits.insert(set, (left, right));
+        }
+        splits
+    }
+
+    /// Build the join tree by repeatedly joining the pair of the smallest result.
+    fn reorder_greedy(&self) -> HashMap<RelationSet, (RelationSet, RelationSet)> {
+        let mut trees: Vec<RelationSet> = (0..self.relations.len()).map(|i| 1 << i).collect();
+        let mut splits = HashMap::new();
+        while trees.len() > 1 {
+            let mut best: Option<(bool, f64, usize, usize)> = None;
+            for i in 0..trees.len() {
+                for j in i + 1..trees.len() {
+                    let is_cross_product = !self.is_connected(trees[i], trees[j]);
+                    let cardinality = self.cardinality(trees[i] | trees[j]);
+                    if best.map_or(true, |(best_is_cross_product, best_cardinality, _, _)| {
+                        (is_cross_product, cardinality) < (best_is_cross_product, best_cardinality)
+                    }) {
+                        best = Some((is_cross_product, cardinality, i, j));
+                    }
+                }
+            }
+            let (_, _, i, j) = best.unwrap();
+            let (left, right) = (trees[i], trees.remove(j));
+            trees[i] = left | right;
+            splits.insert(left | right, (left, right));
+        }
+        splits
+    }
+
+    /// Build the plan of the join tree of `set`. Returns the plan and the original indexes of
+    /// its output columns.
+    fn build(
+        &self,
+        set: RelationSet,
+        splits: &HashMap<RelationSet, (RelationSet, RelationSet)>,
+    ) -> (PlanRef, Vec<usize>) {
+        let (left, right) = match splits.get(&set) {
+            Some(split) => *split,
+            None => {
+                let relation = set.trailing_zeros() as usize;
+                let plan = self.relations[relation].clone();
+                let offset = self.offsets[relation];
+                let columns = (offset..offset + plan.out_types().len()).collect();
+                return (plan, columns);
+            }
+        };
+        let (left_plan, mut columns) = self.build(left, splits);
+        let (right_plan, right_columns) = self.build(right, splits);
+        columns.extend(right_columns);
+
+        let full = self.all_relations();
+        let mut positions = vec![0; self.num_columns()];
+        for (position, column) in columns.iter().enumerate() {
+            positions[*column] = position;
+        }
+        let mapping = ColumnMapping(&positions);
+        let conditions = self
+            .conditions
+            .iter()
+            .filter(|(_, relations)| {
+                // conditions without columns are put on the root
+                (*relations == 0 && set == full)
+                    || (relations & !set == 0 && relations & !left != 0 && relations & !right != 0)
+            })
+            .map(|(condition, _)| {
+                let mut condition = condition.clone();
+                mapping.rewrite_expr(&mut condition);
+                condition
+            });
+        let plan = Arc::new(LogicalJoin::create(
+            left_plan,
+            right_plan,
+            BoundJoinOperator::Inner,
+            merge_conjunctions(conditions),
+        ));
+        (plan, columns)
+    }
+}
+
+/// Maps the columns of the join tree to their positions in the output of a reordered join.
+struct ColumnMapping<'a>(&'a [usize]);
+
+impl ExprRewriter for ColumnMapping<'_> {
+    fn rewrite_input_ref(&self, expr: &mut BoundExpr) {
+        if let BoundExpr::InputRef(input_ref) = expr {
+            input_ref.index = self.0[input_ref.index];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binder::BoundBinaryOp;
+    use crate::catalog::TableRefId;
+    use crate::optimizer::statistics::TableStatistics;
+    use crate::parser::BinaryOperator;
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    #[test]
+    fn test_join_reorder() {
+        let ty = DataTypeKind::Int(None).not_null();
+        let scan = |table_id, columns: &[&str], row_count, distinct_values: &[usize]| -> PlanRef {
+            let statistics = TableStatistics {
+                row_count,
+                distinct_values: (0..).zip(distinct_values.iter().copied()).collect(),
+            };
+            Arc::new(
+                LogicalTableScan::new(
+                    TableRefId::new(0, 0, table_id),
+                    (0..columns.len() as u32).collect(),
+                    columns
+                        .iter()
+                        .map(|name| ty.clone().to_column(name.to_string()))
+                        .collect(),
+                    false,
+                    false,
+                    None,
+                    None,
+                )
+                .with_statistics(Arc::new(statistics)),
+            )
+        };
+        let eq = |left, right| {
+            let col = |index| {
+                Box::new(BoundExpr::InputRef(BoundInputRef {
+                    index,
+                    return_type: ty.clone(),
+                }))
+            };
+            BoundExpr::BinaryOp(BoundBinaryOp {
+                op: BinaryOperator::Eq,
+                left_expr: col(left),
+                right_expr: col(right),
+                return_type: Some(DataTypeKind::Boolean.nullable()),
+            })
+        };
+        let a = scan(0, &["a"], 1000, &[100]);
+        let b = scan(1, &["b1", "b2"], 100, &[100, 10]);
+        let c = scan(2, &["c"], 10, &[10]);
+
+        // (a JOIN b ON a = b1) JOIN c ON b2 = c
+        let ab = Arc::new(LogicalJoin::create(
+            a,
+            b,
+            BoundJoinOperator::Inner,
+            eq(0, 1),
+        ));
+        let plan = Arc::new(LogicalJoin::create(
+            ab,
+            c,
+            BoundJoinOperator::Inner,
+            eq(2, 3),
+        ));
+        assert_eq!(plan.estimated_cardinality(), 1000);
+
+        // a JOIN (b JOIN c ON b2 = c) ON a = b1
+        let plan = JoinReorder.rewrite(plan);
+        let join = plan.as_logical_join().unwrap();
+        assert!(join.left().as_logical_table_scan().is_ok());
+        let bc = join.right();
+        let bc = bc.as_logical_join().unwrap();
+        assert_eq!(bc.predicate().to_on_clause(), eq(1, 2));
+        assert_eq!(join.predicate().to_on_clause(), eq(0, 1));
+        assert_eq!(plan.estimated_cardinality(), 1000);
+    }
+}
@@ -10,6 +10,7 @@ mod constant_folding;
 mod constant_moving;
 mod convert_physical;
 mod input_ref_resolver;
+mod join_reorder;
 
 pub use arith_expr_simplification::*;
 pub use bool_expr_simplification::*;
@@ -18,6 +19,7 @@ pub use constant_moving::*;
 pub use convert_physical::*;
 pub use input_ref_resolver::*;
 use itertools::Itertools;
+pub use join_reorder::*;
 use paste::paste;
 
 pub use crate::binder::ExprRewriter;
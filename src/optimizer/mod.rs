@@ -10,12 +10,14 @@ pub mod logical_plan_rewriter;
 pub mod plan_nodes;
 mod plan_visitor;
 mod rules;
+pub mod statistics;
 
 use self::heuristic::HeuristicOptimizer;
 use self::logical_plan_rewriter::*;
 use self::plan_nodes::PlanRef;
 pub use self::plan_visitor::*;
 use self::rules::*;
+use self::statistics::{Statistics, StatisticsAttacher};
 
 /// The optimizer will do query optimization.
 ///
@@ -26,6 +28,8 @@ use self::rules::*;
 #[derive(Default)]
 pub struct Optimizer {
     pub enable_filter_scan: bool,
+    /// Statistics of the tables used by the query, for cost-based optimization.
+    pub statistics: Statistics,
 }
 
 impl Optimizer {
@@ -48,6 +52,13 @@ impl Optimizer {
         }
         let hep_optimizer = HeuristicOptimizer { rules };
         plan = hep_optimizer.optimize(plan);
+        if !self.statistics.is_empty() {
+            let mut statistics_attacher = StatisticsAttacher {
+                statistics: &self.statistics,
+            };
+            plan = statistics_attacher.rewrite(plan);
+        }
+        plan = JoinReorder.rewrite(plan);
         let out_types_num = plan.out_types().len();
         plan = plan.prune_col(BitSet::from_iter(0..out_types_num));
         let mut phy_converter = PhysicalConverter;
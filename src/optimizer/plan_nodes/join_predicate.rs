@@ -138,6 +138,17 @@ impl JoinPredicate {
             .collect()
     }
 
+    /// Get the conjunction of all conditions except the eq conds.
+    pub fn non_eq_cond(&self) -> BoundExpr {
+        merge_conjunctions(
+            self.left_conds
+                .iter()
+                .cloned()
+                .chain(self.right_conds.iter().cloned())
+                .chain(self.other_conds.iter().cloned()),
+        )
+    }
+
     /// Get a reference to the join predicate's eq keys.
     pub fn eq_keys(&self) -> &[(BoundInputRef, BoundInputRef)] {
         self.eq_keys.as_ref()
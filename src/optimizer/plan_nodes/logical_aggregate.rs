@@ -44,7 +44,7 @@ impl LogicalAggregate {
         let mut new_agg_calls = self.agg_calls().to_vec();
         let mut new_keys = self.group_keys().to_vec();
         for agg in &mut new_agg_calls {
-            for arg in &mut agg.args {
+            for arg in agg.args.iter_mut().chain(agg.filter.as_deref_mut()) {
                 rewriter.rewrite_expr(arg);
             }
         }
@@ -110,22 +110,34 @@ mod tests {
                 BoundAggCall {
                     kind: AggKind::Sum,
                     args: vec![],
+                    params: vec![],
+                    filter: None,
                     return_type: DataTypeKind::Double.not_null(),
+                    distinct: false,
                 },
                 BoundAggCall {
                     kind: AggKind::Avg,
                     args: vec![],
+                    params: vec![],
+                    filter: None,
                     return_type: DataTypeKind::Double.not_null(),
+                    distinct: false,
                 },
                 BoundAggCall {
                     kind: AggKind::Count,
                     args: vec![],
+                    params: vec![],
+                    filter: None,
                     return_type: DataTypeKind::Double.not_null(),
+                    distinct: false,
                 },
                 BoundAggCall {
                     kind: AggKind::RowCount,
                     args: vec![],
+                    params: vec![],
+                    filter: None,
                     return_type: DataTypeKind::Double.not_null(),
+                    distinct: false,
                 },
             ],
             vec![],
@@ -8,6 +8,7 @@ use serde::Serialize;
 use super::*;
 use crate::binder::{BoundExpr, ExprVisitor};
 use crate::optimizer::logical_plan_rewriter::ExprRewriter;
+use crate::optimizer::statistics::selectivity;
 
 /// The logical plan of filter operation.
 #[derive(Debug, Clone, Serialize)]
@@ -52,7 +53,15 @@ impl PlanNode for LogicalFilter {
     }
 
     fn estimated_cardinality(&self) -> usize {
-        self.child().estimated_cardinality()
+        let child = &self.child;
+        let selectivity = selectivity(&self.expr, &|column| {
+            child.estimated_distinct_values(column)
+        });
+        (child.estimated_cardinality() as f64 * selectivity).ceil() as usize
+    }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        self.child.estimated_distinct_values(column)
     }
 
     fn prune_col(&self, required_cols: BitSet) -> PlanRef {
@@ -152,6 +161,7 @@ mod tests {
             false,
             false,
             None,
+            None,
         );
         let filter = LogicalFilter::new(
             BoundExpr::BinaryOp(BoundBinaryOp {
@@ -218,6 +228,7 @@ mod tests {
             false,
             false,
             None,
+            None,
         );
         let filter = LogicalFilter::new(
             BoundExpr::BinaryOp(BoundBinaryOp {
@@ -7,6 +7,7 @@ use serde::Serialize;
 use super::*;
 use crate::binder::BoundJoinOperator;
 use crate::optimizer::logical_plan_rewriter::ExprRewriter;
+use crate::optimizer::statistics::selectivity;
 
 /// The logical plan of join, it only records join tables and operators.
 ///
@@ -75,6 +76,16 @@ impl LogicalJoin {
     pub fn predicate(&self) -> &JoinPredicate {
         &self.predicate
     }
+
+    /// Estimated output size of the inner join, ignoring the unmatched rows of outer joins.
+    pub fn estimated_inner_cardinality(&self) -> f64 {
+        let selectivity = selectivity(&self.predicate.to_on_clause(), &|column| {
+            self.estimated_distinct_values(column)
+        });
+        self.left_plan.estimated_cardinality() as f64
+            * self.right_plan.estimated_cardinality() as f64
+            * selectivity
+    }
 }
 impl PlanTreeNodeBinary for LogicalJoin {
     fn left(&self) -> PlanRef {
@@ -96,7 +107,27 @@ impl PlanNode for LogicalJoin {
     }
 
     fn estimated_cardinality(&self) -> usize {
-        self.left().estimated_cardinality() * self.right().estimated_cardinality()
+        let inner = self.estimated_inner_cardinality().ceil() as usize;
+        let (left, right) = (
+            self.left_plan.estimated_cardinality(),
+            self.right_plan.estimated_cardinality(),
+        );
+        match self.join_op {
+            BoundJoinOperator::Inner => inner,
+            BoundJoinOperator::LeftOuter => inner.max(left),
+            BoundJoinOperator::RightOuter => inner.max(right),
+            BoundJoinOperator::FullOuter => inner.max(left + right),
+        }
+    }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        let left_cols = self.left_plan.schema().len();
+        if column < left_cols {
+            self.left_plan.estimated_distinct_values(column)
+        } else {
+            self.right_plan
+                .estimated_distinct_values(column - left_cols)
+        }
     }
 }
 
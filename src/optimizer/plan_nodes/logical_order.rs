@@ -54,6 +54,10 @@ impl PlanNode for LogicalOrder {
     fn estimated_cardinality(&self) -> usize {
         self.child().estimated_cardinality()
     }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        self.child().estimated_distinct_values(column)
+    }
 }
 
 impl fmt::Display for LogicalOrder {
@@ -106,6 +106,13 @@ impl PlanNode for LogicalProjection {
     fn estimated_cardinality(&self) -> usize {
         self.child().estimated_cardinality()
     }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        match &self.project_expressions[column] {
+            BoundExpr::InputRef(input_ref) => self.child.estimated_distinct_values(input_ref.index),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for LogicalProjection {
@@ -7,7 +7,8 @@ use serde::Serialize;
 
 use super::*;
 use crate::catalog::{ColumnDesc, TableRefId};
-use crate::types::ColumnId;
+use crate::optimizer::statistics::{selectivity, TableStatistics};
+use crate::types::{ColumnId, DataValue};
 /// The logical plan of sequential scan operation.
 #[derive(Debug, Clone, Serialize)]
 pub struct LogicalTableScan {
@@ -17,6 +18,12 @@ pub struct LogicalTableScan {
     with_row_handler: bool,
     is_sorted: bool,
     expr: Option<BoundExpr>,
+    as_of_epoch: Option<u64>,
+    /// The inclusive range of the sort key to scan. Empty for unbounded.
+    begin_keys: Vec<DataValue>,
+    end_keys: Vec<DataValue>,
+    /// Statistics of the table, `None` if unknown.
+    statistics: Option<Arc<TableStatistics>>,
 }
 
 impl LogicalTableScan {
@@ -27,6 +34,7 @@ impl LogicalTableScan {
         with_row_handler: bool,
         is_sorted: bool,
         expr: Option<BoundExpr>,
+        as_of_epoch: Option<u64>,
     ) -> Self {
         Self {
             table_ref_id,
@@ -35,6 +43,27 @@ impl LogicalTableScan {
             with_row_handler,
             is_sorted,
             expr,
+            as_of_epoch,
+            begin_keys: vec![],
+            end_keys: vec![],
+            statistics: None,
+        }
+    }
+
+    /// Bound the scan by an inclusive range of the sort key.
+    pub fn with_key_range(self, begin_keys: Vec<DataValue>, end_keys: Vec<DataValue>) -> Self {
+        Self {
+            begin_keys,
+            end_keys,
+            ..self
+        }
+    }
+
+    /// Attach the statistics of the table, which are used to estimate the cardinality.
+    pub fn with_statistics(self, statistics: Arc<TableStatistics>) -> Self {
+        Self {
+            statistics: Some(statistics),
+            ..self
         }
     }
 
@@ -67,6 +96,21 @@ impl LogicalTableScan {
     pub fn expr(&self) -> Option<&BoundExpr> {
         self.expr.as_ref()
     }
+
+    /// Get the epoch to read from. `None` for the latest one.
+    pub fn as_of_epoch(&self) -> Option<u64> {
+        self.as_of_epoch
+    }
+
+    /// Get the inclusive lower bound of the sort key. Empty for unbounded.
+    pub fn begin_keys(&self) -> &[DataValue] {
+        &self.begin_keys
+    }
+
+    /// Get the inclusive upper bound of the sort key. Empty for unbounded.
+    pub fn end_keys(&self) -> &[DataValue] {
+        &self.end_keys
+    }
 }
 impl PlanTreeNodeLeaf for LogicalTableScan {}
 impl_plan_tree_node_for_leaf!(LogicalTableScan);
@@ -76,9 +120,24 @@ impl PlanNode for LogicalTableScan {
         self.column_descs.clone()
     }
 
-    // TODO: get statistics from storage system
     fn estimated_cardinality(&self) -> usize {
-        1
+        let statistics = match &self.statistics {
+            Some(statistics) => statistics,
+            None => return 1,
+        };
+        let selectivity = match &self.expr {
+            Some(expr) => selectivity(expr, &|column| self.estimated_distinct_values(column)),
+            None => 1.0,
+        };
+        (statistics.row_count as f64 * selectivity).ceil() as usize
+    }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        let statistics = self.statistics.as_ref()?;
+        let distinct_values = statistics
+            .distinct_values
+            .get(self.column_ids.get(column)?)?;
+        Some((*distinct_values).min(statistics.row_count))
     }
 
     fn prune_col(&self, required_cols: BitSet) -> PlanRef {
@@ -98,13 +157,17 @@ impl PlanNode for LogicalTableScan {
             with_row_handler: self.with_row_handler,
             is_sorted: self.is_sorted,
             expr: self.expr.clone(),
+            as_of_epoch: self.as_of_epoch,
+            begin_keys: self.begin_keys.clone(),
+            end_keys: self.end_keys.clone(),
+            statistics: self.statistics.clone(),
         }
         .into_plan_ref()
     }
 }
 impl fmt::Display for LogicalTableScan {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(
+        write!(
                 f,
                 "LogicalTableScan: table #{}, columns [{}], with_row_handler: {}, is_sorted: {}, expr: {}",
                 self.table_ref_id.table_id,
@@ -112,6 +175,18 @@ impl fmt::Display for LogicalTableScan {
                 self.with_row_handler,
                 self.is_sorted,
                 self.expr.clone().map_or_else(|| "None".to_string(), |expr| format!("{:?}", expr))
-            )
+            )?;
+        if let Some(epoch) = self.as_of_epoch {
+            write!(f, ", as_of_epoch: {}", epoch)?;
+        }
+        if !self.begin_keys.is_empty() || !self.end_keys.is_empty() {
+            write!(
+                f,
+                ", key_range: [{}] to [{}]",
+                self.begin_keys.iter().join(", "),
+                self.end_keys.iter().join(", ")
+            )?;
+        }
+        writeln!(f)
     }
 }
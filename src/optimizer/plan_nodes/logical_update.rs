@@ -0,0 +1,82 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::*;
+use crate::catalog::TableRefId;
+use crate::optimizer::logical_plan_rewriter::ExprRewriter;
+use crate::types::DataTypeKind;
+
+/// The logical plan of `UPDATE`.
+///
+/// The child should output all columns of the table followed by the row handler. Each matching
+/// row is deleted and replaced by a new row built from `value_exprs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogicalUpdate {
+    table_ref_id: TableRefId,
+    value_exprs: Vec<BoundExpr>,
+    child: PlanRef,
+}
+
+impl LogicalUpdate {
+    pub fn new(table_ref_id: TableRefId, value_exprs: Vec<BoundExpr>, child: PlanRef) -> Self {
+        Self {
+            table_ref_id,
+            value_exprs,
+            child,
+        }
+    }
+
+    /// Get a reference to the logical update's table ref id.
+    pub fn table_ref_id(&self) -> TableRefId {
+        self.table_ref_id
+    }
+
+    /// Get a reference to the logical update's value expressions.
+    pub fn value_exprs(&self) -> &[BoundExpr] {
+        self.value_exprs.as_ref()
+    }
+
+    pub fn clone_with_rewrite_expr(
+        &self,
+        new_child: PlanRef,
+        rewriter: &impl ExprRewriter,
+    ) -> Self {
+        let mut new_exprs = self.value_exprs.clone();
+        for expr in &mut new_exprs {
+            rewriter.rewrite_expr(expr);
+        }
+        Self::new(self.table_ref_id, new_exprs, new_child)
+    }
+}
+impl PlanTreeNodeUnary for LogicalUpdate {
+    fn child(&self) -> PlanRef {
+        self.child.clone()
+    }
+
+    fn clone_with_child(&self, child: PlanRef) -> Self {
+        Self::new(self.table_ref_id(), self.value_exprs.clone(), child)
+    }
+}
+impl_plan_tree_node_for_unary!(LogicalUpdate);
+impl PlanNode for LogicalUpdate {
+    fn schema(&self) -> Vec<ColumnDesc> {
+        vec![ColumnDesc::new(
+            DataType::new(DataTypeKind::Int(None), false),
+            "$update.row_counts".to_string(),
+            false,
+        )]
+    }
+}
+
+impl fmt::Display for LogicalUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "LogicalUpdate: table {}, values {:?}",
+            self.table_ref_id.table_id, self.value_exprs
+        )
+    }
+}
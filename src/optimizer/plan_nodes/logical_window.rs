@@ -0,0 +1,91 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::*;
+use crate::binder::BoundWindowCall;
+use crate::optimizer::logical_plan_rewriter::ExprRewriter;
+
+/// The logical plan of window functions.
+///
+/// The output columns are the input columns followed by the results of window functions.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogicalWindow {
+    window_calls: Vec<BoundWindowCall>,
+    child: PlanRef,
+}
+
+impl LogicalWindow {
+    pub fn new(window_calls: Vec<BoundWindowCall>, child: PlanRef) -> Self {
+        Self {
+            window_calls,
+            child,
+        }
+    }
+
+    /// Get a reference to the logical window's window calls.
+    pub fn window_calls(&self) -> &[BoundWindowCall] {
+        self.window_calls.as_ref()
+    }
+
+    pub fn clone_with_rewrite_expr(
+        &self,
+        new_child: PlanRef,
+        rewriter: &impl ExprRewriter,
+    ) -> Self {
+        let mut new_calls = self.window_calls().to_vec();
+        for call in &mut new_calls {
+            for expr in &mut call.args {
+                rewriter.rewrite_expr(expr);
+            }
+            for expr in &mut call.partition_by {
+                rewriter.rewrite_expr(expr);
+            }
+            for cmp in &mut call.order_by {
+                rewriter.rewrite_expr(&mut cmp.expr);
+            }
+        }
+        LogicalWindow::new(new_calls, new_child)
+    }
+}
+impl PlanTreeNodeUnary for LogicalWindow {
+    fn child(&self) -> PlanRef {
+        self.child.clone()
+    }
+    #[must_use]
+    fn clone_with_child(&self, child: PlanRef) -> Self {
+        Self::new(self.window_calls().to_vec(), child)
+    }
+}
+impl_plan_tree_node_for_unary!(LogicalWindow);
+impl PlanNode for LogicalWindow {
+    fn schema(&self) -> Vec<ColumnDesc> {
+        let mut schema = self.child.schema();
+        schema.extend(
+            self.window_calls
+                .iter()
+                .map(|call| call.return_type.clone().to_column(format!("{}", call.kind))),
+        );
+        schema
+    }
+
+    fn estimated_cardinality(&self) -> usize {
+        self.child().estimated_cardinality()
+    }
+
+    fn estimated_distinct_values(&self, column: usize) -> Option<usize> {
+        if column < self.child().out_types().len() {
+            self.child().estimated_distinct_values(column)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for LogicalWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "LogicalWindow: {:?}", self.window_calls)
+    }
+}
@@ -48,7 +48,9 @@ mod logical_order;
 mod logical_projection;
 mod logical_table_scan;
 mod logical_top_n;
+mod logical_update;
 mod logical_values;
+mod logical_window;
 mod physical_copy_from_file;
 mod physical_copy_to_file;
 mod physical_create_table;
@@ -64,9 +66,13 @@ mod physical_nested_loop_join;
 mod physical_order;
 mod physical_projection;
 mod physical_simple_agg;
+mod physical_sort_agg;
+mod physical_sort_merge_join;
 mod physical_table_scan;
 mod physical_top_n;
+mod physical_update;
 mod physical_values;
+mod physical_window;
 
 pub use dummy::*;
 pub use internal::*;
@@ -85,7 +91,9 @@ pub use logical_order::*;
 pub use logical_projection::*;
 pub use logical_table_scan::*;
 pub use logical_top_n::*;
+pub use logical_update::*;
 pub use logical_values::*;
+pub use logical_window::*;
 pub use physical_copy_from_file::*;
 pub use physical_copy_to_file::*;
 pub use physical_create_table::*;
@@ -101,9 +109,13 @@ pub use physical_nested_loop_join::*;
 pub use physical_order::*;
 pub use physical_projection::*;
 pub use physical_simple_agg::*;
+pub use physical_sort_agg::*;
+pub use physical_sort_merge_join::*;
 pub use physical_table_scan::*;
 pub use physical_top_n::*;
+pub use physical_update::*;
 pub use physical_values::*;
+pub use physical_window::*;
 
 use crate::catalog::ColumnDesc;
 
@@ -149,6 +161,12 @@ pub trait PlanNode:
     fn estimated_cardinality(&self) -> usize {
         1
     }
+
+    /// Estimated number of distinct values of an output column. `None` if unknown.
+    fn estimated_distinct_values(&self, _column: usize) -> Option<usize> {
+        None
+    }
+
     /// transform the plan node to only output the required columns ordered by index number, only
     /// logical plan node will use it, though all plan node impl it.
     fn prune_col(&self, required_cols: BitSet) -> PlanRef {
@@ -225,8 +243,10 @@ macro_rules! for_all_plan_nodes {
             LogicalLimit,
             LogicalTopN,
             LogicalDelete,
+            LogicalUpdate,
             LogicalCopyFromFile,
             LogicalCopyToFile,
+            LogicalWindow,
             PhysicalTableScan,
             PhysicalInsert,
             PhysicalValues,
@@ -239,12 +259,16 @@ macro_rules! for_all_plan_nodes {
             PhysicalSimpleAgg,
             PhysicalHashAgg,
             PhysicalHashJoin,
+            PhysicalSortAgg,
+            PhysicalSortMergeJoin,
             PhysicalOrder,
             PhysicalLimit,
             PhysicalTopN,
             PhysicalDelete,
+            PhysicalUpdate,
             PhysicalCopyFromFile,
-            PhysicalCopyToFile
+            PhysicalCopyToFile,
+            PhysicalWindow
         }
     };
 }
@@ -38,7 +38,7 @@ impl PlanNode for PhysicalFilter {
     }
 
     fn estimated_cardinality(&self) -> usize {
-        self.child().estimated_cardinality()
+        self.logical().estimated_cardinality()
     }
 }
 impl fmt::Display for PhysicalFilter {
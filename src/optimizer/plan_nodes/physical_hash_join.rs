@@ -8,6 +8,9 @@ use serde::Serialize;
 use super::*;
 
 /// The phyiscal plan of join.
+///
+/// The hash table is built from the left child and probed by the right child, so the side of the
+/// smaller estimated cardinality is put on the left.
 #[derive(Clone, Debug, Serialize)]
 pub struct PhysicalHashJoin {
     logical: LogicalJoin,
@@ -44,11 +47,9 @@ impl PlanNode for PhysicalHashJoin {
     }
 
     fn estimated_cardinality(&self) -> usize {
-        self.left().estimated_cardinality() * self.right().estimated_cardinality()
+        self.logical().estimated_cardinality()
     }
 }
-/// Currently, we only use default join ordering.
-/// We will implement DP or DFS algorithms for join orders.
 impl fmt::Display for PhysicalHashJoin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(
@@ -44,11 +44,9 @@ impl PlanNode for PhysicalNestedLoopJoin {
     }
 
     fn estimated_cardinality(&self) -> usize {
-        self.left().estimated_cardinality() * self.right().estimated_cardinality()
+        self.logical().estimated_cardinality()
     }
 }
-/// Currently, we only use default join ordering.
-/// We will implement DP or DFS algorithms for join orders.
 impl fmt::Display for PhysicalNestedLoopJoin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(
@@ -0,0 +1,65 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt;
+
+use indoc::indoc;
+use serde::Serialize;
+
+use super::*;
+
+/// The physical plan of sort merge join.
+///
+/// Both children are sorted in ascending order by the join key, which is the only eq key of the
+/// predicate. Only inner joins are supported.
+#[derive(Clone, Debug, Serialize)]
+pub struct PhysicalSortMergeJoin {
+    logical: LogicalJoin,
+}
+
+impl PhysicalSortMergeJoin {
+    pub fn new(logical: LogicalJoin) -> Self {
+        Self { logical }
+    }
+
+    /// Get a reference to the physical sort merge join's logical.
+    pub fn logical(&self) -> &LogicalJoin {
+        &self.logical
+    }
+}
+impl PlanTreeNodeBinary for PhysicalSortMergeJoin {
+    fn left(&self) -> PlanRef {
+        self.logical.left()
+    }
+    fn right(&self) -> PlanRef {
+        self.logical.right()
+    }
+
+    #[must_use]
+    fn clone_with_left_right(&self, left: PlanRef, right: PlanRef) -> Self {
+        Self::new(self.logical.clone_with_left_right(left, right))
+    }
+}
+impl_plan_tree_node_for_binary!(PhysicalSortMergeJoin);
+
+impl PlanNode for PhysicalSortMergeJoin {
+    fn schema(&self) -> Vec<ColumnDesc> {
+        self.logical().schema()
+    }
+
+    fn estimated_cardinality(&self) -> usize {
+        self.logical().estimated_cardinality()
+    }
+}
+impl fmt::Display for PhysicalSortMergeJoin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            indoc! {"
+			PhysicalSortMergeJoin:
+			  op {:?},
+			  predicate: {}"},
+            self.logical().join_op(),
+            self.logical().predicate()
+        )
+    }
+}
@@ -31,15 +31,14 @@ impl PlanNode for PhysicalTableScan {
     fn schema(&self) -> Vec<ColumnDesc> {
         self.logical().schema()
     }
-    // TODO: get statistics from storage system
     fn estimated_cardinality(&self) -> usize {
-        1
+        self.logical().estimated_cardinality()
     }
 }
 
 impl fmt::Display for PhysicalTableScan {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(
+        write!(
             f,
             indoc! {"
 			PhysicalTableScan:
@@ -59,6 +58,19 @@ impl fmt::Display for PhysicalTableScan {
             self.logical()
                 .expr()
                 .map_or_else(|| "None".to_string(), |expr| format!("{:?}", expr))
-        )
+        )?;
+        if let Some(epoch) = self.logical().as_of_epoch() {
+            write!(f, ",\n  as_of_epoch: {}", epoch)?;
+        }
+        let logical = self.logical();
+        if !logical.begin_keys().is_empty() || !logical.end_keys().is_empty() {
+            write!(
+                f,
+                ",\n  key_range: [{}] to [{}]",
+                logical.begin_keys().iter().join(", "),
+                logical.end_keys().iter().join(", ")
+            )?;
+        }
+        writeln!(f)
     }
 }
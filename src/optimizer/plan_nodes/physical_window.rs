@@ -0,0 +1,56 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt;
+
+use indoc::indoc;
+use serde::Serialize;
+
+use super::*;
+
+/// The physical plan of window functions.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhysicalWindow {
+    logical: LogicalWindow,
+}
+
+impl PhysicalWindow {
+    pub fn new(logical: LogicalWindow) -> Self {
+        Self { logical }
+    }
+
+    /// Get a reference to the physical window's logical.
+    pub fn logical(&self) -> &LogicalWindow {
+        &self.logical
+    }
+}
+
+impl PlanTreeNodeUnary for PhysicalWindow {
+    fn child(&self) -> PlanRef {
+        self.logical.child()
+    }
+    #[must_use]
+    fn clone_with_child(&self, child: PlanRef) -> Self {
+        Self::new(self.logical().clone_with_child(child))
+    }
+}
+impl_plan_tree_node_for_unary!(PhysicalWindow);
+impl PlanNode for PhysicalWindow {
+    fn schema(&self) -> Vec<ColumnDesc> {
+        self.logical().schema()
+    }
+
+    fn estimated_cardinality(&self) -> usize {
+        self.child().estimated_cardinality()
+    }
+}
+impl fmt::Display for PhysicalWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            indoc! {"
+                PhysicalWindow:
+                  {:?}"},
+            self.logical().window_calls()
+        )
+    }
+}
@@ -2,8 +2,15 @@
 
 use std::sync::Arc;
 
+use itertools::Itertools;
+
 use super::*;
+use crate::binder::{BoundBinaryOp, BoundExpr, BoundInputRef};
+use crate::catalog::ColumnDesc;
+use crate::optimizer::expr_utils::conjunctions;
 use crate::optimizer::plan_nodes::{LogicalTableScan, PlanTreeNodeUnary};
+use crate::parser::BinaryOperator::*;
+use crate::types::DataValue;
 
 pub struct FilterScanRule {}
 
@@ -12,13 +19,141 @@ impl Rule for FilterScanRule {
         let filter = plan.as_logical_filter()?;
         let child = filter.child();
         let scan = child.as_logical_table_scan()?.clone();
-        Ok(Arc::new(LogicalTableScan::new(
-            scan.table_ref_id(),
-            scan.column_ids().to_vec(),
-            scan.column_descs().to_vec(),
-            scan.with_row_handler(),
-            scan.is_sorted(),
-            Some(filter.expr().clone()),
-        )))
+        let (begin_keys, end_keys) = key_range(scan.column_descs(), filter.expr());
+        Ok(Arc::new(
+            LogicalTableScan::new(
+                scan.table_ref_id(),
+                scan.column_ids().to_vec(),
+                scan.column_descs().to_vec(),
+                scan.with_row_handler(),
+                scan.is_sorted(),
+                Some(filter.expr().clone()),
+                scan.as_of_epoch(),
+            )
+            .with_key_range(begin_keys, end_keys),
+        ))
+    }
+}
+
+/// Derive the inclusive range of the primary key from comparisons between the primary key and
+/// constants in the conjunctions of `expr`, e.g. `pk BETWEEN a AND b`.
+///
+/// The filter is still evaluated on the range, so the range can be looser than the filter.
+fn key_range(column_descs: &[ColumnDesc], expr: &BoundExpr) -> (Vec<DataValue>, Vec<DataValue>) {
+    let primary_keys = column_descs
+        .iter()
+        .positions(|desc| desc.is_primary())
+        .collect_vec();
+    let key_idx = match primary_keys[..] {
+        [idx] => idx,
+        _ => return (vec![], vec![]),
+    };
+    let kind = column_descs[key_idx].datatype().physical_kind();
+
+    let mut begin: Option<DataValue> = None;
+    let mut end: Option<DataValue> = None;
+    for conjunction in conjunctions(expr.clone()) {
+        let (op, left_expr, right_expr) = match conjunction {
+            BoundExpr::BinaryOp(BoundBinaryOp {
+                op,
+                left_expr,
+                right_expr,
+                ..
+            }) => (op, *left_expr, *right_expr),
+            _ => continue,
+        };
+        // normalize to `pk op value`
+        let (op, value) = match (left_expr, right_expr) {
+            (BoundExpr::InputRef(BoundInputRef { index, .. }), BoundExpr::Constant(value))
+                if index == key_idx =>
+            {
+                (op, value)
+            }
+            (BoundExpr::Constant(value), BoundExpr::InputRef(BoundInputRef { index, .. }))
+                if index == key_idx =>
+            {
+                let op = match op {
+                    Gt => Lt,
+                    GtEq => LtEq,
+                    Lt => Gt,
+                    LtEq => GtEq,
+                    op => op,
+                };
+                (op, value)
+            }
+            _ => continue,
+        };
+        if value.data_type().map(|ty| ty.physical_kind()) != Some(kind.clone()) {
+            continue;
+        }
+        let (is_lower, is_upper) = match op {
+            Eq => (true, true),
+            Gt | GtEq => (true, false),
+            Lt | LtEq => (false, true),
+            _ => continue,
+        };
+        if is_lower && begin.as_ref().map_or(true, |begin| value > *begin) {
+            begin = Some(value.clone());
+        }
+        if is_upper && end.as_ref().map_or(true, |end| value < *end) {
+            end = Some(value);
+        }
+    }
+    (begin.into_iter().collect(), end.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::BinaryOperator;
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    fn cmp(op: BinaryOperator, left_expr: BoundExpr, right_expr: BoundExpr) -> BoundExpr {
+        BoundExpr::BinaryOp(BoundBinaryOp {
+            op,
+            left_expr: Box::new(left_expr),
+            right_expr: Box::new(right_expr),
+            return_type: Some(DataTypeKind::Boolean.not_null()),
+        })
+    }
+
+    #[test]
+    fn test_key_range() {
+        let ty = DataTypeKind::Int(None).not_null();
+        let column_descs = vec![
+            ty.clone().to_column("v1".into()),
+            ty.clone().to_column_primary_key("k".into()),
+        ];
+        let col = |index| {
+            BoundExpr::InputRef(BoundInputRef {
+                index,
+                return_type: ty.clone(),
+            })
+        };
+        let int = |v| BoundExpr::Constant(DataValue::Int32(v));
+
+        // k BETWEEN 3 AND 10 AND v1 > 5 AND 8 >= k AND k > 1
+        let expr = [
+            cmp(GtEq, col(1), int(3)),
+            cmp(LtEq, col(1), int(10)),
+            cmp(Gt, col(0), int(5)),
+            cmp(GtEq, int(8), col(1)),
+            cmp(Gt, col(1), int(1)),
+        ]
+        .into_iter()
+        .reduce(|left, right| cmp(And, left, right))
+        .unwrap();
+        assert_eq!(
+            key_range(&column_descs, &expr),
+            (vec![DataValue::Int32(3)], vec![DataValue::Int32(8)])
+        );
+
+        // only conjunctions can bound the key
+        let expr = cmp(Or, cmp(Eq, col(1), int(3)), cmp(Eq, col(1), int(5)));
+        assert_eq!(key_range(&column_descs, &expr), (vec![], vec![]));
+
+        // constants of other types can't be used to seek
+        let expr = cmp(Eq, col(1), BoundExpr::Constant(DataValue::Int64(3)));
+        assert_eq!(key_range(&column_descs, &expr), (vec![], vec![]));
     }
 }
@@ -0,0 +1,167 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+//! Statistics of tables and cardinality estimation of plans.
+//!
+//! The statistics are collected from the block statistics of the storage before optimization,
+//! and attached to [`LogicalTableScan`]s by [`StatisticsAttacher`]. Plan nodes estimate their
+//! output cardinality and the number of distinct values of their columns from them, which are
+//! used to reorder joins and to choose the build side of hash joins.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::logical_plan_rewriter::PlanRewriter;
+use super::plan_nodes::{LogicalTableScan, PlanRef};
+use crate::binder::BoundExpr;
+use crate::catalog::TableRefId;
+use crate::parser::{BinaryOperator, UnaryOperator};
+use crate::types::{ColumnId, DataValue};
+
+/// Statistics of a table.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TableStatistics {
+    /// Number of rows in the table.
+    pub row_count: usize,
+    /// Upper bound of the number of distinct values of each column.
+    pub distinct_values: HashMap<ColumnId, usize>,
+}
+
+/// Statistics of the tables used by a query.
+pub type Statistics = HashMap<TableRefId, Arc<TableStatistics>>;
+
+/// Selectivity of an equality predicate on columns of unknown distinct values.
+const EQ_SELECTIVITY: f64 = 0.1;
+
+/// Selectivity of a range predicate.
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// Selectivity of other predicates.
+const DEFAULT_SELECTIVITY: f64 = 0.5;
+
+/// Estimate the fraction of input rows satisfying `expr`. `distinct_values` gives the estimated
+/// number of distinct values of an input column, or `None` if unknown.
+pub fn selectivity(expr: &BoundExpr, distinct_values: &dyn Fn(usize) -> Option<usize>) -> f64 {
+    use BinaryOperator::*;
+
+    let column_distinct_values = |expr: &BoundExpr| match expr {
+        BoundExpr::InputRef(input_ref) => distinct_values(input_ref.index),
+        _ => None,
+    };
+    // `a = b` matches `1 / max(ndv(a), ndv(b))` of the rows
+    let eq = |left: &BoundExpr, right: &BoundExpr| match (
+        column_distinct_values(left),
+        column_distinct_values(right),
+    ) {
+        (Some(l), Some(r)) => 1.0 / l.max(r).max(1) as f64,
+        (Some(n), None) | (None, Some(n)) => 1.0 / n.max(1) as f64,
+        (None, None) => EQ_SELECTIVITY,
+    };
+
+    match expr {
+        BoundExpr::Constant(DataValue::Bool(true)) => 1.0,
+        BoundExpr::Constant(DataValue::Bool(false) | DataValue::Null) => 0.0,
+        BoundExpr::BinaryOp(op) => {
+            let (left, right) = (&*op.left_expr, &*op.right_expr);
+            match op.op {
+                And => selectivity(left, distinct_values) * selectivity(right, distinct_values),
+                Or => {
+                    let left = selectivity(left, distinct_values);
+                    let right = selectivity(right, distinct_values);
+                    left + right - left * right
+                }
+                Eq => eq(left, right),
+                NotEq => 1.0 - eq(left, right),
+                Lt | LtEq | Gt | GtEq => RANGE_SELECTIVITY,
+                _ => DEFAULT_SELECTIVITY,
+            }
+        }
+        BoundExpr::UnaryOp(op) if op.op == UnaryOperator::Not => {
+            1.0 - selectivity(&op.expr, distinct_values)
+        }
+        BoundExpr::InList(in_list) => {
+            let eq = match column_distinct_values(&in_list.expr) {
+                Some(n) => 1.0 / n.max(1) as f64,
+                None => EQ_SELECTIVITY,
+            };
+            let selectivity = (eq * in_list.list.len() as f64).min(1.0);
+            match in_list.negated {
+                true => 1.0 - selectivity,
+                false => selectivity,
+            }
+        }
+        _ => DEFAULT_SELECTIVITY,
+    }
+}
+
+/// Attach the statistics of tables to [`LogicalTableScan`]s.
+pub struct StatisticsAttacher<'a> {
+    pub statistics: &'a Statistics,
+}
+
+impl PlanRewriter for StatisticsAttacher<'_> {
+    fn rewrite_logical_table_scan(&mut self, plan: &LogicalTableScan) -> PlanRef {
+        match self.statistics.get(&plan.table_ref_id()) {
+            Some(statistics) => Arc::new(plan.clone().with_statistics(statistics.clone())),
+            None => Arc::new(plan.clone()),
+        }
+    }
+}
+
+/// Get the tables scanned by the plan.
+pub fn scanned_tables(plan: &PlanRef) -> Vec<TableRefId> {
+    let mut tables = vec![];
+    if let Ok(scan) = plan.as_logical_table_scan() {
+        tables.push(scan.table_ref_id());
+    }
+    for child in plan.children() {
+        for table in scanned_tables(&child) {
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binder::{BoundBinaryOp, BoundInputRef};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    #[test]
+    fn test_selectivity() {
+        let ty = DataTypeKind::Int(None).not_null();
+        let col = |index| {
+            Box::new(BoundExpr::InputRef(BoundInputRef {
+                index,
+                return_type: ty.clone(),
+            }))
+        };
+        let binary = |op, left_expr, right_expr| {
+            BoundExpr::BinaryOp(BoundBinaryOp {
+                op,
+                left_expr,
+                right_expr,
+                return_type: Some(DataTypeKind::Boolean.not_null()),
+            })
+        };
+        let constant = Box::new(BoundExpr::Constant(DataValue::Int32(1)));
+        let distinct_values = |index| [Some(10), Some(100), None][index];
+
+        let expr = binary(BinaryOperator::Eq, col(0), constant.clone());
+        assert_eq!(selectivity(&expr, &distinct_values), 0.1);
+        let expr = binary(BinaryOperator::Eq, col(0), col(1));
+        assert_eq!(selectivity(&expr, &distinct_values), 0.01);
+        let expr = binary(BinaryOperator::Eq, col(2), constant);
+        assert_eq!(selectivity(&expr, &distinct_values), EQ_SELECTIVITY);
+        let expr = binary(
+            BinaryOperator::And,
+            Box::new(binary(BinaryOperator::Eq, col(0), col(1))),
+            Box::new(binary(BinaryOperator::Eq, col(1), col(2))),
+        );
+        assert!((selectivity(&expr, &distinct_values) - 0.0001).abs() < 1e-12);
+    }
+}
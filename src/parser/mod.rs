@@ -7,9 +7,124 @@ pub use sqlparser::ast::*;
 use sqlparser::dialect::PostgreSqlDialect;
 use sqlparser::parser::Parser;
 pub use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, Tokenizer};
 
 /// Parse the SQL string into a list of ASTs.
+///
+/// Besides the PostgreSQL dialect, time travel queries `FROM t AS OF EPOCH n` are accepted. As
+/// [`sqlparser`] doesn't support this clause, it is rewritten into the table arguments
+/// `t(epoch => n)` before parsing. Likewise, aggregate filters `f(x) FILTER (WHERE cond)` are
+/// rewritten into the function arguments `f(x, filter => cond)`.
 pub fn parse(sql: &str) -> Result<Vec<Statement>, ParserError> {
     let dialect = PostgreSqlDialect {};
-    Parser::parse_sql(&dialect, sql)
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    let tokens = rewrite_agg_filter(rewrite_as_of_epoch(tokens));
+    let mut parser = Parser::new(tokens, &dialect);
+
+    // Same as `Parser::parse_sql`.
+    let mut stmts = Vec::new();
+    let mut expecting_statement_delimiter = false;
+    loop {
+        while parser.consume_token(&Token::SemiColon) {
+            expecting_statement_delimiter = false;
+        }
+        if parser.peek_token() == Token::EOF {
+            break;
+        }
+        if expecting_statement_delimiter {
+            return parser.expected("end of statement", parser.peek_token());
+        }
+        stmts.push(parser.parse_statement()?);
+        expecting_statement_delimiter = true;
+    }
+    Ok(stmts)
+}
+
+fn is_word(token: &Token, word: &str) -> bool {
+    match token {
+        Token::Word(w) => w.quote_style.is_none() && w.value.eq_ignore_ascii_case(word),
+        _ => false,
+    }
+}
+
+/// Rewrite `AS OF EPOCH n` into `(epoch => n)`.
+fn rewrite_as_of_epoch(tokens: Vec<Token>) -> Vec<Token> {
+    // whitespaces are skipped by the parser anyway
+    let tokens: Vec<Token> = tokens
+        .into_iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .collect();
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i..] {
+            [as_, of, epoch, Token::Number(n, long), ..]
+                if is_word(as_, "AS") && is_word(of, "OF") && is_word(epoch, "EPOCH") =>
+            {
+                output.extend([
+                    Token::LParen,
+                    Token::make_word("epoch", None),
+                    Token::RArrow,
+                    Token::Number(n.clone(), *long),
+                    Token::RParen,
+                ]);
+                i += 4;
+            }
+            _ => {
+                output.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+/// Rewrite `) FILTER (WHERE` into `, filter =>`, so that the closing parenthesis of the filter
+/// closes the function arguments instead. Whitespaces must have been removed.
+fn rewrite_agg_filter(tokens: Vec<Token>) -> Vec<Token> {
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i..] {
+            [Token::RParen, filter, Token::LParen, where_, ..]
+                if is_word(filter, "FILTER") && is_word(where_, "WHERE") =>
+            {
+                // no comma after an empty argument list
+                if output.last() != Some(&Token::LParen) {
+                    output.push(Token::Comma);
+                }
+                output.extend([Token::make_word("filter", None), Token::RArrow]);
+                i += 4;
+            }
+            _ => {
+                output.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_of_epoch() {
+        let stmts = parse("select * from t as of epoch 42 as x, u where a = 1").unwrap();
+        assert_eq!(
+            stmts[0].to_string(),
+            "SELECT * FROM t(epoch => 42) AS x, u WHERE a = 1"
+        );
+    }
+
+    #[test]
+    fn test_agg_filter() {
+        let stmts = parse("select count(*) filter (where a > (1)), sum(b) FILTER (WHERE c) from t")
+            .unwrap();
+        assert_eq!(
+            stmts[0].to_string(),
+            "SELECT count(*, filter => a > (1)), sum(b, filter => c) FROM t"
+        );
+    }
 }
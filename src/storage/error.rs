@@ -27,6 +27,12 @@ pub enum StorageError {
     Decode(String),
     #[error("Invalid checksum: found {0}, expected {1}")]
     Checksum(u64, u64),
+    #[error("{0} is not supported")]
+    Unsupported(&'static str),
+    #[error("invalid transaction state: {0}")]
+    TransactionState(&'static str),
+    #[error("transaction conflict on table {0} rowset {1}, please retry")]
+    Conflict(u32, u32),
     #[error("Prost encode error: {0}")]
     ProstEncode(prost::EncodeError),
     #[error("Prost decode error: {0}")]
@@ -105,6 +111,23 @@ impl TracedStorageError {
     pub fn checksum(found: u64, expected: u64) -> Self {
         StorageError::Checksum(found, expected).into()
     }
+
+    pub fn unsupported(feature: &'static str) -> Self {
+        StorageError::Unsupported(feature).into()
+    }
+
+    pub fn transaction_state(message: &'static str) -> Self {
+        StorageError::TransactionState(message).into()
+    }
+
+    pub fn conflict(table_id: u32, rowset_id: u32) -> Self {
+        StorageError::Conflict(table_id, rowset_id).into()
+    }
+
+    /// Whether the failed operation may succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.source, StorageError::Conflict(..))
+    }
 }
 
 pub type StorageResult<T> = std::result::Result<T, TracedStorageError>;
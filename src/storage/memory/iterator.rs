@@ -7,7 +7,7 @@ use std::sync::Arc;
 use bitvec::prelude::BitVec;
 
 use crate::array::{ArrayImpl, DataChunk, I64Array};
-use crate::storage::{StorageColumnRef, StorageResult, TxnIterator};
+use crate::storage::{ScanPartition, StorageColumnRef, StorageResult, TxnIterator};
 
 /// An iterator over all data in a transaction.
 ///
@@ -18,6 +18,8 @@ pub struct InMemoryTxnIterator {
     chunks: Arc<Vec<DataChunk>>,
     deleted_rows: Arc<HashSet<usize>>,
     col_idx: Vec<StorageColumnRef>,
+    /// The chunks to scan.
+    partition: ScanPartition,
     cnt: usize,
     row_cnt: usize,
 }
@@ -27,10 +29,12 @@ impl InMemoryTxnIterator {
         chunks: Arc<Vec<DataChunk>>,
         deleted_rows: Arc<HashSet<usize>>,
         col_idx: &[StorageColumnRef],
+        partition: ScanPartition,
     ) -> Self {
         Self {
             chunks,
             col_idx: col_idx.to_vec(),
+            partition,
             cnt: 0,
             row_cnt: 0,
             deleted_rows,
@@ -41,6 +45,11 @@ impl InMemoryTxnIterator {
         &mut self,
         _expected_size: Option<usize>,
     ) -> StorageResult<Option<DataChunk>> {
+        // skip chunks of other partitions
+        while self.cnt < self.chunks.len() && !self.partition.contains(self.cnt) {
+            self.row_cnt += self.chunks[self.cnt].cardinality();
+            self.cnt += 1;
+        }
         if self.cnt >= self.chunks.len() {
             Ok(None)
         } else {
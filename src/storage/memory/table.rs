@@ -72,6 +72,8 @@ impl Table for InMemoryTable {
         impl Future<Output = StorageResult<Self::TransactionType>> + Send + 'a;
     type UpdateResultFuture<'a> =
         impl Future<Output = StorageResult<Self::TransactionType>> + Send + 'a;
+    type ReadAsOfResultFuture<'a> =
+        impl Future<Output = StorageResult<Self::TransactionType>> + Send + 'a;
 
     fn columns(&self) -> StorageResult<Arc<[ColumnCatalog]>> {
         Ok(self.columns.clone())
@@ -92,4 +94,12 @@ impl Table for InMemoryTable {
     fn update(&self) -> Self::UpdateResultFuture<'_> {
         async move { InMemoryTransaction::start(self) }
     }
+
+    fn read_as_of(&self, _epoch: u64) -> Self::ReadAsOfResultFuture<'_> {
+        async move {
+            Err(TracedStorageError::unsupported(
+                "time travel on in-memory storage",
+            ))
+        }
+    }
 }
@@ -12,7 +12,7 @@ use super::{InMemoryRowHandler, InMemoryTable, InMemoryTxnIterator};
 use crate::array::{ArrayBuilderImpl, ArrayImplBuilderPickExt, ArrayImplSortExt, DataChunk};
 use crate::binder::BoundExpr;
 use crate::catalog::{find_sort_key_id, ColumnCatalog};
-use crate::storage::{StorageColumnRef, StorageResult, Transaction};
+use crate::storage::{ScanPartition, StorageColumnRef, StorageResult, Transaction};
 use crate::types::DataValue;
 
 /// A transaction running on `InMemoryStorage`.
@@ -123,6 +123,7 @@ impl Transaction for InMemoryTransaction {
         is_sorted: bool,
         reversed: bool,
         expr: Option<BoundExpr>,
+        partition: ScanPartition,
     ) -> Self::ScanResultFuture<'a> {
         async move {
             assert!(expr.is_none(), "MemTxn doesn't support filter scan");
@@ -147,6 +148,7 @@ impl Transaction for InMemoryTransaction {
                 snapshot,
                 self.deleted_rows.clone(),
                 col_idx,
+                partition,
             ))
         }
     }
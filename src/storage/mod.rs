@@ -6,7 +6,9 @@ mod memory;
 pub use memory::InMemoryStorage;
 
 mod secondary;
-pub use secondary::{SecondaryStorage, StorageOptions as SecondaryStorageOptions};
+pub use secondary::{
+    BlockCompression, SecondaryStorage, StorageOptions as SecondaryStorageOptions,
+};
 
 mod error;
 pub use error::{StorageError, StorageResult, TracedStorageError};
@@ -30,6 +32,10 @@ pub enum StorageImpl {
     SecondaryStorage(Arc<SecondaryStorage>),
 }
 
+/// Identifies a session, i.e. a client connection, whose statements share the transaction
+/// started by `BEGIN`.
+pub type SessionId = u64;
+
 /// A trait for implementing `From` and `Into` [`StorageImpl`] with `enum_dispatch`.
 #[enum_dispatch]
 pub trait StorageDispatch {}
@@ -50,6 +56,36 @@ impl StorageImpl {
             Self::InMemoryStorage(_) => false,
         }
     }
+
+    /// Start a transaction spanning multiple statements in `session`.
+    pub fn begin_transaction(&self, session: SessionId) -> StorageResult<()> {
+        match self {
+            Self::SecondaryStorage(storage) => storage.begin_transaction(session),
+            Self::InMemoryStorage(_) => Err(TracedStorageError::unsupported(
+                "transaction on in-memory storage",
+            )),
+        }
+    }
+
+    /// Commit the transaction started by `BEGIN` in `session`.
+    pub async fn commit_transaction(&self, session: SessionId) -> StorageResult<()> {
+        match self {
+            Self::SecondaryStorage(storage) => storage.commit_transaction(session).await,
+            Self::InMemoryStorage(_) => Err(TracedStorageError::unsupported(
+                "transaction on in-memory storage",
+            )),
+        }
+    }
+
+    /// Abort the transaction started by `BEGIN` in `session`.
+    pub async fn rollback_transaction(&self, session: SessionId) -> StorageResult<()> {
+        match self {
+            Self::SecondaryStorage(storage) => storage.rollback_transaction(session).await,
+            Self::InMemoryStorage(_) => Err(TracedStorageError::unsupported(
+                "transaction on in-memory storage",
+            )),
+        }
+    }
 }
 
 /// Represents a storage engine.
@@ -80,6 +116,16 @@ pub trait Storage: Sync + Send + 'static {
 
     fn get_table(&self, table_id: TableRefId) -> StorageResult<Self::TableType>;
 
+    /// Get a table whose transactions run in the transaction started by `BEGIN` in `session`,
+    /// if any.
+    fn get_table_in_session(
+        &self,
+        table_id: TableRefId,
+        _session: SessionId,
+    ) -> StorageResult<Self::TableType> {
+        self.get_table(table_id)
+    }
+
     fn drop_table(&self, table_id: TableRefId) -> Self::DropTableResultFuture<'_>;
 }
 
@@ -96,6 +142,9 @@ pub trait Table: Sync + Send + Clone + 'static {
     where
         Self: 'a;
     type UpdateResultFuture<'a>: Future<Output = StorageResult<Self::TransactionType>> + Send + 'a
+    where
+        Self: 'a;
+    type ReadAsOfResultFuture<'a>: Future<Output = StorageResult<Self::TransactionType>> + Send + 'a
     where
         Self: 'a;
 
@@ -111,6 +160,9 @@ pub trait Table: Sync + Send + Clone + 'static {
     /// Begin a txn that might delete or update rows
     fn update(&self) -> Self::UpdateResultFuture<'_>;
 
+    /// Begin a read-only txn on the snapshot of a past epoch
+    fn read_as_of(&self, epoch: u64) -> Self::ReadAsOfResultFuture<'_>;
+
     /// Get table id
     fn table_id(&self) -> TableRefId;
 }
@@ -127,6 +179,26 @@ pub enum StorageColumnRef {
     Idx(u32),
 }
 
+/// A part of a table to scan, i.e. the `index`-th of `count` disjoint parts of the table.
+///
+/// Scanning all parts of a table in parallel is the same as scanning the whole table, but the
+/// order of rows is not preserved.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScanPartition {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl ScanPartition {
+    /// The whole table.
+    pub const ALL: Self = ScanPartition { index: 0, count: 1 };
+
+    /// Returns true if the `i`-th unit of data (e.g. a RowSet or a chunk) belongs to this part.
+    pub fn contains(&self, i: usize) -> bool {
+        i % self.count == self.index
+    }
+}
+
 /// A temporary reference to a row in table.
 pub trait RowHandler: Sync + Send + 'static {
     fn from_column(column: &ArrayImpl, idx: usize) -> Self;
@@ -135,7 +207,7 @@ pub trait RowHandler: Sync + Send + 'static {
 /// Represents a transaction in storage engine.
 pub trait Transaction: Sync + Send + 'static {
     /// Type of the table iterator
-    type TxnIteratorType: TxnIterator;
+    type TxnIteratorType: TxnIterator + 'static;
 
     /// Type of the unique reference to a row
     type RowHandlerType: RowHandler;
@@ -155,7 +227,8 @@ pub trait Transaction: Sync + Send + 'static {
     type AbortResultFuture<'a>: Future<Output = StorageResult<()>> + Send + 'a
     where
         Self: 'a;
-    /// Scan one or multiple columns.
+    /// Scan one or multiple columns of a part of the table.
+    #[allow(clippy::too_many_arguments)]
     fn scan<'a>(
         &'a self,
         begin_sort_key: &'a [DataValue],
@@ -164,6 +237,7 @@ pub trait Transaction: Sync + Send + 'static {
         is_sorted: bool,
         reversed: bool,
         expr: Option<BoundExpr>,
+        partition: ScanPartition,
     ) -> Self::ScanResultFuture<'a>;
 
     /// Append data to the table. Generally, `columns` should be in the same order as
@@ -6,7 +6,11 @@
 
 mod blob_block_builder;
 mod blob_block_iterator;
+mod blob_nullable_block_builder;
+mod blob_nullable_block_iterator;
 mod char_block_builder;
+mod dict_block_builder;
+mod dict_block_iterator;
 mod fake_block_iterator;
 mod primitive_block_builder;
 mod primitive_block_iterator;
@@ -17,7 +21,11 @@ mod rle_block_iterator;
 
 pub use blob_block_builder::*;
 pub use blob_block_iterator::*;
+pub use blob_nullable_block_builder::*;
+pub use blob_nullable_block_iterator::*;
 pub use char_block_builder::*;
+pub use dict_block_builder::*;
+pub use dict_block_iterator::*;
 pub use fake_block_iterator::*;
 pub use primitive_block_builder::*;
 pub use primitive_block_iterator::*;
@@ -30,7 +38,9 @@ pub use rle_block_builder::*;
 pub use rle_block_iterator::*;
 mod block_index_builder;
 pub use block_index_builder::*;
+mod compression;
 use bytes::{Buf, BufMut, Bytes};
+pub use compression::*;
 use risinglight_proto::rowset::block_checksum::ChecksumType;
 use risinglight_proto::rowset::block_index::BlockType;
 
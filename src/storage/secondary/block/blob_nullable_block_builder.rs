@@ -0,0 +1,108 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::marker::PhantomData;
+
+use bitvec::prelude::{BitVec, Lsb0};
+use bytes::BufMut;
+use risinglight_proto::rowset::BlockStatistics;
+
+use super::super::statistics::StatisticsBuilder;
+use super::BlockBuilder;
+use crate::storage::secondary::encode::BlobEncode;
+
+/// Encodes offset and data into a block, with null element support. Null elements are stored as
+/// empty data. The layout is
+/// ```plain
+/// | offset (u32) | offset | offset | data | data | data | bitmap (u8) |
+/// ```
+pub struct PlainBlobNullableBlockBuilder<T: BlobEncode + ?Sized> {
+    data: Vec<u8>,
+    offsets: Vec<u32>,
+    bitmap: BitVec<u8, Lsb0>,
+    target_size: usize,
+
+    phantom: PhantomData<T>,
+}
+
+impl<T: BlobEncode + ?Sized> PlainBlobNullableBlockBuilder<T> {
+    pub fn new(target_size: usize) -> Self {
+        let data = Vec::with_capacity(target_size);
+        Self {
+            data,
+            offsets: vec![],
+            bitmap: BitVec::new(),
+            target_size,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BlobEncode + ?Sized> BlockBuilder<T::ArrayType> for PlainBlobNullableBlockBuilder<T> {
+    fn append(&mut self, item: Option<&T>) {
+        if let Some(item) = item {
+            self.data.extend(item.to_byte_slice());
+        }
+        self.offsets.push(self.data.len() as u32);
+        self.bitmap.push(item.is_some());
+    }
+
+    fn estimated_size(&self) -> usize {
+        let bitmap_byte_len = (self.bitmap.len() + 7) / 8;
+        self.data.len() + self.offsets.len() * std::mem::size_of::<u32>() + bitmap_byte_len
+    }
+
+    fn should_finish(&self, next_item: &Option<&T>) -> bool {
+        !self.offsets.is_empty()
+            && self.estimated_size()
+                + next_item.map(|x| x.len()).unwrap_or(0)
+                + std::mem::size_of::<u32>()
+                + 1
+                > self.target_size
+    }
+
+    fn get_statistics(&self) -> Vec<BlockStatistics> {
+        let mut stats_builder = StatisticsBuilder::new();
+        let mut last_pos: usize = 0;
+        for (idx, pos) in self.offsets.iter().enumerate() {
+            let cur_pos = *pos as usize;
+            if self.bitmap[idx] {
+                stats_builder.add_item(Some(&self.data[last_pos..cur_pos]));
+            } else {
+                stats_builder.add_item(None);
+            }
+            last_pos = cur_pos;
+        }
+        stats_builder.get_statistics()
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut encoded_data = vec![];
+        for offset in self.offsets {
+            encoded_data.put_u32_le(offset);
+        }
+        encoded_data.extend(self.data);
+        encoded_data.extend(self.bitmap.as_raw_slice().iter());
+        encoded_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_nullable_str() {
+        let mut builder = PlainBlobNullableBlockBuilder::<str>::new(128);
+        builder.append(Some("233"));
+        builder.append(None);
+        builder.append(Some("23"));
+        assert_eq!(builder.estimated_size(), 5 + 4 * 3 + 1);
+        assert!(!builder.should_finish(&Some("23333333")));
+        let data = builder.finish();
+        // bitmap should be 101 and Lsb0, so u8 will be 0b101 = 5
+        let expected_data: Vec<u8> = vec![
+            3, 0, 0, 0, 3, 0, 0, 0, 5, 0, 0, 0, b'2', b'3', b'3', b'2', b'3', 5,
+        ];
+        assert_eq!(data, expected_data);
+    }
+}
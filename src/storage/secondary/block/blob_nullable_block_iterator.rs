@@ -0,0 +1,143 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::marker::PhantomData;
+
+use bitvec::prelude::Lsb0;
+use bitvec::slice::BitSlice;
+use bytes::Buf;
+
+use super::{Block, BlockIterator};
+use crate::array::{Array, ArrayBuilder};
+use crate::storage::secondary::encode::BlobEncode;
+
+/// Scans one or several arrays from the nullable blob block content.
+pub struct PlainBlobNullableBlockIterator<T: BlobEncode + ?Sized> {
+    /// Block content
+    block: Block,
+
+    /// Total count of elements in block
+    row_count: usize,
+
+    /// Indicates the beginning row of the next batch
+    next_row: usize,
+
+    phantom: PhantomData<T>,
+}
+
+impl<T: BlobEncode + ?Sized> PlainBlobNullableBlockIterator<T> {
+    pub fn new(block: Block, row_count: usize) -> Self {
+        Self {
+            block,
+            row_count,
+            next_row: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BlobEncode + ?Sized> BlockIterator<T::ArrayType> for PlainBlobNullableBlockIterator<T> {
+    fn next_batch(
+        &mut self,
+        expected_size: Option<usize>,
+        builder: &mut <T::ArrayType as Array>::Builder,
+    ) -> usize {
+        if self.next_row >= self.row_count {
+            return 0;
+        }
+
+        // TODO(chi): error handling on corrupted block
+
+        let mut cnt = 0;
+        const OFFSET: usize = std::mem::size_of::<u32>();
+        let offsets_length = OFFSET * self.row_count;
+        let offset_buffer = &self.block[0..offsets_length];
+        let data_length = (&offset_buffer[offsets_length - OFFSET..]).get_u32_le() as usize;
+        let data_buffer = &self.block[offsets_length..offsets_length + data_length];
+        let bitmap_buffer = &self.block[offsets_length + data_length..];
+        let bitmap_slice = BitSlice::<u8, Lsb0>::from_slice(bitmap_buffer);
+
+        loop {
+            if let Some(expected_size) = expected_size {
+                assert!(expected_size > 0);
+                if cnt >= expected_size {
+                    break;
+                }
+            }
+
+            if self.next_row >= self.row_count {
+                break;
+            }
+
+            if bitmap_slice[self.next_row] {
+                let from;
+                let to;
+
+                if self.next_row == 0 {
+                    let mut cur_offsets = offset_buffer;
+                    from = 0;
+                    to = cur_offsets.get_u32_le() as usize;
+                } else {
+                    let mut cur_offsets = &offset_buffer[(self.next_row - 1) * OFFSET..];
+                    from = cur_offsets.get_u32_le() as usize;
+                    to = cur_offsets.get_u32_le() as usize;
+                }
+                builder.push(Some(T::from_byte_slice(&data_buffer[from..to])));
+            } else {
+                builder.push(None);
+            }
+
+            cnt += 1;
+            self.next_row += 1;
+        }
+
+        cnt
+    }
+
+    fn skip(&mut self, cnt: usize) {
+        self.next_row += cnt;
+    }
+
+    fn remaining_items(&self) -> usize {
+        self.row_count - self.next_row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::array::{ArrayBuilder, ArrayToVecExt, Utf8ArrayBuilder};
+    use crate::storage::secondary::block::{BlockBuilder, PlainBlobNullableBlockBuilder};
+    use crate::storage::secondary::BlockIterator;
+
+    #[test]
+    fn test_scan_nullable_varchar() {
+        let mut builder = PlainBlobNullableBlockBuilder::<str>::new(128);
+        builder.append(Some("233"));
+        builder.append(None);
+        builder.append(Some("23333"));
+        builder.append(None);
+        let data = builder.finish();
+
+        let mut scanner = PlainBlobNullableBlockIterator::<str>::new(Bytes::from(data), 4);
+
+        let mut builder = Utf8ArrayBuilder::new();
+
+        scanner.skip(1);
+        assert_eq!(scanner.remaining_items(), 3);
+
+        assert_eq!(scanner.next_batch(Some(2), &mut builder), 2);
+        assert_eq!(
+            builder.finish().to_vec(),
+            vec![None, Some("23333".to_string())]
+        );
+
+        let mut builder = Utf8ArrayBuilder::new();
+        assert_eq!(scanner.next_batch(Some(2), &mut builder), 1);
+        assert_eq!(builder.finish().to_vec(), vec![None]);
+
+        let mut builder = Utf8ArrayBuilder::new();
+        assert_eq!(scanner.next_batch(None, &mut builder), 0);
+    }
+}
@@ -3,7 +3,9 @@
 use risinglight_proto::rowset::block_index::BlockType;
 use risinglight_proto::rowset::{BlockIndex, BlockStatistics};
 
-use super::{BlockHeader, BLOCK_HEADER_SIZE};
+use super::{compress_block, BlockHeader, BLOCK_HEADER_SIZE};
+use crate::array::Array;
+use crate::storage::secondary::statistics::{BloomFilterBuilder, BloomHash};
 use crate::storage::secondary::{build_checksum, ColumnBuilderOptions};
 
 /// Builds the block index.
@@ -22,6 +24,9 @@ pub struct BlockIndexBuilder {
 
     /// Builder options
     options: ColumnBuilderOptions,
+
+    /// Bloom filter of the current block, if enabled in the options
+    bloom_filter: Option<BloomFilterBuilder>,
 }
 
 impl BlockIndexBuilder {
@@ -31,19 +36,32 @@ impl BlockIndexBuilder {
             last_row_count: 0,
             indexes: vec![],
             block_header: vec![],
+            bloom_filter: options.bloom_filter.then(BloomFilterBuilder::new),
             options,
         }
     }
 
-    /// Record information of a block and produce a new index entry.
+    /// Record information of a block and produce a new index entry. The block will be
+    /// compressed if compression is enabled in the options.
     pub fn finish_block(
         &mut self,
         block_type: BlockType,
         column_data: &mut Vec<u8>,
         block_data: &mut Vec<u8>,
-        stats: Vec<BlockStatistics>,
+        mut stats: Vec<BlockStatistics>,
         first_key: Option<Vec<u8>>,
     ) {
+        if let Some(bloom_filter) = &mut self.bloom_filter {
+            stats.push(bloom_filter.finish());
+        }
+
+        let (block_type, mut block_data) = compress_block(
+            self.options.compression,
+            block_type,
+            std::mem::take(block_data),
+        );
+        let block_data = &mut block_data;
+
         self.indexes.push(BlockIndex {
             offset: column_data.len() as u64,
             length: block_data.len() as u64 + BLOCK_HEADER_SIZE as u64,
@@ -82,6 +100,17 @@ impl BlockIndexBuilder {
         self.row_count += rows;
     }
 
+    /// Add the items of `array` in `range`, which are appended to the current block, into the
+    /// bloom filter of the block if enabled.
+    pub fn add_bloom_filter_items<A: Array>(&mut self, array: &A, range: std::ops::Range<usize>)
+    where
+        A::Item: BloomHash,
+    {
+        if let Some(bloom_filter) = &mut self.bloom_filter {
+            bloom_filter.add_array(array, range);
+        }
+    }
+
     pub fn into_index(self) -> Vec<BlockIndex> {
         self.indexes
     }
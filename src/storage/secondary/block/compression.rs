@@ -0,0 +1,131 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use bytes::{Buf, BufMut};
+use risinglight_proto::rowset::block_index::BlockType;
+
+use super::Block;
+use crate::storage::secondary::StorageResult;
+use crate::storage::TracedStorageError;
+
+/// Compression applied on encoded blocks.
+///
+/// Compression works as a layer over all kinds of blocks: the encoded data of any block builder
+/// is compressed before written to the column file, and is decompressed when read from the
+/// column, before being passed to the block iterator. A compressed block has the type
+/// `ZstdCompress` or `Lz4Compress` in its header, and its data is encoded as follows:
+///
+/// ```plain
+/// | inner_block_type | uncompressed_len | compressed data |
+/// |        4B        |        4B        |    variable     |
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockCompression {
+    /// Store blocks as is
+    None,
+    /// Compress blocks with zstd
+    Zstd,
+    /// Compress blocks with lz4
+    Lz4,
+}
+
+/// Compression level of zstd, `0` means the default level.
+const ZSTD_LEVEL: i32 = 0;
+
+const COMPRESSED_HEADER_SIZE: usize = 4 + 4;
+
+/// Compress an encoded block. If the compressed block is not smaller than the original one, the
+/// block will be kept uncompressed.
+pub fn compress_block(
+    compression: BlockCompression,
+    block_type: BlockType,
+    block_data: Vec<u8>,
+) -> (BlockType, Vec<u8>) {
+    let (compressed_type, compressed) = match compression {
+        BlockCompression::None => return (block_type, block_data),
+        BlockCompression::Zstd => (
+            BlockType::ZstdCompress,
+            zstd::bulk::compress(&block_data, ZSTD_LEVEL).expect("failed to compress block"),
+        ),
+        BlockCompression::Lz4 => (
+            BlockType::Lz4Compress,
+            lz4_flex::block::compress(&block_data),
+        ),
+    };
+    if compressed.len() + COMPRESSED_HEADER_SIZE >= block_data.len() {
+        return (block_type, block_data);
+    }
+
+    let mut data = Vec::with_capacity(compressed.len() + COMPRESSED_HEADER_SIZE);
+    data.put_i32(block_type.into());
+    data.put_u32(block_data.len() as u32);
+    data.extend_from_slice(&compressed);
+    (compressed_type, data)
+}
+
+/// Decompress a block if it is compressed, and return the type of the inner block.
+pub fn decompress_block(block_type: BlockType, block: Block) -> StorageResult<(BlockType, Block)> {
+    if !matches!(block_type, BlockType::ZstdCompress | BlockType::Lz4Compress) {
+        return Ok((block_type, block));
+    }
+
+    let mut header = &block[..];
+    if header.remaining() < COMPRESSED_HEADER_SIZE {
+        return Err(TracedStorageError::decode(
+            "compressed block is smaller than header size",
+        ));
+    }
+    let inner_type = BlockType::from_i32(header.get_i32())
+        .ok_or_else(|| TracedStorageError::decode("expected valid block type"))?;
+    if matches!(inner_type, BlockType::ZstdCompress | BlockType::Lz4Compress) {
+        return Err(TracedStorageError::decode("nested compressed block"));
+    }
+    let uncompressed_len = header.get_u32() as usize;
+
+    let data = match block_type {
+        BlockType::ZstdCompress => zstd::bulk::decompress(header, uncompressed_len)
+            .map_err(|e| TracedStorageError::decode(format!("zstd: {}", e)))?,
+        BlockType::Lz4Compress => lz4_flex::block::decompress(header, uncompressed_len)
+            .map_err(|e| TracedStorageError::decode(format!("lz4: {}", e)))?,
+        _ => unreachable!(),
+    };
+    if data.len() != uncompressed_len {
+        return Err(TracedStorageError::decode("unexpected uncompressed size"));
+    }
+    Ok((inner_type, Block::from(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_block() {
+        let block_data = [1, 2, 3, 4].repeat(256);
+        for (compression, compressed_type) in [
+            (BlockCompression::Zstd, BlockType::ZstdCompress),
+            (BlockCompression::Lz4, BlockType::Lz4Compress),
+        ] {
+            let (block_type, data) =
+                compress_block(compression, BlockType::PlainNullable, block_data.clone());
+            assert_eq!(block_type, compressed_type);
+            assert!(data.len() < block_data.len());
+
+            let (block_type, block) = decompress_block(block_type, data.into()).unwrap();
+            assert_eq!(block_type, BlockType::PlainNullable);
+            assert_eq!(&block[..], &block_data[..]);
+        }
+    }
+
+    #[test]
+    fn test_incompressible_block() {
+        let block_data = vec![1, 2, 3];
+        let (block_type, data) =
+            compress_block(BlockCompression::Zstd, BlockType::Plain, block_data.clone());
+        assert_eq!(block_type, BlockType::Plain);
+        assert_eq!(data, block_data);
+
+        let (block_type, block) = decompress_block(block_type, data.into()).unwrap();
+        assert_eq!(block_type, BlockType::Plain);
+        assert_eq!(&block[..], &block_data[..]);
+    }
+}
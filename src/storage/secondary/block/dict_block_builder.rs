@@ -0,0 +1,120 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::HashMap;
+
+use bytes::BufMut;
+use risinglight_proto::rowset::BlockStatistics;
+
+use super::super::statistics::StatisticsBuilder;
+use super::{BlockBuilder, PlainBlobBlockBuilder};
+use crate::array::Utf8Array;
+
+/// Encodes strings into a block with dictionary encoding. The distinct values are stored in a
+/// plain varchar block, followed by the codes of all items. The layout is
+/// ```plain
+/// | dict_num (u32) | dict_size (u32) | dictionary (varchar block) | code (u16) | code | code |
+/// ```
+pub struct DictBlockBuilder {
+    dict: HashMap<String, u16>,
+    dict_builder: PlainBlobBlockBuilder<str>,
+    codes: Vec<u16>,
+    target_size: usize,
+}
+
+impl DictBlockBuilder {
+    pub fn new(target_size: usize) -> Self {
+        Self {
+            dict: HashMap::new(),
+            dict_builder: PlainBlobBlockBuilder::new(target_size),
+            codes: vec![],
+            target_size,
+        }
+    }
+
+    /// Number of distinct values in the block.
+    pub fn cardinality(&self) -> usize {
+        self.dict.len()
+    }
+
+    /// Number of items in the block.
+    pub fn row_count(&self) -> usize {
+        self.codes.len()
+    }
+}
+
+impl BlockBuilder<Utf8Array> for DictBlockBuilder {
+    fn append(&mut self, item: Option<&str>) {
+        let item = item.expect("nullable item found in non-nullable block builder");
+        let code = match self.dict.get(item) {
+            Some(code) => *code,
+            None => {
+                let code = self.dict.len() as u16;
+                self.dict.insert(item.to_string(), code);
+                self.dict_builder.append(Some(item));
+                code
+            }
+        };
+        self.codes.push(code);
+    }
+
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<u32>() * 2
+            + self.dict_builder.estimated_size()
+            + self.codes.len() * std::mem::size_of::<u16>()
+    }
+
+    fn should_finish(&self, next_item: &Option<&str>) -> bool {
+        if self.codes.is_empty() {
+            return false;
+        }
+        let mut next_size = std::mem::size_of::<u16>();
+        if let Some(item) = next_item {
+            if !self.dict.contains_key(*item) {
+                if self.dict.len() > u16::MAX as usize {
+                    return true;
+                }
+                next_size += item.len() + std::mem::size_of::<u32>();
+            }
+        }
+        self.estimated_size() + next_size > self.target_size
+    }
+
+    fn get_statistics(&self) -> Vec<BlockStatistics> {
+        let mut stats_builder = StatisticsBuilder::new();
+        for item in self.dict.keys() {
+            stats_builder.add_item(Some(item.as_bytes()));
+        }
+        stats_builder.get_statistics()
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut encoded_data = vec![];
+        let dict_data = self.dict_builder.finish();
+        encoded_data.put_u32_le(self.dict.len() as u32);
+        encoded_data.put_u32_le(dict_data.len() as u32);
+        encoded_data.extend(dict_data);
+        for code in self.codes {
+            encoded_data.put_u16_le(code);
+        }
+        encoded_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_dict() {
+        let mut builder = DictBlockBuilder::new(128);
+        for item in ["N", "R", "A", "N", "N", "R"] {
+            builder.append(Some(item));
+        }
+        assert_eq!(builder.cardinality(), 3);
+        assert_eq!(builder.row_count(), 6);
+        assert_eq!(builder.estimated_size(), 8 + 3 * 5 + 6 * 2);
+        assert!(!builder.should_finish(&Some("N")));
+        assert!(builder.should_finish(&Some(&"F".repeat(100))));
+        builder.finish();
+    }
+}
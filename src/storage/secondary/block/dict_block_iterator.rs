@@ -0,0 +1,152 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::sync::Arc;
+
+use bytes::Buf;
+
+use super::{Block, BlockIterator, PlainBlobBlockIterator};
+use crate::array::{ArrayBuilder, Utf8Array, Utf8ArrayBuilder};
+
+/// Decode a dictionary block into `(dict_num, dictionary, codes)`.
+pub fn decode_dict_block(data: Block) -> (usize, Block, Block) {
+    let mut buffer = &data[..];
+    let dict_num = buffer.get_u32_le() as usize;
+    let dict_size = buffer.get_u32_le() as usize;
+    let dict_begin = std::mem::size_of::<u32>() * 2;
+    let dict_data = data.slice(dict_begin..dict_begin + dict_size);
+    let codes = data.slice(dict_begin + dict_size..);
+    (dict_num, dict_data, codes)
+}
+
+/// Scans one or several arrays from the dictionary block content.
+///
+/// The iterator can either produce decoded arrays through [`BlockIterator`], which carry the
+/// dictionary codes (see [`BytesDictionary`](crate::array::BytesDictionary)), or produce the
+/// codes only by [`next_codes`](Self::next_codes).
+pub struct DictBlockIterator {
+    /// Distinct values of the block
+    dict: Arc<Utf8Array>,
+
+    /// Codes of the block
+    codes: Block,
+
+    /// Total count of elements in block
+    row_count: usize,
+
+    /// Indicates the beginning row of the next batch
+    next_row: usize,
+}
+
+impl DictBlockIterator {
+    pub fn new(block: Block, row_count: usize) -> Self {
+        let (dict_num, dict_data, codes) = decode_dict_block(block);
+        let mut dict_iter = PlainBlobBlockIterator::<str>::new(dict_data, dict_num);
+        let mut builder = Utf8ArrayBuilder::with_capacity(dict_num);
+        dict_iter.next_batch(None, &mut builder);
+        Self {
+            dict: Arc::new(builder.finish()),
+            codes,
+            row_count,
+            next_row: 0,
+        }
+    }
+
+    /// Get the dictionary of the block.
+    pub fn dictionary(&self) -> &Arc<Utf8Array> {
+        &self.dict
+    }
+
+    fn code_at(&self, row: usize) -> u32 {
+        let mut buffer = &self.codes[row * std::mem::size_of::<u16>()..];
+        buffer.get_u16_le() as u32
+    }
+
+    /// Get a batch of dictionary codes from the block.
+    pub fn next_codes(&mut self, expected_size: Option<usize>, codes: &mut Vec<u32>) -> usize {
+        let cnt = self.batch_size(expected_size);
+        codes.extend((self.next_row..self.next_row + cnt).map(|row| self.code_at(row)));
+        self.next_row += cnt;
+        cnt
+    }
+
+    fn batch_size(&self, expected_size: Option<usize>) -> usize {
+        let remaining = self.remaining_items();
+        match expected_size {
+            Some(expected_size) => {
+                assert!(expected_size > 0);
+                expected_size.min(remaining)
+            }
+            None => remaining,
+        }
+    }
+}
+
+impl BlockIterator<Utf8Array> for DictBlockIterator {
+    fn next_batch(
+        &mut self,
+        expected_size: Option<usize>,
+        builder: &mut Utf8ArrayBuilder,
+    ) -> usize {
+        let cnt = self.batch_size(expected_size);
+        for row in self.next_row..self.next_row + cnt {
+            builder.push_dictionary_code(&self.dict, self.code_at(row));
+        }
+        self.next_row += cnt;
+        cnt
+    }
+
+    fn skip(&mut self, cnt: usize) {
+        self.next_row += cnt;
+    }
+
+    fn remaining_items(&self) -> usize {
+        self.row_count - self.next_row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::array::ArrayToVecExt;
+    use crate::storage::secondary::block::{BlockBuilder, DictBlockBuilder};
+
+    #[test]
+    fn test_scan_dict() {
+        let mut builder = DictBlockBuilder::new(128);
+        for item in ["N", "R", "A", "N", "N", "R"] {
+            builder.append(Some(item));
+        }
+        let data = builder.finish();
+
+        let mut scanner = DictBlockIterator::new(Bytes::from(data), 6);
+        assert_eq!(
+            scanner.dictionary().to_vec(),
+            vec![
+                Some("N".to_string()),
+                Some("R".to_string()),
+                Some("A".to_string())
+            ]
+        );
+
+        scanner.skip(1);
+        assert_eq!(scanner.remaining_items(), 5);
+
+        let mut builder = Utf8ArrayBuilder::new();
+        assert_eq!(scanner.next_batch(Some(2), &mut builder), 2);
+        let array = builder.finish();
+        assert_eq!(
+            array.to_vec(),
+            vec![Some("R".to_string()), Some("A".to_string())]
+        );
+        assert_eq!(array.dictionary().unwrap().codes(), &[1, 2]);
+
+        let mut codes = vec![];
+        assert_eq!(scanner.next_codes(None, &mut codes), 3);
+        assert_eq!(codes, vec![0, 0, 1]);
+
+        let mut builder = Utf8ArrayBuilder::new();
+        assert_eq!(scanner.next_batch(None, &mut builder), 0);
+    }
+}
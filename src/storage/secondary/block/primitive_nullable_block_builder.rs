@@ -56,10 +56,12 @@ impl<T: PrimitiveFixedWidthEncode> BlockBuilder<T::ArrayType>
     }
 
     fn get_statistics(&self) -> Vec<BlockStatistics> {
-        let mut stats_builder = StatisticsBuilder::new();
+        let mut stats_builder = StatisticsBuilder::new_primitive::<T>();
         for (idx, item) in enumerate(self.data.chunks(T::WIDTH)) {
             if self.bitmap[idx] {
                 stats_builder.add_item(Some(item));
+            } else {
+                stats_builder.add_item(None);
             }
         }
         stats_builder.get_statistics()
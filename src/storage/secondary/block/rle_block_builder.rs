@@ -3,6 +3,7 @@
 use std::borrow::Borrow;
 
 use bytes::BufMut;
+use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
 use risinglight_proto::rowset::BlockStatistics;
 
 use super::BlockBuilder;
@@ -23,6 +24,7 @@ where
     rle_counts: Vec<u16>,
     previous_value: Option<<A::Item as ToOwned>::Owned>,
     cur_count: u16,
+    null_count: u64,
 }
 
 impl<A, B> RleBlockBuilder<A, B>
@@ -37,6 +39,7 @@ where
             rle_counts: Vec::new(),
             previous_value: None,
             cur_count: 0,
+            null_count: 0,
         }
     }
 }
@@ -48,6 +51,9 @@ where
     A::Item: PartialEq,
 {
     fn append(&mut self, item: Option<&A::Item>) {
+        if item.is_none() {
+            self.null_count += 1;
+        }
         if self.cur_count == 0 {
             // only happens for the very first append
             self.previous_value = item.map(|x| x.to_owned());
@@ -77,7 +83,14 @@ where
     }
 
     fn get_statistics(&self) -> Vec<BlockStatistics> {
-        self.block_builder.get_statistics()
+        // the inner block only contains one item per run
+        let mut stats = self.block_builder.get_statistics();
+        for stat in &mut stats {
+            if stat.block_stat_type() == BlockStatisticsType::NullCount {
+                stat.body = self.null_count.to_le_bytes().to_vec();
+            }
+        }
+        stats
     }
 
     fn finish(mut self) -> Vec<u8> {
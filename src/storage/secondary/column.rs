@@ -37,10 +37,11 @@ use bytes::Bytes;
 pub use char_column_factory::*;
 use moka::future::Cache;
 
-use super::{Block, BlockCacheKey, BlockHeader, ColumnIndex, BLOCK_HEADER_SIZE};
+use super::{decompress_block, Block, BlockCacheKey, BlockHeader, ColumnIndex, BLOCK_HEADER_SIZE};
 use crate::array::Array;
 use crate::storage::secondary::verify_checksum;
 use crate::storage::{StorageResult, TracedStorageError};
+use crate::types::DataValue;
 
 /// Builds a column. [`ColumnBuilder`] will automatically chunk [`Array`] into
 /// blocks, calls `BlockBuilder` to generate a block, and builds index for a
@@ -82,11 +83,12 @@ pub trait ColumnIterator<A: Array> {
 }
 
 /// When creating an iterator, a [`ColumnSeekPosition`] should be set as the initial location.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum ColumnSeekPosition {
     RowId(u32),
-    #[allow(dead_code)]
-    SortKey(()),
+    /// Seek to the first block that may contain the sort key, which requires the first keys of
+    /// the sort key column to be recorded.
+    SortKey(DataValue),
 }
 
 impl ColumnSeekPosition {
@@ -150,63 +152,71 @@ impl Column {
 
         let key = self.base_block_key.clone().block(block_id);
 
-        let mut block_header = BlockHeader::default();
-        let mut do_verify_checksum = false;
-
         // support multiple I/O backend
-        let block =
-            self.block_cache
-                .get_or_try_insert_with(key, async {
-                    // block has not been in cache, so we fetch it from disk
-                    let file = self.file.clone();
-                    let info = self.index.index(block_id).clone();
-                    let block = tokio::task::spawn_blocking(move || {
-                        let data = match file {
-                            ColumnReadableFile::PositionedRead(file) => {
-                                let mut data = vec![0; info.length as usize];
-                                file.read_exact_at(&mut data[..], info.offset)?;
-                                Bytes::from(data)
-                            }
-                            ColumnReadableFile::NormalRead(file) => {
-                                let mut data = vec![0; info.length as usize];
-                                let mut file = file.lock().unwrap();
-                                file.seek(SeekFrom::Start(info.offset as u64))?;
-                                file.read_exact(&mut data[..])?;
-                                Bytes::from(data)
-                            }
-                            ColumnReadableFile::InMemory(file) => file
-                                .slice(info.offset as usize..(info.offset + info.length) as usize),
-                        };
-                        Ok::<_, TracedStorageError>(data)
-                    })
-                    .await
-                    .unwrap();
-                    // TODO(chi): we should invalidate cache item after a RowSet has been compacted.
-                    // self.block_cache.insert(key, block.clone()).await;
-
-                    // need to verify checksum when read from disk
-                    do_verify_checksum = true;
-                    block
+        let block = self
+            .block_cache
+            .get_or_try_insert_with(key, async {
+                // block has not been in cache, so we fetch it from disk
+                let file = self.file.clone();
+                let info = self.index.index(block_id).clone();
+                let block = tokio::task::spawn_blocking(move || {
+                    let data = match file {
+                        ColumnReadableFile::PositionedRead(file) => {
+                            let mut data = vec![0; info.length as usize];
+                            file.read_exact_at(&mut data[..], info.offset)?;
+                            Bytes::from(data)
+                        }
+                        ColumnReadableFile::NormalRead(file) => {
+                            let mut data = vec![0; info.length as usize];
+                            let mut file = file.lock().unwrap();
+                            file.seek(SeekFrom::Start(info.offset as u64))?;
+                            file.read_exact(&mut data[..])?;
+                            Bytes::from(data)
+                        }
+                        ColumnReadableFile::InMemory(file) => {
+                            file.slice(info.offset as usize..(info.offset + info.length) as usize)
+                        }
+                    };
+                    Ok::<_, TracedStorageError>(data)
                 })
-                .await?;
+                .await
+                .unwrap()?;
+                // TODO(chi): we should invalidate cache item after a RowSet has been compacted.
+                // self.block_cache.insert(key, block.clone()).await;
+
+                if block.len() < BLOCK_HEADER_SIZE {
+                    return Err(TracedStorageError::decode(
+                        "block is smaller than header size",
+                    ));
+                }
+                let mut block_header = BlockHeader::default();
+                block_header.decode(&mut &block[..BLOCK_HEADER_SIZE])?;
+
+                // need to verify checksum when read from disk
+                verify_checksum(
+                    block_header.checksum_type,
+                    &block[BLOCK_HEADER_SIZE..],
+                    block_header.checksum,
+                )?;
+
+                // cache the decompressed block, so that it is decompressed only once
+                let (block_type, data) =
+                    decompress_block(block_header.block_type, block.slice(BLOCK_HEADER_SIZE..))?;
+                if block_type == block_header.block_type {
+                    return Ok(block);
+                }
+                block_header.block_type = block_type;
+                let mut decompressed = Vec::with_capacity(BLOCK_HEADER_SIZE + data.len());
+                block_header.encode(&mut decompressed);
+                decompressed.extend_from_slice(&data);
+                Ok(Block::from(decompressed))
+            })
+            .await?;
 
-        if block.len() < BLOCK_HEADER_SIZE {
-            return Err(TracedStorageError::decode(
-                "block is smaller than header size",
-            ));
-        }
-        let mut header = &block[..BLOCK_HEADER_SIZE];
-        let block_data = &block[BLOCK_HEADER_SIZE..];
-        block_header.decode(&mut header)?;
-
-        if do_verify_checksum {
-            verify_checksum(
-                block_header.checksum_type,
-                block_data,
-                block_header.checksum,
-            )?;
-        }
+        let mut block_header = BlockHeader::default();
+        block_header.decode(&mut &block[..BLOCK_HEADER_SIZE])?;
+        let block = block.slice(BLOCK_HEADER_SIZE..);
 
-        Ok((block_header, block.slice(BLOCK_HEADER_SIZE..)))
+        Ok((block_header, block))
     }
 }
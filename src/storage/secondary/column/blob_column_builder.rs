@@ -74,6 +74,7 @@ impl BlobColumnBuilder {
 impl ColumnBuilder<BlobArray> for BlobColumnBuilder {
     fn append(&mut self, array: &BlobArray) {
         let mut iter = array.iter().peekable();
+        let mut pos = 0;
 
         while iter.peek().is_some() {
             if self.current_builder.is_none() {
@@ -103,6 +104,9 @@ impl ColumnBuilder<BlobArray> for BlobColumnBuilder {
             };
 
             self.block_index_builder.add_rows(row_count);
+            self.block_index_builder
+                .add_bloom_filter_items(array, pos..pos + row_count);
+            pos += row_count;
 
             // finish the current block
             if should_finish {
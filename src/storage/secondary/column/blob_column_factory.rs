@@ -74,6 +74,9 @@ impl BlockIteratorFactory<BlobArray> for BlobBlockIteratorFactory {
                 );
                 BlobBlockIteratorImpl::RleBlob(it)
             }
+            BlockType::ZstdCompress | BlockType::Lz4Compress => {
+                unreachable!("compressed blocks are decompressed by the column")
+            }
             _ => todo!(),
         };
         it.skip(start_pos - index.first_rowid as usize);
@@ -6,7 +6,9 @@ use risinglight_proto::rowset::BlockIndex;
 use super::super::{BlockBuilder, BlockIndexBuilder, PlainCharBlockBuilder};
 use super::{append_one_by_one, ColumnBuilder};
 use crate::array::{Array, Utf8Array};
-use crate::storage::secondary::block::{PlainBlobBlockBuilder, RleBlockBuilder};
+use crate::storage::secondary::block::{
+    DictBlockBuilder, PlainBlobBlockBuilder, PlainBlobNullableBlockBuilder, RleBlockBuilder,
+};
 use crate::storage::secondary::ColumnBuilderOptions;
 
 /// All supported block builders for char types.
@@ -15,6 +17,9 @@ pub(super) enum CharBlockBuilderImpl {
     PlainVarchar(PlainBlobBlockBuilder<str>),
     RleFixedChar(RleBlockBuilder<Utf8Array, PlainCharBlockBuilder>),
     RleVarchar(RleBlockBuilder<Utf8Array, PlainBlobBlockBuilder<str>>),
+    PlainNullableVarchar(PlainBlobNullableBlockBuilder<str>),
+    RleNullableVarchar(RleBlockBuilder<Utf8Array, PlainBlobNullableBlockBuilder<str>>),
+    Dictionary(DictBlockBuilder),
 }
 
 /// Column builder of char types.
@@ -36,6 +41,9 @@ pub struct CharColumnBuilder {
 
     /// First key
     first_key: Option<Vec<u8>>,
+
+    /// Whether dictionary encoding is disabled, as the column has high cardinality
+    dict_disabled: bool,
 }
 
 impl CharColumnBuilder {
@@ -48,6 +56,7 @@ impl CharColumnBuilder {
             nullable,
             char_width,
             first_key: None,
+            dict_disabled: false,
         }
     }
 
@@ -77,6 +86,27 @@ impl CharColumnBuilder {
                 builder.get_statistics(),
                 builder.finish(),
             ),
+            CharBlockBuilderImpl::PlainNullableVarchar(builder) => (
+                BlockType::PlainNullableVarchar,
+                builder.get_statistics(),
+                builder.finish(),
+            ),
+            CharBlockBuilderImpl::RleNullableVarchar(builder) => (
+                BlockType::RleNullableVarchar,
+                builder.get_statistics(),
+                builder.finish(),
+            ),
+            CharBlockBuilderImpl::Dictionary(builder) => {
+                // fall back to other encodings if most values are distinct
+                if builder.cardinality() * 2 > builder.row_count() {
+                    self.dict_disabled = true;
+                }
+                (
+                    BlockType::Dictionary,
+                    builder.get_statistics(),
+                    builder.finish(),
+                )
+            }
         };
 
         self.block_index_builder.finish_block(
@@ -92,10 +122,17 @@ impl CharColumnBuilder {
 impl ColumnBuilder<Utf8Array> for CharColumnBuilder {
     fn append(&mut self, array: &Utf8Array) {
         let mut iter = array.iter().peekable();
+        let mut pos = 0;
 
         while iter.peek().is_some() {
             if self.current_builder.is_none() {
+                let is_dict = self.options.is_dict && !self.dict_disabled;
                 match (self.char_width, self.nullable, self.options.is_rle) {
+                    (_, false, _) if is_dict => {
+                        self.current_builder = Some(CharBlockBuilderImpl::Dictionary(
+                            DictBlockBuilder::new(self.options.target_block_size - 16),
+                        ));
+                    }
                     (Some(char_width), false, true) => {
                         let builder = PlainCharBlockBuilder::new(
                             self.options.target_block_size - 16,
@@ -117,7 +154,23 @@ impl ColumnBuilder<Utf8Array> for CharColumnBuilder {
                             ),
                         ));
                     }
-                    (None, _, true) => {
+                    (None, true, true) => {
+                        let builder =
+                            PlainBlobNullableBlockBuilder::new(self.options.target_block_size - 16);
+                        self.current_builder =
+                            Some(CharBlockBuilderImpl::RleNullableVarchar(RleBlockBuilder::<
+                                Utf8Array,
+                                PlainBlobNullableBlockBuilder<str>,
+                            >::new(
+                                builder
+                            )));
+                    }
+                    (None, true, false) => {
+                        self.current_builder = Some(CharBlockBuilderImpl::PlainNullableVarchar(
+                            PlainBlobNullableBlockBuilder::new(self.options.target_block_size - 16),
+                        ));
+                    }
+                    (None, false, true) => {
                         let builder =
                             PlainBlobBlockBuilder::new(self.options.target_block_size - 16);
                         self.current_builder =
@@ -128,7 +181,7 @@ impl ColumnBuilder<Utf8Array> for CharColumnBuilder {
                                 builder
                             )));
                     }
-                    (None, _, false) => {
+                    (None, false, false) => {
                         self.current_builder = Some(CharBlockBuilderImpl::PlainVarchar(
                             PlainBlobBlockBuilder::new(self.options.target_block_size - 16),
                         ));
@@ -158,9 +211,19 @@ impl ColumnBuilder<Utf8Array> for CharColumnBuilder {
                     append_one_by_one(&mut iter, builder)
                 }
                 CharBlockBuilderImpl::RleVarchar(builder) => append_one_by_one(&mut iter, builder),
+                CharBlockBuilderImpl::PlainNullableVarchar(builder) => {
+                    append_one_by_one(&mut iter, builder)
+                }
+                CharBlockBuilderImpl::RleNullableVarchar(builder) => {
+                    append_one_by_one(&mut iter, builder)
+                }
+                CharBlockBuilderImpl::Dictionary(builder) => append_one_by_one(&mut iter, builder),
             };
 
             self.block_index_builder.add_rows(row_count);
+            self.block_index_builder
+                .add_bloom_filter_items(array, pos..pos + row_count);
+            pos += row_count;
 
             // finish the current block
             if should_finish {
@@ -180,6 +243,7 @@ impl ColumnBuilder<Utf8Array> for CharColumnBuilder {
 mod tests {
     use std::iter::FromIterator;
 
+    use super::super::super::BlockHeader;
     use super::*;
 
     #[test]
@@ -244,4 +308,55 @@ mod tests {
         let (index, _) = builder.finish();
         assert_eq!(index.len(), 10);
     }
+
+    #[test]
+    fn test_char_column_dict_builder() {
+        let block_type_of = |data: &[u8], index: &BlockIndex| {
+            let mut header = BlockHeader::default();
+            header.decode(&mut &data[index.offset as usize..]).unwrap();
+            header.block_type
+        };
+        let mut options = ColumnBuilderOptions::default_for_block_test();
+        options.is_dict = true;
+
+        // low cardinality column is dictionary-encoded
+        let mut builder = CharColumnBuilder::new(false, Some(8), options.clone());
+        builder.append(&Utf8Array::from_iter(
+            [Some("A"), Some("N"), Some("R")]
+                .iter()
+                .cycle()
+                .cloned()
+                .take(100),
+        ));
+        let (index, data) = builder.finish();
+        assert_eq!(index.len(), 3);
+        assert!(index
+            .iter()
+            .all(|index| block_type_of(&data, index) == BlockType::Dictionary));
+
+        // high cardinality column falls back to plain encoding after the first block
+        let mut builder = CharColumnBuilder::new(false, Some(8), options.clone());
+        builder.append(&Utf8Array::from_iter(
+            (0..100).map(|num| Some(num.to_string())),
+        ));
+        let (index, data) = builder.finish();
+        assert_eq!(block_type_of(&data, &index[0]), BlockType::Dictionary);
+        assert!(index[1..]
+            .iter()
+            .all(|index| block_type_of(&data, index) == BlockType::PlainFixedChar));
+
+        // dictionary blocks don't support nulls
+        let mut builder = CharColumnBuilder::new(true, None, options);
+        builder.append(&Utf8Array::from_iter(
+            [Some("A"), None, Some("R")]
+                .iter()
+                .cycle()
+                .cloned()
+                .take(100),
+        ));
+        let (index, data) = builder.finish();
+        assert!(index
+            .iter()
+            .all(|index| block_type_of(&data, index) == BlockType::PlainNullableVarchar));
+    }
 }
@@ -7,8 +7,8 @@ use super::super::{Block, BlockIterator};
 use super::{BlockIteratorFactory, ConcreteColumnIterator};
 use crate::array::{Utf8Array, Utf8ArrayBuilder};
 use crate::storage::secondary::block::{
-    decode_rle_block, FakeBlockIterator, PlainBlobBlockIterator, PlainCharBlockIterator,
-    RleBlockIterator,
+    decode_rle_block, DictBlockIterator, FakeBlockIterator, PlainBlobBlockIterator,
+    PlainBlobNullableBlockIterator, PlainCharBlockIterator, RleBlockIterator,
 };
 
 /// All supported block iterators for char types.
@@ -17,6 +17,9 @@ pub enum CharBlockIteratorImpl {
     PlainVarchar(PlainBlobBlockIterator<str>),
     RleFixedChar(RleBlockIterator<Utf8Array, PlainCharBlockIterator>),
     RleVarchar(RleBlockIterator<Utf8Array, PlainBlobBlockIterator<str>>),
+    PlainNullableVarchar(PlainBlobNullableBlockIterator<str>),
+    RleNullableVarchar(RleBlockIterator<Utf8Array, PlainBlobNullableBlockIterator<str>>),
+    Dictionary(DictBlockIterator),
     Fake(FakeBlockIterator<Utf8Array>),
 }
 
@@ -31,6 +34,9 @@ impl BlockIterator<Utf8Array> for CharBlockIteratorImpl {
             Self::PlainVarchar(it) => it.next_batch(expected_size, builder),
             Self::RleFixedChar(it) => it.next_batch(expected_size, builder),
             Self::RleVarchar(it) => it.next_batch(expected_size, builder),
+            Self::PlainNullableVarchar(it) => it.next_batch(expected_size, builder),
+            Self::RleNullableVarchar(it) => it.next_batch(expected_size, builder),
+            Self::Dictionary(it) => it.next_batch(expected_size, builder),
             Self::Fake(it) => it.next_batch(expected_size, builder),
         }
     }
@@ -41,6 +47,9 @@ impl BlockIterator<Utf8Array> for CharBlockIteratorImpl {
             Self::PlainVarchar(it) => it.skip(cnt),
             Self::RleFixedChar(it) => it.skip(cnt),
             Self::RleVarchar(it) => it.skip(cnt),
+            Self::PlainNullableVarchar(it) => it.skip(cnt),
+            Self::RleNullableVarchar(it) => it.skip(cnt),
+            Self::Dictionary(it) => it.skip(cnt),
             Self::Fake(it) => it.skip(cnt),
         }
     }
@@ -51,6 +60,9 @@ impl BlockIterator<Utf8Array> for CharBlockIteratorImpl {
             Self::PlainVarchar(it) => it.remaining_items(),
             Self::RleFixedChar(it) => it.remaining_items(),
             Self::RleVarchar(it) => it.remaining_items(),
+            Self::PlainNullableVarchar(it) => it.remaining_items(),
+            Self::RleNullableVarchar(it) => it.remaining_items(),
+            Self::Dictionary(it) => it.remaining_items(),
             Self::Fake(it) => it.remaining_items(),
         }
     }
@@ -104,6 +116,24 @@ impl BlockIteratorFactory<Utf8Array> for CharBlockIteratorFactory {
                 );
                 CharBlockIteratorImpl::RleVarchar(it)
             }
+            (BlockType::PlainNullableVarchar, _) => {
+                let it = PlainBlobNullableBlockIterator::new(block, index.row_count as usize);
+                CharBlockIteratorImpl::PlainNullableVarchar(it)
+            }
+            (BlockType::RleNullableVarchar, _) => {
+                let (rle_num, rle_data, block_data) = decode_rle_block(block);
+                let block_iter = PlainBlobNullableBlockIterator::new(block_data, rle_num);
+                let it = RleBlockIterator::<Utf8Array, PlainBlobNullableBlockIterator<str>>::new(
+                    block_iter, rle_data, rle_num,
+                );
+                CharBlockIteratorImpl::RleNullableVarchar(it)
+            }
+            (BlockType::Dictionary, _) => CharBlockIteratorImpl::Dictionary(
+                DictBlockIterator::new(block, index.row_count as usize),
+            ),
+            (BlockType::ZstdCompress | BlockType::Lz4Compress, _) => {
+                unreachable!("compressed blocks are decompressed by the column")
+            }
             _ => todo!(),
         };
         it.skip(start_pos - index.first_rowid as usize);
@@ -10,8 +10,10 @@ use super::primitive_column_builder::{
 };
 use super::{BoolColumnBuilder, ColumnBuilder};
 use crate::array::ArrayImpl;
-use crate::storage::secondary::column::IntervalColumnBuilder;
-use crate::types::{DataType, DataTypeKind};
+use crate::storage::secondary::column::{
+    IntervalColumnBuilder, TimestampColumnBuilder, TimestampTzColumnBuilder,
+};
+use crate::types::{is_timestamptz, DataType, DataTypeKind};
 
 /// [`ColumnBuilder`] of all types
 pub enum ColumnBuilderImpl {
@@ -23,6 +25,8 @@ pub enum ColumnBuilderImpl {
     Decimal(DecimalColumnBuilder),
     Date(DateColumnBuilder),
     Interval(IntervalColumnBuilder),
+    Timestamp(TimestampColumnBuilder),
+    TimestampTz(TimestampTzColumnBuilder),
     Blob(BlobColumnBuilder),
 }
 
@@ -63,6 +67,13 @@ impl ColumnBuilderImpl {
             DataTypeKind::Interval => {
                 Self::Interval(IntervalColumnBuilder::new(datatype.is_nullable(), options))
             }
+            DataTypeKind::Timestamp => {
+                Self::Timestamp(TimestampColumnBuilder::new(datatype.is_nullable(), options))
+            }
+            ty if is_timestamptz(&ty) => Self::TimestampTz(TimestampTzColumnBuilder::new(
+                datatype.is_nullable(),
+                options,
+            )),
             DataTypeKind::Bytea => Self::Blob(BlobColumnBuilder::new(options)),
             other_datatype => todo!("column builder for {:?} is not implemented", other_datatype),
         }
@@ -78,6 +89,8 @@ impl ColumnBuilderImpl {
             (Self::Decimal(builder), ArrayImpl::Decimal(array)) => builder.append(array),
             (Self::Date(builder), ArrayImpl::Date(array)) => builder.append(array),
             (Self::Interval(builder), ArrayImpl::Interval(array)) => builder.append(array),
+            (Self::Timestamp(builder), ArrayImpl::Timestamp(array)) => builder.append(array),
+            (Self::TimestampTz(builder), ArrayImpl::TimestampTz(array)) => builder.append(array),
             (Self::Blob(builder), ArrayImpl::Blob(array)) => builder.append(array),
             _ => todo!(),
         }
@@ -93,6 +106,8 @@ impl ColumnBuilderImpl {
             Self::Decimal(builder) => builder.finish(),
             Self::Date(builder) => builder.finish(),
             Self::Interval(builder) => builder.finish(),
+            Self::Timestamp(builder) => builder.finish(),
+            Self::TimestampTz(builder) => builder.finish(),
             Self::Blob(builder) => builder.finish(),
         }
     }
@@ -7,8 +7,10 @@ use super::{
 };
 use crate::array::{Array, ArrayImpl};
 use crate::catalog::ColumnCatalog;
-use crate::storage::secondary::column::{DateColumnIterator, IntervalColumnIterator};
-use crate::types::DataTypeKind;
+use crate::storage::secondary::column::{
+    DateColumnIterator, IntervalColumnIterator, TimestampColumnIterator, TimestampTzColumnIterator,
+};
+use crate::types::{is_timestamptz, DataTypeKind};
 
 /// [`ColumnIteratorImpl`] of all types
 pub enum ColumnIteratorImpl {
@@ -20,6 +22,8 @@ pub enum ColumnIteratorImpl {
     Decimal(DecimalColumnIterator),
     Date(DateColumnIterator),
     Interval(IntervalColumnIterator),
+    Timestamp(TimestampColumnIterator),
+    TimestampTz(TimestampTzColumnIterator),
     Blob(BlobColumnIterator),
     /// Special for row handler and not correspond to any data type
     RowHandler(RowHandlerColumnIterator),
@@ -80,6 +84,22 @@ impl ColumnIteratorImpl {
                 )
                 .await?,
             ),
+            DataTypeKind::Timestamp => Self::Timestamp(
+                TimestampColumnIterator::new(
+                    column,
+                    start_pos,
+                    PrimitiveBlockIteratorFactory::new(),
+                )
+                .await?,
+            ),
+            ty if is_timestamptz(&ty) => Self::TimestampTz(
+                TimestampTzColumnIterator::new(
+                    column,
+                    start_pos,
+                    PrimitiveBlockIteratorFactory::new(),
+                )
+                .await?,
+            ),
             DataTypeKind::Bytea => Self::Blob(
                 BlobColumnIterator::new(
                     column,
@@ -124,6 +144,8 @@ impl ColumnIteratorImpl {
             Self::Decimal(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
             Self::Date(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
             Self::Interval(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
+            Self::Timestamp(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
+            Self::TimestampTz(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
             Self::Blob(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
             Self::RowHandler(it) => Self::erase_concrete_type(it.next_batch(expected_size).await?),
         };
@@ -140,6 +162,8 @@ impl ColumnIteratorImpl {
             Self::Decimal(it) => it.fetch_hint(),
             Self::Date(it) => it.fetch_hint(),
             Self::Interval(it) => it.fetch_hint(),
+            Self::Timestamp(it) => it.fetch_hint(),
+            Self::TimestampTz(it) => it.fetch_hint(),
             Self::Blob(it) => it.fetch_hint(),
             Self::RowHandler(it) => it.fetch_hint(),
         }
@@ -155,6 +179,8 @@ impl ColumnIteratorImpl {
             Self::Decimal(it) => it.fetch_current_row_id(),
             Self::Date(it) => it.fetch_current_row_id(),
             Self::Interval(it) => it.fetch_current_row_id(),
+            Self::Timestamp(it) => it.fetch_current_row_id(),
+            Self::TimestampTz(it) => it.fetch_current_row_id(),
             Self::Blob(it) => it.fetch_current_row_id(),
             Self::RowHandler(it) => it.fetch_current_row_id(),
         }
@@ -170,6 +196,8 @@ impl ColumnIteratorImpl {
             Self::Decimal(it) => it.skip(cnt),
             Self::Date(it) => it.skip(cnt),
             Self::Interval(it) => it.skip(cnt),
+            Self::Timestamp(it) => it.skip(cnt),
+            Self::TimestampTz(it) => it.skip(cnt),
             Self::Blob(it) => it.skip(cnt),
             Self::RowHandler(it) => it.skip(cnt),
         }
@@ -194,7 +194,7 @@ impl<A: Array, F: BlockIteratorFactory<A>> ConcreteColumnIterator<A, F> {
             let row_count = self.column.index().index(self.current_block_id).row_count;
             let start_pos = self.column.index().index(self.current_block_id).first_rowid;
             let mut reached = start_pos + row_count;
-            while self.current_row_id > reached {
+            while self.current_row_id >= reached {
                 if self.incre_block_id() {
                     return;
                 }
@@ -13,7 +13,8 @@ use super::super::{
 use super::ColumnBuilder;
 use crate::array::Array;
 use crate::storage::secondary::block::RleBlockBuilder;
-use crate::types::{Date, Interval};
+use crate::storage::secondary::statistics::BloomHash;
+use crate::types::{Date, Interval, Timestamp, TimestampTz};
 
 /// All supported block builders for primitive types.
 pub(super) enum BlockBuilderImpl<T: PrimitiveFixedWidthEncode> {
@@ -30,6 +31,8 @@ pub type BoolColumnBuilder = PrimitiveColumnBuilder<bool>;
 pub type DecimalColumnBuilder = PrimitiveColumnBuilder<Decimal>;
 pub type DateColumnBuilder = PrimitiveColumnBuilder<Date>;
 pub type IntervalColumnBuilder = PrimitiveColumnBuilder<Interval>;
+pub type TimestampColumnBuilder = PrimitiveColumnBuilder<Timestamp>;
+pub type TimestampTzColumnBuilder = PrimitiveColumnBuilder<TimestampTz>;
 
 /// Column builder of primitive types.
 pub struct PrimitiveColumnBuilder<T: PrimitiveFixedWidthEncode> {
@@ -127,9 +130,12 @@ pub fn append_one_by_one<'a, A: Array>(
     (cnt, false)
 }
 
-impl<T: PrimitiveFixedWidthEncode> ColumnBuilder<T::ArrayType> for PrimitiveColumnBuilder<T> {
+impl<T: PrimitiveFixedWidthEncode + BloomHash> ColumnBuilder<T::ArrayType>
+    for PrimitiveColumnBuilder<T>
+{
     fn append(&mut self, array: &T::ArrayType) {
         let mut iter = array.iter().peekable();
+        let mut pos = 0;
         while iter.peek().is_some() {
             if self.current_builder.is_none() {
                 match (self.nullable, self.options.is_rle) {
@@ -189,6 +195,9 @@ impl<T: PrimitiveFixedWidthEncode> ColumnBuilder<T::ArrayType> for PrimitiveColu
             };
 
             self.block_index_builder.add_rows(row_count);
+            self.block_index_builder
+                .add_bloom_filter_items(array, pos..pos + row_count);
+            pos += row_count;
 
             // finish the current block
             if should_finish {
@@ -13,7 +13,7 @@ use super::super::{
 use super::{BlockIteratorFactory, ConcreteColumnIterator};
 use crate::array::Array;
 use crate::storage::secondary::block::{decode_rle_block, FakeBlockIterator, RleBlockIterator};
-use crate::types::{Date, Interval};
+use crate::types::{Date, Interval, Timestamp, TimestampTz};
 
 /// All supported block iterators for primitive types.
 pub enum PrimitiveBlockIteratorImpl<T: PrimitiveFixedWidthEncode> {
@@ -85,6 +85,8 @@ pub type BoolColumnIterator = PrimitiveColumnIterator<bool>;
 pub type DecimalColumnIterator = PrimitiveColumnIterator<Decimal>;
 pub type DateColumnIterator = PrimitiveColumnIterator<Date>;
 pub type IntervalColumnIterator = PrimitiveColumnIterator<Interval>;
+pub type TimestampColumnIterator = PrimitiveColumnIterator<Timestamp>;
+pub type TimestampTzColumnIterator = PrimitiveColumnIterator<TimestampTz>;
 
 impl<T: PrimitiveFixedWidthEncode> BlockIteratorFactory<T::ArrayType>
     for PrimitiveBlockIteratorFactory<T>
@@ -100,15 +102,18 @@ impl<T: PrimitiveFixedWidthEncode> BlockIteratorFactory<T::ArrayType>
     ) -> Self::BlockIteratorImpl {
         let mut it = match block_type {
             BlockType::Plain => {
+                let block = upgrade_legacy_block::<T>(block, index.row_count as usize, false);
                 let it = PlainPrimitiveBlockIterator::new(block, index.row_count as usize);
                 PrimitiveBlockIteratorImpl::Plain(it)
             }
             BlockType::PlainNullable => {
+                let block = upgrade_legacy_block::<T>(block, index.row_count as usize, true);
                 let it = PlainPrimitiveNullableBlockIterator::new(block, index.row_count as usize);
                 PrimitiveBlockIteratorImpl::PlainNullable(it)
             }
             BlockType::RunLength => {
                 let (rle_num, rle_data, block_data) = decode_rle_block(block);
+                let block_data = upgrade_legacy_block::<T>(block_data, rle_num, false);
                 let block_iter = PlainPrimitiveBlockIterator::<T>::new(block_data, rle_num);
                 let it = RleBlockIterator::<T::ArrayType, PlainPrimitiveBlockIterator<T>>::new(
                     block_iter, rle_data, rle_num,
@@ -117,6 +122,7 @@ impl<T: PrimitiveFixedWidthEncode> BlockIteratorFactory<T::ArrayType>
             }
             BlockType::RleNullable => {
                 let (rle_num, rle_data, block_data) = decode_rle_block(block);
+                let block_data = upgrade_legacy_block::<T>(block_data, rle_num, true);
                 let block_iter = PlainPrimitiveNullableBlockIterator::<T>::new(block_data, rle_num);
                 let it =
                     RleBlockIterator::<T::ArrayType, PlainPrimitiveNullableBlockIterator<T>>::new(
@@ -124,6 +130,9 @@ impl<T: PrimitiveFixedWidthEncode> BlockIteratorFactory<T::ArrayType>
                     );
                 PrimitiveBlockIteratorImpl::RleNullable(it)
             }
+            BlockType::ZstdCompress | BlockType::Lz4Compress => {
+                unreachable!("compressed blocks are decompressed by the column")
+            }
             _ => todo!(),
         };
         it.skip(start_pos - index.first_rowid as usize);
@@ -140,14 +149,41 @@ impl<T: PrimitiveFixedWidthEncode> BlockIteratorFactory<T::ArrayType>
 
 #[cfg(test)]
 mod tests {
+    use bytes::BufMut;
     use itertools::Itertools;
 
     use super::*;
-    use crate::array::ArrayToVecExt;
+    use crate::array::{ArrayBuilder, ArrayToVecExt, IntervalArrayBuilder};
     use crate::storage::secondary::column::Column;
     use crate::storage::secondary::rowset::tests::{helper_build_rle_rowset, helper_build_rowset};
     use crate::storage::secondary::{ColumnIterator, PrimitiveColumnIterator};
 
+    #[test]
+    fn test_upgrade_legacy_interval() {
+        // `1 month 2 days` and NULL in the legacy encoding
+        let mut data = vec![];
+        data.put_i32(1);
+        data.put_i32(2);
+        data.put_i32(0);
+        data.put_i32(0);
+        data.put_u8(0b01);
+        let block = upgrade_legacy_block::<Interval>(Block::from(data), 2, true);
+        assert_eq!(block.len(), 2 * Interval::WIDTH + 1);
+        let mut it = PlainPrimitiveNullableBlockIterator::<Interval>::new(block.clone(), 2);
+        let mut builder = IntervalArrayBuilder::new();
+        it.next_batch(None, &mut builder);
+        assert_eq!(
+            builder.finish().to_vec(),
+            vec![Some(Interval::from_md(1, 2)), None]
+        );
+
+        // blocks in the current encoding are not changed
+        assert_eq!(
+            upgrade_legacy_block::<Interval>(block.clone(), 2, true),
+            block
+        );
+    }
+
     #[tokio::test]
     async fn test_scan_i32() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -374,3 +410,28 @@ mod tests {
         }
     }
 }
+
+/// Re-encode a plain block of `row_count` elements written in the legacy width of `T`, see
+/// [`PrimitiveFixedWidthEncode::LEGACY_WIDTH`]. The width is told by the length of the block, and
+/// blocks in the current width are returned as is.
+fn upgrade_legacy_block<T: PrimitiveFixedWidthEncode>(
+    block: Block,
+    row_count: usize,
+    nullable: bool,
+) -> Block {
+    let legacy_width = match T::LEGACY_WIDTH {
+        Some(width) => width,
+        None => return block,
+    };
+    let bitmap_len = if nullable { (row_count + 7) / 8 } else { 0 };
+    if row_count == 0 || block.len() != row_count * legacy_width + bitmap_len {
+        return block;
+    }
+    let mut data = Vec::with_capacity(row_count * T::WIDTH + bitmap_len);
+    let mut buffer = &block[..row_count * legacy_width];
+    for _ in 0..row_count {
+        T::decode_legacy(&mut buffer).encode(&mut data);
+    }
+    data.extend_from_slice(&block[row_count * legacy_width..]);
+    Block::from(data)
+}
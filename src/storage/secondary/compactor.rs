@@ -29,7 +29,12 @@ impl Compactor {
         Self { storage, stop }
     }
 
-    async fn compact_table(&self, snapshot: &Snapshot, table: SecondaryTable) -> StorageResult<()> {
+    async fn compact_table(
+        &self,
+        epoch: u64,
+        snapshot: &Snapshot,
+        table: SecondaryTable,
+    ) -> StorageResult<()> {
         let rowsets = if let Some(rowsets) = snapshot.get_rowsets_of(table.table_id()) {
             rowsets
         } else {
@@ -99,7 +104,7 @@ impl Compactor {
 
         let mut builder = RowsetBuilder::new(
             table.columns.clone(),
-            ColumnBuilderOptions::from_storage_options(&table.storage_options),
+            ColumnBuilderOptions::from_storage_options(&table.storage_options, &table.table_name),
         );
 
         while let Some(batch) = iter.next_batch(None).await? {
@@ -139,7 +144,7 @@ impl Compactor {
                     rowset_id: rowset.rowset_id(),
                     table_id: table.table_ref_id,
                 },
-                rowset,
+                Arc::new(rowset),
             ));
 
             changes.push(add_rowset_op);
@@ -154,7 +159,21 @@ impl Compactor {
             })
         }));
 
-        self.storage.version.commit_changes(changes).await?;
+        // Deletions committed during the compaction would be lost, so the compaction is
+        // aborted in that case. It will be retried in the next round.
+        if let Err(err) = self
+            .storage
+            .version
+            .commit_changes(Some(epoch), changes)
+            .await
+        {
+            if let Some(rowset_id) = rowset_id {
+                tokio::fs::remove_dir_all(table.get_rowset_path(rowset_id))
+                    .await
+                    .ok();
+            }
+            return Err(err);
+        }
 
         match rowset_id {
             Some(rowset_id) => {
@@ -186,7 +205,7 @@ impl Compactor {
                         .txn_mgr
                         .try_lock_for_compaction(table.table_id())
                     {
-                        if let Err(err) = self.compact_table(&*snapshot, table).await {
+                        if let Err(err) = self.compact_table(epoch, &*snapshot, table).await {
                             warn!("failed to compact: {:?}", err);
                         }
                     }
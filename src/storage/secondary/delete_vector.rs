@@ -73,6 +73,22 @@ impl DeleteVector {
         self.rowset_id
     }
 
+    /// Check whether two DVs delete any row in common.
+    pub fn overlaps(&self, other: &DeleteVector) -> bool {
+        let (mut a, mut b) = (
+            self.deletes.iter().peekable(),
+            other.deletes.iter().peekable(),
+        );
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            match x.cmp(y) {
+                std::cmp::Ordering::Less => a.next(),
+                std::cmp::Ordering::Greater => b.next(),
+                std::cmp::Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
     /// Apply the current DV info to a visibility bitmap
     pub fn apply_to(&self, data: &mut BitVec, offset_row_id: u32) {
         let pos = self.deletes.partition_point(|x| *x < offset_row_id);
@@ -123,4 +139,13 @@ mod tests {
         dv.apply_to(&mut bv, 4);
         assert_eq!(bv, bitvec![1, 0, 1]);
     }
+
+    #[test]
+    fn test_dv_overlaps() {
+        let records = |rows: &[u32]| rows.iter().map(|&row_id| DeleteRecord { row_id }).collect();
+        let dv = DeleteVector::new(0, 0, records(&[1, 3, 5]));
+        assert!(dv.overlaps(&DeleteVector::new(1, 0, records(&[4, 5]))));
+        assert!(!dv.overlaps(&DeleteVector::new(2, 0, records(&[0, 2, 4, 6]))));
+        assert!(!dv.overlaps(&DeleteVector::new(3, 0, records(&[]))));
+    }
 }
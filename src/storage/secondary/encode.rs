@@ -5,12 +5,14 @@ use rust_decimal::Decimal;
 
 use crate::array::{
     Array, BlobArray, BoolArray, DateArray, DecimalArray, F64Array, I32Array, I64Array,
-    IntervalArray, Utf8Array,
+    IntervalArray, TimestampArray, TimestampTzArray, Utf8Array,
 };
-use crate::types::{BlobRef, Date, Interval};
+use crate::types::{BlobRef, Date, Interval, Timestamp, TimestampTz};
 
 /// Encode a primitive value into fixed-width buffer
-pub trait PrimitiveFixedWidthEncode: Copy + Clone + 'static + Send + Sync + PartialEq {
+pub trait PrimitiveFixedWidthEncode:
+    Copy + Clone + 'static + Send + Sync + PartialEq + PartialOrd
+{
     /// Width of each element
     const WIDTH: usize;
     const DEFAULT_VALUE: &'static Self;
@@ -22,6 +24,15 @@ pub trait PrimitiveFixedWidthEncode: Copy + Clone + 'static + Send + Sync + Part
 
     /// Decode a data from a bytes array.
     fn decode(buffer: &mut impl Buf) -> Self;
+
+    /// Width of each element written by earlier versions, if the encoding has been widened.
+    /// Blocks of the legacy width are decoded by [`decode_legacy`](Self::decode_legacy).
+    const LEGACY_WIDTH: Option<usize> = None;
+
+    /// Decode a data in the legacy encoding from a bytes array.
+    fn decode_legacy(_buffer: &mut impl Buf) -> Self {
+        unreachable!("no legacy encoding")
+    }
 }
 
 impl PrimitiveFixedWidthEncode for bool {
@@ -114,7 +125,8 @@ impl PrimitiveFixedWidthEncode for Date {
 }
 
 impl PrimitiveFixedWidthEncode for Interval {
-    const WIDTH: usize = std::mem::size_of::<i32>() + std::mem::size_of::<i32>();
+    const WIDTH: usize =
+        std::mem::size_of::<i32>() + std::mem::size_of::<i32>() + std::mem::size_of::<i64>();
     const DEFAULT_VALUE: &'static Self = &Interval::from_days(0);
 
     type ArrayType = IntervalArray;
@@ -122,15 +134,56 @@ impl PrimitiveFixedWidthEncode for Interval {
     fn encode(&self, buffer: &mut impl BufMut) {
         buffer.put_i32(self.num_months());
         buffer.put_i32(self.days());
+        buffer.put_i64(self.micros());
     }
 
     fn decode(buffer: &mut impl Buf) -> Self {
+        let months = buffer.get_i32();
+        let days = buffer.get_i32();
+        let micros = buffer.get_i64();
+        Interval::from_mdm(months, days, micros)
+    }
+
+    /// Intervals were encoded as months and days before the sub-day part was added.
+    const LEGACY_WIDTH: Option<usize> = Some(std::mem::size_of::<i32>() * 2);
+
+    fn decode_legacy(buffer: &mut impl Buf) -> Self {
         let months = buffer.get_i32();
         let days = buffer.get_i32();
         Interval::from_md(months, days)
     }
 }
 
+impl PrimitiveFixedWidthEncode for Timestamp {
+    const WIDTH: usize = std::mem::size_of::<i64>();
+    const DEFAULT_VALUE: &'static Self = &Timestamp::new(0);
+
+    type ArrayType = TimestampArray;
+
+    fn encode(&self, buffer: &mut impl BufMut) {
+        buffer.put_i64(self.get_inner());
+    }
+
+    fn decode(buffer: &mut impl Buf) -> Self {
+        Timestamp::new(buffer.get_i64())
+    }
+}
+
+impl PrimitiveFixedWidthEncode for TimestampTz {
+    const WIDTH: usize = std::mem::size_of::<i64>();
+    const DEFAULT_VALUE: &'static Self = &TimestampTz::new(0);
+
+    type ArrayType = TimestampTzArray;
+
+    fn encode(&self, buffer: &mut impl BufMut) {
+        buffer.put_i64(self.get_inner());
+    }
+
+    fn decode(buffer: &mut impl Buf) -> Self {
+        TimestampTz::new(buffer.get_i64())
+    }
+}
+
 pub trait BlobEncode {
     type ArrayType: Array<Item = Self>;
 
@@ -7,9 +7,11 @@ use prost::Message;
 use risinglight_proto::rowset::block_checksum::ChecksumType;
 use risinglight_proto::rowset::BlockIndex;
 
+use super::statistics::decode_value;
 use super::{ColumnSeekPosition, SECONDARY_INDEX_MAGIC};
 use crate::storage::secondary::{verify_checksum, INDEX_FOOTER_SIZE};
 use crate::storage::{StorageResult, TracedStorageError};
+use crate::types::DataValue;
 
 #[derive(Clone)]
 pub struct ColumnIndex {
@@ -29,6 +31,13 @@ impl ColumnIndex {
         self.indexes.len()
     }
 
+    #[cfg(test)]
+    pub fn from_indexes(indexes: Vec<BlockIndex>) -> Self {
+        Self {
+            indexes: indexes.into(),
+        }
+    }
+
     pub fn from_bytes(data: &[u8]) -> StorageResult<Self> {
         // TODO(chi): error handling
         let mut index_data = &data[..data.len() - INDEX_FOOTER_SIZE];
@@ -58,10 +67,32 @@ impl ColumnIndex {
     pub fn block_of_seek_position(&self, seek_pos: ColumnSeekPosition) -> u32 {
         match seek_pos {
             ColumnSeekPosition::RowId(row_id) => self.block_of_row(row_id),
-            ColumnSeekPosition::SortKey(_) => todo!(),
+            ColumnSeekPosition::SortKey(key) => self.block_of_sort_key(&key).unwrap_or(0),
         }
     }
 
+    /// Find the first block that may contain `key`, by binary search over the first keys of
+    /// blocks. The column must be sorted.
+    ///
+    /// Returns `None` if the first keys are not recorded, or they can't be compared with `key`.
+    pub fn block_of_sort_key(&self, key: &DataValue) -> Option<u32> {
+        let kind = key.data_type()?.physical_kind();
+        let first_keys = self
+            .indexes
+            .iter()
+            .map(|index| match index.is_first_key_null {
+                true => None,
+                false => decode_value(&kind, &index.first_key),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        // Rows equal to `key` may be at the end of the block before the first block whose first
+        // key is no less than `key`, so we partition the blocks by `first_key < key`, and begin
+        // with the block at `partition_point - 1`.
+        let pp = first_keys.partition_point(|first_key| first_key < key) as u32;
+        Some(pp.saturating_sub(1))
+    }
+
     /// Find corresponding block of a row.
     pub fn block_of_row(&self, rowid: u32) -> u32 {
         // For example, there are 3 blocks, each of which has a first rowid of `233`, `2333`,
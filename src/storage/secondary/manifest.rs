@@ -1,35 +1,73 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
-//! Basic serialization implementation of `RisingLight`.
+//! Manifest of the secondary storage engine.
 //!
-//! Note that this storage format is not stable. The current manifest persistence
-//! depends on the stability of state machine of in-memory catalog. Any change in
-//! catalog implementation, e.g., [`TableId`](crate::types::TableId) assignment, will break the
-//! manifest. We will later come up with a better manifest design.
+//! The manifest is a log of [`ManifestOperation`]s grouped by `Begin` and `End`. It starts with a
+//! header line of [`MANIFEST_MAGIC`] and the format version, followed by one entry per line. Each
+//! entry is encoded in JSON and prefixed with its CRC32 checksum:
+//!
+//! ```text
+//! RISINGLIGHT_MANIFEST 1
+//! 6a3f2c1b "Begin"
+//! 0b8e7d2a {"AddRowSet":{"table_id":...,"rowset_id":3}}
+//! 9c1d4e5f "End"
+//! ```
+//!
+//! All entries carry explicit IDs, so that the manifest doesn't depend on how the catalog assigns
+//! them. To keep the log from growing forever, a checkpoint with only the live tables, RowSets
+//! and DVs periodically replaces the whole log.
+//!
+//! Manifests written before the format was versioned (`manifest.json`) are upgraded on open.
 
-use std::io::SeekFrom;
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::{SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
+use risinglight_proto::rowset::block_checksum::ChecksumType;
 use serde::{Deserialize, Serialize};
 use serde_json::Deserializer;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
-use tracing::warn;
+use tracing::{info, warn};
 
 use super::version_manager::EpochOp;
-use super::{SecondaryStorage, SecondaryTable, StorageResult, TracedStorageError};
+use super::{
+    build_checksum, verify_checksum, SecondaryStorage, SecondaryTable, StorageResult,
+    TracedStorageError,
+};
 use crate::catalog::{ColumnCatalog, TableRefId};
-use crate::types::{ColumnId, DatabaseId, SchemaId};
+use crate::types::{ColumnId, DatabaseId, SchemaId, TableId};
+
+/// Magic string at the beginning of the manifest.
+pub const MANIFEST_MAGIC: &str = "RISINGLIGHT_MANIFEST";
+
+/// Version of the manifest format.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// File name of the manifest.
+pub const MANIFEST_FILE_NAME: &str = "MANIFEST";
+
+/// File name of the unversioned manifest, which is upgraded on open.
+pub const LEGACY_MANIFEST_FILE_NAME: &str = "manifest.json";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CreateTableEntry {
     pub database_id: DatabaseId,
     pub schema_id: SchemaId,
+    /// Absent in legacy manifests, in which case it is assigned on upgrade.
+    #[serde(default)]
+    pub table_id: TableId,
     pub table_name: String,
     pub column_descs: Vec<ColumnCatalog>,
     pub ordered_pk_ids: Vec<ColumnId>,
 }
 
+impl CreateTableEntry {
+    pub fn table_ref_id(&self) -> TableRefId {
+        TableRefId::new(self.database_id, self.schema_id, self.table_id)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DropTableEntry {
     pub table_id: TableRefId,
@@ -60,6 +98,14 @@ pub struct DeleteDVEntry {
     pub rowset_id: u32,
 }
 
+/// The first entry of a checkpoint. IDs of deleted RowSets and DVs are not in the checkpoint, so
+/// the next IDs to allocate are recorded here.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointEntry {
+    pub next_rowset_id: u32,
+    pub next_dv_id: u64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ManifestOperation {
     CreateTable(CreateTableEntry),
@@ -68,42 +114,265 @@ pub enum ManifestOperation {
     DeleteRowSet(DeleteRowsetEntry),
     AddDV(AddDVEntry),
     DeleteDV(DeleteDVEntry),
+    Checkpoint(CheckpointEntry),
     // begin transaction
     Begin,
     // end transaction
     End,
 }
 
+/// The live entries in the manifest, from which a checkpoint is made.
+#[derive(Default)]
+struct ManifestState {
+    tables: HashMap<TableRefId, CreateTableEntry>,
+    rowsets: HashMap<(u32, u32), AddRowSetEntry>,
+    dvs: HashMap<(u32, u32, u64), AddDVEntry>,
+    next_rowset_id: u32,
+    next_dv_id: u64,
+}
+
+impl ManifestState {
+    fn apply(&mut self, op: &ManifestOperation) {
+        match op {
+            ManifestOperation::CreateTable(entry) => {
+                self.tables.insert(entry.table_ref_id(), entry.clone());
+            }
+            ManifestOperation::DropTable(entry) => {
+                let table_id = entry.table_id.table_id;
+                self.tables.remove(&entry.table_id);
+                self.rowsets.retain(|(t, _), _| *t != table_id);
+                self.dvs.retain(|(t, _, _), _| *t != table_id);
+            }
+            ManifestOperation::AddRowSet(entry) => {
+                self.next_rowset_id = self.next_rowset_id.max(entry.rowset_id + 1);
+                self.rowsets
+                    .insert((entry.table_id.table_id, entry.rowset_id), entry.clone());
+            }
+            ManifestOperation::DeleteRowSet(entry) => {
+                let key = (entry.table_id.table_id, entry.rowset_id);
+                self.rowsets.remove(&key);
+                // DVs of a deleted RowSet are useless
+                self.dvs.retain(|(t, r, _), _| (*t, *r) != key);
+            }
+            ManifestOperation::AddDV(entry) => {
+                self.next_dv_id = self.next_dv_id.max(entry.dv_id + 1);
+                self.dvs.insert(
+                    (entry.table_id.table_id, entry.rowset_id, entry.dv_id),
+                    entry.clone(),
+                );
+            }
+            ManifestOperation::DeleteDV(entry) => {
+                self.dvs
+                    .remove(&(entry.table_id.table_id, entry.rowset_id, entry.dv_id));
+            }
+            ManifestOperation::Checkpoint(entry) => {
+                self.next_rowset_id = self.next_rowset_id.max(entry.next_rowset_id);
+                self.next_dv_id = self.next_dv_id.max(entry.next_dv_id);
+            }
+            ManifestOperation::Begin | ManifestOperation::End => {}
+        }
+    }
+
+    /// Generate the entries of a checkpoint, in the order that they can be replayed.
+    fn checkpoint_entries(&self) -> Vec<ManifestOperation> {
+        let mut tables = self.tables.values().collect::<Vec<_>>();
+        tables.sort_by_key(|e| (e.database_id, e.schema_id, e.table_id));
+        let mut rowsets = self.rowsets.iter().collect::<Vec<_>>();
+        rowsets.sort_by_key(|(k, _)| **k);
+        let mut dvs = self.dvs.iter().collect::<Vec<_>>();
+        dvs.sort_by_key(|(k, _)| **k);
+
+        let mut entries = vec![ManifestOperation::Checkpoint(CheckpointEntry {
+            next_rowset_id: self.next_rowset_id,
+            next_dv_id: self.next_dv_id,
+        })];
+        entries.extend(
+            tables
+                .into_iter()
+                .map(|e| ManifestOperation::CreateTable(e.clone())),
+        );
+        entries.extend(
+            rowsets
+                .into_iter()
+                .map(|(_, e)| ManifestOperation::AddRowSet(e.clone())),
+        );
+        entries.extend(
+            dvs.into_iter()
+                .map(|(_, e)| ManifestOperation::AddDV(e.clone())),
+        );
+        entries
+    }
+}
+
 /// Handles all reads and writes to a manifest file
 pub struct Manifest {
+    /// Path of the manifest file.
+    path: PathBuf,
     file: tokio::fs::File,
     enable_fsync: bool,
+
+    /// Live entries of the manifest.
+    state: ManifestState,
+
+    /// Number of entries appended since the last checkpoint.
+    entries_since_checkpoint: usize,
+
+    /// Write a checkpoint after this number of entries are appended.
+    checkpoint_interval: usize,
 }
 
 impl Manifest {
-    pub async fn open(path: impl AsRef<Path>, enable_fsync: bool) -> StorageResult<Self> {
+    /// Open the manifest in `directory`. [`replay`](Self::replay) must be called before appending
+    /// entries.
+    pub async fn open(
+        directory: impl AsRef<Path>,
+        enable_fsync: bool,
+        checkpoint_interval: usize,
+    ) -> StorageResult<Self> {
+        let path = directory.as_ref().join(MANIFEST_FILE_NAME);
         let file = OpenOptions::default()
             .read(true)
             .write(true)
             .create(true)
-            .open(path.as_ref())
+            .open(&path)
             .await?;
-        Ok(Self { file, enable_fsync })
+        Ok(Self {
+            path,
+            file,
+            enable_fsync,
+            state: ManifestState::default(),
+            entries_since_checkpoint: 0,
+            checkpoint_interval,
+        })
     }
 
+    /// Read all committed entries from the manifest. Uncommitted entries at the end of the file
+    /// are discarded.
     pub async fn replay(&mut self) -> StorageResult<Vec<ManifestOperation>> {
         let mut data = String::new();
         self.file.seek(SeekFrom::Start(0)).await?;
-        let mut reader = BufReader::new(&mut self.file);
+        BufReader::new(&mut self.file)
+            .read_to_string(&mut data)
+            .await?;
+
+        let legacy_path = self.path.with_file_name(LEGACY_MANIFEST_FILE_NAME);
+        if data.is_empty() {
+            let ops = if tokio::fs::metadata(&legacy_path).await.is_ok() {
+                info!("upgrading manifest from {:?}", legacy_path);
+                let mut legacy = String::new();
+                tokio::fs::File::open(&legacy_path)
+                    .await?
+                    .read_to_string(&mut legacy)
+                    .await?;
+                Self::decode_legacy(&legacy)?
+            } else {
+                vec![]
+            };
+            for op in &ops {
+                self.state.apply(op);
+            }
+            self.checkpoint().await?;
+            if tokio::fs::metadata(&legacy_path).await.is_ok() {
+                tokio::fs::remove_file(&legacy_path).await?;
+            }
+            return Ok(ops);
+        }
+
+        if tokio::fs::metadata(&legacy_path).await.is_ok() {
+            // the upgrade was interrupted after the new manifest was written
+            tokio::fs::remove_file(&legacy_path).await?;
+        }
+
+        let (ops, valid_len) = Self::decode(&data)?;
+        if valid_len != data.len() {
+            // drop the uncommitted entries, so that they won't be mixed with new ones
+            self.file.set_len(valid_len as u64).await?;
+        }
+        self.file.seek(SeekFrom::Start(valid_len as u64)).await?;
+        for op in &ops {
+            self.state.apply(op);
+        }
+        Ok(ops)
+    }
+
+    /// Decode the manifest, returning the committed entries and the length of the data they take.
+    fn decode(data: &str) -> StorageResult<(Vec<ManifestOperation>, usize)> {
+        let (header, mut rest) = data
+            .split_once('\n')
+            .ok_or_else(|| TracedStorageError::decode("incomplete manifest header"))?;
+        match header.split_once(' ') {
+            Some((MANIFEST_MAGIC, version)) => match version.parse::<u32>() {
+                Ok(MANIFEST_VERSION) => {}
+                _ => {
+                    return Err(TracedStorageError::decode(format!(
+                        "unsupported manifest version {}",
+                        version
+                    )))
+                }
+            },
+            _ => return Err(TracedStorageError::decode("invalid manifest header")),
+        }
+
+        let mut ops = vec![];
+        let mut buffered_ops = vec![];
+        let mut begin = false;
+        let mut valid_len = header.len() + 1;
+        let mut offset = valid_len;
+
+        while !rest.is_empty() {
+            let (line, remaining) = match rest.split_once('\n') {
+                Some(x) => x,
+                None => {
+                    warn!("manifest: find incomplete entry");
+                    break;
+                }
+            };
+            rest = remaining;
+            offset += line.len() + 1;
+
+            let (checksum, json) = line
+                .split_once(' ')
+                .ok_or_else(|| TracedStorageError::decode("invalid manifest entry"))?;
+            let checksum = u64::from_str_radix(checksum, 16)
+                .map_err(|_| TracedStorageError::decode("invalid manifest entry checksum"))?;
+            verify_checksum(ChecksumType::Crc32, json.as_bytes(), checksum)?;
+
+            match serde_json::from_str(json)? {
+                ManifestOperation::Begin => {
+                    buffered_ops.clear();
+                    begin = true;
+                }
+                ManifestOperation::End => {
+                    ops.append(&mut buffered_ops);
+                    begin = false;
+                    valid_len = offset;
+                }
+                op => {
+                    if begin {
+                        buffered_ops.push(op);
+                    } else {
+                        warn!("manifest: find entry without txn begin");
+                    }
+                }
+            }
+        }
+
+        if valid_len != data.len() {
+            warn!("manifest: find uncommitted entries");
+        }
 
-        // TODO: don't read all to memory
-        reader.read_to_string(&mut data).await?;
+        Ok((ops, valid_len))
+    }
 
-        let stream = Deserializer::from_str(&data).into_iter::<ManifestOperation>();
+    /// Decode the unversioned manifest, which is a stream of JSON entries. Table IDs were not
+    /// recorded, so they are assigned in the same way as the catalog did.
+    fn decode_legacy(data: &str) -> StorageResult<Vec<ManifestOperation>> {
+        let stream = Deserializer::from_str(data).into_iter::<ManifestOperation>();
 
         let mut ops = vec![];
         let mut buffered_ops = vec![];
         let mut begin = false;
+        let mut next_table_ids: HashMap<(DatabaseId, SchemaId), TableId> = HashMap::new();
 
         for value in stream {
             let value = value?;
@@ -113,6 +382,14 @@ impl Manifest {
                     ops.append(&mut buffered_ops);
                     begin = false;
                 }
+                ManifestOperation::CreateTable(mut entry) if begin => {
+                    let next_table_id = next_table_ids
+                        .entry((entry.database_id, entry.schema_id))
+                        .or_default();
+                    entry.table_id = *next_table_id;
+                    *next_table_id += 1;
+                    buffered_ops.push(ManifestOperation::CreateTable(entry));
+                }
                 op => {
                     if begin {
                         buffered_ops.push(op);
@@ -130,31 +407,75 @@ impl Manifest {
         Ok(ops)
     }
 
+    /// Encode a group of entries as a txn.
+    fn encode(buf: &mut Vec<u8>, entries: &[ManifestOperation]) -> StorageResult<()> {
+        let begin = std::iter::once(&ManifestOperation::Begin);
+        let end = std::iter::once(&ManifestOperation::End);
+        for entry in begin.chain(entries).chain(end) {
+            let json = serde_json::to_vec(entry)?;
+            write!(buf, "{:08x} ", build_checksum(ChecksumType::Crc32, &json))?;
+            buf.extend_from_slice(&json);
+            buf.push(b'\n');
+        }
+        Ok(())
+    }
+
     pub async fn append(&mut self, entries: &[ManifestOperation]) -> StorageResult<()> {
-        let mut json = Vec::new();
-        serde_json::to_writer(&mut json, &ManifestOperation::Begin)?;
+        let mut data = Vec::new();
+        Self::encode(&mut data, entries)?;
+        self.file.write_all(&data).await?;
+        if self.enable_fsync {
+            self.file.sync_data().await?;
+        }
         for entry in entries {
-            serde_json::to_writer(&mut json, entry)?;
+            self.state.apply(entry);
         }
-        serde_json::to_writer(&mut json, &ManifestOperation::End)?;
-        self.file.write_all(&json).await?;
+
+        self.entries_since_checkpoint += entries.len();
+        if self.entries_since_checkpoint >= self.checkpoint_interval {
+            self.checkpoint().await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the manifest with a checkpoint of the live entries.
+    pub async fn checkpoint(&mut self) -> StorageResult<()> {
+        let mut data = format!("{} {}\n", MANIFEST_MAGIC, MANIFEST_VERSION).into_bytes();
+        Self::encode(&mut data, &self.state.checkpoint_entries())?;
+
+        // write to a temporary file and rename it, so that the manifest is replaced atomically
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = OpenOptions::default()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)
+            .await?;
+        file.write_all(&data).await?;
         if self.enable_fsync {
-            self.file.sync_data().await?;
+            file.sync_data().await?;
         }
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+
+        self.file = OpenOptions::default()
+            .read(true)
+            .write(true)
+            .open(&self.path)
+            .await?;
+        self.file.seek(SeekFrom::End(0)).await?;
+        self.entries_since_checkpoint = 0;
         Ok(())
     }
 }
 
 impl SecondaryStorage {
-    pub(super) fn apply_create_table(&self, entry: &CreateTableEntry) -> StorageResult<()> {
-        let CreateTableEntry {
-            database_id,
-            schema_id,
-            table_name,
-            column_descs,
-            ordered_pk_ids,
-        } = entry.clone();
-
+    /// Check whether a table can be created.
+    fn check_create_table(
+        &self,
+        database_id: DatabaseId,
+        schema_id: SchemaId,
+        table_name: &str,
+    ) -> StorageResult<()> {
         let db = self
             .catalog
             .get_database_by_id(database_id)
@@ -162,36 +483,47 @@ impl SecondaryStorage {
         let schema = db
             .get_schema_by_id(schema_id)
             .ok_or_else(|| TracedStorageError::not_found("schema", schema_id))?;
-        if schema.get_table_by_name(&table_name).is_some() {
+        if schema.get_table_by_name(table_name).is_some() {
             return Err(TracedStorageError::duplicated("table", table_name));
         }
-        let ref_id = TableRefId::new(database_id, schema_id, 0);
-        let table_id = self
-            .catalog
-            .add_table(
-                ref_id,
-                table_name.clone(),
-                column_descs.to_vec(),
-                false,
-                ordered_pk_ids,
-            )
-            .map_err(|_| TracedStorageError::duplicated("table", table_name))?;
+        Ok(())
+    }
 
-        let id = TableRefId {
-            database_id,
-            schema_id,
-            table_id,
-        };
+    fn add_table(&self, id: TableRefId, table_name: &str, column_descs: &[ColumnCatalog]) {
         let table = SecondaryTable::new(
             self.options.clone(),
             id,
-            &column_descs,
+            table_name,
+            column_descs,
             self.next_id.clone(),
             self.version.clone(),
             self.block_cache.clone(),
             self.txn_mgr.clone(),
         );
         self.tables.write().insert(id, table);
+    }
+
+    pub(super) fn apply_create_table(&self, entry: &CreateTableEntry) -> StorageResult<()> {
+        let CreateTableEntry {
+            database_id,
+            schema_id,
+            table_name,
+            column_descs,
+            ordered_pk_ids,
+            ..
+        } = entry.clone();
+
+        self.check_create_table(database_id, schema_id, &table_name)?;
+        self.catalog
+            .add_table_with_id(
+                entry.table_ref_id(),
+                table_name.clone(),
+                column_descs.clone(),
+                false,
+                ordered_pk_ids,
+            )
+            .map_err(|_| TracedStorageError::duplicated("table", table_name))?;
+        self.add_table(entry.table_ref_id(), &table_name, &column_descs);
 
         Ok(())
     }
@@ -204,21 +536,39 @@ impl SecondaryStorage {
         column_descs: &[ColumnCatalog],
         ordered_pk_ids: &[ColumnId],
     ) -> StorageResult<()> {
+        // the table ID is assigned by catalog first, so that it can be persisted to manifest
+        self.check_create_table(database_id, schema_id, table_name)?;
+        let table_id = self
+            .catalog
+            .add_table(
+                TableRefId::new(database_id, schema_id, 0),
+                table_name.to_string(),
+                column_descs.to_vec(),
+                false,
+                ordered_pk_ids.to_vec(),
+            )
+            .map_err(|_| TracedStorageError::duplicated("table", table_name))?;
         let entry = CreateTableEntry {
             database_id,
             schema_id,
+            table_id,
             table_name: table_name.to_string(),
             column_descs: column_descs.to_vec(),
             ordered_pk_ids: ordered_pk_ids.to_vec(),
         };
-
-        // persist to manifest first
-        self.version
-            .commit_changes(vec![EpochOp::CreateTable(entry.clone())])
-            .await?;
-
-        // then apply to catalog
-        self.apply_create_table(&entry)?;
+        self.add_table(entry.table_ref_id(), table_name, column_descs);
+
+        // then persist to manifest, and revert the change on failure
+        if let Err(err) = self
+            .version
+            .commit_changes(None, vec![EpochOp::CreateTable(entry.clone())])
+            .await
+        {
+            self.apply_drop_table(&DropTableEntry {
+                table_id: entry.table_ref_id(),
+            })?;
+            return Err(err);
+        }
 
         Ok(())
     }
@@ -279,8 +629,118 @@ impl SecondaryStorage {
         self.version.unpin(epoch);
 
         // and then persist to manifest
-        self.version.commit_changes(changeset).await?;
+        self.version.commit_changes(None, changeset).await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_rowset(table_id: u32, rowset_id: u32) -> ManifestOperation {
+        ManifestOperation::AddRowSet(AddRowSetEntry {
+            table_id: TableRefId::new(0, 0, table_id),
+            rowset_id,
+        })
+    }
+
+    fn encode(txns: &[&[ManifestOperation]]) -> String {
+        let mut data = format!("{} {}\n", MANIFEST_MAGIC, MANIFEST_VERSION).into_bytes();
+        for txn in txns {
+            Manifest::encode(&mut data, txn).unwrap();
+        }
+        String::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn test_manifest_decode() {
+        let data = encode(&[&[add_rowset(0, 1)], &[add_rowset(0, 2), add_rowset(1, 3)]]);
+        let (ops, valid_len) = Manifest::decode(&data).unwrap();
+        assert_eq!(ops.len(), 3);
+        assert_eq!(valid_len, data.len());
+
+        // uncommitted and incomplete entries are discarded
+        let uncommitted = encode(&[&[add_rowset(0, 4)]]);
+        let uncommitted = uncommitted.split_once('\n').unwrap().1;
+        let torn = format!("{}{}", data, &uncommitted[..uncommitted.len() - 10]);
+        let (ops, valid_len) = Manifest::decode(&torn).unwrap();
+        assert_eq!(ops.len(), 3);
+        assert_eq!(valid_len, data.len());
+    }
+
+    #[test]
+    fn test_manifest_corruption() {
+        let data = encode(&[&[add_rowset(0, 1)]]).replace("\"rowset_id\":1", "\"rowset_id\":7");
+        assert!(Manifest::decode(&data).is_err());
+
+        let data = encode(&[&[add_rowset(0, 1)]]).replacen(MANIFEST_MAGIC, "SOMETHING_ELSE", 1);
+        assert!(Manifest::decode(&data).is_err());
+
+        let data = encode(&[&[add_rowset(0, 1)]]).replacen(
+            &format!("{} {}", MANIFEST_MAGIC, MANIFEST_VERSION),
+            &format!("{} {}", MANIFEST_MAGIC, MANIFEST_VERSION + 1),
+            1,
+        );
+        assert!(Manifest::decode(&data).is_err());
+    }
+
+    #[test]
+    fn test_manifest_decode_legacy() {
+        let create_table = |name: &str| {
+            serde_json::json!({ "CreateTable": {
+                "database_id": 0,
+                "schema_id": 0,
+                "table_name": name,
+                "column_descs": [],
+                "ordered_pk_ids": [],
+            }})
+        };
+        let data = format!(
+            "\"Begin\"{}{}\"End\"\"Begin\"{}\"End\"",
+            create_table("t1"),
+            create_table("t2"),
+            create_table("t3")
+        );
+        let ops = Manifest::decode_legacy(&data).unwrap();
+        let table_ids = ops
+            .iter()
+            .map(|op| match op {
+                ManifestOperation::CreateTable(entry) => entry.table_id,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(table_ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_manifest_checkpoint_entries() {
+        let mut state = ManifestState::default();
+        for op in [
+            add_rowset(0, 1),
+            add_rowset(0, 2),
+            ManifestOperation::AddDV(AddDVEntry {
+                table_id: TableRefId::new(0, 0, 0),
+                dv_id: 5,
+                rowset_id: 1,
+            }),
+            ManifestOperation::DeleteRowSet(DeleteRowsetEntry {
+                table_id: TableRefId::new(0, 0, 0),
+                rowset_id: 1,
+            }),
+        ] {
+            state.apply(&op);
+        }
+        let entries = state.checkpoint_entries();
+        assert_eq!(entries.len(), 2);
+        match &entries[0] {
+            ManifestOperation::Checkpoint(entry) => {
+                assert_eq!(entry.next_rowset_id, 3);
+                assert_eq!(entry.next_dv_id, 6);
+            }
+            _ => panic!("expect checkpoint entry"),
+        }
+        assert!(matches!(&entries[1], ManifestOperation::AddRowSet(e) if e.rowset_id == 2));
+    }
+}
@@ -7,6 +7,7 @@ use std::future::Future;
 use std::sync::atomic::{AtomicU32, AtomicU64};
 use std::sync::Arc;
 
+pub use block::BlockCompression;
 use block::*;
 pub use checksum::*;
 use column::*;
@@ -23,6 +24,7 @@ pub use options::*;
 use parking_lot::RwLock;
 pub use row_handler::*;
 use rowset::*;
+use session::*;
 pub use table::*;
 use tokio::sync::oneshot::Sender;
 use tokio::sync::Mutex;
@@ -33,7 +35,7 @@ use transaction_manager::*;
 pub use txn_iterator::*;
 use version_manager::*;
 
-use super::{Storage, StorageResult, TracedStorageError};
+use super::{SessionId, Storage, StorageResult, TracedStorageError};
 use crate::catalog::{ColumnCatalog, RootCatalogRef, TableRefId};
 use crate::types::{ColumnId, DatabaseId, SchemaId};
 
@@ -57,6 +59,7 @@ mod index_builder;
 mod manifest;
 mod merge_iterator;
 mod rowset;
+mod session;
 mod statistics;
 mod storage;
 mod transaction_manager;
@@ -98,6 +101,9 @@ pub struct SecondaryStorage {
 
     /// Manages all ongoing txns
     txn_mgr: Arc<TransactionManager>,
+
+    /// The transactions started by `BEGIN` in each session.
+    sessions: RwLock<HashMap<SessionId, Arc<SessionTransaction>>>,
 }
 
 impl SecondaryStorage {
@@ -109,6 +115,11 @@ impl SecondaryStorage {
         &self.catalog
     }
 
+    /// Get the latest epoch, which can be used by `AS OF EPOCH` later.
+    pub fn current_epoch(&self) -> u64 {
+        self.version.current_epoch()
+    }
+
     pub async fn spawn_compactor(self: &Arc<Self>) {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let storage = self.clone();
@@ -181,6 +192,18 @@ impl Storage for SecondaryStorage {
         self.get_table_inner(table_id)
     }
 
+    fn get_table_in_session(
+        &self,
+        table_id: TableRefId,
+        session: SessionId,
+    ) -> StorageResult<SecondaryTable> {
+        let table = self.get_table_inner(table_id)?;
+        Ok(SecondaryTable {
+            session: self.get_session(session),
+            ..table
+        })
+    }
+
     fn drop_table(&self, table_id: TableRefId) -> Self::DropTableResultFuture<'_> {
         async move { self.drop_table_inner(table_id).await }
     }
@@ -9,6 +9,9 @@ use parking_lot::Mutex;
 use risinglight_proto::rowset::block_checksum::ChecksumType;
 use tracing::warn;
 
+use super::BlockCompression;
+use crate::catalog::ColumnCatalog;
+
 /// IO Backend of the rowset readers
 #[derive(Clone)]
 pub enum IOBackend {
@@ -54,8 +57,25 @@ pub struct StorageOptions {
     /// Whether using run-length encoding
     pub is_rle: bool,
 
+    /// Whether using dictionary encoding for char columns. Columns turn back to plain or
+    /// run-length encoding once they are found to have high cardinality.
+    pub is_dict: bool,
+
+    /// Compression applied on blocks, which is opt-in as it costs CPU on every cache miss
+    pub compression: BlockCompression,
+
+    /// Per-column compression, keyed by table name and column name, overriding `compression`
+    pub column_compression: HashMap<(String, String), BlockCompression>,
+
     /// Whether record first_key of each block into block_index
     pub record_first_key: bool,
+
+    /// Number of recent epochs that can be read by `AS OF EPOCH`. RowSets and DVs are not
+    /// vacuumed until they are out of this window.
+    pub retention_epochs: u64,
+
+    /// Number of manifest entries appended before the manifest is replaced by a checkpoint
+    pub manifest_checkpoint_interval: usize,
 }
 
 impl StorageOptions {
@@ -73,7 +93,12 @@ impl StorageOptions {
             },
             checksum_type: ChecksumType::Crc32,
             is_rle: false,
+            is_dict: true,
+            compression: BlockCompression::None,
+            column_compression: HashMap::new(),
             record_first_key: false,
+            retention_epochs: 1024,
+            manifest_checkpoint_interval: 4096,
         }
     }
 
@@ -86,7 +111,12 @@ impl StorageOptions {
             io_backend: IOBackend::in_memory(),
             checksum_type: ChecksumType::None,
             is_rle: false,
+            is_dict: true,
+            compression: BlockCompression::None,
+            column_compression: HashMap::new(),
             record_first_key: false,
+            retention_epochs: 16,
+            manifest_checkpoint_interval: 64,
         }
     }
 }
@@ -103,26 +133,67 @@ pub struct ColumnBuilderOptions {
     /// Whether using run-length encoding
     pub is_rle: bool,
 
-    /// Whether record first_key of each block
+    /// Whether using dictionary encoding for char columns. Columns turn back to plain or
+    /// run-length encoding once they are found to have high cardinality.
+    pub is_dict: bool,
+
+    /// Compression applied on blocks
+    pub compression: BlockCompression,
+
+    /// Per-column compression, resolved by [`for_column`](Self::for_column)
+    pub column_compression: Arc<HashMap<String, BlockCompression>>,
+
+    /// Whether building a bloom filter for each block, resolved by
+    /// [`for_column`](Self::for_column)
+    pub bloom_filter: bool,
+
+    /// Whether record first_key of each block. Always enabled for primary key columns by
+    /// [`for_column`](Self::for_column).
     pub record_first_key: bool,
 }
 
 impl ColumnBuilderOptions {
-    pub fn from_storage_options(options: &StorageOptions) -> Self {
+    /// Get the options for the columns of table `table_name`.
+    pub fn from_storage_options(options: &StorageOptions, table_name: &str) -> Self {
         Self {
             target_block_size: options.target_block_size,
             checksum_type: options.checksum_type,
             is_rle: options.is_rle,
+            is_dict: options.is_dict,
+            compression: options.compression,
+            column_compression: Arc::new(
+                (options.column_compression.iter())
+                    .filter(|((table, _), _)| table == table_name)
+                    .map(|((_, column), compression)| (column.clone(), *compression))
+                    .collect(),
+            ),
+            bloom_filter: false,
             record_first_key: options.record_first_key,
         }
     }
 
+    /// Get the options for the column, with per-column overrides applied.
+    pub fn for_column(&self, column: &ColumnCatalog) -> Self {
+        let mut options = self.clone();
+        if let Some(compression) = self.column_compression.get(column.name()) {
+            options.compression = *compression;
+        }
+        options.bloom_filter = column.has_bloom_filter();
+        // first keys of the sort key are used to seek blocks in range scans
+        options.record_first_key |= column.is_primary();
+        options
+    }
+
     #[cfg(test)]
     pub fn default_for_test() -> Self {
         Self {
             target_block_size: 4096,
             checksum_type: ChecksumType::Crc32,
             is_rle: false,
+            is_dict: false,
+            compression: BlockCompression::None,
+            column_compression: Arc::new(HashMap::new()),
+            bloom_filter: false,
             record_first_key: false,
         }
     }
@@ -133,6 +204,10 @@ impl ColumnBuilderOptions {
             target_block_size: 128,
             checksum_type: ChecksumType::None,
             is_rle: false,
+            is_dict: false,
+            compression: BlockCompression::None,
+            column_compression: Arc::new(HashMap::new()),
+            bloom_filter: false,
             record_first_key: false,
         }
     }
@@ -143,6 +218,10 @@ impl ColumnBuilderOptions {
             target_block_size: 128,
             checksum_type: ChecksumType::None,
             is_rle: true,
+            is_dict: false,
+            compression: BlockCompression::None,
+            column_compression: Arc::new(HashMap::new()),
+            bloom_filter: false,
             record_first_key: false,
         }
     }
@@ -153,6 +232,10 @@ impl ColumnBuilderOptions {
             target_block_size: 128,
             checksum_type: ChecksumType::None,
             is_rle: false,
+            is_dict: false,
+            compression: BlockCompression::None,
+            column_compression: Arc::new(HashMap::new()),
+            bloom_filter: false,
             record_first_key: true,
         }
     }
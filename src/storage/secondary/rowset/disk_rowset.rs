@@ -1,6 +1,5 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
-use std::borrow::Borrow;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
@@ -10,12 +9,12 @@ use moka::future::Cache;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncReadExt;
 
+use super::super::statistics::{may_match, ZoneMap};
 use super::super::{Block, BlockCacheKey, Column, ColumnIndex, ColumnSeekPosition, IOBackend};
 use super::{path_of_data_column, path_of_index_column, RowSetIterator};
 use crate::binder::BoundExpr;
-use crate::catalog::ColumnCatalog;
+use crate::catalog::{find_sort_key_id, ColumnCatalog};
 use crate::storage::secondary::column::ColumnReadableFile;
-use crate::storage::secondary::encode::PrimitiveFixedWidthEncode;
 use crate::storage::secondary::DeleteVector;
 use crate::storage::{StorageColumnRef, StorageResult};
 use crate::types::DataValue;
@@ -117,6 +116,34 @@ impl DiskRowset {
         self.rowset_id
     }
 
+    /// Get the zone map of the block containing `row_id` in a column, or of the whole column if
+    /// `row_id` is `None`.
+    pub fn zone_map(&self, column_ref: &StorageColumnRef, row_id: Option<u32>) -> Option<ZoneMap> {
+        let idx = match column_ref {
+            StorageColumnRef::Idx(idx) => *idx as usize,
+            StorageColumnRef::RowHandler => return None,
+        };
+        let index = self.columns[idx].index();
+        let kind = self.column_infos[idx].datatype().physical_kind();
+        match row_id {
+            Some(row_id) => {
+                let block_id = index.block_of_row(row_id);
+                ZoneMap::from_block(index.index(block_id), &kind)
+            }
+            None => ZoneMap::from_blocks(index.indexes(), &kind),
+        }
+    }
+
+    /// Check whether any row of the RowSet may satisfy the filter, based on the zone maps of
+    /// the columns. The `InputRef`s in `expr` are indexes of `column_refs`.
+    pub fn may_match(&self, column_refs: &[StorageColumnRef], expr: &BoundExpr) -> bool {
+        let zone_maps = column_refs
+            .iter()
+            .map(|column_ref| self.zone_map(column_ref, None))
+            .collect_vec();
+        may_match(expr, &zone_maps)
+    }
+
     pub async fn iter(
         self: &Arc<Self>,
         column_refs: Arc<[StorageColumnRef]>,
@@ -146,61 +173,60 @@ impl DiskRowset {
             .unwrap_or(0)
     }
 
-    /// Get the start row id to begin with for later table scanning.
-    /// If `begin_keys` is empty, we return `ColumnSeekPosition::RowId(0)` to indicate scanning
-    /// from the beginning, otherwise we scan the rowsets' first column indexes, find the first
-    /// block who contains data greater than or equal to `begin_key` and return the row id of
-    /// the block's first key. Currently, only the first column of the rowsets can be used to get
-    /// the start row id and this column should be primary key.
-    /// If `begin_key` is greater than all blocks' `first_key`, we return the `first_key` of the
-    /// last block.
-    /// Todo: support multi sort-keys range filter
-    pub async fn start_rowid(&self, begin_keys: &[DataValue]) -> ColumnSeekPosition {
-        if begin_keys.is_empty() {
-            return ColumnSeekPosition::RowId(0);
-        }
+    /// Index of the sort key column, which rows of the RowSet are sorted by.
+    pub fn sort_key_idx(&self) -> Option<usize> {
+        find_sort_key_id(&self.column_infos)
+    }
 
-        // for now, we only use the first column to get the start row id, which means the length
-        // of `begin_keys` can only be 0 or 1.
-        let begin_key = begin_keys[0].borrow();
-        let column = self.column(0);
-        let column_index = column.index();
-
-        let start_row_id = match *begin_key {
-            DataValue::Int32(begin_val) => {
-                let mut pre_block_first_key = 0;
-                for index in column_index.indexes() {
-                    let mut first_key: &[u8] = &index.first_key;
-                    let first_val: i32 = PrimitiveFixedWidthEncode::decode(&mut first_key);
-
-                    if first_val > begin_val {
-                        break;
-                    }
-                    pre_block_first_key = index.first_rowid;
-                }
-                pre_block_first_key
-            }
-            // Todo: support ohter type
-            _ => panic!("for now support range-filter scan by sort key type of int32"),
+    /// Get the first row id of the block who may contain the first row no less than `key` in
+    /// the sort key column, by binary search over the first keys of blocks.
+    ///
+    /// Returns 0 if the RowSet has no sort key, or the first keys are not recorded, in which
+    /// case the scan should begin from the start.
+    pub fn row_id_of_sort_key(&self, key: &DataValue) -> u32 {
+        let sort_key_idx = match self.sort_key_idx() {
+            Some(idx) => idx,
+            None => return 0,
         };
-        ColumnSeekPosition::RowId(start_row_id)
+        let kind = self.column_infos[sort_key_idx].datatype().physical_kind();
+        if key.data_type().map(|ty| ty.physical_kind()) != Some(kind) {
+            return 0;
+        }
+        let index = self.columns[sort_key_idx].index();
+        match index.block_of_sort_key(key) {
+            Some(block_id) if index.len() > 0 => index.index(block_id).first_rowid,
+            _ => 0,
+        }
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use std::borrow::Borrow;
-
     use tempfile::TempDir;
 
     use super::*;
     use crate::array::ArrayImpl;
     use crate::storage::secondary::rowset::rowset_builder::RowsetBuilder;
     use crate::storage::secondary::rowset::RowsetWriter;
-    use crate::storage::secondary::ColumnBuilderOptions;
+    use crate::storage::secondary::{BlockCompression, ColumnBuilderOptions};
     use crate::types::{DataTypeExt, DataTypeKind};
 
     pub async fn helper_build_rowset(tempdir: &TempDir, nullable: bool, len: usize) -> DiskRowset {
+        helper_build_rowset_with_options(
+            tempdir,
+            nullable,
+            len,
+            ColumnBuilderOptions::default_for_test(),
+        )
+        .await
+    }
+
+    pub async fn helper_build_rowset_with_options(
+        tempdir: &TempDir,
+        nullable: bool,
+        len: usize,
+        column_options: ColumnBuilderOptions,
+    ) -> DiskRowset {
         let columns = vec![
             ColumnCatalog::new(
                 0,
@@ -240,10 +266,7 @@ pub mod tests {
             ),
         ];
 
-        let mut builder = RowsetBuilder::new(
-            columns.clone().into(),
-            ColumnBuilderOptions::default_for_test(),
-        );
+        let mut builder = RowsetBuilder::new(columns.clone().into(), column_options);
 
         for _ in 0..100 {
             builder.append(
@@ -399,25 +422,55 @@ pub mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_start_id() {
+    async fn test_get_compressed_block() {
         let tempdir = tempfile::tempdir().unwrap();
-        let rowset = helper_build_rowset_with_first_key_recorded(&tempdir).await;
-        let start_keys = vec![DataValue::Int32(222)];
+        let rowset = helper_build_rowset(&tempdir, true, 1000).await;
 
-        {
-            let start_rid = match rowset.start_rowid(start_keys.borrow()).await {
-                ColumnSeekPosition::RowId(x) => x,
-                _ => panic!("Unable to reach the branch"),
-            };
-            assert_eq!(start_rid, 196_u32);
-        }
-        {
-            let start_keys = vec![DataValue::Int32(10000)];
-            let start_rid = match rowset.start_rowid(start_keys.borrow()).await {
-                ColumnSeekPosition::RowId(x) => x,
-                _ => panic!("Unable to reach the branch"),
-            };
-            assert_eq!(start_rid, 252_u32);
+        let mut column_options = ColumnBuilderOptions::default_for_test();
+        column_options.compression = BlockCompression::Zstd;
+        column_options.column_compression = Arc::new(
+            [
+                ("v2".to_string(), BlockCompression::Lz4),
+                ("v3".to_string(), BlockCompression::None),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let tempdir = tempfile::tempdir().unwrap();
+        let compressed =
+            helper_build_rowset_with_options(&tempdir, true, 1000, column_options).await;
+
+        for idx in 0..3 {
+            let (column, compressed_column) = (rowset.column(idx), compressed.column(idx));
+            let index = column.index().indexes();
+            let compressed_index = compressed_column.index().indexes();
+            assert_eq!(index.len(), compressed_index.len());
+            if idx == 2 {
+                assert_eq!(index, compressed_index);
+            } else {
+                assert!(index[0].length > compressed_index[0].length);
+            }
+            for block_id in 0..index.len() as u32 {
+                let (header, block) = column.get_block(block_id).await.unwrap();
+                let (compressed_header, compressed_block) =
+                    compressed_column.get_block(block_id).await.unwrap();
+                assert_eq!(header.block_type, compressed_header.block_type);
+                assert_eq!(block, compressed_block);
+            }
         }
     }
+
+    #[tokio::test]
+    async fn test_row_id_of_sort_key() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let rowset = helper_build_rowset_with_first_key_recorded(&tempdir).await;
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int32(222)), 196);
+        // a key at the beginning of a block may also be at the end of the previous block
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int32(224)), 196);
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int32(225)), 224);
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int32(10000)), 252);
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int32(-1)), 0);
+        // keys of other types can't be used to seek
+        assert_eq!(rowset.row_id_of_sort_key(&DataValue::Int64(222)), 0);
+    }
 }
@@ -31,7 +31,10 @@ impl RowsetBuilder {
             builders: columns
                 .iter()
                 .map(|column| {
-                    ColumnBuilderImpl::new_from_datatype(&column.datatype(), column_options.clone())
+                    ColumnBuilderImpl::new_from_datatype(
+                        &column.datatype(),
+                        column_options.for_column(column),
+                    )
                 })
                 .collect_vec(),
             columns,
@@ -3,8 +3,10 @@
 use std::sync::Arc;
 
 use bitvec::prelude::BitVec;
+use itertools::Itertools;
 use smallvec::smallvec;
 
+use super::super::statistics::may_match;
 use super::super::{ColumnIteratorImpl, ColumnSeekPosition, SecondaryIteratorImpl};
 use super::DiskRowset;
 use crate::array::{Array, ArrayImpl};
@@ -18,10 +20,14 @@ const ROWSET_MAX_OUTPUT: usize = 2048;
 
 /// Iterates on a `RowSet`
 pub struct RowSetIterator {
+    rowset: Arc<DiskRowset>,
     column_refs: Arc<[StorageColumnRef]>,
     dvs: Vec<Arc<DeleteVector>>,
     column_iterators: Vec<ColumnIteratorImpl>,
     filter_expr: Option<(BoundExpr, BitVec)>,
+    /// Position of the sort key column in `column_refs`, by which the rows are trimmed to
+    /// `start_keys` and `end_keys`.
+    sort_key_pos: Option<usize>,
     start_keys: Vec<DataValue>,
     end_keys: Vec<DataValue>,
     meet_start_key_before: bool,
@@ -39,7 +45,7 @@ impl RowSetIterator {
     ) -> StorageResult<Self> {
         let start_row_id = match seek_pos {
             ColumnSeekPosition::RowId(row_id) => row_id,
-            _ => todo!(),
+            ColumnSeekPosition::SortKey(key) => rowset.row_id_of_sort_key(&key),
         };
 
         if column_refs.len() == 0 {
@@ -96,11 +102,19 @@ impl RowSetIterator {
             None
         };
 
+        let sort_key_pos = rowset.sort_key_idx().and_then(|sort_key| {
+            column_refs
+                .iter()
+                .position(|column_ref| *column_ref == StorageColumnRef::Idx(sort_key as u32))
+        });
+
         Ok(Self {
+            rowset,
             column_refs,
             dvs,
             column_iterators,
             filter_expr,
+            sort_key_pos,
             start_keys: start_keys.to_vec(),
             end_keys: end_keys.to_vec(),
             meet_end_key_before: false,
@@ -120,7 +134,7 @@ impl RowSetIterator {
         let filter_context = self.filter_expr.as_ref();
         // It's guaranteed that `expected_size` <= the number of items left
         // in the current block, if provided
-        let mut fetch_size = {
+        let fetch_size = {
             // We find the minimum fetch hints from the column iterators first
             let mut min = None;
             for it in &self.column_iterators {
@@ -133,14 +147,33 @@ impl RowSetIterator {
                     }
                 }
             }
-            min.unwrap_or(ROWSET_MAX_OUTPUT)
+            min
         };
+        let in_block = fetch_size.is_some();
+        let mut fetch_size = fetch_size.unwrap_or(ROWSET_MAX_OUTPUT);
         if let Some(x) = expected_size {
             // Then, if `expected_size` is available, let `fetch_size`
             // be the min(fetch_size, expected_size)
             fetch_size = if x > fetch_size { fetch_size } else { x }
         }
 
+        // The rows to fetch lie in one block of each column. If the zone maps of these blocks
+        // show that no row can satisfy the filter, skip the rows without reading the blocks.
+        if let (true, Some((expr, _))) = (in_block, filter_context) {
+            let row_id = self.column_iterators[0].fetch_current_row_id();
+            let zone_maps = self
+                .column_refs
+                .iter()
+                .map(|column_ref| self.rowset.zone_map(column_ref, Some(row_id)))
+                .collect_vec();
+            if !may_match(expr, &zone_maps) {
+                for iter in &mut self.column_iterators {
+                    iter.skip(fetch_size);
+                }
+                return Ok((false, None));
+            }
+        }
+
         let mut arrays: PackedVec<Option<ArrayImpl>> = smallvec![];
         let mut common_chunk_range = None;
 
@@ -249,8 +282,6 @@ impl RowSetIterator {
             }
             visibility_map = Some(filter_bitmap);
         }
-        // whether we have meet end key in cur scan.
-        let mut meet_end_key = false;
         // At this stage, we know that some rows survived from the filter scan if happend, so
         // just fetch the next batch for every other columns, and we have `visibility_map` to
         // indicate the visibility of its rows
@@ -280,52 +311,46 @@ impl RowSetIterator {
                     arrays[id] = Some(array);
                 }
             }
-            // For now, we only support range-filter scan by first column.
-            if id == 0 {
-                if !self.start_keys.is_empty() && !self.meet_start_key_before {
-                    // find the first row in range to begin with
-                    self.meet_start_key_before = true;
-                    let array = arrays[0].as_ref().unwrap();
-                    let len = array.len();
-                    let start_key = &self.start_keys[0];
-                    let start_row_id =
-                        (0..len).position(|idx| start_key - &array.get(idx) <= DataValue::Int32(0));
-                    if start_row_id.is_none() {
-                        // the `begin_key` is greater than all of the data, so on item survives in
-                        // this scan
-                        return Ok((true, None));
-                    }
-                    let start_row_id = start_row_id.unwrap();
-                    let new_bitmap =
-                        Self::mark_inaccessible(visibility_map.as_ref(), 0, start_row_id, len)
-                            .await;
-                    visibility_map = Some(new_bitmap);
-                }
+        }
+
+        if common_chunk_range.is_none() {
+            return Ok((true, None));
+        };
 
-                if !self.end_keys.is_empty() && arrays[0].is_some() {
-                    let array = arrays[0].as_ref().unwrap();
-                    let len = array.len();
-                    let end_key = &self.end_keys[0];
-                    if end_key - &array.get(len - 1) < DataValue::Int32(0) {
-                        // this block's last key is greater than the `end_key`,
-                        // so we will finish scan after scan this block
-                        meet_end_key = true;
-                        let end_row_id = (0..len)
-                            .position(|idx| end_key - &array.get(idx) < DataValue::Int32(0))
-                            .unwrap();
+        // whether we have meet end key in cur scan.
+        let mut meet_end_key = false;
+        // Rows in a RowSet are sorted by the sort key, so we trim the rows out of the range of
+        // `start_keys` and `end_keys` (both inclusive). For now, we only support range-filter
+        // scan by the first sort key.
+        if let Some(array) = self.sort_key_pos.and_then(|pos| arrays[pos].as_ref()) {
+            let len = array.len();
+            if let (Some(start_key), false) = (self.start_keys.first(), self.meet_start_key_before)
+            {
+                // find the first row in range to begin with
+                match (0..len).position(|idx| array.get(idx) >= *start_key) {
+                    Some(start_row_id) => {
+                        self.meet_start_key_before = true;
                         let new_bitmap =
-                            Self::mark_inaccessible(visibility_map.as_ref(), end_row_id, len, len)
+                            Self::mark_inaccessible(visibility_map.as_ref(), 0, start_row_id, len)
                                 .await;
                         visibility_map = Some(new_bitmap);
                     }
+                    // all rows in this batch are less than the `start_key`
+                    None => return Ok((false, None)),
+                }
+            }
+            if let Some(end_key) = self.end_keys.first() {
+                if let Some(end_row_id) = (0..len).position(|idx| array.get(idx) > *end_key) {
+                    // we will finish scan after this batch
+                    meet_end_key = true;
+                    let new_bitmap =
+                        Self::mark_inaccessible(visibility_map.as_ref(), end_row_id, len, len)
+                            .await;
+                    visibility_map = Some(new_bitmap);
                 }
             }
         }
 
-        if common_chunk_range.is_none() {
-            return Ok((true, None));
-        };
-
         Ok((
             meet_end_key,
             StorageChunk::construct(
@@ -544,6 +569,59 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_rowset_iterator_skip_blocks() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let rowset = Arc::new(helper_build_rowset_with_first_key_recorded(&tempdir).await);
+        let column_refs: Arc<[StorageColumnRef]> =
+            vec![StorageColumnRef::Idx(0), StorageColumnRef::Idx(1)].into();
+
+        // v0 >= 250: blocks before row 250 are skipped by zone maps
+        let filter = |op, value| {
+            BoundExpr::BinaryOp(BoundBinaryOp {
+                op,
+                left_expr: Box::new(BoundExpr::InputRef(BoundInputRef {
+                    index: 0,
+                    return_type: DataType {
+                        kind: DataTypeKind::Int(None),
+                        physical_kind: PhysicalDataTypeKind::Int32,
+                        nullable: false,
+                    },
+                })),
+                right_expr: Box::new(BoundExpr::Constant(DataValue::Int32(value))),
+                return_type: Some(DataType {
+                    kind: DataTypeKind::Boolean,
+                    physical_kind: PhysicalDataTypeKind::Bool,
+                    nullable: false,
+                }),
+            })
+        };
+        let expr = filter(BinaryOperator::GtEq, 250);
+        assert!(rowset.may_match(&column_refs, &expr));
+        let mut it = rowset
+            .iter(
+                column_refs.clone(),
+                vec![],
+                ColumnSeekPosition::RowId(0),
+                Some(expr),
+                &[],
+                &[],
+            )
+            .await
+            .unwrap();
+        let mut column0 = vec![];
+        let mut column1 = vec![];
+        while let Some(chunk) = it.next_batch(None).await.unwrap() {
+            data_from_chunk(&chunk, &mut column0, 0).await;
+            data_from_chunk(&chunk, &mut column1, 1).await;
+        }
+        assert_eq!(column0, (250..=279).collect_vec());
+        assert_eq!(column1, (251..=280).collect_vec());
+
+        // v0 > 279: no rows in the rowset can match
+        assert!(!rowset.may_match(&column_refs, &filter(BinaryOperator::Gt, 279)));
+    }
+
     #[tokio::test]
     async fn test_rowset_iterator_with_range_filter() {
         {
@@ -817,6 +895,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_rowset_iterator_seek_sort_key() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let rowset = Arc::new(helper_build_rowset_with_first_key_recorded(&tempdir).await);
+        // the sort key is not the first column to scan, and the range crosses blocks
+        let start_keys = vec![DataValue::Int32(100)];
+        let end_keys = vec![DataValue::Int32(230)];
+        let mut it = rowset
+            .iter(
+                vec![StorageColumnRef::Idx(1), StorageColumnRef::Idx(0)].into(),
+                vec![],
+                ColumnSeekPosition::SortKey(start_keys[0].clone()),
+                None,
+                &start_keys,
+                &end_keys,
+            )
+            .await
+            .unwrap();
+
+        let mut column0 = vec![];
+        let mut column1 = vec![];
+        while let Some(chunk) = it.next_batch(Some(10)).await.unwrap() {
+            data_from_chunk(&chunk, &mut column1, 0).await;
+            data_from_chunk(&chunk, &mut column0, 1).await;
+        }
+        assert_eq!(column0, (100..=230).collect_vec());
+        assert_eq!(column1, (101..=231).collect_vec());
+    }
+
     async fn data_from_chunk(chunk: &StorageChunk, column: &mut Vec<i32>, index: usize) {
         if let ArrayImpl::Int32(array) = chunk.array_at(index) {
             let bit_map = match chunk.visibility() {
@@ -0,0 +1,286 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use super::version_manager::{EpochOp, Snapshot, VersionManager};
+use super::{DeleteVector, DiskRowset, SecondaryStorage, StorageResult, TracedStorageError};
+use crate::storage::SessionId;
+
+/// A transaction spanning multiple statements, started by `BEGIN` and finished by `COMMIT` or
+/// `ROLLBACK`.
+///
+/// Each statement still runs in its own [`SecondaryTransaction`](super::SecondaryTransaction).
+/// Instead of committing to the [`VersionManager`], a statement hands its changes over to the
+/// session, and the following statements read from the session snapshot, so that they can see
+/// the uncommitted changes. On commit, all RowSets and DVs of the session are committed
+/// together, which produces a single `Begin`/`End` pair in the manifest and a single epoch.
+///
+/// Changes of the session are checked for conflicts against the epoch pinned at `BEGIN`, so a
+/// `COMMIT` may fail with a retryable error if another transaction committed first.
+///
+/// Catalog operations (`CREATE TABLE`, `DROP TABLE`) are not part of the session and take
+/// effect immediately.
+pub struct SessionTransaction {
+    /// Epoch pinned at `BEGIN`.
+    epoch: u64,
+
+    /// Reference version manager.
+    version: Arc<VersionManager>,
+
+    inner: Mutex<SessionTransactionInner>,
+}
+
+struct SessionTransactionInner {
+    /// The snapshot pinned at `BEGIN`, with the changes of finished statements applied.
+    snapshot: Arc<Snapshot>,
+
+    /// (TableId, RowSetId) -> RowSets added in this session
+    rowsets: HashMap<(u32, u32), Arc<DiskRowset>>,
+
+    /// (TableId, DVId) -> DVs added in this session
+    dvs: HashMap<(u32, u64), Arc<DeleteVector>>,
+
+    /// Changes to be committed, in the order they are made.
+    changeset: Vec<EpochOp>,
+}
+
+impl SessionTransaction {
+    fn begin(version: Arc<VersionManager>) -> Self {
+        let (epoch, snapshot) = version.pin();
+        Self {
+            epoch,
+            version,
+            inner: Mutex::new(SessionTransactionInner {
+                snapshot,
+                rowsets: HashMap::new(),
+                dvs: HashMap::new(),
+                changeset: vec![],
+            }),
+        }
+    }
+
+    /// Get the epoch pinned by the session.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Get the snapshot of the session, including the changes made by previous statements.
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        self.inner.lock().snapshot.clone()
+    }
+
+    /// Get a RowSet added in this session.
+    pub fn get_rowset(&self, table_id: u32, rowset_id: u32) -> Option<Arc<DiskRowset>> {
+        self.inner
+            .lock()
+            .rowsets
+            .get(&(table_id, rowset_id))
+            .cloned()
+    }
+
+    /// Get a DV added in this session.
+    pub fn get_dv(&self, table_id: u32, dv_id: u64) -> Option<Arc<DeleteVector>> {
+        self.inner.lock().dvs.get(&(table_id, dv_id)).cloned()
+    }
+
+    /// Apply the changes of a finished statement to the session.
+    pub fn add_changes(&self, changeset: Vec<EpochOp>) {
+        let mut inner = self.inner.lock();
+        let mut snapshot = inner.snapshot.as_ref().clone();
+        for op in &changeset {
+            match op {
+                EpochOp::AddRowSet((entry, rowset)) => {
+                    let table_id = entry.table_id.table_id;
+                    inner
+                        .rowsets
+                        .insert((table_id, entry.rowset_id), rowset.clone());
+                    snapshot.add_rowset(table_id, entry.rowset_id);
+                }
+                EpochOp::AddDV((entry, dv)) => {
+                    let table_id = entry.table_id.table_id;
+                    inner.dvs.insert((table_id, entry.dv_id), dv.clone());
+                    snapshot.add_dv(table_id, entry.rowset_id, entry.dv_id);
+                }
+                op => unreachable!("unexpected operation in session: {:?}", op),
+            }
+        }
+        inner.snapshot = Arc::new(snapshot);
+        inner.changeset.extend(changeset);
+    }
+
+    /// Commit all changes of the session in one epoch.
+    async fn commit(&self) -> StorageResult<()> {
+        let changeset = {
+            let mut inner = self.inner.lock();
+            inner.rowsets.clear();
+            inner.dvs.clear();
+            std::mem::take(&mut inner.changeset)
+        };
+        let result = if changeset.is_empty() {
+            Ok(())
+        } else {
+            self.version
+                .commit_changes(Some(self.epoch), changeset)
+                .await
+                .map(|_| ())
+        };
+        self.version.unpin(self.epoch);
+        result
+    }
+
+    /// Discard all changes of the session, and return them so that their files can be removed.
+    fn rollback(&self) -> Vec<EpochOp> {
+        let changeset = {
+            let mut inner = self.inner.lock();
+            inner.rowsets.clear();
+            inner.dvs.clear();
+            std::mem::take(&mut inner.changeset)
+        };
+        self.version.unpin(self.epoch);
+        changeset
+    }
+}
+
+impl SecondaryStorage {
+    /// Start a transaction spanning multiple statements in `session`.
+    pub fn begin_transaction(&self, session: SessionId) -> StorageResult<()> {
+        let mut sessions = self.sessions.write();
+        if sessions.contains_key(&session) {
+            return Err(TracedStorageError::transaction_state(
+                "there is already a transaction in progress",
+            ));
+        }
+        sessions.insert(
+            session,
+            Arc::new(SessionTransaction::begin(self.version.clone())),
+        );
+        Ok(())
+    }
+
+    /// Commit the transaction started by [`begin_transaction`](Self::begin_transaction).
+    pub async fn commit_transaction(&self, session: SessionId) -> StorageResult<()> {
+        let txn = self.take_session(session)?;
+        txn.commit().await
+    }
+
+    /// Abort the transaction started by [`begin_transaction`](Self::begin_transaction), and
+    /// remove the RowSets and DVs it has flushed to disk.
+    pub async fn rollback_transaction(&self, session: SessionId) -> StorageResult<()> {
+        let txn = self.take_session(session)?;
+        for op in txn.rollback() {
+            // the files are never referenced by the manifest, so failures only leak them
+            match op {
+                EpochOp::AddRowSet((entry, _)) => {
+                    let path = self
+                        .options
+                        .path
+                        .join(format!("{}_{}", entry.table_id.table_id, entry.rowset_id));
+                    tokio::fs::remove_dir_all(path).await.ok();
+                }
+                EpochOp::AddDV((entry, _)) => {
+                    let path = self.options.path.join(format!(
+                        "dv/{}_{}_{}.dv",
+                        entry.table_id.table_id, entry.rowset_id, entry.dv_id
+                    ));
+                    tokio::fs::remove_file(path).await.ok();
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the transaction in progress of `session`, if any.
+    pub(super) fn get_session(&self, session: SessionId) -> Option<Arc<SessionTransaction>> {
+        self.sessions.read().get(&session).cloned()
+    }
+
+    fn take_session(&self, session: SessionId) -> StorageResult<Arc<SessionTransaction>> {
+        self.sessions.write().remove(&session).ok_or_else(|| {
+            TracedStorageError::transaction_state("there is no transaction in progress")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
+
+    use super::*;
+    use crate::array::{ArrayImpl, DataChunk};
+    use crate::catalog::{ColumnCatalog, TableRefId};
+    use crate::storage::secondary::{IOBackend, StorageOptions};
+    use crate::storage::{Storage, StorageColumnRef, Table, Transaction};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    async fn row_count(
+        storage: &SecondaryStorage,
+        table_id: TableRefId,
+        session: SessionId,
+    ) -> usize {
+        let table = storage.get_table_in_session(table_id, session).unwrap();
+        let txn = table.read().await.unwrap();
+        let values = txn
+            .aggreagate_block_stat(&[(BlockStatisticsType::RowCount, StorageColumnRef::Idx(0))])
+            .unwrap();
+        txn.abort().await.unwrap();
+        values[0].as_usize().unwrap().unwrap_or(0)
+    }
+
+    /// Count the RowSet directories of the first table.
+    fn rowset_dirs(path: &std::path::Path) -> usize {
+        std::fs::read_dir(path)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with("0_")
+            })
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_session_isolation_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = StorageOptions {
+            io_backend: IOBackend::NormalRead,
+            ..StorageOptions::default_for_test(dir.path().to_path_buf())
+        };
+        let storage = SecondaryStorage::open(options).await.unwrap();
+        let column =
+            ColumnCatalog::new(0, DataTypeKind::Int(None).not_null().to_column("v".into()));
+        storage
+            .create_table(0, 0, "t", &[column], &[])
+            .await
+            .unwrap();
+        let table_id = TableRefId {
+            database_id: 0,
+            schema_id: 0,
+            table_id: 0,
+        };
+
+        storage.begin_transaction(1).unwrap();
+        storage.begin_transaction(2).unwrap();
+        let table = storage.get_table_in_session(table_id, 1).unwrap();
+        let mut txn = table.write().await.unwrap();
+        txn.append(DataChunk::from_iter([ArrayImpl::new_int32(
+            [Some(1), Some(2)].into_iter().collect(),
+        )]))
+        .await
+        .unwrap();
+        txn.commit().await.unwrap();
+        assert_eq!(rowset_dirs(dir.path()), 1);
+
+        // the changes are only visible in the session making them
+        assert_eq!(row_count(&storage, table_id, 1).await, 2);
+        assert_eq!(row_count(&storage, table_id, 2).await, 0);
+        assert_eq!(row_count(&storage, table_id, 0).await, 0);
+
+        storage.rollback_transaction(1).await.unwrap();
+        assert_eq!(rowset_dirs(dir.path()), 0);
+        storage.commit_transaction(2).await.unwrap();
+        assert_eq!(row_count(&storage, table_id, 0).await, 0);
+    }
+}
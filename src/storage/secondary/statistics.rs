@@ -10,18 +10,39 @@
 //!
 //! RowCount is NOT a precise statistics. It simply adds up the row counts of all blocks. As there
 //! might be rows deleted in deletion vector, the aggregated RowCount is not always accurate.
+//!
+//! ## MinValue, MaxValue and NullCount
+//!
+//! Minimum and maximum non-null values of a block, in the encoding of the block, and the number
+//! of nulls in a block. They form the [`ZoneMap`] of a block, which is used to skip blocks and
+//! RowSets that cannot satisfy the filter of a scan.
+//!
+//! ## BloomFilter
+//!
+//! A [`BloomFilter`] of the non-null values of a block, only built for the columns created with
+//! the `bloom_filter_columns` table option. It is part of the [`ZoneMap`], and used to skip
+//! blocks and RowSets on equality and IN predicates.
 
 use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
 
 use super::index::ColumnIndex;
-use crate::types::DataValue;
+use crate::storage::{StorageResult, TracedStorageError};
+use crate::types::{DataValue, PhysicalDataTypeKind};
 
 mod row_count;
 use row_count::*;
 mod distinct_value;
 use distinct_value::*;
+mod null_count;
+use null_count::*;
+mod min_max_value;
+use min_max_value::*;
+mod bloom_filter;
+pub use bloom_filter::*;
 mod statistics_builder;
 pub use statistics_builder::*;
+mod zone_map;
+pub use zone_map::*;
 
 /// Get the aggregated statistics from pre-aggregated per-block statistics.
 pub trait StatisticsGlobalAgg {
@@ -29,11 +50,25 @@ pub trait StatisticsGlobalAgg {
     fn get_output(&self) -> DataValue;
 }
 
+/// Create the aggregator of statistics `ty` on a column of `kind`.
+///
+/// Bloom filters can't be aggregated into a value, as the filters of blocks may have different
+/// sizes and can't be merged.
 pub fn create_statistics_global_aggregator(
     ty: BlockStatisticsType,
-) -> Box<dyn StatisticsGlobalAgg> {
-    match ty {
+    kind: PhysicalDataTypeKind,
+) -> StorageResult<Box<dyn StatisticsGlobalAgg>> {
+    Ok(match ty {
         BlockStatisticsType::RowCount => Box::new(RowCountGlobalAgg::create()),
         BlockStatisticsType::DistinctValue => Box::new(DistinctValueGlobalAgg::create()),
-    }
+        BlockStatisticsType::NullCount => Box::new(NullCountGlobalAgg::create()),
+        BlockStatisticsType::MinValue | BlockStatisticsType::MaxValue => {
+            Box::new(MinMaxValueGlobalAgg::create(ty, kind))
+        }
+        BlockStatisticsType::BloomFilter => {
+            return Err(TracedStorageError::unsupported(
+                "global aggregation of bloom filters",
+            ))
+        }
+    })
 }
@@ -0,0 +1,229 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use bytes::{Buf, BufMut};
+use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
+use risinglight_proto::rowset::BlockStatistics;
+use rust_decimal::Decimal;
+
+use crate::array::Array;
+use crate::storage::secondary::encode::PrimitiveFixedWidthEncode;
+use crate::types::{BlobRef, DataValue, Date, Interval, Timestamp, TimestampTz};
+
+/// Number of bits per distinct value, which gives a false positive rate of about 1%.
+const BITS_PER_KEY: usize = 10;
+
+/// Number of hash functions, which is optimal for [`BITS_PER_KEY`].
+const NUM_HASHES: u32 = 7;
+
+/// Values that can be added into a [`BloomFilter`].
+///
+/// Equal values must have the same hash, and the hash must be stable across processes, as bloom
+/// filters are persisted.
+pub trait BloomHash {
+    fn bloom_hash(&self) -> u64;
+}
+
+macro_rules! impl_bloom_hash_by_encode {
+    ($($t:ty),*) => {
+        $(
+            impl BloomHash for $t {
+                fn bloom_hash(&self) -> u64 {
+                    let mut buffer = Vec::with_capacity(<$t>::WIDTH);
+                    self.encode(&mut buffer);
+                    hash_bytes(&buffer)
+                }
+            }
+        )*
+    };
+}
+
+impl_bloom_hash_by_encode!(bool, i32, i64, Date, Interval, Timestamp, TimestampTz);
+
+impl BloomHash for f64 {
+    fn bloom_hash(&self) -> u64 {
+        // `0.0` and `-0.0` are equal
+        let value = if *self == 0.0 { 0.0 } else { *self };
+        hash_bytes(&value.to_le_bytes())
+    }
+}
+
+impl BloomHash for Decimal {
+    fn bloom_hash(&self) -> u64 {
+        // decimals of different scales, e.g. `1.0` and `1.00`, are equal
+        hash_bytes(&self.normalize().serialize())
+    }
+}
+
+impl BloomHash for str {
+    fn bloom_hash(&self) -> u64 {
+        hash_bytes(self.as_bytes())
+    }
+}
+
+impl BloomHash for BlobRef {
+    fn bloom_hash(&self) -> u64 {
+        hash_bytes(self.as_ref())
+    }
+}
+
+/// Get the bloom hash of a value. Returns `None` for nulls.
+pub fn bloom_hash_of_value(value: &DataValue) -> Option<u64> {
+    Some(match value {
+        DataValue::Null => return None,
+        DataValue::Bool(v) => v.bloom_hash(),
+        DataValue::Int32(v) => v.bloom_hash(),
+        DataValue::Int64(v) => v.bloom_hash(),
+        DataValue::Float64(v) => v.bloom_hash(),
+        DataValue::String(v) => str::bloom_hash(v),
+        DataValue::Blob(v) => BlobRef::bloom_hash(v),
+        DataValue::Decimal(v) => v.bloom_hash(),
+        DataValue::Date(v) => v.bloom_hash(),
+        DataValue::Interval(v) => v.bloom_hash(),
+        DataValue::Timestamp(v) => v.bloom_hash(),
+        DataValue::TimestampTz(v) => v.bloom_hash(),
+    })
+}
+
+/// 64-bit FNV-1a, followed by the finalizer of MurmurHash3 to spread the bits.
+fn hash_bytes(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+    hash
+}
+
+/// Positions of the bits of a hash in a bloom filter of `num_bits` bits, by double hashing.
+fn bit_positions(hash: u64, num_bits: usize, num_hashes: u32) -> impl Iterator<Item = usize> {
+    let delta = hash.rotate_left(32) | 1;
+    (0..num_hashes as u64)
+        .map(move |i| (hash.wrapping_add(i.wrapping_mul(delta)) % num_bits as u64) as usize)
+}
+
+/// Collects the hashes of values in a block, and builds the `BloomFilter` block statistics.
+#[derive(Default)]
+pub struct BloomFilterBuilder {
+    hashes: Vec<u64>,
+}
+
+impl BloomFilterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the non-null items of `array` in `range`.
+    pub fn add_array<A: Array>(&mut self, array: &A, range: std::ops::Range<usize>)
+    where
+        A::Item: BloomHash,
+    {
+        for idx in range {
+            if let Some(item) = array.get(idx) {
+                self.hashes.push(item.bloom_hash());
+            }
+        }
+    }
+
+    /// Build the statistics from all values added since the last call, and reset the builder.
+    ///
+    /// The body is the number of hash functions as `u32`, followed by the bits of the filter.
+    pub fn finish(&mut self) -> BlockStatistics {
+        let mut hashes = std::mem::take(&mut self.hashes);
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let num_bytes = ((hashes.len() * BITS_PER_KEY).max(64) + 7) / 8;
+        let num_bits = num_bytes * 8;
+        let mut bits = vec![0u8; num_bytes];
+        for hash in hashes {
+            for pos in bit_positions(hash, num_bits, NUM_HASHES) {
+                bits[pos / 8] |= 1 << (pos % 8);
+            }
+        }
+
+        let mut body = Vec::with_capacity(4 + bits.len());
+        body.put_u32_le(NUM_HASHES);
+        body.extend(bits);
+        BlockStatistics {
+            block_stat_type: BlockStatisticsType::BloomFilter as i32,
+            body,
+        }
+    }
+}
+
+/// A bloom filter decoded from the `BloomFilter` block statistics.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    num_hashes: u32,
+    bits: Vec<u8>,
+}
+
+impl BloomFilter {
+    /// Decode a bloom filter from the body of the statistics.
+    pub fn decode(mut body: &[u8]) -> Option<Self> {
+        if body.len() <= 4 {
+            return None;
+        }
+        let num_hashes = body.get_u32_le();
+        Some(Self {
+            num_hashes,
+            bits: body.to_vec(),
+        })
+    }
+
+    /// Returns `false` if no value of the hash has been added into the filter.
+    pub fn may_contain(&self, hash: u64) -> bool {
+        bit_positions(hash, self.bits.len() * 8, self.num_hashes)
+            .all(|pos| self.bits[pos / 8] & (1 << (pos % 8)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{I32Array, Utf8Array};
+
+    #[test]
+    fn test_bloom_filter() {
+        let array = (0..1000).map(Some).collect::<I32Array>();
+        let mut builder = BloomFilterBuilder::new();
+        builder.add_array(&array, 0..500);
+        let filter = BloomFilter::decode(&builder.finish().body).unwrap();
+
+        // no false negatives
+        for i in 0..500i32 {
+            assert!(filter.may_contain(i.bloom_hash()));
+        }
+        let false_positives = (500..1000)
+            .filter(|i: &i32| filter.may_contain(i.bloom_hash()))
+            .count();
+        assert!(false_positives < 50);
+
+        // the builder is reset after finishing a block
+        builder.add_array(&array, 500..1000);
+        let filter = BloomFilter::decode(&builder.finish().body).unwrap();
+        assert!(filter.may_contain(600i32.bloom_hash()));
+    }
+
+    #[test]
+    fn test_bloom_hash_of_value() {
+        let array = [Some("MAIL"), None, Some("SHIP")]
+            .into_iter()
+            .collect::<Utf8Array>();
+        let mut builder = BloomFilterBuilder::new();
+        builder.add_array(&array, 0..3);
+        let filter = BloomFilter::decode(&builder.finish().body).unwrap();
+        let hash = |s: &str| bloom_hash_of_value(&DataValue::String(s.into())).unwrap();
+        assert!(filter.may_contain(hash("MAIL")));
+        assert!(filter.may_contain(hash("SHIP")));
+        assert_eq!(bloom_hash_of_value(&DataValue::Null), None);
+
+        let scaled = Decimal::new(100, 2);
+        assert_eq!(scaled.bloom_hash(), Decimal::new(1, 0).bloom_hash());
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::cmp::Ordering;
+
+use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
+
+use super::{decode_value, StatisticsGlobalAgg};
+use crate::storage::secondary::index::ColumnIndex;
+use crate::types::{DataValue, PhysicalDataTypeKind};
+
+/// Gather the minimum or maximum non-null value from column index.
+pub struct MinMaxValueGlobalAgg {
+    ty: BlockStatisticsType,
+    kind: PhysicalDataTypeKind,
+    value: Option<DataValue>,
+}
+
+impl MinMaxValueGlobalAgg {
+    /// Create an aggregator of `ty`, which is either `MinValue` or `MaxValue`, on a column of
+    /// `kind`.
+    pub fn create(ty: BlockStatisticsType, kind: PhysicalDataTypeKind) -> Self {
+        Self {
+            ty,
+            kind,
+            value: None,
+        }
+    }
+}
+
+impl StatisticsGlobalAgg for MinMaxValueGlobalAgg {
+    fn apply_batch(&mut self, index: &ColumnIndex) {
+        let keep = match self.ty {
+            BlockStatisticsType::MinValue => Ordering::Less,
+            _ => Ordering::Greater,
+        };
+        for index in index.indexes() {
+            for stat in &index.stats {
+                if stat.block_stat_type() != self.ty {
+                    continue;
+                }
+                let value = decode_value(&self.kind, &stat.body).expect("invalid statistics");
+                match &self.value {
+                    Some(current) if value.partial_cmp(current) != Some(keep) => {}
+                    _ => self.value = Some(value),
+                }
+            }
+        }
+    }
+
+    fn get_output(&self) -> DataValue {
+        self.value.clone().unwrap_or(DataValue::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risinglight_proto::rowset::{BlockIndex, BlockStatistics};
+
+    use super::*;
+
+    fn block_index(min: i32, max: i32) -> BlockIndex {
+        let stat = |ty: BlockStatisticsType, value: i32| BlockStatistics {
+            block_stat_type: ty as i32,
+            body: value.to_le_bytes().to_vec(),
+        };
+        BlockIndex {
+            stats: vec![
+                stat(BlockStatisticsType::MinValue, min),
+                stat(BlockStatisticsType::MaxValue, max),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_min_max_value() {
+        let index = ColumnIndex::from_indexes(vec![block_index(3, 7), block_index(-2, 5)]);
+        let mut min = MinMaxValueGlobalAgg::create(
+            BlockStatisticsType::MinValue,
+            PhysicalDataTypeKind::Int32,
+        );
+        let mut max = MinMaxValueGlobalAgg::create(
+            BlockStatisticsType::MaxValue,
+            PhysicalDataTypeKind::Int32,
+        );
+        assert_eq!(min.get_output(), DataValue::Null);
+        min.apply_batch(&index);
+        max.apply_batch(&index);
+        assert_eq!(min.get_output(), DataValue::Int32(-2));
+        assert_eq!(max.get_output(), DataValue::Int32(7));
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
+
+use super::StatisticsGlobalAgg;
+use crate::storage::secondary::index::ColumnIndex;
+use crate::types::DataValue;
+
+/// Gather null count from column index.
+pub struct NullCountGlobalAgg {
+    cnt: u64,
+}
+
+impl NullCountGlobalAgg {
+    pub fn create() -> Self {
+        Self { cnt: 0 }
+    }
+}
+
+impl StatisticsGlobalAgg for NullCountGlobalAgg {
+    fn apply_batch(&mut self, index: &ColumnIndex) {
+        for index in index.indexes() {
+            for stat in &index.stats {
+                if stat.block_stat_type() == BlockStatisticsType::NullCount {
+                    self.cnt += u64::from_le_bytes(stat.body.clone().try_into().unwrap());
+                }
+            }
+        }
+    }
+
+    fn get_output(&self) -> DataValue {
+        DataValue::Int64(self.cnt as i64)
+    }
+}
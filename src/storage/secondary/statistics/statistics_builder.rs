@@ -1,24 +1,59 @@
 // Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
 use risinglight_proto::rowset::BlockStatistics;
 
+use crate::storage::secondary::encode::PrimitiveFixedWidthEncode;
+
+/// Builds statistics of a block from the encoded items.
+///
+/// Min and max values are kept in the encoding of the block, and are compared by `cmp`, which
+/// is byte order by default.
 pub struct StatisticsBuilder<'a> {
     distinct_values: HashSet<&'a [u8]>,
+    min: Option<&'a [u8]>,
+    max: Option<&'a [u8]>,
+    null_count: u64,
+    cmp: fn(&[u8], &[u8]) -> Ordering,
 }
 
 impl<'a> StatisticsBuilder<'a> {
     pub fn new() -> Self {
         Self {
             distinct_values: HashSet::<&'a [u8]>::new(),
+            min: None,
+            max: None,
+            null_count: 0,
+            cmp: |a, b| a.cmp(b),
+        }
+    }
+
+    /// Create a builder for items encoded by [`PrimitiveFixedWidthEncode`].
+    pub fn new_primitive<T: PrimitiveFixedWidthEncode>() -> Self {
+        Self {
+            cmp: |mut a, mut b| {
+                T::decode(&mut a)
+                    .partial_cmp(&T::decode(&mut b))
+                    .unwrap_or(Ordering::Equal)
+            },
+            ..Self::new()
         }
     }
 
     pub fn add_item(&mut self, data: Option<&'a [u8]>) {
         if let Some(data) = data {
             self.distinct_values.insert(data);
+            if self.min.map_or(true, |min| (self.cmp)(data, min).is_lt()) {
+                self.min = Some(data);
+            }
+            if self.max.map_or(true, |max| (self.cmp)(data, max).is_gt()) {
+                self.max = Some(data);
+            }
+        } else {
+            self.null_count += 1;
         }
     }
 
@@ -28,7 +63,22 @@ impl<'a> StatisticsBuilder<'a> {
             block_stat_type: BlockStatisticsType::DistinctValue as i32,
             body: distinct_count.to_le_bytes().to_vec(),
         };
-        vec![distinct_stat]
+        let mut stats = vec![distinct_stat];
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            stats.push(BlockStatistics {
+                block_stat_type: BlockStatisticsType::MinValue as i32,
+                body: min.to_vec(),
+            });
+            stats.push(BlockStatistics {
+                block_stat_type: BlockStatisticsType::MaxValue as i32,
+                body: max.to_vec(),
+            });
+        }
+        stats.push(BlockStatistics {
+            block_stat_type: BlockStatisticsType::NullCount as i32,
+            body: self.null_count.to_le_bytes().to_vec(),
+        });
+        stats
     }
 }
 
@@ -54,4 +104,30 @@ mod tests {
         let mut body = &stats[0].body[..];
         assert_eq!(body.get_u64_le(), 3);
     }
+
+    #[test]
+    fn test_min_max() {
+        let items = [5i32, -3, 42, 7].map(|x| x.to_le_bytes());
+        let mut builder = StatisticsBuilder::new_primitive::<i32>();
+        for item in &items {
+            builder.add_item(Some(item));
+        }
+        builder.add_item(None);
+        let stats = builder.get_statistics();
+        assert_eq!(
+            stats[1].block_stat_type,
+            BlockStatisticsType::MinValue as i32
+        );
+        assert_eq!((&stats[1].body[..]).get_i32_le(), -3);
+        assert_eq!(
+            stats[2].block_stat_type,
+            BlockStatisticsType::MaxValue as i32
+        );
+        assert_eq!((&stats[2].body[..]).get_i32_le(), 42);
+        assert_eq!(
+            stats[3].block_stat_type,
+            BlockStatisticsType::NullCount as i32
+        );
+        assert_eq!((&stats[3].body[..]).get_u64_le(), 1);
+    }
 }
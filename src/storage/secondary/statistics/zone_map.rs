@@ -0,0 +1,323 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::cmp::Ordering;
+
+use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
+use risinglight_proto::rowset::BlockIndex;
+use rust_decimal::Decimal;
+
+use super::{bloom_hash_of_value, BloomFilter};
+use crate::binder::BoundExpr;
+use crate::parser::BinaryOperator;
+use crate::storage::secondary::encode::PrimitiveFixedWidthEncode;
+use crate::types::{Blob, DataValue, Date, Interval, PhysicalDataTypeKind, Timestamp, TimestampTz};
+
+/// Value range of a block or a RowSet, decoded from the `MinValue`, `MaxValue` and `NullCount`
+/// block statistics, together with the bloom filters of the blocks if there are any.
+#[derive(Debug, Clone)]
+pub struct ZoneMap {
+    /// Minimum and maximum non-null values, `None` if all values are null.
+    range: Option<(DataValue, DataValue)>,
+    null_count: u64,
+    row_count: u64,
+    /// Bloom filters of all blocks, `None` if any block doesn't have one.
+    bloom_filters: Option<Vec<BloomFilter>>,
+}
+
+impl ZoneMap {
+    /// Decode the zone map of a block. Returns `None` if the block has no such statistics.
+    pub fn from_block(index: &BlockIndex, kind: &PhysicalDataTypeKind) -> Option<Self> {
+        let (mut min, mut max, mut null_count, mut bloom_filter) = (None, None, None, None);
+        for stat in &index.stats {
+            match stat.block_stat_type() {
+                BlockStatisticsType::MinValue => min = decode_value(kind, &stat.body),
+                BlockStatisticsType::MaxValue => max = decode_value(kind, &stat.body),
+                BlockStatisticsType::NullCount => {
+                    null_count = Some(u64::from_le_bytes(stat.body.clone().try_into().ok()?))
+                }
+                BlockStatisticsType::BloomFilter => bloom_filter = BloomFilter::decode(&stat.body),
+                _ => {}
+            }
+        }
+        let range = match (min, max) {
+            (Some(min), Some(max)) => Some((min, max)),
+            _ => None,
+        };
+        let zone_map = Self {
+            range,
+            null_count: null_count?,
+            row_count: index.row_count as u64,
+            bloom_filters: bloom_filter.map(|bloom_filter| vec![bloom_filter]),
+        };
+        // a block with non-null values must have a range
+        if zone_map.range.is_none() && zone_map.null_count != zone_map.row_count {
+            return None;
+        }
+        Some(zone_map)
+    }
+
+    /// Decode the zone map of all blocks in a column.
+    pub fn from_blocks(indexes: &[BlockIndex], kind: &PhysicalDataTypeKind) -> Option<Self> {
+        let mut zone_map: Option<Self> = None;
+        for index in indexes {
+            let block = Self::from_block(index, kind)?;
+            zone_map = Some(match zone_map {
+                None => block,
+                Some(zone_map) => zone_map.merge(block)?,
+            });
+        }
+        zone_map
+    }
+
+    fn merge(self, other: Self) -> Option<Self> {
+        let range = match (self.range, other.range) {
+            (Some((min1, max1)), Some((min2, max2))) => {
+                let min = match min1.partial_cmp(&min2)? {
+                    Ordering::Greater => min2,
+                    _ => min1,
+                };
+                let max = match max1.partial_cmp(&max2)? {
+                    Ordering::Less => max2,
+                    _ => max1,
+                };
+                Some((min, max))
+            }
+            (range, None) | (None, range) => range,
+        };
+        Some(Self {
+            range,
+            null_count: self.null_count + other.null_count,
+            row_count: self.row_count + other.row_count,
+            bloom_filters: match (self.bloom_filters, other.bloom_filters) {
+                (Some(mut filters), Some(other)) => {
+                    filters.extend(other);
+                    Some(filters)
+                }
+                _ => None,
+            },
+        })
+    }
+
+    /// Check whether any non-null value may equal `value` by the bloom filters.
+    fn may_contain(&self, value: &DataValue) -> bool {
+        match (&self.bloom_filters, bloom_hash_of_value(value)) {
+            (Some(filters), Some(hash)) => filters.iter().any(|filter| filter.may_contain(hash)),
+            _ => true,
+        }
+    }
+}
+
+pub fn decode_value(kind: &PhysicalDataTypeKind, mut body: &[u8]) -> Option<DataValue> {
+    fn decode<T: PrimitiveFixedWidthEncode>(body: &mut &[u8]) -> Option<T> {
+        if body.len() != T::WIDTH {
+            return None;
+        }
+        Some(T::decode(body))
+    }
+    use PhysicalDataTypeKind as Kind;
+    Some(match kind {
+        Kind::Bool => DataValue::Bool(decode::<bool>(&mut body)?),
+        Kind::Int32 => DataValue::Int32(decode::<i32>(&mut body)?),
+        Kind::Int64 => DataValue::Int64(decode::<i64>(&mut body)?),
+        Kind::Float64 => DataValue::Float64(decode::<f64>(&mut body)?),
+        Kind::Decimal => DataValue::Decimal(decode::<Decimal>(&mut body)?),
+        Kind::Date => DataValue::Date(decode::<Date>(&mut body)?),
+        Kind::Interval => DataValue::Interval(decode::<Interval>(&mut body)?),
+        Kind::Timestamp => DataValue::Timestamp(decode::<Timestamp>(&mut body)?),
+        Kind::TimestampTz => DataValue::TimestampTz(decode::<TimestampTz>(&mut body)?),
+        Kind::String => {
+            // fixed-width chars are padded with `\0`
+            let len = body.iter().rposition(|x| *x != 0).map_or(0, |pos| pos + 1);
+            DataValue::String(std::str::from_utf8(&body[..len]).ok()?.to_string())
+        }
+        Kind::Blob => DataValue::Blob(Blob::from(body)),
+    })
+}
+
+/// Check whether any row in the zone maps may satisfy the filter. `zone_maps` are indexed by
+/// the `InputRef`s in the filter, and `None` means the range of the column is unknown.
+pub fn may_match(expr: &BoundExpr, zone_maps: &[Option<ZoneMap>]) -> bool {
+    use BinaryOperator::*;
+    match expr {
+        BoundExpr::BinaryOp(op) => match (&op.op, &*op.left_expr, &*op.right_expr) {
+            (And, left, right) => may_match(left, zone_maps) && may_match(right, zone_maps),
+            (Or, left, right) => may_match(left, zone_maps) || may_match(right, zone_maps),
+            (cmp, BoundExpr::InputRef(input_ref), BoundExpr::Constant(value)) => {
+                match &zone_maps[input_ref.index] {
+                    Some(zone_map) => compare_may_match(zone_map, cmp, value),
+                    None => true,
+                }
+            }
+            (cmp, BoundExpr::Constant(value), BoundExpr::InputRef(input_ref)) => {
+                let cmp = match cmp {
+                    Lt => Gt,
+                    LtEq => GtEq,
+                    Gt => Lt,
+                    GtEq => LtEq,
+                    cmp => cmp.clone(),
+                };
+                match &zone_maps[input_ref.index] {
+                    Some(zone_map) => compare_may_match(zone_map, &cmp, value),
+                    None => true,
+                }
+            }
+            _ => true,
+        },
+        BoundExpr::InList(in_list) if !in_list.negated => match &*in_list.expr {
+            BoundExpr::InputRef(input_ref) => match &zone_maps[input_ref.index] {
+                Some(zone_map) => in_list.list.iter().any(|item| match item {
+                    BoundExpr::Constant(value) => compare_may_match(zone_map, &Eq, value),
+                    _ => true,
+                }),
+                None => true,
+            },
+            _ => true,
+        },
+        BoundExpr::IsNull(isnull) => match &*isnull.expr {
+            BoundExpr::InputRef(input_ref) => match &zone_maps[input_ref.index] {
+                Some(zone_map) => zone_map.null_count > 0,
+                None => true,
+            },
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// Check whether `column <cmp> value` may be true for any row in the zone map.
+fn compare_may_match(zone_map: &ZoneMap, cmp: &BinaryOperator, value: &DataValue) -> bool {
+    use BinaryOperator::*;
+    if !matches!(cmp, Eq | NotEq | Lt | LtEq | Gt | GtEq) || *value == DataValue::Null {
+        return true;
+    }
+    // comparisons on null values are never true
+    let (min, max) = match &zone_map.range {
+        Some(range) => range,
+        None => return false,
+    };
+    if std::mem::discriminant(min) != std::mem::discriminant(value) {
+        return true;
+    }
+    let (min_cmp, max_cmp) = match (min.partial_cmp(value), max.partial_cmp(value)) {
+        (Some(min_cmp), Some(max_cmp)) => (min_cmp, max_cmp),
+        _ => return true,
+    };
+    match cmp {
+        Eq => min_cmp.is_le() && max_cmp.is_ge() && zone_map.may_contain(value),
+        NotEq => !(min_cmp.is_eq() && max_cmp.is_eq()),
+        Lt => min_cmp.is_lt(),
+        LtEq => min_cmp.is_le(),
+        Gt => max_cmp.is_gt(),
+        GtEq => max_cmp.is_ge(),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risinglight_proto::rowset::BlockStatistics;
+
+    use super::*;
+    use crate::array::I32Array;
+    use crate::binder::{BoundBinaryOp, BoundInList, BoundInputRef};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    fn block(min: i32, max: i32, null_count: u64) -> BlockIndex {
+        let stat = |ty: BlockStatisticsType, body: Vec<u8>| BlockStatistics {
+            block_stat_type: ty as i32,
+            body,
+        };
+        BlockIndex {
+            row_count: 100,
+            stats: vec![
+                stat(BlockStatisticsType::MinValue, min.to_le_bytes().to_vec()),
+                stat(BlockStatisticsType::MaxValue, max.to_le_bytes().to_vec()),
+                stat(
+                    BlockStatisticsType::NullCount,
+                    null_count.to_le_bytes().to_vec(),
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn cmp(op: BinaryOperator, value: i32) -> BoundExpr {
+        BoundExpr::BinaryOp(BoundBinaryOp {
+            op,
+            left_expr: Box::new(BoundExpr::InputRef(BoundInputRef {
+                index: 0,
+                return_type: DataTypeKind::Int(None).not_null(),
+            })),
+            right_expr: Box::new(BoundExpr::Constant(DataValue::Int32(value))),
+            return_type: Some(DataTypeKind::Boolean.not_null()),
+        })
+    }
+
+    #[test]
+    fn test_zone_map() {
+        let zone_map = ZoneMap::from_block(&block(10, 20, 0), &PhysicalDataTypeKind::Int32);
+        let zone_maps = [zone_map];
+        assert!(may_match(&cmp(BinaryOperator::Eq, 15), &zone_maps));
+        assert!(!may_match(&cmp(BinaryOperator::Eq, 21), &zone_maps));
+        assert!(!may_match(&cmp(BinaryOperator::Lt, 10), &zone_maps));
+        assert!(may_match(&cmp(BinaryOperator::LtEq, 10), &zone_maps));
+        assert!(!may_match(&cmp(BinaryOperator::Gt, 20), &zone_maps));
+        assert!(may_match(&cmp(BinaryOperator::NotEq, 10), &zone_maps));
+
+        let and = BoundExpr::BinaryOp(BoundBinaryOp {
+            op: BinaryOperator::And,
+            left_expr: Box::new(cmp(BinaryOperator::Gt, 12)),
+            right_expr: Box::new(cmp(BinaryOperator::Lt, 5)),
+            return_type: Some(DataTypeKind::Boolean.not_null()),
+        });
+        assert!(!may_match(&and, &zone_maps));
+
+        // unknown ranges always match
+        assert!(may_match(&cmp(BinaryOperator::Eq, 21), &[None]));
+    }
+
+    #[test]
+    fn test_merge_zone_map() {
+        let zone_map = ZoneMap::from_blocks(
+            &[block(10, 20, 1), block(-5, 3, 2)],
+            &PhysicalDataTypeKind::Int32,
+        )
+        .unwrap();
+        assert_eq!(
+            zone_map.range,
+            Some((DataValue::Int32(-5), DataValue::Int32(20)))
+        );
+        assert_eq!(zone_map.null_count, 3);
+        assert_eq!(zone_map.row_count, 200);
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut index = block(10, 20, 0);
+        let mut builder = BloomFilterBuilder::new();
+        builder.add_array(&[10, 20].into_iter().collect::<I32Array>(), 0..2);
+        index.stats.push(builder.finish());
+        let zone_maps = [ZoneMap::from_block(&index, &PhysicalDataTypeKind::Int32)];
+
+        assert!(may_match(&cmp(BinaryOperator::Eq, 10), &zone_maps));
+        assert!(!may_match(&cmp(BinaryOperator::Eq, 15), &zone_maps));
+        // bloom filters are only used on equality
+        assert!(may_match(&cmp(BinaryOperator::Gt, 15), &zone_maps));
+
+        let in_list = |values: &[i32]| {
+            BoundExpr::InList(BoundInList {
+                expr: Box::new(BoundExpr::InputRef(BoundInputRef {
+                    index: 0,
+                    return_type: DataTypeKind::Int(None).not_null(),
+                })),
+                list: values
+                    .iter()
+                    .map(|v| BoundExpr::Constant(DataValue::Int32(*v)))
+                    .collect(),
+                negated: false,
+            })
+        };
+        assert!(may_match(&in_list(&[15, 20]), &zone_maps));
+        assert!(!may_match(&in_list(&[15, 16, 30]), &zone_maps));
+    }
+}
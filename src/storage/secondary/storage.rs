@@ -36,7 +36,12 @@ impl SecondaryStorage {
 
         let enable_fsync = !matches!(options.io_backend, IOBackend::InMemory(_));
 
-        let mut manifest = Manifest::open(options.path.join("manifest.json"), enable_fsync).await?;
+        let mut manifest = Manifest::open(
+            &options.path,
+            enable_fsync,
+            options.manifest_checkpoint_interval,
+        )
+        .await?;
 
         let manifest_ops = manifest.replay().await?;
 
@@ -52,6 +57,7 @@ impl SecondaryStorage {
             compactor_handler: Mutex::new((None, None)),
             vacuum_handler: Mutex::new((None, None)),
             txn_mgr: Arc::new(TransactionManager::default()),
+            sessions: RwLock::new(HashMap::new()),
         };
 
         info!("applying {} manifest entries", manifest_ops.len());
@@ -92,6 +98,16 @@ impl SecondaryStorage {
                 ManifestOperation::DeleteDV(entry) => {
                     dvs_to_open.remove(&(entry.table_id.table_id, entry.rowset_id, entry.dv_id));
                 }
+                ManifestOperation::Checkpoint(entry) => {
+                    engine
+                        .next_id
+                        .0
+                        .fetch_max(entry.next_rowset_id, std::sync::atomic::Ordering::SeqCst);
+                    engine
+                        .next_id
+                        .1
+                        .fetch_max(entry.next_dv_id, std::sync::atomic::Ordering::SeqCst);
+                }
                 ManifestOperation::Begin | ManifestOperation::End => {}
             }
         }
@@ -139,7 +155,7 @@ impl SecondaryStorage {
                 options.io_backend.clone(),
             )
             .await?;
-            changeset.push(EpochOp::AddRowSet((entry, disk_rowset)));
+            changeset.push(EpochOp::AddRowSet((entry, Arc::new(disk_rowset))));
         }
 
         for (_, entry) in dvs_to_open {
@@ -150,10 +166,11 @@ impl SecondaryStorage {
                 table.get_dv_path(entry.rowset_id, entry.dv_id),
             )
             .await?;
-            changeset.push(EpochOp::AddDV((entry, dv)));
+            changeset.push(EpochOp::AddDV((entry, Arc::new(dv))));
         }
 
-        engine.version.commit_changes(changeset).await?;
+        // stale DVs of compacted RowSets may be replayed here, so skip conflict detection
+        engine.version.commit_changes(None, changeset).await?;
 
         // TODO: compact manifest entries
 
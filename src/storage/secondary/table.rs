@@ -6,7 +6,6 @@ use std::sync::atomic::{AtomicU32, AtomicU64};
 use std::sync::Arc;
 
 use moka::future::Cache;
-use tokio::sync::OwnedMutexGuard;
 
 use super::*;
 use crate::catalog::TableRefId;
@@ -22,6 +21,9 @@ pub struct SecondaryTable {
     /// Table id
     pub table_ref_id: TableRefId,
 
+    /// Table name, used to look up per-column options
+    pub table_name: Arc<str>,
+
     /// All columns (ordered) in table
     pub columns: Arc<[ColumnCatalog]>,
 
@@ -44,12 +46,16 @@ pub struct SecondaryTable {
 
     /// Next RowSet Id and DV Id of the current storage engine
     next_id: Arc<(AtomicU32, AtomicU64)>,
+
+    /// The multi-statement transaction this table is accessed in, if any.
+    pub(super) session: Option<Arc<SessionTransaction>>,
 }
 
 impl SecondaryTable {
     pub fn new(
         storage_options: Arc<StorageOptions>,
         table_ref_id: TableRefId,
+        table_name: &str,
         columns: &[ColumnCatalog],
         next_id: Arc<(AtomicU32, AtomicU64)>,
         version: Arc<VersionManager>,
@@ -64,11 +70,13 @@ impl SecondaryTable {
                 .map(|(idx, col)| (col.id(), idx))
                 .collect(),
             table_ref_id,
+            table_name: table_name.into(),
             storage_options,
             next_id,
             version,
             block_cache,
             txn_mgr,
+            session: None,
         }
     }
 
@@ -99,10 +107,6 @@ impl SecondaryTable {
     pub fn table_id(&self) -> u32 {
         self.table_ref_id.table_id
     }
-
-    pub async fn lock_for_deletion(&self) -> OwnedMutexGuard<()> {
-        self.txn_mgr.lock_for_deletion(self.table_id()).await
-    }
 }
 
 impl Table for SecondaryTable {
@@ -110,6 +114,7 @@ impl Table for SecondaryTable {
     type ReadResultFuture<'a> = impl Future<Output = StorageResult<Self::TransactionType>> + 'a;
     type WriteResultFuture<'a> = impl Future<Output = StorageResult<Self::TransactionType>> + 'a;
     type UpdateResultFuture<'a> = impl Future<Output = StorageResult<Self::TransactionType>> + 'a;
+    type ReadAsOfResultFuture<'a> = impl Future<Output = StorageResult<Self::TransactionType>> + 'a;
 
     fn columns(&self) -> StorageResult<Arc<[ColumnCatalog]>> {
         Ok(self.columns.clone())
@@ -120,14 +125,18 @@ impl Table for SecondaryTable {
     }
 
     fn write(&self) -> Self::WriteResultFuture<'_> {
-        async move { SecondaryTransaction::start(self, false, false).await }
+        async move { SecondaryTransaction::start(self, false, false, None).await }
     }
 
     fn read(&self) -> Self::ReadResultFuture<'_> {
-        async move { SecondaryTransaction::start(self, true, false).await }
+        async move { SecondaryTransaction::start(self, true, false, None).await }
     }
 
     fn update(&self) -> Self::UpdateResultFuture<'_> {
-        async move { SecondaryTransaction::start(self, false, true).await }
+        async move { SecondaryTransaction::start(self, false, true, None).await }
+    }
+
+    fn read_as_of(&self, epoch: u64) -> Self::ReadAsOfResultFuture<'_> {
+        async move { SecondaryTransaction::start(self, true, false, Some(epoch)).await }
     }
 }
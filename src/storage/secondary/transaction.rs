@@ -7,20 +7,19 @@ use futures::Future;
 use itertools::Itertools;
 use risinglight_proto::rowset::block_statistics::BlockStatisticsType;
 use risinglight_proto::rowset::DeleteRecord;
-use tokio::sync::OwnedMutexGuard;
 use tracing::{info, warn};
 
 use super::version_manager::{Snapshot, VersionManager};
 use super::{
-    AddDVEntry, AddRowSetEntry, ColumnBuilderOptions, ConcatIterator, DeleteVector, DiskRowset,
-    EpochOp, MergeIterator, RowSetIterator, SecondaryMemRowsetImpl, SecondaryRowHandler,
-    SecondaryTable, SecondaryTableTxnIterator,
+    AddDVEntry, AddRowSetEntry, ColumnBuilderOptions, ColumnSeekPosition, ConcatIterator,
+    DeleteVector, DiskRowset, EpochOp, MergeIterator, RowSetIterator, SecondaryMemRowsetImpl,
+    SecondaryRowHandler, SecondaryTable, SecondaryTableTxnIterator, SessionTransaction,
 };
 use crate::array::DataChunk;
 use crate::binder::BoundExpr;
 use crate::catalog::find_sort_key_id;
 use crate::storage::secondary::statistics::create_statistics_global_aggregator;
-use crate::storage::{StorageColumnRef, StorageResult, Transaction};
+use crate::storage::{ScanPartition, StorageColumnRef, StorageResult, Transaction};
 use crate::types::DataValue;
 
 /// A transaction running on `SecondaryStorage`.
@@ -51,10 +50,16 @@ pub struct SecondaryTransaction {
     /// The rowsets produced in the txn.
     to_be_committed_rowsets: Vec<DiskRowset>,
 
-    delete_lock: Option<OwnedMutexGuard<()>>,
-
     read_only: bool,
 
+    /// Whether the txn is started for update. Deletions are not locked, but checked for
+    /// conflicts on commit.
+    update: bool,
+
+    /// The multi-statement transaction this txn belongs to. If set, the changes are handed over
+    /// to the session on commit, and the epoch is pinned by the session instead of this txn.
+    session: Option<Arc<SessionTransaction>>,
+
     /// Total size of written data in the current txn
     ///
     /// TODO: we only calculate batch insert here. Need to estimate delete vector size.
@@ -62,15 +67,26 @@ pub struct SecondaryTransaction {
 }
 
 impl SecondaryTransaction {
-    /// Start a transaction on Secondary. If `update` is set to true, we will hold the delete lock
-    /// of a table.
+    /// Start a transaction on Secondary. Only txns started with `update` set to true may delete
+    /// rows. If `as_of_epoch` is set, the txn reads the snapshot of that epoch, regardless of the
+    /// session.
     pub(super) async fn start(
         table: &SecondaryTable,
         read_only: bool,
         update: bool,
+        as_of_epoch: Option<u64>,
     ) -> StorageResult<Self> {
-        // pin a snapshot at version manager
-        let (epoch, snapshot) = table.version.pin();
+        let session = match as_of_epoch {
+            Some(_) => None,
+            None => table.session.clone(),
+        };
+
+        // pin a snapshot at version manager, or reuse the one of the session
+        let (epoch, snapshot) = match (as_of_epoch, &session) {
+            (Some(epoch), _) => (epoch, table.version.pin_epoch(epoch)?),
+            (None, Some(session)) => (session.epoch(), session.snapshot()),
+            (None, None) => table.version.pin(),
+        };
 
         Ok(Self {
             finished: false,
@@ -80,13 +96,10 @@ impl SecondaryTransaction {
             version: table.version.clone(),
             epoch,
             snapshot,
-            delete_lock: if update {
-                Some(table.lock_for_deletion().await)
-            } else {
-                None
-            },
             to_be_committed_rowsets: vec![],
             read_only,
+            update,
+            session,
             total_size: 0,
         })
     }
@@ -180,7 +193,7 @@ impl SecondaryTransaction {
                     rowset_id: x.rowset_id(),
                     table_id: self.table.table_ref_id,
                 },
-                x,
+                Arc::new(x),
             ))
         }));
 
@@ -192,19 +205,51 @@ impl SecondaryTransaction {
                     dv_id: x.dv_id(),
                     table_id: self.table.table_ref_id,
                 },
-                x,
+                Arc::new(x),
             ))
         }));
 
         // Commit changeset
-        self.version.commit_changes(changeset).await?;
-
-        self.finished = true;
-        self.version.unpin(self.epoch);
+        if let Some(session) = &self.session {
+            session.add_changes(changeset);
+            self.finished = true;
+        } else {
+            let result = self
+                .version
+                .commit_changes(Some(self.epoch), changeset)
+                .await;
+            self.finished = true;
+            self.version.unpin(self.epoch);
+            result?;
+        }
 
         Ok(())
     }
 
+    /// Unpin the epoch if it is pinned by this txn.
+    fn unpin(&self) {
+        if self.session.is_none() {
+            self.version.unpin(self.epoch);
+        }
+    }
+
+    fn get_rowset(&self, rowset_id: u32) -> Arc<DiskRowset> {
+        let table_id = self.table.table_id();
+        self.session
+            .as_ref()
+            .and_then(|session| session.get_rowset(table_id, rowset_id))
+            .unwrap_or_else(|| self.version.get_rowset(table_id, rowset_id))
+    }
+
+    fn get_dv(&self, dv_id: u64) -> Arc<DeleteVector> {
+        let table_id = self.table.table_id();
+        self.session
+            .as_ref()
+            .and_then(|session| session.get_dv(table_id, dv_id))
+            .unwrap_or_else(|| self.version.get_dv(table_id, dv_id))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn scan_inner(
         &self,
         begin_keys: &[DataValue],
@@ -213,33 +258,43 @@ impl SecondaryTransaction {
         is_sorted: bool,
         reversed: bool,
         expr: Option<BoundExpr>,
+        partition: ScanPartition,
     ) -> StorageResult<SecondaryTableTxnIterator> {
         assert!(!reversed, "reverse iterator is not supported for now");
 
         let mut iters: Vec<RowSetIterator> = vec![];
 
         if let Some(rowsets) = self.snapshot.get_rowsets_of(self.table.table_id()) {
-            for rowset_id in rowsets {
-                let rowset = self.version.get_rowset(self.table.table_id(), *rowset_id);
+            // split the table across RowSets, which are iterated in the same order for all
+            // partitions of the snapshot
+            let rowsets = rowsets.iter().enumerate();
+            for (_, rowset_id) in rowsets.filter(|(i, _)| partition.contains(*i)) {
+                let rowset = self.get_rowset(*rowset_id);
+
+                // Skip the RowSet if none of its rows can satisfy the filter
+                if let Some(expr) = &expr {
+                    if !rowset.may_match(col_idx, expr) {
+                        continue;
+                    }
+                }
 
                 // Get DV id and read DVs
                 let dvs = self
                     .snapshot
                     .get_dvs_of(self.table.table_id(), *rowset_id)
-                    .map(|dvs| {
-                        dvs.iter()
-                            .map(|dv_id| self.version.get_dv(self.table.table_id(), *dv_id))
-                            .collect_vec()
-                    })
+                    .map(|dvs| dvs.iter().map(|dv_id| self.get_dv(*dv_id)).collect_vec())
                     .unwrap_or_default();
 
-                let start_rowid = rowset.start_rowid(begin_keys).await;
+                let seek_pos = match begin_keys.first() {
+                    Some(begin_key) => ColumnSeekPosition::SortKey(begin_key.clone()),
+                    None => ColumnSeekPosition::start(),
+                };
                 iters.push(
                     rowset
                         .iter(
                             col_idx.into(),
                             dvs,
-                            start_rowid,
+                            seek_pos,
                             expr.clone(),
                             begin_keys,
                             end_keys,
@@ -280,15 +335,23 @@ impl SecondaryTransaction {
     pub fn aggreagate_block_stat(
         &self,
         ty: &[(BlockStatisticsType, StorageColumnRef)],
-    ) -> Vec<DataValue> {
-        let mut agg = ty
+    ) -> StorageResult<Vec<DataValue>> {
+        let mut agg: Vec<_> = ty
             .iter()
-            .map(|(ty, _)| create_statistics_global_aggregator(*ty))
-            .collect_vec();
+            .map(|(ty, col_idx)| {
+                let kind = match col_idx {
+                    StorageColumnRef::Idx(idx) => {
+                        self.table.columns[*idx as usize].datatype().physical_kind()
+                    }
+                    _ => panic!("unsupported column ref for block aggregation"),
+                };
+                create_statistics_global_aggregator(*ty, kind)
+            })
+            .try_collect()?;
 
         if let Some(rowsets) = self.snapshot.get_rowsets_of(self.table.table_id()) {
             for rowset_id in rowsets {
-                let rowset = self.version.get_rowset(self.table.table_id(), *rowset_id);
+                let rowset = self.get_rowset(*rowset_id);
                 for ((_, col_idx), agg) in ty.iter().zip(agg.iter_mut()) {
                     let user_col_idx = match col_idx {
                         StorageColumnRef::Idx(idx) => idx,
@@ -300,7 +363,7 @@ impl SecondaryTransaction {
             }
         }
 
-        agg.into_iter().map(|agg| agg.get_output()).collect_vec()
+        Ok(agg.into_iter().map(|agg| agg.get_output()).collect_vec())
     }
 
     pub async fn append_inner(&mut self, columns: DataChunk) -> StorageResult<()> {
@@ -314,7 +377,10 @@ impl SecondaryTransaction {
 
             self.mem = Some(SecondaryMemRowsetImpl::new(
                 self.table.columns.clone(),
-                ColumnBuilderOptions::from_storage_options(&*self.table.storage_options),
+                ColumnBuilderOptions::from_storage_options(
+                    &*self.table.storage_options,
+                    &self.table.table_name,
+                ),
                 rowset_id,
             ));
         }
@@ -356,6 +422,7 @@ impl Transaction for SecondaryTransaction {
         is_sorted: bool,
         reversed: bool,
         expr: Option<BoundExpr>,
+        partition: ScanPartition,
     ) -> Self::ScanResultFuture<'a> {
         async move {
             self.scan_inner(
@@ -365,6 +432,7 @@ impl Transaction for SecondaryTransaction {
                 is_sorted,
                 reversed,
                 expr,
+                partition,
             )
             .await
         }
@@ -376,10 +444,7 @@ impl Transaction for SecondaryTransaction {
 
     fn delete<'a>(&'a mut self, id: &'a Self::RowHandlerType) -> Self::DeleteResultFuture<'a> {
         async move {
-            assert!(
-                self.delete_lock.is_some(),
-                "delete lock is not held for this txn"
-            );
+            assert!(self.update, "txn is not started for update");
             self.delete_buffer.push(*id);
             Ok(())
         }
@@ -392,7 +457,7 @@ impl Transaction for SecondaryTransaction {
     fn abort<'a>(mut self) -> Self::AbortResultFuture<'a> {
         async move {
             self.finished = true;
-            self.version.unpin(self.epoch);
+            self.unpin();
             Ok(())
         }
     }
@@ -402,7 +467,7 @@ impl Drop for SecondaryTransaction {
     fn drop(&mut self) {
         if !self.finished {
             warn!("Transaction dropped without committing or aborting");
-            self.version.unpin(self.epoch);
+            self.unpin();
         }
     }
 }
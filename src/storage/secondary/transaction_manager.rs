@@ -15,9 +15,14 @@ use tokio::sync::{Mutex, OwnedMutexGuard};
 ///   SI.
 /// * Implement concurrent deletion and compaction, and allow lazy detection of conflicts.
 /// * Implement true SI write conflict detection.
+///
+/// We are now at phase three. Deletions no longer lock the table. Each txn records the row
+/// handlers it deleted in its DVs, and conflicts are detected on commit by
+/// [`VersionManager::commit_changes`](super::VersionManager::commit_changes) following the
+/// first-committer-wins rule. Only compactions are still serialized by this manager.
 #[derive(Default)]
 pub struct TransactionManager {
-    /// A single big lock for each table
+    /// A single big lock for each table, held by compaction
     lock_map: PLMutex<HashMap<u32, Arc<Mutex<()>>>>,
 }
 
@@ -49,9 +54,4 @@ impl TransactionManager {
     pub async fn lock_for_compaction(&self, table: u32) -> OwnedMutexGuard<()> {
         self.lock(table).await
     }
-
-    /// Get a lock for deletion
-    pub async fn lock_for_deletion(&self, table: u32) -> OwnedMutexGuard<()> {
-        self.lock(table).await
-    }
 }
@@ -9,16 +9,16 @@ use tokio::select;
 use tracing::{info, warn};
 
 use super::manifest::*;
-use super::{DeleteVector, DiskRowset, StorageOptions, StorageResult};
+use super::{DeleteVector, DiskRowset, StorageOptions, StorageResult, TracedStorageError};
 
 /// The operations sent to the version manager. Compared with manifest entries, operations
 /// like `AddRowSet` needs to be associated with a `DiskRowSet` struct.
 pub enum EpochOp {
     CreateTable(CreateTableEntry),
     DropTable(DropTableEntry),
-    AddRowSet((AddRowSetEntry, DiskRowset)),
+    AddRowSet((AddRowSetEntry, Arc<DiskRowset>)),
     DeleteRowSet(DeleteRowsetEntry),
-    AddDV((AddDVEntry, DeleteVector)),
+    AddDV((AddDVEntry, Arc<DeleteVector>)),
     DeleteDV(DeleteDVEntry),
 }
 
@@ -96,6 +96,11 @@ impl Snapshot {
         }
         None
     }
+
+    pub fn contains_rowset(&self, table_id: u32, rowset_id: u32) -> bool {
+        self.get_rowsets_of(table_id)
+            .map_or(false, |rowsets| rowsets.contains(&rowset_id))
+    }
 }
 
 #[derive(Default)]
@@ -120,6 +125,96 @@ pub struct VersionManagerInner {
     epoch: u64,
 }
 
+impl VersionManagerInner {
+    /// Get DVs of a RowSet committed after `read_snapshot` was taken.
+    fn new_dvs_of<'a>(
+        read_snapshot: &'a Snapshot,
+        current_snapshot: &'a Snapshot,
+        table_id: u32,
+        rowset_id: u32,
+    ) -> impl Iterator<Item = u64> + 'a {
+        let read_dvs = read_snapshot.get_dvs_of(table_id, rowset_id);
+        current_snapshot
+            .get_dvs_of(table_id, rowset_id)
+            .into_iter()
+            .flatten()
+            .filter(move |dv_id| !read_dvs.map_or(false, |dvs| dvs.contains(dv_id)))
+            .copied()
+    }
+
+    /// Check whether `ops`, made by a txn reading the snapshot of `read_epoch`, conflict with the
+    /// changes committed after `read_epoch`. The first committer wins, so the txn committing now
+    /// will be aborted on conflict.
+    ///
+    /// * Deleting rows from a RowSet conflicts with deletions of the same rows, and with the
+    ///   compaction of the RowSet.
+    /// * Compacting a RowSet conflicts with any deletion on it, as the compacted RowSet was built
+    ///   without those deletions.
+    fn check_conflicts(&self, read_epoch: u64, ops: &[EpochOp]) -> StorageResult<()> {
+        if read_epoch == self.epoch {
+            return Ok(());
+        }
+        let read_snapshot = self.status.get(&read_epoch).cloned().unwrap_or_default();
+        let current_snapshot = self.status.get(&self.epoch).cloned().unwrap_or_default();
+
+        // RowSets added by the txn itself are not visible to others.
+        let added_rowsets: HashSet<(u32, u32)> = ops
+            .iter()
+            .filter_map(|op| match op {
+                EpochOp::AddRowSet((entry, _)) => Some((entry.table_id.table_id, entry.rowset_id)),
+                _ => None,
+            })
+            .collect();
+
+        for op in ops {
+            match op {
+                EpochOp::AddDV((entry, dv)) => {
+                    let table_id = entry.table_id.table_id;
+                    if added_rowsets.contains(&(table_id, entry.rowset_id)) {
+                        continue;
+                    }
+                    if !current_snapshot.contains_rowset(table_id, entry.rowset_id) {
+                        return Err(TracedStorageError::conflict(table_id, entry.rowset_id));
+                    }
+                    for dv_id in Self::new_dvs_of(
+                        &read_snapshot,
+                        &current_snapshot,
+                        table_id,
+                        entry.rowset_id,
+                    ) {
+                        // a DV which is no longer tracked can't be checked, so regard it as
+                        // conflicting
+                        let conflicts = match self.dvs.get(&(table_id, dv_id)) {
+                            Some(committed) => committed.overlaps(dv),
+                            None => true,
+                        };
+                        if conflicts {
+                            return Err(TracedStorageError::conflict(table_id, entry.rowset_id));
+                        }
+                    }
+                }
+                EpochOp::DeleteRowSet(entry) => {
+                    let table_id = entry.table_id.table_id;
+                    if !current_snapshot.contains_rowset(table_id, entry.rowset_id)
+                        || Self::new_dvs_of(
+                            &read_snapshot,
+                            &current_snapshot,
+                            table_id,
+                            entry.rowset_id,
+                        )
+                        .next()
+                        .is_some()
+                    {
+                        return Err(TracedStorageError::conflict(table_id, entry.rowset_id));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Manages the state history of the storage engine and vacuum the stale files on disk.
 ///
 /// Generally, when a transaction starts, it will take a snapshot and store the state of the
@@ -179,8 +274,16 @@ impl VersionManager {
         }
     }
 
-    /// Commit changes and return a new epoch number
-    pub async fn commit_changes(&self, ops: Vec<EpochOp>) -> StorageResult<u64> {
+    /// Commit changes and return a new epoch number.
+    ///
+    /// If `read_epoch` is given, the changes were made upon the snapshot of that epoch, and will
+    /// be checked against the changes committed since then. On conflict, nothing is committed and
+    /// a retryable [`StorageError::Conflict`](crate::storage::StorageError::Conflict) is returned.
+    pub async fn commit_changes(
+        &self,
+        read_epoch: Option<u64>,
+        ops: Vec<EpochOp>,
+    ) -> StorageResult<u64> {
         // Hold the manifest lock so that no one else could commit changes.
         let mut manifest = self.manifest.lock().await;
 
@@ -198,6 +301,10 @@ impl VersionManager {
             // Save the current epoch for later integrity check.
             current_epoch = inner.epoch;
 
+            if let Some(read_epoch) = read_epoch {
+                inner.check_conflicts(read_epoch, &ops)?;
+            }
+
             // Get snapshot of latest version.
             snapshot = inner
                 .status
@@ -223,7 +330,7 @@ impl VersionManager {
                         // record the rowset into the pool
                         inner
                             .rowsets
-                            .insert((entry.table_id.table_id, entry.rowset_id), Arc::new(rowset));
+                            .insert((entry.table_id.table_id, entry.rowset_id), rowset);
                         // update the snapshot
                         snapshot.add_rowset(entry.table_id.table_id, entry.rowset_id);
                         entries.push(ManifestOperation::AddRowSet(entry));
@@ -235,9 +342,7 @@ impl VersionManager {
                     }
                     EpochOp::AddDV((entry, dv)) => {
                         // record the DV into the pool
-                        inner
-                            .dvs
-                            .insert((entry.table_id.table_id, entry.dv_id), Arc::new(dv));
+                        inner.dvs.insert((entry.table_id.table_id, entry.dv_id), dv);
                         // update the snapshot
                         snapshot.add_dv(entry.table_id.table_id, entry.rowset_id, entry.dv_id);
                         entries.push(ManifestOperation::AddDV(entry));
@@ -275,6 +380,27 @@ impl VersionManager {
         (epoch, inner.status.get(&epoch).unwrap().clone())
     }
 
+    /// Pin the snapshot of a past epoch for time travel. Only the latest `retention_epochs`
+    /// epochs are available. Note that epochs are not persisted, so the history is lost on restart.
+    pub fn pin_epoch(&self, epoch: u64) -> StorageResult<Arc<Snapshot>> {
+        let mut inner = self.inner.lock();
+        if epoch > inner.epoch || epoch + self.storage_options.retention_epochs < inner.epoch {
+            return Err(TracedStorageError::not_found("epoch", epoch));
+        }
+        let snapshot = inner
+            .status
+            .get(&epoch)
+            .cloned()
+            .ok_or_else(|| TracedStorageError::not_found("epoch", epoch))?;
+        *inner.ref_cnt.entry(epoch).or_default() += 1;
+        Ok(snapshot)
+    }
+
+    /// Get the current epoch number.
+    pub fn current_epoch(&self) -> u64 {
+        self.inner.lock().epoch
+    }
+
     /// Unpin a snapshot of one epoch. When reference counter becomes 0, files might be vacuumed.
     pub fn unpin(&self, epoch: u64) {
         let mut inner = self.inner.lock();
@@ -307,8 +433,14 @@ impl VersionManager {
         let mut inner = self.inner.lock();
         let min_pinned_epoch = inner.ref_cnt.keys().min().cloned();
 
-        // If there is no pinned epoch, all deletions can be applied.
-        let vacuum_epoch = min_pinned_epoch.unwrap_or(inner.epoch);
+        // Snapshots in the retention window must be kept readable.
+        let min_retained_epoch = inner
+            .epoch
+            .saturating_sub(self.storage_options.retention_epochs);
+
+        // If there is no pinned epoch, all deletions out of the retention window can be applied.
+        let vacuum_epoch =
+            min_pinned_epoch.map_or(min_retained_epoch, |epoch| epoch.min(min_retained_epoch));
 
         let can_apply = |epoch, vacuum_epoch| epoch <= vacuum_epoch;
 
@@ -322,6 +454,8 @@ impl VersionManager {
         inner
             .rowset_deletion_to_apply
             .retain(|k, _| !can_apply(*k, vacuum_epoch));
+        // Older snapshots may refer to the vacuumed RowSets.
+        inner.status.retain(|k, _| *k >= vacuum_epoch);
         for deletion in &deletions {
             if let Some(rowset) = inner.rowsets.remove(deletion) {
                 match Arc::try_unwrap(rowset) {
@@ -364,3 +498,57 @@ impl VersionManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{ArrayImpl, DataChunk};
+    use crate::catalog::{ColumnCatalog, TableRefId};
+    use crate::storage::secondary::{SecondaryRowHandler, SecondaryStorage};
+    use crate::storage::{Storage, Table, Transaction};
+    use crate::types::{DataTypeExt, DataTypeKind};
+
+    #[tokio::test]
+    async fn test_conflicting_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage =
+            SecondaryStorage::open(StorageOptions::default_for_test(dir.path().to_path_buf()))
+                .await
+                .unwrap();
+        let column =
+            ColumnCatalog::new(0, DataTypeKind::Int(None).not_null().to_column("v".into()));
+        storage
+            .create_table(0, 0, "t", &[column], &[])
+            .await
+            .unwrap();
+        let table = storage
+            .get_table(TableRefId {
+                database_id: 0,
+                schema_id: 0,
+                table_id: 0,
+            })
+            .unwrap();
+        let mut txn = table.write().await.unwrap();
+        txn.append(DataChunk::from_iter([ArrayImpl::new_int32(
+            [Some(1), Some(2), Some(3)].into_iter().collect(),
+        )]))
+        .await
+        .unwrap();
+        txn.commit().await.unwrap();
+
+        // the txns read the same snapshot, and both txn1 and txn2 delete row 1 of RowSet 0
+        let mut txn1 = table.update().await.unwrap();
+        let mut txn2 = table.update().await.unwrap();
+        let mut txn3 = table.update().await.unwrap();
+        txn1.delete(&SecondaryRowHandler(0, 0)).await.unwrap();
+        txn1.delete(&SecondaryRowHandler(0, 1)).await.unwrap();
+        txn2.delete(&SecondaryRowHandler(0, 1)).await.unwrap();
+        txn3.delete(&SecondaryRowHandler(0, 2)).await.unwrap();
+
+        txn1.commit().await.unwrap();
+        let err = txn2.commit().await.unwrap_err();
+        assert!(err.is_retryable(), "{}", err);
+        // deletions of other rows don't conflict
+        txn3.commit().await.unwrap();
+    }
+}
@@ -6,7 +6,7 @@ use std::str::FromStr;
 use chrono::{Datelike, NaiveDate};
 use serde::Serialize;
 
-use crate::types::Interval;
+use crate::types::{ConvertError, Interval};
 
 /// The same as `NaiveDate::from_ymd(1970, 1, 1).num_days_from_ce()`.
 /// Minus this magic number to store the number of days since 1970-01-01.
@@ -25,14 +25,93 @@ impl Date {
     pub fn get_inner(&self) -> i32 {
         self.0
     }
+
+    fn from_naive(date: NaiveDate) -> Self {
+        Date(date.num_days_from_ce() - UNIX_EPOCH_DAYS)
+    }
+
+    fn to_naive(self) -> NaiveDate {
+        NaiveDate::from_num_days_from_ce(self.0 + UNIX_EPOCH_DAYS)
+    }
+
+    /// Extract a field from the date.
+    pub fn extract(&self, field: DateField) -> i32 {
+        let date = self.to_naive();
+        match field {
+            DateField::Year => date.year(),
+            DateField::Quarter => (date.month0() / 3 + 1) as i32,
+            DateField::Month => date.month() as i32,
+            DateField::Week => date.iso_week().week() as i32,
+            DateField::Day => date.day() as i32,
+            DateField::DayOfWeek => date.weekday().num_days_from_sunday() as i32,
+            DateField::DayOfYear => date.ordinal() as i32,
+            DateField::Hour | DateField::Minute | DateField::Second => 0,
+        }
+    }
+
+    /// Truncate the date to the first day of the year, quarter, month or week (Monday). A date
+    /// truncated to a sub-day field is itself.
+    ///
+    /// Returns `None` if the date can not be truncated to the field.
+    pub fn truncate(&self, field: DateField) -> Option<Date> {
+        let date = self.to_naive();
+        let truncated = match field {
+            DateField::Year => NaiveDate::from_ymd(date.year(), 1, 1),
+            DateField::Quarter => NaiveDate::from_ymd(date.year(), date.month0() / 3 * 3 + 1, 1),
+            DateField::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+            DateField::Week => {
+                return Some(Date(self.0 - date.weekday().num_days_from_monday() as i32))
+            }
+            DateField::Day | DateField::Hour | DateField::Minute | DateField::Second => date,
+            DateField::DayOfWeek | DateField::DayOfYear => return None,
+        };
+        Some(Date::from_naive(truncated))
+    }
+}
+
+/// A field of date, used by `EXTRACT`, `date_part` and `date_trunc`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DateField {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    /// Day of the week, from 0 (Sunday) to 6 (Saturday).
+    DayOfWeek,
+    /// Day of the year, from 1 to 366.
+    DayOfYear,
+    Hour,
+    Minute,
+    /// Seconds of the minute, without the fractional part.
+    Second,
+}
+
+impl FromStr for DateField {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "year" | "years" => Self::Year,
+            "quarter" | "quarters" => Self::Quarter,
+            "month" | "months" => Self::Month,
+            "week" | "weeks" => Self::Week,
+            "day" | "days" => Self::Day,
+            "dow" => Self::DayOfWeek,
+            "doy" => Self::DayOfYear,
+            "hour" | "hours" => Self::Hour,
+            "minute" | "minutes" => Self::Minute,
+            "second" | "seconds" => Self::Second,
+            _ => return Err(ConvertError::ParseDateField(s.to_string())),
+        })
+    }
 }
 
 impl FromStr for Date {
     type Err = chrono::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        NaiveDate::parse_from_str(s, "%Y-%m-%d")
-            .map(|ret| Date(ret.num_days_from_ce() - UNIX_EPOCH_DAYS))
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").map(Date::from_naive)
     }
 }
 
@@ -123,3 +202,38 @@ impl Display for Date {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_and_truncate() {
+        // 2022-05-18 is a Wednesday.
+        let date: Date = "2022-05-18".parse().unwrap();
+        assert_eq!(date.extract(DateField::Year), 2022);
+        assert_eq!(date.extract(DateField::Quarter), 2);
+        assert_eq!(date.extract(DateField::Month), 5);
+        assert_eq!(date.extract(DateField::Week), 20);
+        assert_eq!(date.extract(DateField::Day), 18);
+        assert_eq!(date.extract(DateField::DayOfWeek), 3);
+        assert_eq!(date.extract(DateField::DayOfYear), 138);
+
+        let trunc = |field| date.truncate(field).unwrap().to_string();
+        assert_eq!(trunc(DateField::Year), "2022-01-01");
+        assert_eq!(trunc(DateField::Quarter), "2022-04-01");
+        assert_eq!(trunc(DateField::Month), "2022-05-01");
+        assert_eq!(trunc(DateField::Week), "2022-05-16");
+        assert_eq!(trunc(DateField::Day), "2022-05-18");
+        assert_eq!(date.truncate(DateField::DayOfWeek), None);
+    }
+
+    #[test]
+    fn test_add_interval() {
+        let date: Date = "2020-01-31".parse().unwrap();
+        assert_eq!((date + Interval::from_months(1)).to_string(), "2020-02-29");
+        assert_eq!((date + Interval::from_months(13)).to_string(), "2021-02-28");
+        assert_eq!((date - Interval::from_years(1)).to_string(), "2019-01-31");
+        assert_eq!((date - Interval::from_months(2)).to_string(), "2019-11-30");
+    }
+}
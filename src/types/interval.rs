@@ -5,31 +5,60 @@ use std::ops::Neg;
 
 use serde::Serialize;
 
+pub const MICROS_PER_SECOND: i64 = 1_000_000;
+pub const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+pub const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+pub const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
 /// Interval type
+///
+/// The sub-day part is kept in microseconds, and is only meaningful for timestamps.
 #[derive(PartialOrd, PartialEq, Debug, Copy, Clone, Default, Hash, Serialize)]
 pub struct Interval {
     months: i32,
     days: i32,
+    micros: i64,
 }
 
 impl Interval {
     pub const fn from_days(days: i32) -> Self {
-        Interval { months: 0, days }
+        Interval::from_md(0, days)
     }
 
     pub const fn from_months(months: i32) -> Self {
-        Interval { months, days: 0 }
+        Interval::from_md(months, 0)
     }
 
     pub const fn from_years(years: i32) -> Self {
+        Interval::from_md(years * 12, 0)
+    }
+
+    pub const fn from_md(months: i32, days: i32) -> Self {
+        Interval::from_mdm(months, days, 0)
+    }
+
+    pub const fn from_mdm(months: i32, days: i32, micros: i64) -> Self {
         Interval {
-            months: years * 12,
-            days: 0,
+            months,
+            days,
+            micros,
         }
     }
 
-    pub const fn from_md(months: i32, days: i32) -> Self {
-        Interval { months, days }
+    pub const fn from_micros(micros: i64) -> Self {
+        Interval::from_mdm(0, 0, micros)
+    }
+
+    pub const fn from_hours(hours: i32) -> Self {
+        Interval::from_micros(hours as i64 * 3600 * MICROS_PER_SECOND)
+    }
+
+    pub const fn from_minutes(minutes: i32) -> Self {
+        Interval::from_micros(minutes as i64 * 60 * MICROS_PER_SECOND)
+    }
+
+    pub const fn from_seconds(seconds: i32) -> Self {
+        Interval::from_micros(seconds as i64 * MICROS_PER_SECOND)
     }
 
     pub const fn years(&self) -> i32 {
@@ -47,6 +76,11 @@ impl Interval {
     pub const fn num_months(&self) -> i32 {
         self.months
     }
+
+    /// The sub-day part in microseconds.
+    pub const fn micros(&self) -> i64 {
+        self.micros
+    }
 }
 
 impl Neg for Interval {
@@ -56,6 +90,7 @@ impl Neg for Interval {
         Interval {
             months: -self.months,
             days: -self.days,
+            micros: -self.micros,
         }
     }
 }
@@ -68,6 +103,23 @@ impl Display for Interval {
             self.years(),
             self.months(),
             self.days()
-        )
+        )?;
+        if self.micros != 0 {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            let micros = self.micros.unsigned_abs();
+            let secs = micros / MICROS_PER_SECOND as u64;
+            write!(
+                f,
+                " {}{:02}:{:02}:{:02}",
+                sign,
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60
+            )?;
+            if micros % MICROS_PER_SECOND as u64 != 0 {
+                write!(f, ".{:06}", micros % MICROS_PER_SECOND as u64)?;
+            }
+        }
+        Ok(())
     }
 }
@@ -14,11 +14,13 @@ mod blob;
 mod date;
 mod interval;
 mod native;
+mod timestamp;
 
 pub use self::blob::*;
 pub use self::date::*;
 pub use self::interval::*;
 pub use self::native::*;
+pub use self::timestamp::*;
 
 /// Physical data type
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -32,6 +34,8 @@ pub enum PhysicalDataTypeKind {
     Decimal,
     Date,
     Interval,
+    Timestamp,
+    TimestampTz,
 }
 
 impl From<DataTypeKind> for PhysicalDataTypeKind {
@@ -47,11 +51,32 @@ impl From<DataTypeKind> for PhysicalDataTypeKind {
             Decimal(_, _) => Self::Decimal,
             Date => Self::Date,
             Interval => Self::Interval,
+            Timestamp => Self::Timestamp,
+            ref kind if is_timestamptz(kind) => Self::TimestampTz,
             _ => todo!("physical type for {:?} is not supported", kind),
         }
     }
 }
 
+impl PhysicalDataTypeKind {
+    /// Get the logical type that is used by default for values of this physical type.
+    pub fn default_logical_kind(&self) -> DataTypeKind {
+        match self {
+            Self::Int32 => DataTypeKind::Int(None),
+            Self::Int64 => DataTypeKind::BigInt(None),
+            Self::Float64 => DataTypeKind::Double,
+            Self::String => DataTypeKind::Varchar(Some(VARCHAR_DEFAULT_LEN)),
+            Self::Blob => DataTypeKind::Blob(0),
+            Self::Bool => DataTypeKind::Boolean,
+            Self::Decimal => DataTypeKind::Decimal(None, None),
+            Self::Date => DataTypeKind::Date,
+            Self::Interval => DataTypeKind::Interval,
+            Self::Timestamp => DataTypeKind::Timestamp,
+            Self::TimestampTz => timestamptz_kind(),
+        }
+    }
+}
+
 /// Data type with nullable.
 #[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DataType {
@@ -143,6 +168,8 @@ pub enum DataValue {
     Decimal(Decimal),
     Date(Date),
     Interval(Interval),
+    Timestamp(Timestamp),
+    TimestampTz(TimestampTz),
 }
 
 /// Implement dispatch functions for `PartialEq`
@@ -178,6 +205,8 @@ impl Hash for DataValue {
             Self::Decimal(v) => v.hash(state),
             Self::Date(v) => v.hash(state),
             Self::Interval(v) => v.hash(state),
+            Self::Timestamp(v) => v.hash(state),
+            Self::TimestampTz(v) => v.hash(state),
         }
     }
 }
@@ -195,6 +224,8 @@ macro_rules! impl_arith_for_datavalue {
                     (&Float64(x), &Float64(y)) => Float64(x.$name(y)),
                     (&Decimal(x), &Decimal(y)) => Decimal(x.$name(y)),
                     (&Date(x), &Interval(y)) => Date(x.$name(y)),
+                    (&Timestamp(x), &Interval(y)) => Timestamp(x.$name(y)),
+                    (&TimestampTz(x), &Interval(y)) => TimestampTz(x.$name(y)),
                     _ => panic!(
                         "invalid operation: {:?} {} {:?}",
                         self,
@@ -240,6 +271,8 @@ impl DataValue {
             Self::Decimal(v) => v.is_sign_positive(),
             Self::Date(_) => false,
             Self::Interval(_) => false,
+            Self::Timestamp(_) => false,
+            Self::TimestampTz(_) => false,
             Self::Null => false,
         }
     }
@@ -256,6 +289,8 @@ impl DataValue {
             Self::Decimal(_) => Some(DataTypeKind::Decimal(None, None).not_null()),
             Self::Date(_) => Some(DataTypeKind::Date.not_null()),
             Self::Interval(_) => Some(DataTypeKind::Interval.not_null()),
+            Self::Timestamp(_) => Some(DataTypeKind::Timestamp.not_null()),
+            Self::TimestampTz(_) => Some(timestamptz_kind().not_null()),
             Self::Null => None,
         }
     }
@@ -288,6 +323,12 @@ impl DataValue {
             &DataValue::Interval(i) => {
                 return Err(ConvertError::Cast(i.to_string(), "usize"));
             }
+            &DataValue::Timestamp(t) => {
+                return Err(ConvertError::Cast(t.to_string(), "usize"));
+            }
+            &DataValue::TimestampTz(t) => {
+                return Err(ConvertError::Cast(t.to_string(), "usize"));
+            }
             DataValue::String(s) => s
                 .parse::<usize>()
                 .map_err(|e| ConvertError::ParseInt(s.clone(), e))?,
@@ -311,6 +352,12 @@ pub enum ConvertError {
     ParseDecimal(String, rust_decimal::Error),
     #[error("failed to convert string {0:?} to date: {:?}")]
     ParseDate(String, chrono::ParseError),
+    #[error("unrecognized date field {0:?}")]
+    ParseDateField(String),
+    #[error("failed to convert string {0:?} to timestamp: {:?}")]
+    ParseTimestamp(String, chrono::ParseError),
+    #[error("failed to convert {0:?} from timestamp")]
+    FromTimestampError(DataTypeKind),
     #[error("failed to convert string {0:?} to interval")]
     ParseInterval(String),
     #[error("failed to convert string {0:?} to blob: {:?}")]
@@ -329,6 +376,8 @@ pub enum ConvertError {
     Cast(String, &'static str),
     #[error("constant {0:?} overflows {1:?}")]
     Overflow(DataValue, DataTypeKind),
+    #[error("function error: {0}")]
+    Function(#[from] crate::function::FunctionError),
 }
 
 /// memory table row type
@@ -347,6 +396,8 @@ impl std::fmt::Display for DataValue {
             Self::Decimal(value) => write!(f, "{}", value)?,
             Self::Date(value) => write!(f, "{}", value)?,
             Self::Interval(value) => write!(f, "{}", value)?,
+            Self::Timestamp(value) => write!(f, "{}", value)?,
+            Self::TimestampTz(value) => write!(f, "{}", value)?,
         }
         Ok(())
     }
@@ -6,6 +6,7 @@ use rust_decimal::Decimal;
 
 use super::date::Date;
 use super::interval::Interval;
+use super::timestamp::{Timestamp, TimestampTz};
 
 pub trait NativeType:
     PartialOrd + PartialEq + Debug + Copy + Send + Sync + Sized + Default + 'static
@@ -18,5 +19,22 @@ macro_rules! impl_native {
     }
 }
 impl_native!(
-    u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, bool, Decimal, Date, Interval
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize,
+    f32,
+    f64,
+    bool,
+    Decimal,
+    Date,
+    Interval,
+    Timestamp,
+    TimestampTz
 );
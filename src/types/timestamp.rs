@@ -0,0 +1,297 @@
+// Copyright 2022 RisingLight Project Authors. Licensed under Apache-2.0.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use sqlparser::ast::{Ident, ObjectName};
+
+use super::{
+    DataTypeKind, Date, DateField, Interval, MICROS_PER_DAY, MICROS_PER_HOUR, MICROS_PER_MINUTE,
+    MICROS_PER_SECOND,
+};
+
+/// Get the data type of `TIMESTAMPTZ`.
+///
+/// The parser has no built-in type for it, so it is represented as a custom type.
+pub fn timestamptz_kind() -> DataTypeKind {
+    DataTypeKind::Custom(ObjectName(vec![Ident::new("TIMESTAMPTZ")]))
+}
+
+/// Returns `true` if the data type is `TIMESTAMPTZ`.
+pub fn is_timestamptz(kind: &DataTypeKind) -> bool {
+    match kind {
+        DataTypeKind::Custom(name) => {
+            name.0.len() == 1 && name.0[0].value.eq_ignore_ascii_case("timestamptz")
+        }
+        _ => false,
+    }
+}
+
+/// Timestamp without time zone, in microseconds since 1970-01-01 00:00:00.
+#[derive(PartialOrd, PartialEq, Debug, Copy, Clone, Default, Hash, Serialize)]
+pub struct Timestamp(i64);
+
+/// Timestamp with time zone, in microseconds since 1970-01-01 00:00:00 UTC.
+///
+/// Values are always displayed in UTC.
+#[derive(PartialOrd, PartialEq, Debug, Copy, Clone, Default, Hash, Serialize)]
+pub struct TimestampTz(i64);
+
+impl Timestamp {
+    pub const fn new(micros: i64) -> Self {
+        Timestamp(micros)
+    }
+
+    /// Get the inner value of timestamp type
+    pub fn get_inner(&self) -> i64 {
+        self.0
+    }
+
+    /// Get the date part of the timestamp.
+    pub fn date(&self) -> Date {
+        Date::new(self.0.div_euclid(MICROS_PER_DAY) as i32)
+    }
+
+    /// Extract a field from the timestamp.
+    pub fn extract(&self, field: DateField) -> i32 {
+        let time = self.0.rem_euclid(MICROS_PER_DAY);
+        match field {
+            DateField::Hour => (time / MICROS_PER_HOUR) as i32,
+            DateField::Minute => (time / MICROS_PER_MINUTE % 60) as i32,
+            DateField::Second => (time / MICROS_PER_SECOND % 60) as i32,
+            _ => self.date().extract(field),
+        }
+    }
+
+    /// Truncate the timestamp to the start of the year, quarter, month, week, day, hour, minute
+    /// or second.
+    ///
+    /// Returns `None` if the timestamp can not be truncated to the field.
+    pub fn truncate(&self, field: DateField) -> Option<Timestamp> {
+        let unit = match field {
+            DateField::Hour => MICROS_PER_HOUR,
+            DateField::Minute => MICROS_PER_MINUTE,
+            DateField::Second => MICROS_PER_SECOND,
+            _ => return self.date().truncate(field).map(Timestamp::from),
+        };
+        Some(Timestamp(self.0 - self.0.rem_euclid(unit)))
+    }
+
+    fn to_naive(self) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(
+            self.0.div_euclid(MICROS_PER_SECOND),
+            (self.0.rem_euclid(MICROS_PER_SECOND) * 1000) as u32,
+        )
+    }
+
+    fn from_naive(datetime: NaiveDateTime) -> Self {
+        Timestamp(
+            datetime.timestamp() * MICROS_PER_SECOND + datetime.timestamp_subsec_micros() as i64,
+        )
+    }
+}
+
+impl From<Date> for Timestamp {
+    fn from(date: Date) -> Self {
+        Timestamp(date.get_inner() as i64 * MICROS_PER_DAY)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f"))
+            .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_hms(0, 0, 0)))
+            .map(Timestamp::from_naive)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_naive().format("%Y-%m-%d %H:%M:%S%.f"))
+    }
+}
+
+impl std::ops::Add<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        // Months and days are added to the date part, so that the day of month is fixed
+        // the same way as dates.
+        let time = self.0.rem_euclid(MICROS_PER_DAY);
+        let date = self.date() + Interval::from_md(rhs.num_months(), rhs.days());
+        Timestamp(Timestamp::from(date).0 + time + rhs.micros())
+    }
+}
+
+impl std::ops::Sub<Interval> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl std::ops::Sub for Timestamp {
+    type Output = Interval;
+
+    fn sub(self, rhs: Timestamp) -> Self::Output {
+        let micros = self.0 - rhs.0;
+        Interval::from_mdm(0, (micros / MICROS_PER_DAY) as i32, micros % MICROS_PER_DAY)
+    }
+}
+
+impl TimestampTz {
+    pub const fn new(micros: i64) -> Self {
+        TimestampTz(micros)
+    }
+
+    /// Get the inner value of timestamp type
+    pub fn get_inner(&self) -> i64 {
+        self.0
+    }
+
+    /// Convert to a timestamp in UTC.
+    pub fn to_utc(self) -> Timestamp {
+        Timestamp(self.0)
+    }
+
+    /// Regard a timestamp as in UTC.
+    pub fn from_utc(timestamp: Timestamp) -> Self {
+        TimestampTz(timestamp.0)
+    }
+
+    /// Extract a field from the timestamp in UTC.
+    pub fn extract(&self, field: DateField) -> i32 {
+        self.to_utc().extract(field)
+    }
+
+    /// Truncate the timestamp in UTC, see [`Timestamp::truncate`].
+    pub fn truncate(&self, field: DateField) -> Option<TimestampTz> {
+        self.to_utc().truncate(field).map(Self::from_utc)
+    }
+}
+
+impl FromStr for TimestampTz {
+    type Err = chrono::ParseError;
+
+    /// Parse a timestamp with an optional time zone offset. A timestamp without offset is
+    /// regarded as in UTC.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z")
+            .or_else(|_| DateTime::parse_from_rfc3339(s))
+        {
+            Ok(datetime) => Ok(Self::from_utc(Timestamp::from_naive(datetime.naive_utc()))),
+            Err(e) => s.parse().map(Self::from_utc).map_err(|_| e),
+        }
+    }
+}
+
+impl Display for TimestampTz {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+00:00", self.to_utc())
+    }
+}
+
+impl std::ops::Add<Interval> for TimestampTz {
+    type Output = TimestampTz;
+
+    fn add(self, rhs: Interval) -> Self::Output {
+        Self::from_utc(self.to_utc() + rhs)
+    }
+}
+
+impl std::ops::Sub<Interval> for TimestampTz {
+    type Output = TimestampTz;
+
+    fn sub(self, rhs: Interval) -> Self::Output {
+        self + (-rhs)
+    }
+}
+
+impl std::ops::Sub for TimestampTz {
+    type Output = Interval;
+
+    fn sub(self, rhs: TimestampTz) -> Self::Output {
+        self.to_utc() - rhs.to_utc()
+    }
+}
+
+macro_rules! impl_invalid_interval_ops {
+    ($($t:ty),*) => {
+        $(
+            impl std::ops::Mul<Interval> for $t {
+                type Output = $t;
+
+                fn mul(self, rhs: Interval) -> Self::Output {
+                    panic!("invalid operation: {:?} * {:?}", self, rhs)
+                }
+            }
+
+            impl std::ops::Div<Interval> for $t {
+                type Output = $t;
+
+                fn div(self, rhs: Interval) -> Self::Output {
+                    panic!("invalid operation: {:?} / {:?}", self, rhs)
+                }
+            }
+
+            impl std::ops::Rem<Interval> for $t {
+                type Output = $t;
+
+                fn rem(self, rhs: Interval) -> Self::Output {
+                    panic!("invalid operation: {:?} % {:?}", self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_invalid_interval_ops!(Timestamp, TimestampTz);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let ts: Timestamp = "2022-05-18 12:34:56.789".parse().unwrap();
+        assert_eq!(ts.to_string(), "2022-05-18 12:34:56.789");
+        let ts: Timestamp = "1969-12-31".parse().unwrap();
+        assert_eq!(ts.to_string(), "1969-12-31 00:00:00");
+        assert_eq!(ts.date().to_string(), "1969-12-31");
+
+        let tz: TimestampTz = "2022-05-18 08:00:00+08:00".parse().unwrap();
+        assert_eq!(tz.to_string(), "2022-05-18 00:00:00+00:00");
+        let tz: TimestampTz = "2022-05-18 08:00:00".parse().unwrap();
+        assert_eq!(tz.to_string(), "2022-05-18 08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_interval_arithmetic() {
+        let ts: Timestamp = "2020-01-31 23:00:00".parse().unwrap();
+        assert_eq!(
+            (ts + Interval::from_months(1)).to_string(),
+            "2020-02-29 23:00:00"
+        );
+        assert_eq!(
+            (ts + Interval::from_hours(2)).to_string(),
+            "2020-02-01 01:00:00"
+        );
+        assert_eq!(
+            (ts - Interval::from_seconds(1)).to_string(),
+            "2020-01-31 22:59:59"
+        );
+        let other: Timestamp = "2020-01-30 22:00:00".parse().unwrap();
+        assert_eq!(
+            ts - other,
+            Interval::from_mdm(0, 1, 3600 * MICROS_PER_SECOND)
+        );
+    }
+}
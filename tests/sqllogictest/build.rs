@@ -9,7 +9,12 @@ fn main() {
     println!("cargo:rerun-if-changed=../../tests/sql");
 
     const PATTERN: &str = "../../tests/sql/**/[!_]*.slt"; // ignore files start with '_'
-    const MEM_BLOCKLIST: &[&str] = &["statistics.slt"];
+    const MEM_BLOCKLIST: &[&str] = &[
+        "statistics.slt",
+        "time_travel.slt",
+        "transaction.slt",
+        "update.slt",
+    ];
     const DISK_BLOCKLIST: &[&str] = &[];
 
     let path = PathBuf::from("tests").join("gen/testcase.rs");